@@ -22,6 +22,7 @@ import type { GlobalsResponse } from "@mcu-debug/shared/dasm-helper/GlobalsRespo
 import type { StaticsResponse } from "@mcu-debug/shared/dasm-helper/StaticsResponse";
 import type { SymbolLookupResponse } from "@mcu-debug/shared/dasm-helper/SymbolLookupResponse";
 import type { HelperEvent } from "@mcu-debug/shared/dasm-helper/HelperEvent";
+import type { RequestError } from "@mcu-debug/shared/dasm-helper/RequestError";
 import { getObjdumpPath } from "./symbols";
 import { DebugProtocol } from "@vscode/debugprotocol";
 
@@ -300,7 +301,12 @@ export class DebugHelper {
             const pending = this.pendingRequests.get(message.seq);
             if (pending) {
                 this.pendingRequests.delete(message.seq);
-                pending.resolve(message as HelperResponse);
+                if (message.error) {
+                    const error = message.error as RequestError;
+                    pending.reject(new Error(`Helper request ${message.req} failed (${error.code}): ${error.message}`));
+                } else {
+                    pending.resolve(message as HelperResponse);
+                }
                 return;
             }
         }
@@ -339,8 +345,11 @@ export class DebugHelper {
                 break;
             }
             case "Progress":
-                const progressMsg = event.message || `${event.operation}: ${event.percentage ?? "?"}%`;
-                this.session.handleMsg(Stdout, progressMsg);
+                // Loads report every 10%, which is only interesting when debugging the helper
+                if (this.session.args.debugFlags?.anyFlags) {
+                    const progressMsg = event.message || `${event.operation}: ${event.percentage ?? "?"}%`;
+                    this.session.handleMsg(Stdout, progressMsg);
+                }
                 break;
 
             case "Output":
@@ -445,10 +454,11 @@ export class DebugHelper {
     }
 
     /**
-     * Get all global variables.
+     * Get all global variables. With `detailed`, the response has `variables` (size, section,
+     * declaration and type reference) instead of the (name, address) tuples.
      */
-    async getGlobals(): Promise<GlobalsResponse> {
-        return this.sendRequest<GlobalsResponse>("globals", {});
+    async getGlobals(detailed = false): Promise<GlobalsResponse> {
+        return this.sendRequest<GlobalsResponse>("globals", detailed ? { detailed } : {});
     }
 
     async getGlobalsNames(): Promise<string[]> {
@@ -457,10 +467,10 @@ export class DebugHelper {
     }
 
     /**
-     * Get static variables for a specific file.
+     * Get static variables for a specific file. See getGlobals for `detailed`.
      */
-    async getStatics(fileName: string): Promise<StaticsResponse> {
-        return this.sendRequest<StaticsResponse>("statics", { file_name: fileName });
+    async getStatics(fileName: string, detailed = false): Promise<StaticsResponse> {
+        return this.sendRequest<StaticsResponse>("statics", detailed ? { file_name: fileName, detailed } : { file_name: fileName });
     }
 
     async getStaticsNames(fileName: string): Promise<string[]> {
@@ -0,0 +1,84 @@
+//! Compares the da-helper wire formats on real disassembly responses: frame size and the time
+//! to encode and decode each one.
+//!
+//!     cargo run --release --example encoding_bench -- [ELF] [OBJDUMP]
+//!
+//! Defaults to the sample ELF in mylfs/ and arm-none-eabi-objdump.
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+use mdbg::common::framing::FrameCodec;
+use mdbg::da_helper::encoding::{decode, WireFormat};
+use mdbg::da_helper::get_disasm_from_objdump;
+use mdbg::da_helper::helper_requests::{Compression, DisasmResponse, Encoding, SerInstruction};
+
+const WINDOWS: [usize; 3] = [500, 2000, 10000];
+
+/// Repeat `f` for at least this long to get a stable average
+const MIN_RUN: Duration = Duration::from_millis(200);
+
+fn time_per_call(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    let mut calls = 0u32;
+    while calls < 3 || start.elapsed() < MIN_RUN {
+        f();
+        calls += 1;
+    }
+    start.elapsed() / calls
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let elf = args.next().unwrap_or_else(|| "../../mylfs/proj_cm4.elf".to_string());
+    let objdump = args.next().unwrap_or_else(|| "arm-none-eabi-objdump".to_string());
+    let listing = get_disasm_from_objdump(&objdump, &elf).unwrap_or_else(|e| {
+        eprintln!("Could not disassemble {} with {}: {}", elf, objdump, e);
+        std::process::exit(1);
+    });
+    println!("{}: {} instructions\n", elf, listing.lines.len());
+
+    let codec = FrameCodec::new();
+    for window in WINDOWS {
+        let count = window.min(listing.lines.len());
+        let instructions = listing.lines[..count]
+            .iter()
+            .map(|line| SerInstruction::from_assembly_line(line))
+            .collect();
+        let response = DisasmResponse::new(1, HashMap::new(), HashMap::new(), instructions);
+        let msg = serde_json::to_value(&response).unwrap();
+        let json_size = codec.encode(&msg).unwrap().len();
+
+        println!("{} instructions", count);
+        println!(
+            "  {:<16} {:>10} {:>7} {:>12} {:>12}",
+            "format", "bytes", "ratio", "encode", "decode"
+        );
+        for encoding in [Encoding::Json, Encoding::Msgpack, Encoding::Cbor] {
+            for compression in [Compression::None, Compression::Zstd, Compression::Deflate] {
+                let format = WireFormat { encoding, compression };
+                let frame = format.encode(&codec, &msg).unwrap();
+                let parsed = codec.read_message(&mut Cursor::new(&frame)).unwrap();
+                let encode = time_per_call(|| {
+                    format.encode(&codec, &msg).unwrap();
+                });
+                let decode_time = time_per_call(|| {
+                    decode(&parsed).unwrap();
+                });
+                println!(
+                    "  {:<16} {:>10} {:>6.1}% {:>12.2?} {:>12.2?}",
+                    format!("{:?}/{:?}", encoding, compression).to_lowercase(),
+                    frame.len(),
+                    100.0 * frame.len() as f64 / json_size as f64,
+                    encode,
+                    decode_time
+                );
+            }
+        }
+        println!();
+        if count == listing.lines.len() {
+            break; // larger windows would repeat the same response
+        }
+    }
+}
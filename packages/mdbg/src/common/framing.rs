@@ -0,0 +1,574 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Content-Length framing (the DAP/LSP base protocol), shared by every transport:
+//!
+//! ```text
+//! Content-Length: 17\r\n
+//! Content-Type: application/vscode-jsonrpc; charset=utf-8\r\n   (optional)
+//! \r\n
+//! {"req":"globals"}
+//! ```
+//!
+//! `FrameCodec` reads and writes frames over blocking (`BufRead`/`Write`) or tokio
+//! (`AsyncBufRead`/`AsyncWrite`) streams. Header parsing is the same code for both. Nothing is
+//! allocated based on a header until it has been checked against the maximum message size.
+
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+
+use serde_json::Value;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Largest message body accepted by default
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Longest header line accepted; real headers are a few dozen bytes
+const MAX_HEADER_LINE: usize = 8 * 1024;
+
+/// Most header lines in one frame
+const MAX_HEADER_LINES: usize = 32;
+
+#[derive(Debug)]
+pub enum FrameError {
+    /// The stream ended cleanly between messages
+    Closed,
+    /// The stream ended in the middle of a message
+    Truncated,
+    Io(io::Error),
+    /// A header line that isn't `Name: value`, or is too long
+    InvalidHeader(String),
+    MissingContentLength,
+    InvalidContentLength(String),
+    DuplicateHeader(String),
+    UnsupportedContentType(String),
+    TooLarge {
+        size: usize,
+        max: usize,
+    },
+    Json(serde_json::Error),
+    /// A body in another encoding (MessagePack, CBOR, compressed) that couldn't be converted
+    Encoding(String),
+}
+
+impl FrameError {
+    /// True if the peer went away, as opposed to sending something malformed
+    pub fn is_disconnect(&self) -> bool {
+        match self {
+            FrameError::Closed | FrameError::Truncated => true,
+            FrameError::Io(e) => matches!(
+                e.kind(),
+                io::ErrorKind::BrokenPipe | io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted
+            ),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::Closed => write!(f, "EOF while reading header"),
+            FrameError::Truncated => write!(f, "stream ended in the middle of a message"),
+            FrameError::Io(e) => write!(f, "{}", e),
+            FrameError::InvalidHeader(line) => write!(f, "invalid header line '{}'", line),
+            FrameError::MissingContentLength => write!(f, "Missing Content-Length header"),
+            FrameError::InvalidContentLength(value) => write!(f, "invalid Content-Length '{}'", value),
+            FrameError::DuplicateHeader(name) => write!(f, "duplicate {} header", name),
+            FrameError::UnsupportedContentType(value) => write!(f, "unsupported Content-Type '{}'", value),
+            FrameError::TooLarge { size, max } => {
+                write!(f, "message of {} bytes exceeds the {} byte limit", size, max)
+            }
+            FrameError::Json(e) => write!(f, "invalid JSON body: {}", e),
+            FrameError::Encoding(e) => write!(f, "could not convert message body: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FrameError::Io(e) => Some(e),
+            FrameError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FrameError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            FrameError::Truncated
+        } else {
+            FrameError::Io(e)
+        }
+    }
+}
+
+impl From<serde_json::Error> for FrameError {
+    fn from(e: serde_json::Error) -> Self {
+        FrameError::Json(e)
+    }
+}
+
+/// Collects header lines until the blank line that ends them
+#[derive(Default)]
+struct HeaderParser {
+    content_length: Option<usize>,
+    content_type: Option<String>,
+    content_encoding: Option<String>,
+    lines: usize,
+}
+
+impl HeaderParser {
+    /// Feed one raw line (including its line ending). Returns the body length once the
+    /// headers are complete.
+    fn line(&mut self, raw: &[u8], max: usize) -> Result<Option<usize>, FrameError> {
+        if !raw.ends_with(b"\n") {
+            return Err(if raw.len() >= MAX_HEADER_LINE {
+                FrameError::InvalidHeader(format!("{}...", String::from_utf8_lossy(&raw[..40])))
+            } else {
+                FrameError::Truncated
+            });
+        }
+        let line = std::str::from_utf8(raw)
+            .map_err(|_| FrameError::InvalidHeader(String::from_utf8_lossy(raw).trim().to_string()))?
+            .trim();
+        if line.is_empty() {
+            if self.lines == 0 {
+                return Ok(None); // tolerate blank lines between messages
+            }
+            let len = self.content_length.ok_or(FrameError::MissingContentLength)?;
+            return Ok(Some(len));
+        }
+        self.lines += 1;
+        if self.lines > MAX_HEADER_LINES {
+            return Err(FrameError::InvalidHeader(format!(
+                "more than {} header lines",
+                MAX_HEADER_LINES
+            )));
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| FrameError::InvalidHeader(line.to_string()))?;
+        let value = value.trim();
+        if name.trim().eq_ignore_ascii_case("content-length") {
+            if self.content_length.is_some() {
+                return Err(FrameError::DuplicateHeader("Content-Length".to_string()));
+            }
+            let size = value
+                .parse::<usize>()
+                .map_err(|_| FrameError::InvalidContentLength(value.to_string()))?;
+            if size > max {
+                return Err(FrameError::TooLarge { size, max });
+            }
+            self.content_length = Some(size);
+        } else if name.trim().eq_ignore_ascii_case("content-type") {
+            if self.content_type.is_some() {
+                return Err(FrameError::DuplicateHeader("Content-Type".to_string()));
+            }
+            check_content_type(value)?;
+            self.content_type = Some(value.to_string());
+        } else if name.trim().eq_ignore_ascii_case("content-encoding") {
+            if self.content_encoding.is_some() {
+                return Err(FrameError::DuplicateHeader("Content-Encoding".to_string()));
+            }
+            self.content_encoding = Some(value.to_string());
+        }
+        // Other headers are ignored
+        Ok(None)
+    }
+
+    fn into_frame(self, body: Vec<u8>) -> Frame {
+        Frame {
+            content_type: self.content_type,
+            content_encoding: self.content_encoding,
+            body,
+        }
+    }
+}
+
+/// Bodies are JSON in UTF-8; the media type itself isn't checked beyond that
+fn check_content_type(value: &str) -> Result<(), FrameError> {
+    for param in value.split(';').skip(1) {
+        if let Some((key, charset)) = param.split_once('=') {
+            let charset = charset.trim().trim_matches('"');
+            if key.trim().eq_ignore_ascii_case("charset")
+                && !charset.eq_ignore_ascii_case("utf-8")
+                && !charset.eq_ignore_ascii_case("utf8")
+            {
+                return Err(FrameError::UnsupportedContentType(value.to_string()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// One frame as read: the body plus the headers that say how to interpret it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// `Content-Type`, if sent; absent means JSON
+    pub content_type: Option<String>,
+    /// `Content-Encoding` (e.g. `zstd`), if the body is compressed
+    pub content_encoding: Option<String>,
+    pub body: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FrameCodec {
+    max_message_size: usize,
+}
+
+impl Default for FrameCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameCodec {
+    pub fn new() -> Self {
+        Self {
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+        }
+    }
+
+    pub fn with_max_message_size(max_message_size: usize) -> Self {
+        Self { max_message_size }
+    }
+
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size
+    }
+
+    /// Header and body for `msg`, ready to be written in one go
+    pub fn encode(&self, msg: &Value) -> Result<Vec<u8>, FrameError> {
+        self.encode_body(&serde_json::to_vec(msg)?, None, None)
+    }
+
+    /// Frame an already encoded body, labelling it with the given headers
+    pub fn encode_body(
+        &self,
+        body: &[u8],
+        content_type: Option<&str>,
+        content_encoding: Option<&str>,
+    ) -> Result<Vec<u8>, FrameError> {
+        if body.len() > self.max_message_size {
+            return Err(FrameError::TooLarge {
+                size: body.len(),
+                max: self.max_message_size,
+            });
+        }
+        let mut frame = format!("Content-Length: {}\r\n", body.len());
+        if let Some(content_type) = content_type {
+            frame.push_str(&format!("Content-Type: {}\r\n", content_type));
+        }
+        if let Some(content_encoding) = content_encoding {
+            frame.push_str(&format!("Content-Encoding: {}\r\n", content_encoding));
+        }
+        frame.push_str("\r\n");
+        let mut frame = frame.into_bytes();
+        frame.extend_from_slice(body);
+        Ok(frame)
+    }
+
+    /// Read one frame's body
+    pub fn read_frame<R: BufRead + ?Sized>(&self, reader: &mut R) -> Result<Vec<u8>, FrameError> {
+        Ok(self.read_message(reader)?.body)
+    }
+
+    /// Read one frame with its headers
+    pub fn read_message<R: BufRead + ?Sized>(&self, reader: &mut R) -> Result<Frame, FrameError> {
+        let mut headers = HeaderParser::default();
+        let mut line = Vec::new();
+        let len = loop {
+            line.clear();
+            let n = reader.take(MAX_HEADER_LINE as u64).read_until(b'\n', &mut line)?;
+            if n == 0 {
+                return Err(if headers.lines == 0 {
+                    FrameError::Closed
+                } else {
+                    FrameError::Truncated
+                });
+            }
+            if let Some(len) = headers.line(&line, self.max_message_size)? {
+                break len;
+            }
+        };
+        let mut body = vec![0u8; len];
+        reader.read_exact(&mut body)?;
+        Ok(headers.into_frame(body))
+    }
+
+    pub fn read_json<R: BufRead + ?Sized>(&self, reader: &mut R) -> Result<Value, FrameError> {
+        Ok(serde_json::from_slice(&self.read_frame(reader)?)?)
+    }
+
+    /// Write one message and flush
+    pub fn write_json<W: Write + ?Sized>(&self, writer: &mut W, msg: &Value) -> Result<(), FrameError> {
+        writer.write_all(&self.encode(msg)?)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub async fn read_frame_async<R: AsyncBufRead + Unpin + ?Sized>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Vec<u8>, FrameError> {
+        Ok(self.read_message_async(reader).await?.body)
+    }
+
+    pub async fn read_message_async<R: AsyncBufRead + Unpin + ?Sized>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Frame, FrameError> {
+        let mut headers = HeaderParser::default();
+        let mut line = Vec::new();
+        let len = loop {
+            line.clear();
+            let n = (&mut *reader)
+                .take(MAX_HEADER_LINE as u64)
+                .read_until(b'\n', &mut line)
+                .await?;
+            if n == 0 {
+                return Err(if headers.lines == 0 {
+                    FrameError::Closed
+                } else {
+                    FrameError::Truncated
+                });
+            }
+            if let Some(len) = headers.line(&line, self.max_message_size)? {
+                break len;
+            }
+        };
+        let mut body = vec![0u8; len];
+        reader.read_exact(&mut body).await?;
+        Ok(headers.into_frame(body))
+    }
+
+    pub async fn read_json_async<R: AsyncBufRead + Unpin + ?Sized>(&self, reader: &mut R) -> Result<Value, FrameError> {
+        Ok(serde_json::from_slice(&self.read_frame_async(reader).await?)?)
+    }
+
+    pub async fn write_json_async<W: AsyncWrite + Unpin + ?Sized>(
+        &self,
+        writer: &mut W,
+        msg: &Value,
+    ) -> Result<(), FrameError> {
+        writer.write_all(&self.encode(msg)?).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    fn read(codec: &FrameCodec, bytes: &[u8]) -> Result<Value, FrameError> {
+        codec.read_json(&mut Cursor::new(bytes))
+    }
+
+    fn read_msg(codec: &FrameCodec, bytes: &[u8]) -> Result<Frame, FrameError> {
+        codec.read_message(&mut Cursor::new(bytes))
+    }
+
+    /// Small deterministic generator so failures reproduce
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    #[test]
+    fn reads_what_it_writes_with_headers_in_any_case() {
+        let codec = FrameCodec::new();
+        let msg = json!({"req": "globals", "seq": 1, "text": "héllo"});
+        let mut buf = Vec::new();
+        codec.write_json(&mut buf, &msg).unwrap();
+        codec.write_json(&mut buf, &json!(2)).unwrap();
+        let mut cursor = Cursor::new(buf);
+        assert_eq!(codec.read_json(&mut cursor).unwrap(), msg);
+        assert_eq!(codec.read_json(&mut cursor).unwrap(), json!(2));
+        assert!(matches!(codec.read_json(&mut cursor), Err(FrameError::Closed)));
+
+        let frame =
+            b"content-length:2\nX-Other: 1\r\nContent-Type: application/vscode-jsonrpc; charset=UTF-8\r\n\r\n{}";
+        assert_eq!(read(&codec, frame).unwrap(), json!({}));
+    }
+
+    #[test]
+    fn binary_frames_carry_their_content_headers() {
+        let codec = FrameCodec::new();
+        let body = [0x81, 0xa1, b'a', 0x01, b'\r', b'\n'];
+        let frame = codec
+            .encode_body(&body, Some("application/msgpack"), Some("zstd"))
+            .unwrap();
+        let read = codec.read_message(&mut Cursor::new(&frame)).unwrap();
+        assert_eq!(read.content_type.as_deref(), Some("application/msgpack"));
+        assert_eq!(read.content_encoding.as_deref(), Some("zstd"));
+        assert_eq!(read.body, body);
+
+        let plain = codec
+            .read_message(&mut Cursor::new(codec.encode(&json!(1)).unwrap()))
+            .unwrap();
+        assert_eq!((plain.content_type, plain.content_encoding), (None, None));
+        let dup = b"Content-Length: 0\r\nContent-Encoding: a\r\nContent-Encoding: a\r\n\r\n";
+        assert!(matches!(read_msg(&codec, dup), Err(FrameError::DuplicateHeader(_))));
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        let codec = FrameCodec::new();
+        // (input, expected variant)
+        let cases: &[(&[u8], &str)] = &[
+            (b"Content-Length: 2\r\nContent-Length: 2\r\n\r\n{}", "DuplicateHeader"),
+            (
+                b"Content-Type: a\r\nContent-Type: a\r\nContent-Length: 2\r\n\r\n{}",
+                "DuplicateHeader",
+            ),
+            (b"Content-Length: -1\r\n\r\n", "InvalidContentLength"),
+            (
+                b"Content-Length: 2\r\nContent-Type: application/json; charset=latin1\r\n\r\n{}",
+                "UnsupportedContentType",
+            ),
+            (b"X-Other: 1\r\n\r\n{}", "MissingContentLength"),
+            (b"no colon here\r\n\r\n", "InvalidHeader"),
+            (b"Content-Length: 2\r\n\r\n{", "Truncated"),
+            (b"Content-Length: 2\r\n\r\n{]", "Json"),
+        ];
+        for (bytes, expected) in cases {
+            let err = read(&codec, bytes).unwrap_err();
+            assert!(
+                format!("{:?}", err).starts_with(expected),
+                "{:?} gave {:?}",
+                String::from_utf8_lossy(bytes),
+                err
+            );
+        }
+
+        // A header line that never ends is cut off rather than buffered forever
+        let mut endless = b"X-Padding: ".to_vec();
+        endless.resize(1024 * 1024, b'a');
+        assert!(matches!(read(&codec, &endless), Err(FrameError::InvalidHeader(_))));
+    }
+
+    #[test]
+    fn oversized_payloads_are_rejected_before_allocating() {
+        let codec = FrameCodec::with_max_message_size(16);
+        let err = read(&codec, b"Content-Length: 18446744073709551615\r\n\r\n").unwrap_err();
+        assert!(matches!(err, FrameError::TooLarge { max: 16, .. }));
+        assert!(matches!(
+            read(&codec, b"Content-Length: 17\r\n\r\n"),
+            Err(FrameError::TooLarge { .. })
+        ));
+        assert!(matches!(
+            codec.encode(&json!("a long string value")),
+            Err(FrameError::TooLarge { .. })
+        ));
+        assert_eq!(
+            read(&codec, b"Content-Length: 16\r\n\r\n\"0123456789abcd\"").unwrap(),
+            "0123456789abcd"
+        );
+    }
+
+    #[test]
+    fn every_truncation_of_a_frame_is_reported() {
+        let codec = FrameCodec::new();
+        let frame = codec.encode(&json!({"req": "symbolLookup", "name": "main"})).unwrap();
+        for cut in 1..frame.len() {
+            let err = read(&codec, &frame[..cut]).unwrap_err();
+            assert!(
+                matches!(err, FrameError::Truncated | FrameError::MissingContentLength),
+                "cut at {} gave {:?}",
+                cut,
+                err
+            );
+            assert!(err.is_disconnect() || matches!(err, FrameError::MissingContentLength));
+        }
+        assert!(matches!(read(&codec, b""), Err(FrameError::Closed)));
+    }
+
+    #[test]
+    fn random_input_never_panics_and_valid_streams_round_trip() {
+        let codec = FrameCodec::with_max_message_size(4096);
+        let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+        let alphabet = b"Content-Length: 0123456789\r\n{}\"";
+        for _ in 0..2000 {
+            let len = rng.below(64);
+            let bytes: Vec<u8> = (0..len).map(|_| alphabet[rng.below(alphabet.len())]).collect();
+            let mut cursor = Cursor::new(bytes);
+            // Whatever happens, the reader makes progress and stops
+            for _ in 0..len + 1 {
+                if codec.read_json(&mut cursor).is_err() {
+                    break;
+                }
+            }
+        }
+
+        for _ in 0..200 {
+            let msgs: Vec<Value> = (0..rng.below(5))
+                .map(|_| json!({"seq": rng.next(), "pad": "x".repeat(rng.below(100))}))
+                .collect();
+            let mut buf = Vec::new();
+            for msg in &msgs {
+                codec.write_json(&mut buf, msg).unwrap();
+            }
+            let mut cursor = Cursor::new(buf);
+            for msg in &msgs {
+                assert_eq!(&codec.read_json(&mut cursor).unwrap(), msg);
+            }
+            assert!(matches!(codec.read_json(&mut cursor), Err(FrameError::Closed)));
+        }
+    }
+
+    #[test]
+    fn async_codec_matches_sync() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let codec = FrameCodec::with_max_message_size(64);
+            let (client, server) = tokio::io::duplex(16);
+            let (server_read, _server_write) = tokio::io::split(server);
+            let mut reader = tokio::io::BufReader::new(server_read);
+            let writer = tokio::spawn(async move {
+                let mut client = client;
+                for seq in 0..3 {
+                    codec.write_json_async(&mut client, &json!({"seq": seq})).await.unwrap();
+                }
+                client.write_all(b"Content-Length: 65\r\n\r\n").await.unwrap();
+            });
+            for seq in 0..3 {
+                assert_eq!(codec.read_json_async(&mut reader).await.unwrap(), json!({"seq": seq}));
+            }
+            assert!(matches!(
+                codec.read_json_async(&mut reader).await,
+                Err(FrameError::TooLarge { size: 65, max: 64 })
+            ));
+            writer.await.unwrap();
+        });
+    }
+}
@@ -14,7 +14,7 @@
 
 //! Cross-platform helpers around [`std::process::Command`].
 
-use std::process::Command;
+use std::process::{Child, Command, ExitStatus};
 
 /// Windows: prevent a console-subsystem child (gdb-server, netstat, objdump, node, ...)
 /// from popping up its own console window.
@@ -41,3 +41,198 @@ pub fn suppress_console_window(cmd: &mut Command) -> &mut Command {
     }
     cmd
 }
+
+/// Unix: start the child in a session of its own, which also makes it the leader of
+/// a new process group whose id is its pid. Everything it forks stays in that group
+/// unless it deliberately leaves, so [`kill_process_group`] can take down the whole
+/// tree — a plain `kill` of the child leaves helpers it spawned (J-Link starts
+/// several) running and holding the USB probe.
+///
+/// No-op elsewhere. On Windows the child's creation flags are already set by
+/// [`suppress_console_window`], and a second `creation_flags` call would replace
+/// them rather than add to them.
+pub fn own_process_group(cmd: &mut Command) -> &mut Command {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // SAFETY: `setsid` is async-signal-safe and runs in the forked child before
+        // exec, touching nothing but the child's own process state.
+        unsafe {
+            cmd.pre_exec(|| {
+                if libc::setsid() == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+    cmd
+}
+
+/// Kill every process in the group `child` leads (see [`own_process_group`]).
+///
+/// Only while `child` is unreaped: until then its pid, and so the group's id, cannot be
+/// reused even if it has exited. Once a `wait` has reaped it the id may already belong
+/// to an unrelated group, so this does nothing; reap with [`try_wait_group`] to kill the
+/// group first. Best-effort and quiet otherwise, as the group may already be empty.
+/// No-op on non-Unix platforms.
+pub fn kill_process_group(child: &Child) {
+    #[cfg(unix)]
+    {
+        let Ok(pgid) = libc::pid_t::try_from(child.id()) else {
+            return;
+        };
+        // Never signal group 0 (our own) or 1; a pid that small is not a child's.
+        if pgid > 1 && peek_exited(pgid).is_some() {
+            // SAFETY: plain syscall; an unknown group just fails with ESRCH.
+            unsafe {
+                libc::killpg(pgid, libc::SIGKILL);
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = child;
+}
+
+/// [`Child::try_wait`] that kills what is left of the child's process group before it
+/// reaps the child, while the group's id is still the child's own.
+pub fn try_wait_group(child: &mut Child) -> std::io::Result<Option<ExitStatus>> {
+    #[cfg(unix)]
+    if libc::pid_t::try_from(child.id()).is_ok_and(|pid| peek_exited(pid) == Some(true)) {
+        kill_process_group(child);
+    }
+    child.try_wait()
+}
+
+/// Whether our child `pid` has exited, without reaping it: `None` when it is not an
+/// unreaped child of ours (any more).
+#[cfg(unix)]
+fn peek_exited(pid: libc::pid_t) -> Option<bool> {
+    // SAFETY: an all-zero `siginfo_t` is valid, and `WNOWAIT` leaves the child waitable.
+    unsafe {
+        let mut info: libc::siginfo_t = std::mem::zeroed();
+        let flags = libc::WEXITED | libc::WNOHANG | libc::WNOWAIT;
+        if libc::waitid(libc::P_PID, pid as libc::id_t, &mut info, flags) != 0 {
+            return None;
+        }
+        // With WNOHANG a child that is still running leaves `si_pid` zero.
+        Some(info.si_pid() != 0)
+    }
+}
+
+/// Unix: apply `setrlimit` limits in the child before exec, so the child and every
+/// process it forks inherit them. `cpu_secs` caps CPU time (`RLIMIT_CPU`);
+/// `address_space_bytes` caps virtual memory (`RLIMIT_AS`).
+///
+/// Returns `false` when limits were asked for on a platform that cannot apply them,
+/// so the caller can say so; the command is left as it was.
+pub fn limit_resources(cmd: &mut Command, cpu_secs: Option<u64>, address_space_bytes: Option<u64>) -> bool {
+    if cpu_secs.is_none() && address_space_bytes.is_none() {
+        return true;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // SAFETY: `setrlimit` is async-signal-safe, and the closure only reads the
+        // two integers it captured by value.
+        unsafe {
+            cmd.pre_exec(move || {
+                let limits = [(libc::RLIMIT_CPU, cpu_secs), (libc::RLIMIT_AS, address_space_bytes)];
+                for (resource, value) in limits {
+                    let Some(value) = value else { continue };
+                    let limit = libc::rlimit {
+                        rlim_cur: value as libc::rlim_t,
+                        rlim_max: value as libc::rlim_t,
+                    };
+                    if libc::setrlimit(resource, &limit) == -1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+        true
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = cmd;
+        false
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::time::{Duration, Instant};
+
+    /// Alive and not a zombie: an orphan's zombie is only waiting for a reaper that a
+    /// container may not have, and holds nothing.
+    fn running(pid: u32) -> bool {
+        match std::fs::read_to_string(format!("/proc/{pid}/stat")) {
+            Ok(stat) => !stat.rsplit(')').next().unwrap_or("").trim_start().starts_with('Z'),
+            Err(_) => false,
+        }
+    }
+
+    fn assert_dies(pid: u32) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while running(pid) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(!running(pid), "helper {pid} outlived its process group");
+    }
+
+    #[test]
+    fn killing_the_group_takes_forked_children_with_it() {
+        // The shell forks a long sleeper and reports its pid, like a server starting
+        // a helper process.
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "sleep 30 & echo $!; wait"]).stdout(Stdio::piped());
+        own_process_group(&mut cmd);
+        let mut child = cmd.spawn().unwrap();
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let helper: u32 = line.trim().parse().unwrap();
+        assert!(running(helper));
+
+        kill_process_group(&child);
+        child.wait().unwrap();
+        assert_dies(helper);
+    }
+
+    #[test]
+    fn reaping_an_exited_leader_kills_the_rest_of_its_group() {
+        // The leader exits at once and leaves its helper behind.
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "sleep 30 & echo $!"]).stdout(Stdio::piped());
+        own_process_group(&mut cmd);
+        let mut child = cmd.spawn().unwrap();
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let helper: u32 = line.trim().parse().unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while try_wait_group(&mut child).unwrap().is_none() {
+            assert!(Instant::now() < deadline, "the leader never exited");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_dies(helper);
+        // Reaped: the id is no longer the child's to signal.
+        assert_eq!(peek_exited(child.id() as libc::pid_t), None);
+    }
+
+    #[test]
+    fn resource_limits_reach_the_child() {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "ulimit -t; ulimit -v"]);
+        assert!(limit_resources(&mut cmd, Some(7), Some(512 * 1024 * 1024)));
+        let out = cmd.output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&out.stdout), "7\n524288\n");
+    }
+}
@@ -12,14 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! Blocking JSON transports built on the shared Content-Length codec in `framing`.
+
 use serde_json::Value;
-use std::error::Error;
-use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
 use std::net::{TcpListener, TcpStream};
+use std::sync::Mutex;
+
+use crate::common::framing::{FrameCodec, FrameError};
+use crate::common::sync::MutexExt;
 
 pub trait Transport {
-    fn read_message(&mut self) -> Result<Value, Box<dyn Error + Send + Sync>>;
-    fn write_message(&mut self, msg: &Value) -> Result<(), Box<dyn Error + Send + Sync>>;
+    fn read_message(&mut self) -> Result<Value, FrameError>;
+    fn write_message(&mut self, msg: &Value) -> Result<(), FrameError>;
 }
 
 // Stdio-based transport (suitable for child-process JSON-RPC/DAP)
@@ -42,39 +47,13 @@ impl Default for StdioTransport {
 }
 
 impl Transport for StdioTransport {
-    fn read_message(&mut self) -> Result<Value, Box<dyn Error + Send + Sync>> {
-        // Read headers until an empty line
-        let mut content_length: Option<usize> = None;
-        loop {
-            let mut header_line = String::new();
-            let n = self.reader.read_line(&mut header_line)?;
-            if n == 0 {
-                return Err("EOF while reading header".into());
-            }
-            let header_trim = header_line.trim();
-            if header_trim.is_empty() {
-                break; // end of headers
-            }
-            if header_trim.to_lowercase().starts_with("content-length") {
-                if let Some(idx) = header_trim.find(':') {
-                    let num = header_trim[idx + 1..].trim();
-                    content_length = Some(num.parse::<usize>()?);
-                }
-            }
-            // ignore other headers
-        }
-
-        let len = content_length.ok_or("Missing Content-Length header")?;
-        let mut buf = vec![0u8; len];
-        self.reader.read_exact(&mut buf)?;
-        let v: Value = serde_json::from_slice(&buf)?;
-        Ok(v)
+    fn read_message(&mut self) -> Result<Value, FrameError> {
+        read_framed_json(&mut self.reader)
     }
 
-    fn write_message(&mut self, msg: &Value) -> Result<(), Box<dyn Error + Send + Sync>> {
+    fn write_message(&mut self, msg: &Value) -> Result<(), FrameError> {
         // Use stdout's built-in locking for thread safety
-        write_json_locked(msg)?;
-        Ok(())
+        write_json_locked(msg)
     }
 }
 
@@ -86,7 +65,7 @@ pub struct TcpTransport {
 
 impl TcpTransport {
     /// Connects to a server at `addr` (eg "127.0.0.1:9257")
-    pub fn connect(addr: &str) -> Result<Self, Box<dyn Error>> {
+    pub fn connect(addr: &str) -> io::Result<Self> {
         let stream = TcpStream::connect(addr)?;
         let reader = BufReader::new(stream.try_clone()?);
         let writer = BufWriter::new(stream);
@@ -94,7 +73,7 @@ impl TcpTransport {
     }
 
     /// Bind to `addr`, accept a single connection and return a transport.
-    pub fn listen_and_accept(addr: &str) -> Result<Self, Box<dyn Error>> {
+    pub fn listen_and_accept(addr: &str) -> io::Result<Self> {
         let listener = TcpListener::bind(addr)?;
         let (stream, _peer) = listener.accept()?;
         let reader = BufReader::new(stream.try_clone()?);
@@ -104,55 +83,77 @@ impl TcpTransport {
 }
 
 impl Transport for TcpTransport {
-    fn read_message(&mut self) -> Result<Value, Box<dyn Error + Send + Sync>> {
-        let mut content_length: Option<usize> = None;
-        loop {
-            let mut header_line = String::new();
-            let n = self.reader.read_line(&mut header_line)?;
-            if n == 0 {
-                return Err("EOF while reading header".into());
-            }
-            let header_trim = header_line.trim();
-            if header_trim.is_empty() {
-                break;
-            }
-            if header_trim.to_lowercase().starts_with("content-length") {
-                if let Some(idx) = header_trim.find(':') {
-                    let num = header_trim[idx + 1..].trim();
-                    content_length = Some(num.parse::<usize>()?);
-                }
-            }
-        }
-        let len = content_length.ok_or("Missing Content-Length header")?;
-        let mut buf = vec![0u8; len];
-        self.reader.read_exact(&mut buf)?;
-        let v: Value = serde_json::from_slice(&buf)?;
-        Ok(v)
+    fn read_message(&mut self) -> Result<Value, FrameError> {
+        read_framed_json(&mut self.reader)
     }
 
-    fn write_message(&mut self, msg: &Value) -> Result<(), Box<dyn Error + Send + Sync>> {
-        // Use the helper to perform the locked write if needed. For TCP we don't
-        // use the global stdout lock, so just serialize and write.
-        let body = serde_json::to_vec(msg)?;
-        write!(self.writer, "Content-Length: {}\r\n\r\n", body.len())?;
-        self.writer.write_all(&body)?;
-        self.writer.flush()?;
-        Ok(())
+    fn write_message(&mut self, msg: &Value) -> Result<(), FrameError> {
+        // For TCP we don't use the global stdout lock, so just serialize and write.
+        write_framed_json(&mut self.writer, msg)
     }
 }
 
+/// Read one Content-Length framed JSON message with the default codec limits.
+pub fn read_framed_json<R: BufRead + ?Sized>(reader: &mut R) -> Result<Value, FrameError> {
+    FrameCodec::new().read_json(reader)
+}
+
+/// Write one JSON message with Content-Length framing and flush.
+pub fn write_framed_json<W: Write + ?Sized>(writer: &mut W, msg: &Value) -> Result<(), FrameError> {
+    FrameCodec::new().write_json(writer, msg)
+}
+
 /// Helper to write a JSON `Value` to stdout using stdout's built-in lock.
 ///
 /// Rust's `io::stdout().lock()` provides process-wide synchronization,
 /// ensuring writes from different threads don't interleave. This function
 /// serializes the message first, then acquires stdout's lock only for the
 /// actual write (header + body + flush) to minimize the critical section.
-pub fn write_json_locked(msg: &Value) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let body = serde_json::to_vec(msg)?;
+pub fn write_json_locked(msg: &Value) -> Result<(), FrameError> {
+    write_frame_locked(&FrameCodec::new().encode(msg)?)
+}
+
+/// Write an already encoded frame to stdout under the same lock as `write_json_locked`
+pub fn write_frame_locked(frame: &[u8]) -> Result<(), FrameError> {
     let stdout = io::stdout();
     let mut w = stdout.lock(); // Process-wide lock on stdout
-    write!(w, "Content-Length: {}\r\n\r\n", body.len())?;
-    w.write_all(&body)?;
+    w.write_all(frame)?;
     w.flush()?;
     Ok(())
 }
+
+/// Any writer shared between threads, with the same encode-then-lock behaviour as
+/// `write_json_locked` (e.g. a client socket written by both responses and notifications).
+pub struct LockedWriter<W: Write> {
+    codec: FrameCodec,
+    writer: Mutex<W>,
+}
+
+impl<W: Write> LockedWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self::with_codec(writer, FrameCodec::new())
+    }
+
+    pub fn with_codec(writer: W, codec: FrameCodec) -> Self {
+        Self {
+            codec,
+            writer: Mutex::new(writer),
+        }
+    }
+
+    pub fn write_json(&self, msg: &Value) -> Result<(), FrameError> {
+        self.write_frame(&self.codec.encode(msg)?)
+    }
+
+    pub fn codec(&self) -> &FrameCodec {
+        &self.codec
+    }
+
+    /// Write an already encoded frame
+    pub fn write_frame(&self, frame: &[u8]) -> Result<(), FrameError> {
+        let mut w = self.writer.lock_recover();
+        w.write_all(frame)?;
+        w.flush()?;
+        Ok(())
+    }
+}
@@ -44,9 +44,10 @@ pub fn canonicalize_path(source_path: &str) -> String {
         }
     }
 
-    // 3. Resolve to absolute path
+    // 3. Resolve to absolute path. Drive paths count as absolute on every host, since DWARF
+    // from a Windows build (or a WSL path converted above) is not relative to our cwd
     let path = Path::new(&path_str);
-    let absolute = if path.is_absolute() {
+    let absolute = if path.is_absolute() || has_drive_letter(&path_str) {
         path.to_path_buf()
     } else {
         env::current_dir().unwrap_or_default().join(path)
@@ -60,9 +61,9 @@ pub fn canonicalize_path(source_path: &str) -> String {
     let mut final_path = canonical.to_string_lossy().replace('\\', "/");
 
     // Windows Drive Letter Normalization (C:/ not c:/)
-    if cfg!(windows) && final_path.chars().nth(1) == Some(':') {
+    if has_drive_letter(&final_path) {
         let drive = final_path.chars().next().unwrap().to_uppercase();
-        final_path = format!("{}:{}", drive, &final_path[1..]);
+        final_path = format!("{}{}", drive, &final_path[1..]);
     }
 
     // UNC Path Normalization (//SERVER/SHARE/path)
@@ -84,9 +85,14 @@ pub fn canonicalize_path(source_path: &str) -> String {
     final_path
 }
 
+/// True for `C:/...` or `C:\...` style paths, regardless of the host OS.
+fn has_drive_letter(path: &str) -> bool {
+    let b = path.as_bytes();
+    b.len() >= 3 && b[0].is_ascii_alphabetic() && b[1] == b':' && (b[2] == b'/' || b[2] == b'\\')
+}
+
 pub fn is_absolute_path(path: &str) -> bool {
-    let path = Path::new(path);
-    path.is_absolute()
+    has_drive_letter(path) || Path::new(path).is_absolute()
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -105,8 +111,48 @@ impl CanonicalPath {
     }
 }
 
+/// Lowercase hex, two digits per byte and no separators
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Bytes from hex digits (either case), two per byte; None for an odd length or a non-hex digit
+pub fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
 impl std::fmt::Display for CanonicalPath {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wsl_mount_paths_become_drive_paths() {
+        assert_eq!(canonicalize_path("/mnt/c/Users/me/proj/main.c"), "C:/Users/me/proj/main.c");
+        assert_eq!(canonicalize_path("file:///mnt/e/a%20b/c.c"), "E:/a b/c.c");
+        // Not a drive mount
+        #[cfg(unix)]
+        assert_eq!(canonicalize_path("/mnt/data/x.c"), "/mnt/data/x.c");
+    }
+
+    #[test]
+    fn windows_drive_letters() {
+        assert_eq!(canonicalize_path("c:/work/main.c"), "C:/work/main.c");
+        assert_eq!(canonicalize_path("C:\\work\\src\\main.c"), "C:/work/src/main.c");
+        assert!(is_absolute_path("d:\\x.c"));
+        assert!(is_absolute_path("/usr/src/x.c"));
+        assert!(!is_absolute_path("src/x.c"));
+        assert_eq!(CanonicalPath::new("c:\\a\\b.c"), CanonicalPath::new("/mnt/c/a/b.c"));
+    }
+}
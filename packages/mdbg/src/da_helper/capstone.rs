@@ -12,8 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! Thumb disassembly with Capstone, for code that only exists as bytes (a loaded HEX or binary
+//! image); ELF files are disassembled with objdump.
+
 use capstone::prelude::*;
 
+use crate::da_helper::image::MemoryImage;
+
 pub struct Disassembler {
     cs: Capstone,
 }
@@ -23,8 +28,8 @@ impl Disassembler {
         // For Cortex-M, we use ARM + Thumb mode
         let cs = Capstone::new()
             .arm()
-            .mode(arch::arm::Mode::Thumb)
-            .extra_mode([arch::arm::ExtraMode::V8].iter().copied())
+            .mode(arch::arm::ArchMode::Thumb)
+            .extra_mode([arch::arm::ArchExtraMode::MClass, arch::arm::ArchExtraMode::V8].iter().copied())
             .detail(true) // Required for getting instruction sizes/registers
             .build()?;
 
@@ -52,6 +57,43 @@ impl Disassembler {
 
         Ok(results)
     }
+
+    /// Disassemble what `image` holds in [start, end), run by run; gaps are skipped. Bytes that
+    /// don't decode are shown as `.short` data and disassembly goes on after them.
+    pub fn disassemble_image(
+        &self,
+        image: &MemoryImage,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<InstructionData>, capstone::Error> {
+        let mut results = Vec::new();
+        for (run_start, bytes) in image.clip(start, end).runs() {
+            let mut offset = 0;
+            while offset < bytes.len() {
+                let address = run_start + offset as u64;
+                let decoded = self.disassemble_block(&bytes[offset..], address)?;
+                offset += decoded.iter().map(|i| i.size as usize).sum::<usize>();
+                results.extend(decoded);
+                if offset < bytes.len() {
+                    // Capstone stops at the first bytes it can't decode
+                    let data = &bytes[offset..(offset + 2).min(bytes.len())];
+                    let (mnemonic, op_str) = match data {
+                        [lo, hi] => (".short", format!("0x{:04x}", u16::from_le_bytes([*lo, *hi]))),
+                        _ => (".byte", format!("0x{:02x}", data[0])),
+                    };
+                    results.push(InstructionData {
+                        address: run_start + offset as u64,
+                        size: data.len() as u8,
+                        mnemonic: mnemonic.to_string(),
+                        op_str,
+                        bytes: data.to_vec(),
+                    });
+                    offset += data.len();
+                }
+            }
+        }
+        Ok(results)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -62,3 +104,46 @@ pub struct InstructionData {
     pub op_str: String,
     pub bytes: Vec<u8>,
 }
+
+impl InstructionData {
+    /// The bytes as objdump shows Thumb code: little-endian halfwords
+    pub fn objdump_bytes(&self) -> String {
+        self.bytes
+            .chunks(2)
+            .map(|pair| match pair {
+                [lo, hi] => format!("{:04x}", u16::from_le_bytes([*lo, *hi])),
+                _ => format!("{:02x}", pair[0]),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disassembles_an_image_across_gaps_and_data() {
+        let mut image = MemoryImage::new();
+        // nop; bl +0; then a halfword that starts a 32-bit encoding but ends the run
+        image.write(0x0800_0000, &[0x00, 0xbf, 0x00, 0xf0, 0x00, 0xf8, 0xff, 0xff]);
+        image.write(0x0800_0100, &[0x70, 0x47]); // bx lr
+        let instructions = Disassembler::new()
+            .unwrap()
+            .disassemble_image(&image, 0x0800_0000, 0x0800_0200)
+            .unwrap();
+        let shown: Vec<_> = instructions
+            .iter()
+            .map(|i| (i.address, i.objdump_bytes(), i.mnemonic.as_str()))
+            .collect();
+        assert_eq!(
+            shown,
+            vec![
+                (0x0800_0000, "bf00".to_string(), "nop"),
+                (0x0800_0002, "f000f800".to_string(), "bl"),
+                (0x0800_0006, "ffff".to_string(), ".short"),
+                (0x0800_0100, "4770".to_string(), "bx"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,532 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Basic-block and control-flow graph construction for a single function.
+//!
+//! We work from the objdump text already held in an `AssemblyBlock`, so the branch
+//! classification below is textual. It understands the ARM/Thumb mnemonics objdump
+//! prints for Cortex-M/A code; anything it does not recognize is treated as a plain
+//! instruction, which at worst merges two basic blocks.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+use std::rc::Rc;
+
+use crate::da_helper::elf_items::FileTable;
+use crate::da_helper::get_assembly::{AssemblyBlock, AssemblyLine};
+use crate::da_helper::helper_requests::{CfgBlock, CfgEdge, CfgEdgeKind, CfgGraph, SerInstruction};
+
+const CONDITIONS: [&str; 16] = [
+    "eq", "ne", "cs", "hs", "cc", "lo", "mi", "pl", "vs", "vc", "hi", "ls", "ge", "lt", "gt", "le",
+];
+
+/// What an instruction does to control flow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BranchKind {
+    /// Not a branch; execution continues with the next instruction
+    None,
+    Jump(Option<BranchTarget>),
+    Conditional(Option<BranchTarget>),
+    Call(Option<BranchTarget>),
+    /// `conditional` is set for predicated returns (`bxeq lr` inside an IT block), which can fall through
+    Return {
+        conditional: bool,
+    },
+    Indirect,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchTarget {
+    pub address: u64,
+    pub symbol: Option<String>,
+}
+
+/// Classify an objdump instruction string such as `"beq.n\t8000124 <main+0x10>"`.
+pub fn classify_instruction(instruction: &str) -> BranchKind {
+    let instruction = instruction.trim();
+    let (mnemonic, operands) = match instruction.find(|c: char| c.is_whitespace()) {
+        Some(ix) => (&instruction[..ix], instruction[ix..].trim()),
+        None => (instruction, ""),
+    };
+    let mnemonic = mnemonic.to_ascii_lowercase();
+    let mnemonic = mnemonic
+        .strip_suffix(".n")
+        .or_else(|| mnemonic.strip_suffix(".w"))
+        .unwrap_or(&mnemonic);
+    // Strip an optional condition code; predicated forms only appear inside IT blocks on Thumb
+    let (base, conditional) = split_condition(mnemonic);
+    let writes_pc = first_operand(operands).eq_ignore_ascii_case("pc");
+
+    match base {
+        "b" => {
+            if conditional {
+                BranchKind::Conditional(parse_target(operands))
+            } else {
+                BranchKind::Jump(parse_target(operands))
+            }
+        }
+        "cbz" | "cbnz" => BranchKind::Conditional(parse_target(operands)),
+        "bl" => BranchKind::Call(parse_target(operands)),
+        "blx" => BranchKind::Call(parse_target(operands)),
+        "bx" if operands.eq_ignore_ascii_case("lr") => BranchKind::Return { conditional },
+        "bx" => BranchKind::Indirect,
+        "tbb" | "tbh" => BranchKind::Indirect,
+        "pop" | "ldm" | "ldmia" | "ldmfd" if operands_include_pc(operands) => BranchKind::Return { conditional },
+        "mov" if writes_pc && operands.to_ascii_lowercase().ends_with("lr") => BranchKind::Return { conditional },
+        "mov" | "ldr" | "add" if writes_pc => BranchKind::Indirect,
+        _ => BranchKind::None,
+    }
+}
+
+fn split_condition(mnemonic: &str) -> (&str, bool) {
+    // Order matters: try the full mnemonic first so "bl" and "bls" don't get confused
+    for base in [
+        "cbnz", "cbz", "ldmia", "ldmfd", "ldm", "pop", "blx", "bl", "bx", "tbb", "tbh", "mov", "ldr", "add", "b",
+    ] {
+        if mnemonic == base {
+            return (base, false);
+        }
+    }
+    for base in [
+        "blx", "bl", "bx", "ldmia", "ldmfd", "ldm", "pop", "mov", "ldr", "add", "b",
+    ] {
+        if let Some(cond) = mnemonic.strip_prefix(base) {
+            if CONDITIONS.contains(&cond) {
+                return (base, true);
+            }
+            if cond == "al" {
+                return (base, false);
+            }
+        }
+    }
+    (mnemonic, false)
+}
+
+fn first_operand(operands: &str) -> &str {
+    operands.split(',').next().unwrap_or("").trim()
+}
+
+fn operands_include_pc(operands: &str) -> bool {
+    let Some(open) = operands.find('{') else {
+        return false;
+    };
+    operands[open + 1..]
+        .trim_end_matches('}')
+        .split([',', '}'])
+        .any(|r| r.trim().eq_ignore_ascii_case("pc"))
+}
+
+/// objdump prints direct targets as `<hex> <symbol+0xoff>`; the hex is the last operand before the `<`.
+fn parse_target(operands: &str) -> Option<BranchTarget> {
+    let (addr_part, symbol) = match operands.find('<') {
+        Some(ix) => {
+            let sym = operands[ix + 1..].trim_end_matches('>');
+            let sym = sym.split('+').next().unwrap_or(sym);
+            (&operands[..ix], Some(sym.to_string()))
+        }
+        None => (operands, None),
+    };
+    let last = addr_part.rsplit([',', ' ', '\t']).find(|s| !s.is_empty())?;
+    let last = last.trim_start_matches("0x");
+    let address = u64::from_str_radix(last, 16).ok()?;
+    Some(BranchTarget { address, symbol })
+}
+
+fn instruction_size(line: &AssemblyLine) -> u64 {
+    (line.bytes.len() as u64 / 2).max(1)
+}
+
+/// A run of instructions with a single entry (the first) and a single exit (the last).
+pub struct BasicBlock {
+    pub id: u32,
+    pub lines: Vec<Rc<AssemblyLine>>,
+}
+
+impl BasicBlock {
+    pub fn start(&self) -> u64 {
+        self.lines.first().map(|l| l.address).unwrap_or(0)
+    }
+
+    pub fn end(&self) -> u64 {
+        self.lines.last().map(|l| l.address + instruction_size(l)).unwrap_or(0)
+    }
+}
+
+pub struct ControlFlowGraph {
+    pub function: String,
+    pub start_address: u64,
+    pub blocks: Vec<BasicBlock>,
+    pub edges: Vec<CfgEdge>,
+}
+
+impl ControlFlowGraph {
+    /// Split a function's instructions into basic blocks at branch targets and after
+    /// every block-ending instruction, then connect the blocks.
+    pub fn from_assembly_block(func: &AssemblyBlock) -> Self {
+        let lines = &func.lines;
+        let kinds: Vec<BranchKind> = lines.iter().map(|l| classify_instruction(&l.instruction)).collect();
+        let addresses: BTreeSet<u64> = lines.iter().map(|l| l.address).collect();
+
+        // 1. Leaders: the entry, every in-function branch target, and whatever follows a block-ending instruction
+        let mut leaders: BTreeSet<u64> = BTreeSet::new();
+        if let Some(first) = lines.first() {
+            leaders.insert(first.address);
+        }
+        for (ix, kind) in kinds.iter().enumerate() {
+            let (ends_block, target) = match kind {
+                BranchKind::Jump(t) | BranchKind::Conditional(t) => (true, t.as_ref()),
+                BranchKind::Return { .. } | BranchKind::Indirect => (true, None),
+                BranchKind::None | BranchKind::Call(_) => (false, None),
+            };
+            if let Some(t) = target {
+                if addresses.contains(&t.address) {
+                    leaders.insert(t.address);
+                }
+            }
+            if ends_block {
+                if let Some(next) = lines.get(ix + 1) {
+                    leaders.insert(next.address);
+                }
+            }
+        }
+
+        // 2. Blocks
+        let mut blocks: Vec<BasicBlock> = Vec::new();
+        let mut block_kinds: Vec<&BranchKind> = Vec::new();
+        for (line, kind) in lines.iter().zip(kinds.iter()) {
+            if leaders.contains(&line.address) || blocks.is_empty() {
+                blocks.push(BasicBlock {
+                    id: blocks.len() as u32,
+                    lines: Vec::new(),
+                });
+                block_kinds.push(&BranchKind::None);
+            }
+            let block = blocks.last_mut().expect("a block was just pushed");
+            block.lines.push(Rc::clone(line));
+            *block_kinds.last_mut().expect("kinds track blocks") = kind;
+        }
+        let block_by_addr: BTreeMap<u64, u32> = blocks.iter().map(|b| (b.start(), b.id)).collect();
+
+        // 3. Edges. Calls are recorded where they occur; the block terminator decides the rest.
+        let mut edges: Vec<CfgEdge> = Vec::new();
+        for (block, terminator) in blocks.iter().zip(block_kinds.iter()) {
+            for line in &block.lines {
+                if let BranchKind::Call(target) = classify_instruction(&line.instruction) {
+                    edges.push(make_edge(block.id, CfgEdgeKind::Call, target.as_ref(), &block_by_addr));
+                }
+            }
+            let next = block_by_addr.get(&block.end()).copied().or_else(|| {
+                // Fall through to the next block in address order even if there is padding between them
+                blocks.get(block.id as usize + 1).map(|b| b.id)
+            });
+            let fallthrough = CfgEdge {
+                from: block.id,
+                to: next,
+                kind: CfgEdgeKind::Fallthrough,
+                target: None,
+                symbol: None,
+            };
+            match terminator {
+                BranchKind::Jump(t) => {
+                    edges.push(make_edge(block.id, CfgEdgeKind::Jump, t.as_ref(), &block_by_addr));
+                }
+                BranchKind::Conditional(t) => {
+                    edges.push(make_edge(
+                        block.id,
+                        CfgEdgeKind::Conditional,
+                        t.as_ref(),
+                        &block_by_addr,
+                    ));
+                    if next.is_some() {
+                        edges.push(fallthrough);
+                    }
+                }
+                BranchKind::Return { conditional } => {
+                    edges.push(make_edge(block.id, CfgEdgeKind::Return, None, &block_by_addr));
+                    if *conditional && next.is_some() {
+                        edges.push(fallthrough);
+                    }
+                }
+                BranchKind::Indirect => {
+                    edges.push(make_edge(block.id, CfgEdgeKind::Indirect, None, &block_by_addr));
+                }
+                BranchKind::None | BranchKind::Call(_) => {
+                    if next.is_some() {
+                        edges.push(fallthrough);
+                    }
+                }
+            }
+        }
+
+        Self {
+            function: func.name.clone(),
+            start_address: func.start_address,
+            blocks,
+            edges,
+        }
+    }
+
+    /// Convert to the wire representation. `file_table` resolves the file ids on each instruction.
+    pub fn to_graph(&self, file_table: Option<&FileTable>) -> CfgGraph {
+        let mut files: HashMap<u32, String> = HashMap::new();
+        let blocks = self
+            .blocks
+            .iter()
+            .map(|b| {
+                for line in &b.lines {
+                    let file_id = line.file_id.get();
+                    if file_id >= 0 {
+                        files.entry(file_id as u32).or_insert_with(|| {
+                            file_table
+                                .and_then(|ft| ft.get_by_id(file_id as u32))
+                                .cloned()
+                                .unwrap_or_else(|| format!("file_{}", file_id))
+                        });
+                    }
+                }
+                CfgBlock {
+                    id: b.id,
+                    start: format!("{:x}", b.start()),
+                    end: format!("{:x}", b.end()),
+                    instructions: b.lines.iter().map(|l| SerInstruction::from_assembly_line(l)).collect(),
+                }
+            })
+            .collect();
+        CfgGraph {
+            function: self.function.clone(),
+            start: format!("{:x}", self.start_address),
+            file_table: files,
+            blocks,
+            edges: self
+                .edges
+                .iter()
+                .map(|e| CfgEdge {
+                    from: e.from,
+                    to: e.to,
+                    kind: e.kind,
+                    target: e.target.clone(),
+                    symbol: e.symbol.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Render as a Graphviz digraph. Edges leaving the function go to ellipse nodes
+    /// named after their target (or a shared `exit` node for returns).
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph \"{}\" {{", dot_escape(&self.function));
+        let _ = writeln!(out, "    node [shape=box, fontname=\"monospace\"];");
+        for block in &self.blocks {
+            let mut label = String::new();
+            for line in &block.lines {
+                let _ = write!(
+                    label,
+                    "{:x}: {}\\l",
+                    line.address,
+                    dot_escape(&line.instruction.replace('\t', " "))
+                );
+            }
+            let _ = writeln!(out, "    b{} [label=\"{}\"];", block.id, label);
+        }
+        let mut external: BTreeSet<String> = BTreeSet::new();
+        for edge in &self.edges {
+            let dest = match edge.to {
+                Some(to) => format!("b{}", to),
+                None => {
+                    let name = match edge.kind {
+                        CfgEdgeKind::Return => "exit".to_string(),
+                        CfgEdgeKind::Indirect => "indirect".to_string(),
+                        _ => edge
+                            .symbol
+                            .clone()
+                            .or_else(|| edge.target.as_ref().map(|t| format!("0x{}", t)))
+                            .unwrap_or_else(|| "unknown".to_string()),
+                    };
+                    let node = format!("\"{}\"", dot_escape(&name));
+                    external.insert(node.clone());
+                    node
+                }
+            };
+            let style = match edge.kind {
+                CfgEdgeKind::Fallthrough => "",
+                CfgEdgeKind::Jump => " [label=\"jump\"]",
+                CfgEdgeKind::Conditional => " [label=\"taken\", color=\"darkgreen\"]",
+                CfgEdgeKind::Call => " [label=\"call\", style=dashed]",
+                CfgEdgeKind::Return => " [label=\"return\", color=\"blue\"]",
+                CfgEdgeKind::Indirect => " [style=dotted]",
+            };
+            let _ = writeln!(out, "    b{} -> {}{};", edge.from, dest, style);
+        }
+        for node in external {
+            let _ = writeln!(out, "    {} [shape=ellipse];", node);
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn make_edge(
+    from: u32,
+    kind: CfgEdgeKind,
+    target: Option<&BranchTarget>,
+    block_by_addr: &BTreeMap<u64, u32>,
+) -> CfgEdge {
+    CfgEdge {
+        from,
+        to: target.and_then(|t| block_by_addr.get(&t.address).copied()),
+        kind,
+        target: target.map(|t| format!("{:x}", t.address)),
+        symbol: target.and_then(|t| t.symbol.clone()),
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn func(name: &str, start: u64, instrs: &[(u64, &str, &str)]) -> AssemblyBlock {
+        let mut block = AssemblyBlock::new(name.to_string(), start, 0);
+        for (addr, bytes, text) in instrs {
+            block.lines.push(Rc::new(AssemblyLine::new(
+                *addr,
+                bytes.to_string(),
+                text.to_string(),
+                String::new(),
+                0,
+                (*addr - start) as u32,
+            )));
+        }
+        block
+    }
+
+    #[test]
+    fn classify_thumb_branches() {
+        assert_eq!(
+            classify_instruction("b.n\t8000124 <main+0x10>"),
+            BranchKind::Jump(Some(BranchTarget {
+                address: 0x8000124,
+                symbol: Some("main".to_string())
+            }))
+        );
+        assert!(matches!(
+            classify_instruction("bne.n\t8000130 <main+0x1c>"),
+            BranchKind::Conditional(Some(_))
+        ));
+        assert!(matches!(
+            classify_instruction("cbz\tr3, 8000140 <main+0x2c>"),
+            BranchKind::Conditional(Some(BranchTarget { address: 0x8000140, .. }))
+        ));
+        assert!(matches!(
+            classify_instruction("bl\t8000310 <HAL_Init>"),
+            BranchKind::Call(Some(_))
+        ));
+        assert_eq!(
+            classify_instruction("bls.n\t8000100"),
+            BranchKind::Conditional(Some(BranchTarget {
+                address: 0x8000100,
+                symbol: None
+            }))
+        );
+        assert_eq!(classify_instruction("blx\tr3"), BranchKind::Call(None));
+        assert_eq!(
+            classify_instruction("bx\tlr"),
+            BranchKind::Return { conditional: false }
+        );
+        assert_eq!(
+            classify_instruction("bxeq\tlr"),
+            BranchKind::Return { conditional: true }
+        );
+        assert_eq!(
+            classify_instruction("pop\t{r4, r7, pc}"),
+            BranchKind::Return { conditional: false }
+        );
+        assert_eq!(
+            classify_instruction("ldmia.w\tsp!, {r4, pc}"),
+            BranchKind::Return { conditional: false }
+        );
+        assert_eq!(classify_instruction("pop\t{r4, r7}"), BranchKind::None);
+        assert_eq!(classify_instruction("tbb\t[pc, r3]"), BranchKind::Indirect);
+        assert_eq!(classify_instruction("ldr.w\tpc, [sp], #4"), BranchKind::Indirect);
+        assert_eq!(classify_instruction("movs\tr0, #0"), BranchKind::None);
+        assert_eq!(classify_instruction("bic.w\tr3, r3, #1"), BranchKind::None);
+    }
+
+    #[test]
+    fn loop_with_call_and_return() {
+        // for (i = 0; i < n; i++) work(); return;
+        let f = func(
+            "loop",
+            0x100,
+            &[
+                (0x100, "b580", "push\t{r7, lr}"),
+                (0x102, "2400", "movs\tr4, #0"),
+                (0x104, "e002", "b.n\t10c <loop+0xc>"),
+                (0x106, "f000f8f0", "bl\t2ea <work>"),
+                (0x10a, "3401", "adds\tr4, #1"),
+                (0x10c, "42ac", "cmp\tr4, r5"),
+                (0x10e, "dbfa", "blt.n\t106 <loop+0x6>"),
+                (0x110, "bd80", "pop\t{r7, pc}"),
+            ],
+        );
+        let cfg = ControlFlowGraph::from_assembly_block(&f);
+        let starts: Vec<u64> = cfg.blocks.iter().map(|b| b.start()).collect();
+        assert_eq!(starts, vec![0x100, 0x106, 0x10c, 0x110]);
+        assert_eq!(cfg.blocks[1].end(), 0x10c);
+
+        let has = |from: u32, to: Option<u32>, kind: CfgEdgeKind| {
+            cfg.edges.iter().any(|e| e.from == from && e.to == to && e.kind == kind)
+        };
+        assert!(has(0, Some(2), CfgEdgeKind::Jump));
+        assert!(!has(0, Some(1), CfgEdgeKind::Fallthrough));
+        assert!(has(1, None, CfgEdgeKind::Call));
+        assert!(has(1, Some(2), CfgEdgeKind::Fallthrough));
+        assert!(has(2, Some(1), CfgEdgeKind::Conditional));
+        assert!(has(2, Some(3), CfgEdgeKind::Fallthrough));
+        assert!(has(3, None, CfgEdgeKind::Return));
+
+        let call = cfg.edges.iter().find(|e| e.kind == CfgEdgeKind::Call).unwrap();
+        assert_eq!(call.symbol.as_deref(), Some("work"));
+        assert_eq!(call.target.as_deref(), Some("2ea"));
+
+        let dot = cfg.to_dot();
+        assert!(dot.starts_with("digraph \"loop\" {"));
+        assert!(dot.contains("b2 -> b1 [label=\"taken\""));
+        assert!(dot.contains("b3 -> \"exit\""));
+        assert!(dot.contains("\"work\" [shape=ellipse]"));
+
+        let graph = cfg.to_graph(None);
+        assert_eq!(graph.blocks.len(), 4);
+        assert_eq!(graph.blocks[2].instructions.len(), 2);
+    }
+
+    #[test]
+    fn tail_call_leaves_function() {
+        let f = func(
+            "wrapper",
+            0x200,
+            &[(0x200, "2001", "movs\tr0, #1"), (0x202, "f000b800", "b.w\t400 <other>")],
+        );
+        let cfg = ControlFlowGraph::from_assembly_block(&f);
+        assert_eq!(cfg.blocks.len(), 1);
+        assert_eq!(cfg.edges.len(), 1);
+        assert_eq!(cfg.edges[0].kind, CfgEdgeKind::Jump);
+        assert_eq!(cfg.edges[0].to, None);
+        assert_eq!(cfg.edges[0].symbol.as_deref(), Some("other"));
+    }
+}
@@ -0,0 +1,456 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `mdbg core`: post-mortem analysis of a core dump against the firmware ELF, as if the target
+//! were halted where the dump was taken.
+
+use anyhow::{anyhow, Context, Result};
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::net::TcpListener;
+use std::path::PathBuf;
+
+use crate::common::utils::encode_hex;
+use crate::da_helper::core_dump::{CoreDump, CoreTarget};
+use crate::da_helper::cortex_m::{analyze_fault, ExcReturn, REGISTER_NAMES};
+use crate::da_helper::demangle::DemangleStyle;
+use crate::da_helper::elf_items::ObjectInfo;
+use crate::da_helper::request_handler::parse_hex_address;
+use crate::da_helper::run::load_elf_info;
+use crate::da_helper::source_map::SourceMap;
+use crate::da_helper::unwind::{
+    locate, locate_frame, unwind, CodeLocation, ExceptionEntry, UnwindMethod, DEFAULT_MAX_FRAMES,
+};
+use crate::rsp::stub::serve_tcp;
+
+#[derive(Args, Debug)]
+pub struct CoreArgs {
+    /// Core dump manifest (see docs/core-dump.md)
+    #[arg(short = 'c', long = "core", global = true)]
+    pub core_file: Option<PathBuf>,
+
+    /// The firmware ELF the dump was taken from
+    #[arg(short = 'e', long = "elf", global = true)]
+    pub elf_file: Option<String>,
+
+    /// Rewrite source paths recorded in DWARF: FROM=TO or re:PATTERN=REPLACEMENT (repeatable)
+    #[arg(long = "source-map", value_name = "RULE", global = true)]
+    pub source_map: Vec<String>,
+
+    /// How Rust and C++ symbol names are shown; lookups accept both demangled and raw names
+    #[arg(
+        long = "demangle",
+        value_enum,
+        value_name = "STYLE",
+        default_value = "full",
+        global = true
+    )]
+    pub demangle: DemangleStyle,
+
+    /// Print in json format for machine parsing
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub command: CoreCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CoreCommand {
+    /// What the dump holds, whether it matches the ELF, and where the target stopped
+    #[command(name = "info")]
+    Info,
+
+    /// Captured register values
+    #[command(name = "registers")]
+    Registers,
+
+    /// The symbol and source line at an address, or the address of a symbol
+    #[command(name = "symbol")]
+    Symbol(SymbolArgs),
+
+    /// Call stack, unwound through the ELF's CFI or EHABI tables and any exception frames
+    #[command(name = "backtrace")]
+    Backtrace(BacktraceArgs),
+
+    /// The value of a global or static variable in the dumped memory
+    #[command(name = "var")]
+    Var(VarArgs),
+
+    /// Active exception, stacked registers and decoded fault status
+    #[command(name = "fault")]
+    Fault,
+
+    /// Serve the dump over the GDB remote protocol, for `target remote` from plain GDB
+    #[command(name = "serve")]
+    Serve(ServeArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct SymbolArgs {
+    /// Hex address, or a symbol name (demangled or raw)
+    pub what: String,
+}
+
+#[derive(Args, Debug)]
+pub struct BacktraceArgs {
+    /// Stop after this many frames
+    #[arg(long, default_value_t = DEFAULT_MAX_FRAMES)]
+    pub max_frames: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct VarArgs {
+    /// Variable name (demangled or raw)
+    pub name: String,
+
+    /// Bytes to show, for symbols without a size
+    #[arg(long)]
+    pub size: Option<usize>,
+}
+
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// Port to listen on (0 picks a free one)
+    #[arg(long, default_value_t = 2331)]
+    pub port: u16,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+}
+
+pub fn run(args: CoreArgs) -> Result<()> {
+    let core_file = args.core_file.clone().ok_or_else(|| anyhow!("--core is required"))?;
+    let elf_file = args.elf_file.clone().ok_or_else(|| anyhow!("--elf is required"))?;
+    let dump = CoreDump::load(&core_file)?;
+    let info = load_elf_info(
+        &elf_file,
+        None,
+        false,
+        SourceMap::from_specs(&args.source_map)?,
+        args.demangle,
+    )?;
+    let mut target = CoreTarget {
+        dump: &dump,
+        elf_image: info.load_image()?,
+    };
+    match &args.command {
+        CoreCommand::Info => run_info(&args, &core_file, &elf_file, &target, &info),
+        CoreCommand::Registers => run_registers(&args, &target, &info),
+        CoreCommand::Symbol(symbol_args) => run_symbol(&args, &info, symbol_args),
+        CoreCommand::Backtrace(bt_args) => run_backtrace(&args, &target, &info, bt_args),
+        CoreCommand::Var(var_args) => run_var(&args, &target, &info, var_args),
+        CoreCommand::Fault => run_fault(&args, &target, &info),
+        CoreCommand::Serve(serve_args) => {
+            let listener = TcpListener::bind((serve_args.host.as_str(), serve_args.port))
+                .with_context(|| format!("Listening on {}:{}", serve_args.host, serve_args.port))?;
+            let addr = listener.local_addr()?;
+            eprintln!(
+                "Serving {} on {}; in GDB: target remote {}",
+                core_file.display(),
+                addr,
+                addr
+            );
+            serve_tcp(&listener, &mut target, &|peer| eprintln!("GDB connected from {}", peer))?;
+            Ok(())
+        }
+    }
+}
+
+fn print_json(value: &impl Serialize) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn elf_sha256(elf_file: &str) -> Result<String> {
+    let data = std::fs::read(elf_file).with_context(|| format!("Reading {}", elf_file))?;
+    Ok(encode_hex(&Sha256::digest(&data)))
+}
+
+fn run_info(
+    args: &CoreArgs,
+    core_file: &std::path::Path,
+    elf_file: &str,
+    target: &CoreTarget,
+    info: &ObjectInfo,
+) -> Result<()> {
+    let dump = target.dump;
+    let elf_matches = match &dump.elf_sha256 {
+        Some(recorded) => Some(recorded.eq_ignore_ascii_case(&elf_sha256(elf_file)?)),
+        None => None,
+    };
+    let stopped_at = target.register("pc").map(|pc| locate(info, pc, false));
+    if args.json {
+        return print_json(&serde_json::json!({
+            "core": core_file,
+            "elf": elf_file,
+            "arch": dump.arch,
+            "note": dump.note,
+            "elf_matches": elf_matches,
+            "regions": dump.regions,
+            "registers": dump.registers.len(),
+            "stopped_at": stopped_at,
+        }));
+    }
+    println!("Core:    {}", core_file.display());
+    if let Some(arch) = &dump.arch {
+        println!("Arch:    {}", arch);
+    }
+    if let Some(note) = &dump.note {
+        println!("Note:    {}", note);
+    }
+    let check = match elf_matches {
+        Some(true) => "matches the dump",
+        Some(false) => "DOES NOT MATCH the ELF the dump was taken from",
+        None => "not recorded in the dump",
+    };
+    println!("ELF:     {} ({})", elf_file, check);
+    println!("Regions:");
+    for region in &dump.regions {
+        println!(
+            "  0x{:08x}-0x{:08x}  {:>8} bytes  {}",
+            region.address,
+            region.address + region.size,
+            region.size,
+            region.name.as_deref().unwrap_or("")
+        );
+    }
+    println!("Registers: {} captured", dump.registers.len());
+    match stopped_at {
+        Some(location) => println!("Stopped at {}", location.describe()),
+        None => println!("Stopped at: unknown (no pc)"),
+    }
+    Ok(())
+}
+
+fn run_registers(args: &CoreArgs, target: &CoreTarget, info: &ObjectInfo) -> Result<()> {
+    let dump = target.dump;
+    if args.json {
+        let registers: serde_json::Map<String, serde_json::Value> = dump
+            .registers
+            .iter()
+            .map(|(name, value)| (name.clone(), format!("0x{:08x}", value).into()))
+            .collect();
+        return print_json(&registers);
+    }
+    // Architectural registers in GDB order, then anything else the dump has
+    let known = REGISTER_NAMES.iter().map(|n| n.to_string());
+    let extra = dump
+        .registers
+        .keys()
+        .filter(|name| !REGISTER_NAMES.contains(&name.as_str()))
+        .cloned();
+    for name in known.chain(extra) {
+        let Some(value) = dump.register(&name) else {
+            continue;
+        };
+        let note = match name.as_str() {
+            "pc" => locate(info, value as u32, false).function,
+            "lr" if ExcReturn::from_lr(value as u32).is_some() => Some("EXC_RETURN".to_string()),
+            "lr" => locate(info, value as u32, true).function,
+            _ => None,
+        };
+        println!("{:<10} 0x{:08x}  {}", name, value, note.unwrap_or_default());
+    }
+    Ok(())
+}
+
+fn run_symbol(args: &CoreArgs, info: &ObjectInfo, symbol_args: &SymbolArgs) -> Result<()> {
+    if let Some(symbol) = info.elf_symbols.get_by_name(&symbol_args.what) {
+        let out = serde_json::json!({
+            "name": symbol.name,
+            "address": format!("0x{:08x}", symbol.address),
+            "size": symbol.size,
+            "section": symbol.section,
+        });
+        if args.json {
+            return print_json(&out);
+        }
+        println!(
+            "{} at 0x{:08x}, {} bytes{}",
+            symbol.name,
+            symbol.address,
+            symbol.size,
+            symbol
+                .section
+                .as_ref()
+                .map(|s| format!(" in {}", s))
+                .unwrap_or_default()
+        );
+        return Ok(());
+    }
+    let addr = parse_hex_address(&symbol_args.what)
+        .ok_or_else(|| anyhow!("No symbol named '{}', and not a hex address", symbol_args.what))?;
+    let location = locate(info, addr as u32, false);
+    if args.json {
+        return print_json(&location);
+    }
+    println!("{}", location.describe());
+    Ok(())
+}
+
+/// One line of `backtrace --json`
+#[derive(Debug, Serialize)]
+struct FrameInfo {
+    location: CodeLocation,
+    sp: String,
+    /// How the frame was recovered; absent for the innermost frame
+    method: Option<UnwindMethod>,
+    exception: Option<ExceptionEntry>,
+}
+
+fn run_backtrace(args: &CoreArgs, target: &CoreTarget, info: &ObjectInfo, bt_args: &BacktraceArgs) -> Result<()> {
+    let read = |addr: u64, len: usize| target.read(addr, len);
+    let backtrace = unwind(
+        &info.unwind_tables,
+        &|name| target.register(name),
+        &read,
+        bt_args.max_frames,
+    );
+    if args.json {
+        let frames: Vec<FrameInfo> = backtrace
+            .frames
+            .iter()
+            .map(|frame| FrameInfo {
+                location: locate_frame(info, frame),
+                sp: format!("0x{:08x}", frame.sp),
+                method: frame.method,
+                exception: frame.exception.clone(),
+            })
+            .collect();
+        return print_json(&serde_json::json!({
+            "frames": frames,
+            "stop_reason": backtrace.stop_reason,
+        }));
+    }
+    if backtrace.frames.is_empty() {
+        return Err(anyhow!("Can't unwind: {}", backtrace.stop_reason));
+    }
+    for (ix, frame) in backtrace.frames.iter().enumerate() {
+        if let Some(exception) = &frame.exception {
+            println!(
+                "    <{} entered, frame at 0x{:08x}>",
+                exception.name.as_deref().unwrap_or("exception"),
+                exception.frame_address
+            );
+        }
+        let how = frame.method.map(|m| format!("  [{}]", m.as_str())).unwrap_or_default();
+        println!(
+            "#{:<2} {}  sp=0x{:08x}{}",
+            ix,
+            locate_frame(info, frame).describe(),
+            frame.sp,
+            how
+        );
+    }
+    println!("({})", backtrace.stop_reason);
+    Ok(())
+}
+
+fn run_var(args: &CoreArgs, target: &CoreTarget, info: &ObjectInfo, var_args: &VarArgs) -> Result<()> {
+    let symbol = info
+        .elf_symbols
+        .get_by_name(&var_args.name)
+        .ok_or_else(|| anyhow!("No symbol named '{}'", var_args.name))?;
+    let value_type = match symbol.type_offset {
+        Some(offset) => info.value_types(&[offset])?.remove(&offset),
+        None => None,
+    };
+    let size = var_args
+        .size
+        .or(value_type.as_ref().map(|t| t.size))
+        .unwrap_or(symbol.size as usize);
+    if size == 0 {
+        return Err(anyhow!("{} has no size in the ELF; give --size", symbol.name));
+    }
+    let bytes = target.read(symbol.address, size).ok_or_else(|| {
+        anyhow!(
+            "{} (0x{:08x}, {} bytes) is not in the dump",
+            symbol.name,
+            symbol.address,
+            size
+        )
+    })?;
+    // Decoded as its DWARF type, the way live watch shows it; without one, only the bytes
+    let value = value_type.as_ref().map(|t| t.format(&bytes));
+    if args.json {
+        return print_json(&serde_json::json!({
+            "name": symbol.name,
+            "address": format!("0x{:08x}", symbol.address),
+            "size": size,
+            "type": value_type.as_ref().map(|t| &t.name),
+            "bytes": encode_hex(&bytes),
+            "value": value,
+        }));
+    }
+    let hex: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    match &value_type {
+        Some(t) => print!("{} {}", t.name, symbol.name),
+        None => print!("{}", symbol.name),
+    }
+    print!(" at 0x{:08x} ({} bytes): {}", symbol.address, size, hex.join(" "));
+    match value {
+        Some(value) => println!("  = {}", value),
+        None => println!(),
+    }
+    Ok(())
+}
+
+fn run_fault(args: &CoreArgs, target: &CoreTarget, info: &ObjectInfo) -> Result<()> {
+    let read = |addr: u64, len: usize| target.read(addr, len);
+    let analysis = analyze_fault(&|name| target.register(name), &read);
+    if args.json {
+        return print_json(&analysis);
+    }
+    match &analysis.exception {
+        Some((number, name)) => println!("Exception: {} ({})", name, number),
+        None => println!("Exception: unknown"),
+    }
+    if let Some(exc) = analysis.exc_return.map(ExcReturn) {
+        println!(
+            "EXC_RETURN 0x{:08x}: {} stack, returning to {} mode, {} frame",
+            exc.0,
+            if exc.uses_psp() { "process" } else { "main" },
+            if exc.to_thread_mode() { "thread" } else { "handler" },
+            if exc.extended_frame() { "extended (FP)" } else { "basic" }
+        );
+    }
+    if let Some(frame) = &analysis.frame {
+        println!("Stacked at 0x{:08x}:", frame.address);
+        println!("  pc   {}", locate(info, frame.pc, false).describe());
+        println!("  lr   {}", locate(info, frame.lr, true).describe());
+        println!(
+            "  r0 0x{:08x}  r1 0x{:08x}  r2 0x{:08x}  r3 0x{:08x}  r12 0x{:08x}  xpsr 0x{:08x}",
+            frame.r0, frame.r1, frame.r2, frame.r3, frame.r12, frame.xpsr
+        );
+    }
+    if let Some(hfsr) = analysis.hfsr {
+        println!("HFSR 0x{:08x}", hfsr);
+    }
+    if let Some(cfsr) = analysis.cfsr {
+        println!("CFSR 0x{:08x}", cfsr);
+    }
+    for flag in &analysis.flags {
+        println!("  {:<12} {}", flag.name, flag.meaning);
+    }
+    if let Some(addr) = analysis.fault_address {
+        println!("Fault address: 0x{:08x}", addr);
+    }
+    for missing in &analysis.missing {
+        println!("Not in the dump: {}", missing);
+    }
+    Ok(())
+}
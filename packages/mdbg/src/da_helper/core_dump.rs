@@ -0,0 +1,321 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Core dumps: registers and memory captured from a halted (usually faulted) target, stored as a
+//! JSON manifest plus raw binary files. See docs/core-dump.md for the format.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::common::utils::decode_hex;
+use crate::da_helper::cortex_m::{canonical_register_name, REGISTER_NAMES};
+use crate::da_helper::image::MemoryImage;
+use crate::da_helper::request_handler::parse_hex_address;
+use crate::rsp::stub::StubTarget;
+
+/// Value of the manifest's `format` field
+pub const CORE_FORMAT: &str = "mdbg-core";
+pub const CORE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct CoreManifest {
+    format: String,
+    version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    arch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    elf_sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    registers: BTreeMap<String, String>,
+    regions: Vec<ManifestRegion>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ManifestRegion {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    address: String,
+    size: u64,
+    /// Binary file, relative to the manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    /// Where the region starts in `file`
+    #[serde(default, skip_serializing_if = "is_zero")]
+    offset: u64,
+    /// Inline hex bytes, for small regions such as peripheral registers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data: Option<String>,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+/// A captured memory range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DumpRegion {
+    pub name: Option<String>,
+    pub address: u64,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CoreDump {
+    pub arch: Option<String>,
+    /// SHA-256 of the ELF the firmware was built as, to catch dumps opened with the wrong ELF
+    pub elf_sha256: Option<String>,
+    pub note: Option<String>,
+    /// By canonical name (see `canonical_register_name`)
+    pub registers: BTreeMap<String, u64>,
+    pub regions: Vec<DumpRegion>,
+    pub memory: MemoryImage,
+}
+
+impl CoreDump {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("Reading {}", path.display()))?;
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        Self::parse(&text, base_dir).with_context(|| format!("In {}", path.display()))
+    }
+
+    /// Parse a manifest; region files are looked up relative to `base_dir`
+    pub fn parse(manifest: &str, base_dir: &Path) -> Result<Self> {
+        let manifest: CoreManifest = serde_json::from_str(manifest)?;
+        if manifest.format != CORE_FORMAT {
+            return Err(anyhow!(
+                "Not an {} manifest (format is '{}')",
+                CORE_FORMAT,
+                manifest.format
+            ));
+        }
+        if manifest.version > CORE_VERSION {
+            return Err(anyhow!("Unsupported {} version {}", CORE_FORMAT, manifest.version));
+        }
+        let mut dump = CoreDump {
+            arch: manifest.arch,
+            elf_sha256: manifest.elf_sha256,
+            note: manifest.note,
+            ..Default::default()
+        };
+        for (name, value) in &manifest.registers {
+            let value = parse_hex_address(value).ok_or_else(|| anyhow!("Bad value for register {}", name))?;
+            dump.registers.insert(canonical_register_name(name), value);
+        }
+        for region in &manifest.regions {
+            let address =
+                parse_hex_address(&region.address).ok_or_else(|| anyhow!("Bad region address '{}'", region.address))?;
+            let bytes = match (&region.file, &region.data) {
+                (Some(file), None) => read_region_file(&base_dir.join(file), region.offset, region.size)?,
+                (None, Some(data)) => {
+                    decode_hex(data.trim()).ok_or_else(|| anyhow!("Bad hex data at 0x{:x}", address))?
+                }
+                _ => return Err(anyhow!("Region at 0x{:x} needs either a file or data", address)),
+            };
+            if bytes.len() as u64 != region.size {
+                return Err(anyhow!(
+                    "Region at 0x{:x} has {} bytes, expected {}",
+                    address,
+                    bytes.len(),
+                    region.size
+                ));
+            }
+            dump.memory.write(address, &bytes);
+            dump.regions.push(DumpRegion {
+                name: region.name.clone(),
+                address,
+                size: region.size,
+            });
+        }
+        Ok(dump)
+    }
+
+    /// Write the manifest to `path` and the memory, one region after another, to the same name with
+    /// a `.bin` extension
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let bin_path = path.with_extension("bin");
+        let bin_name = bin_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| anyhow!("Bad dump path {}", path.display()))?
+            .to_string();
+        let mut bin = Vec::new();
+        let mut regions = Vec::new();
+        for region in &self.regions {
+            let bytes = self
+                .memory
+                .read(region.address, region.size as usize)
+                .ok_or_else(|| anyhow!("No memory for region at 0x{:x}", region.address))?;
+            regions.push(ManifestRegion {
+                name: region.name.clone(),
+                address: format!("0x{:08x}", region.address),
+                size: region.size,
+                file: Some(bin_name.clone()),
+                offset: bin.len() as u64,
+                data: None,
+            });
+            bin.extend_from_slice(bytes);
+        }
+        let manifest = CoreManifest {
+            format: CORE_FORMAT.to_string(),
+            version: CORE_VERSION,
+            arch: self.arch.clone(),
+            elf_sha256: self.elf_sha256.clone(),
+            note: self.note.clone(),
+            registers: self
+                .registers
+                .iter()
+                .map(|(name, value)| (name.clone(), format!("0x{:08x}", value)))
+                .collect(),
+            regions,
+        };
+        std::fs::write(&bin_path, bin).with_context(|| format!("Writing {}", bin_path.display()))?;
+        std::fs::write(path, serde_json::to_string_pretty(&manifest)?)
+            .with_context(|| format!("Writing {}", path.display()))
+    }
+
+    pub fn register(&self, name: &str) -> Option<u64> {
+        self.registers.get(&canonical_register_name(name)).copied()
+    }
+}
+
+fn read_region_file(path: &Path, offset: u64, size: u64) -> Result<Vec<u8>> {
+    let data = std::fs::read(path).with_context(|| format!("Reading {}", path.display()))?;
+    offset
+        .checked_add(size)
+        .and_then(|end| data.get(usize::try_from(offset).ok()?..usize::try_from(end).ok()?))
+        .map(|b| b.to_vec())
+        .ok_or_else(|| {
+            anyhow!(
+                "{} is too short for {} bytes at offset {}",
+                path.display(),
+                size,
+                offset
+            )
+        })
+}
+
+/// A dump as a halted target: memory comes from the dump, and where the dump has none, from the
+/// ELF's load image (code and constants in flash rarely change, so dumps often leave them out).
+pub struct CoreTarget<'a> {
+    pub dump: &'a CoreDump,
+    pub elf_image: &'a MemoryImage,
+}
+
+impl CoreTarget<'_> {
+    /// The bytes at `addr`, or as many of them as the dump and the ELF hold contiguously. `len`
+    /// comes from GDB or the command line, so the buffer grows only with what is actually copied.
+    pub fn read_prefix(&self, addr: u64, len: usize) -> Vec<u8> {
+        let mut out = Vec::new();
+        while out.len() < len {
+            let Some(at) = addr.checked_add(out.len() as u64) else {
+                break;
+            };
+            let Some((start, bytes)) = self.dump.memory.run_at(at).or_else(|| self.elf_image.run_at(at)) else {
+                break;
+            };
+            let from = (at - start) as usize;
+            let take = (len - out.len()).min(bytes.len() - from);
+            out.extend_from_slice(&bytes[from..from + take]);
+        }
+        out
+    }
+
+    pub fn read(&self, addr: u64, len: usize) -> Option<Vec<u8>> {
+        let bytes = self.read_prefix(addr, len);
+        (bytes.len() == len).then_some(bytes)
+    }
+
+    pub fn register(&self, name: &str) -> Option<u32> {
+        self.dump.register(name).map(|v| v as u32)
+    }
+}
+
+impl StubTarget for CoreTarget<'_> {
+    fn target_xml(&self) -> Option<String> {
+        Some(crate::da_helper::cortex_m::target_xml())
+    }
+
+    fn register_count(&self) -> usize {
+        REGISTER_NAMES.len()
+    }
+
+    fn read_register(&mut self, regnum: usize) -> Option<Vec<u8>> {
+        let name = REGISTER_NAMES.get(regnum)?;
+        self.register(name).map(|v| v.to_le_bytes().to_vec())
+    }
+
+    fn register_size(&self, _regnum: usize) -> usize {
+        4
+    }
+
+    fn read_memory(&mut self, addr: u64, len: usize) -> Vec<u8> {
+        self.read_prefix(addr, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_with_files_and_inline_data() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::write(dir.join("ram.bin"), [0xaa, 0xbb, 1, 2, 3, 4]).unwrap();
+        let manifest = r#"{
+            "format": "mdbg-core", "version": 1, "arch": "armv7e-m",
+            "registers": { "PC": "0x08000200", "r13": "20000ff0", "xpsr": "0x21000003" },
+            "regions": [
+                { "name": "ram", "address": "0x20000000", "size": 4, "file": "ram.bin", "offset": 2 },
+                { "name": "scb", "address": "0xe000ed28", "size": 4, "data": "00000002" }
+            ]
+        }"#;
+        let dump = CoreDump::parse(manifest, dir).unwrap();
+        assert_eq!(dump.register("pc"), Some(0x0800_0200));
+        assert_eq!(dump.register("sp"), Some(0x2000_0ff0));
+        assert_eq!(dump.memory.read(0x2000_0000, 4), Some(&[1, 2, 3, 4][..]));
+        assert_eq!(dump.memory.read(0xe000_ed28, 4), Some(&[0, 0, 0, 2][..]));
+
+        let saved = dir.join("copy.json");
+        dump.save(&saved).unwrap();
+        let reloaded = CoreDump::load(&saved).unwrap();
+        assert_eq!(reloaded.registers, dump.registers);
+        assert_eq!(reloaded.regions, dump.regions);
+        assert_eq!(reloaded.memory, dump.memory);
+
+        let wrong = manifest.replace("mdbg-core", "other");
+        assert!(CoreDump::parse(&wrong, dir).is_err());
+        let past_end = manifest.replace(r#""offset": 2"#, r#""offset": 18446744073709551615"#);
+        assert!(CoreDump::parse(&past_end, dir).is_err());
+    }
+
+    #[test]
+    fn target_falls_back_to_the_elf() {
+        let mut dump = CoreDump::default();
+        dump.memory.write(0x2000_0000, &[1, 2]);
+        let elf = MemoryImage::from_binary(&[9, 9, 9, 9], 0x0800_0000);
+        let target = CoreTarget {
+            dump: &dump,
+            elf_image: &elf,
+        };
+        assert_eq!(target.read(0x0800_0002, 2), Some(vec![9, 9]));
+        assert_eq!(target.read_prefix(0x2000_0000, 8), vec![1, 2]);
+        assert_eq!(target.read(0x2000_0000, 8), None);
+        assert_eq!(target.read_prefix(0x2000_0000, usize::MAX), vec![1, 2]);
+        assert_eq!(target.read_prefix(u64::MAX, usize::MAX), Vec::<u8>::new());
+    }
+}
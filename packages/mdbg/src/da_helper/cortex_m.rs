@@ -0,0 +1,385 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cortex-M architecture knowledge: the register file as GDB numbers it, exception entry frames and
+//! EXC_RETURN values, and the fault status registers in the System Control Block.
+
+use serde::Serialize;
+
+/// Registers in GDB's order for the `target.xml` below (r13-r15 go by their usual names)
+pub const REGISTER_NAMES: [&str; 23] = [
+    "r0",
+    "r1",
+    "r2",
+    "r3",
+    "r4",
+    "r5",
+    "r6",
+    "r7",
+    "r8",
+    "r9",
+    "r10",
+    "r11",
+    "r12",
+    "sp",
+    "lr",
+    "pc",
+    "xpsr",
+    "msp",
+    "psp",
+    "primask",
+    "basepri",
+    "faultmask",
+    "control",
+];
+
+/// Canonical name for a register: r13-r15 and psr are accepted for sp, lr, pc and xpsr
+pub fn canonical_register_name(name: &str) -> String {
+    let lower = name.to_ascii_lowercase();
+    match lower.as_str() {
+        "r13" => "sp".to_string(),
+        "r14" => "lr".to_string(),
+        "r15" => "pc".to_string(),
+        "psr" | "cpsr" => "xpsr".to_string(),
+        _ => lower,
+    }
+}
+
+/// GDB target description matching `REGISTER_NAMES`. With `m-system`, GDB knows about MSP and PSP and
+/// can unwind through exception frames by itself.
+pub fn target_xml() -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\"?>\n<!DOCTYPE target SYSTEM \"gdb-target.dtd\">\n<target version=\"1.0\">\n\
+         <architecture>arm</architecture>\n<feature name=\"org.gnu.gdb.arm.m-profile\">\n",
+    );
+    for (regnum, name) in REGISTER_NAMES.iter().enumerate() {
+        if *name == "msp" {
+            xml.push_str("</feature>\n<feature name=\"org.gnu.gdb.arm.m-system\">\n");
+        }
+        let (kind, group) = match *name {
+            "sp" | "msp" | "psp" => ("data_ptr", "general"),
+            "pc" => ("code_ptr", "general"),
+            _ if regnum <= 16 => ("uint32", "general"),
+            _ => ("uint32", "system"),
+        };
+        xml.push_str(&format!(
+            "<reg name=\"{}\" bitsize=\"32\" regnum=\"{}\" type=\"{}\" group=\"{}\"/>\n",
+            name, regnum, kind, group
+        ));
+    }
+    xml.push_str("</feature>\n</target>\n");
+    xml
+}
+
+/// Exception number (IPSR, the low 9 bits of xPSR) as the reference manual names it
+pub fn exception_name(number: u32) -> String {
+    match number {
+        0 => "Thread mode".to_string(),
+        1 => "Reset".to_string(),
+        2 => "NMI".to_string(),
+        3 => "HardFault".to_string(),
+        4 => "MemManage".to_string(),
+        5 => "BusFault".to_string(),
+        6 => "UsageFault".to_string(),
+        7 => "SecureFault".to_string(),
+        11 => "SVCall".to_string(),
+        12 => "DebugMonitor".to_string(),
+        14 => "PendSV".to_string(),
+        15 => "SysTick".to_string(),
+        n if n >= 16 => format!("IRQ {}", n - 16),
+        n => format!("Reserved exception {}", n),
+    }
+}
+
+/// A value loaded into PC on exception return, normally found in LR inside a handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExcReturn(pub u32);
+
+impl ExcReturn {
+    pub fn from_lr(lr: u32) -> Option<Self> {
+        (lr >> 24 == 0xff).then_some(ExcReturn(lr))
+    }
+
+    /// The frame was pushed on the process stack (else the main stack)
+    pub fn uses_psp(&self) -> bool {
+        self.0 & (1 << 2) != 0
+    }
+
+    /// Returning to Thread mode (else to Handler mode, a nested exception)
+    pub fn to_thread_mode(&self) -> bool {
+        self.0 & (1 << 3) != 0
+    }
+
+    /// The frame includes the FP context (S0-S15, FPSCR)
+    pub fn extended_frame(&self) -> bool {
+        self.0 & (1 << 4) == 0
+    }
+}
+
+/// The registers the processor pushes on exception entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExceptionFrame {
+    /// Where the frame starts (the SP value the handler was entered with)
+    pub address: u32,
+    pub r0: u32,
+    pub r1: u32,
+    pub r2: u32,
+    pub r3: u32,
+    pub r12: u32,
+    pub lr: u32,
+    /// Return address: the instruction that was interrupted or faulted
+    pub pc: u32,
+    pub xpsr: u32,
+    pub extended: bool,
+}
+
+impl ExceptionFrame {
+    /// Read the frame at `sp`; `read` returns the bytes at an address, or None if unreadable
+    pub fn read(sp: u32, extended: bool, read: &dyn Fn(u64, usize) -> Option<Vec<u8>>) -> Option<Self> {
+        let bytes = read(sp as u64, 32)?;
+        let word =
+            |ix: usize| u32::from_le_bytes([bytes[ix * 4], bytes[ix * 4 + 1], bytes[ix * 4 + 2], bytes[ix * 4 + 3]]);
+        Some(ExceptionFrame {
+            address: sp,
+            r0: word(0),
+            r1: word(1),
+            r2: word(2),
+            r3: word(3),
+            r12: word(4),
+            lr: word(5),
+            pc: word(6),
+            xpsr: word(7),
+            extended,
+        })
+    }
+
+    /// The interrupted code's SP: past the frame, and past the alignment word when xPSR bit 9 says
+    /// the processor inserted one
+    pub fn caller_sp(&self) -> u32 {
+        let size = if self.extended { 0x68 } else { 0x20 };
+        let align = if self.xpsr & (1 << 9) != 0 { 4 } else { 0 };
+        self.address.wrapping_add(size + align)
+    }
+}
+
+/// System Control Block fault registers
+pub const CFSR: u64 = 0xE000_ED28;
+pub const HFSR: u64 = 0xE000_ED2C;
+pub const DFSR: u64 = 0xE000_ED30;
+pub const MMFAR: u64 = 0xE000_ED34;
+pub const BFAR: u64 = 0xE000_ED38;
+
+const CFSR_BITS: &[(u32, &str, &str)] = &[
+    (
+        0,
+        "IACCVIOL",
+        "MemManage: instruction fetch from a no-execute or protected region",
+    ),
+    (1, "DACCVIOL", "MemManage: data access to a protected region"),
+    (3, "MUNSTKERR", "MemManage: fault while unstacking on exception return"),
+    (4, "MSTKERR", "MemManage: fault while stacking on exception entry"),
+    (5, "MLSPERR", "MemManage: fault during lazy FP state preservation"),
+    (7, "MMARVALID", "MMFAR holds the faulting address"),
+    (8, "IBUSERR", "BusFault: instruction fetch bus error"),
+    (9, "PRECISERR", "BusFault: precise data bus error"),
+    (
+        10,
+        "IMPRECISERR",
+        "BusFault: imprecise data bus error (the stacked PC is after the access)",
+    ),
+    (
+        11,
+        "UNSTKERR",
+        "BusFault: bus error while unstacking on exception return",
+    ),
+    (
+        12,
+        "STKERR",
+        "BusFault: bus error while stacking on exception entry (stack overflow?)",
+    ),
+    (13, "LSPERR", "BusFault: bus error during lazy FP state preservation"),
+    (15, "BFARVALID", "BFAR holds the faulting address"),
+    (16, "UNDEFINSTR", "UsageFault: undefined instruction"),
+    (
+        17,
+        "INVSTATE",
+        "UsageFault: invalid state (branch to an address without the Thumb bit?)",
+    ),
+    (18, "INVPC", "UsageFault: invalid EXC_RETURN on exception return"),
+    (
+        19,
+        "NOCP",
+        "UsageFault: coprocessor access with the coprocessor disabled (FPU off?)",
+    ),
+    (20, "STKOF", "UsageFault: stack limit (PSPLIM/MSPLIM) exceeded"),
+    (24, "UNALIGNED", "UsageFault: unaligned access"),
+    (25, "DIVBYZERO", "UsageFault: division by zero"),
+];
+
+const HFSR_BITS: &[(u32, &str, &str)] = &[
+    (1, "VECTTBL", "HardFault: bus error reading the vector table"),
+    (
+        30,
+        "FORCED",
+        "HardFault: escalated from a configurable fault (see CFSR)",
+    ),
+    (31, "DEBUGEVT", "HardFault: debug event with the debug monitor disabled"),
+];
+
+/// A set status bit: its name in the reference manual and what it means
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FaultFlag {
+    pub name: &'static str,
+    pub meaning: &'static str,
+}
+
+fn decode_bits(value: u32, bits: &[(u32, &'static str, &'static str)]) -> Vec<FaultFlag> {
+    bits.iter()
+        .filter(|(bit, _, _)| value & (1 << bit) != 0)
+        .map(|&(_, name, meaning)| FaultFlag { name, meaning })
+        .collect()
+}
+
+pub fn decode_cfsr(cfsr: u32) -> Vec<FaultFlag> {
+    decode_bits(cfsr, CFSR_BITS)
+}
+
+pub fn decode_hfsr(hfsr: u32) -> Vec<FaultFlag> {
+    decode_bits(hfsr, HFSR_BITS)
+}
+
+/// What a halted Cortex-M was doing, and why, when it faulted
+#[derive(Debug, Clone, Serialize)]
+pub struct FaultAnalysis {
+    /// Active exception number and name, from IPSR
+    pub exception: Option<(u32, String)>,
+    /// LR when it holds an EXC_RETURN value
+    pub exc_return: Option<u32>,
+    /// The frame pushed on entry to the active exception
+    pub frame: Option<ExceptionFrame>,
+    pub cfsr: Option<u32>,
+    pub hfsr: Option<u32>,
+    pub flags: Vec<FaultFlag>,
+    /// Faulting data address, when MMFAR or BFAR is marked valid
+    pub fault_address: Option<u32>,
+    /// Registers or memory the analysis needed but the dump doesn't have
+    pub missing: Vec<String>,
+}
+
+/// Work out the active exception, its stacked frame and the fault status. `reg` returns a register
+/// by name; `read` returns memory, including the SCB fault registers if they were captured.
+pub fn analyze_fault(reg: &dyn Fn(&str) -> Option<u32>, read: &dyn Fn(u64, usize) -> Option<Vec<u8>>) -> FaultAnalysis {
+    let read_u32 = |addr: u64| read(addr, 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+    let mut missing = Vec::new();
+
+    let exception = match reg("xpsr") {
+        Some(xpsr) => Some((xpsr & 0x1ff, exception_name(xpsr & 0x1ff))),
+        None => {
+            missing.push("xpsr".to_string());
+            None
+        }
+    };
+    let exc_return = reg("lr").and_then(ExcReturn::from_lr);
+    let frame = exc_return.and_then(|exc| {
+        let sp_name = if exc.uses_psp() { "psp" } else { "msp" };
+        // In a handler SP is MSP; use it if the dump has no banked copy
+        let sp = reg(sp_name).or_else(|| if exc.uses_psp() { None } else { reg("sp") });
+        let Some(sp) = sp else {
+            missing.push(sp_name.to_string());
+            return None;
+        };
+        let frame = ExceptionFrame::read(sp, exc.extended_frame(), read);
+        if frame.is_none() {
+            missing.push(format!("stack at 0x{:08x}", sp));
+        }
+        frame
+    });
+
+    let cfsr = read_u32(CFSR);
+    let hfsr = read_u32(HFSR);
+    if cfsr.is_none() || hfsr.is_none() {
+        missing.push("SCB fault registers (0xe000ed28-0xe000ed3b)".to_string());
+    }
+    let mut flags = hfsr.map(decode_hfsr).unwrap_or_default();
+    flags.extend(cfsr.map(decode_cfsr).unwrap_or_default());
+    let fault_address = match cfsr {
+        Some(cfsr) if cfsr & (1 << 7) != 0 => read_u32(MMFAR),
+        Some(cfsr) if cfsr & (1 << 15) != 0 => read_u32(BFAR),
+        _ => None,
+    };
+
+    FaultAnalysis {
+        exception,
+        exc_return: exc_return.map(|e| e.0),
+        frame,
+        cfsr,
+        hfsr,
+        flags,
+        fault_address,
+        missing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn exc_return_bits() {
+        assert_eq!(ExcReturn::from_lr(0x0800_1234), None);
+        let thread_psp = ExcReturn::from_lr(0xffff_fffd).unwrap();
+        assert!(thread_psp.uses_psp() && thread_psp.to_thread_mode() && !thread_psp.extended_frame());
+        let handler_fp = ExcReturn::from_lr(0xffff_ffe1).unwrap();
+        assert!(!handler_fp.uses_psp() && !handler_fp.to_thread_mode() && handler_fp.extended_frame());
+    }
+
+    #[test]
+    fn hard_fault_from_divide_by_zero() {
+        let mut memory: HashMap<u64, u32> = HashMap::new();
+        let frame = [1, 2, 3, 4, 12, 0x0800_0101, 0x0800_0200, 0x2100_0000 | (1 << 9)];
+        for (ix, word) in frame.iter().enumerate() {
+            memory.insert(0x2000_0fe0 + ix as u64 * 4, *word);
+        }
+        memory.insert(CFSR, 1 << 25);
+        memory.insert(HFSR, 1 << 30);
+        let read = |addr: u64, len: usize| -> Option<Vec<u8>> {
+            (0..len as u64)
+                .step_by(4)
+                .map(|off| memory.get(&(addr + off)).map(|w| w.to_le_bytes()))
+                .collect::<Option<Vec<_>>>()
+                .map(|words| words.concat())
+        };
+        let regs: HashMap<&str, u32> = [("xpsr", 3), ("lr", 0xffff_fff9), ("msp", 0x2000_0fe0)].into();
+        let analysis = analyze_fault(&|name| regs.get(name).copied(), &read);
+
+        assert_eq!(analysis.exception, Some((3, "HardFault".to_string())));
+        let frame = analysis.frame.unwrap();
+        assert_eq!(frame.pc, 0x0800_0200);
+        assert_eq!(frame.caller_sp(), 0x2000_1004);
+        let names: Vec<_> = analysis.flags.iter().map(|f| f.name).collect();
+        assert_eq!(names, vec!["FORCED", "DIVBYZERO"]);
+        assert_eq!(analysis.fault_address, None);
+        assert!(analysis.missing.is_empty());
+    }
+
+    #[test]
+    fn target_xml_numbers_every_register() {
+        let xml = target_xml();
+        for (regnum, name) in REGISTER_NAMES.iter().enumerate() {
+            assert!(xml.contains(&format!("name=\"{}\" bitsize=\"32\" regnum=\"{}\"", name, regnum)));
+        }
+        assert_eq!(canonical_register_name("R15"), "pc");
+    }
+}
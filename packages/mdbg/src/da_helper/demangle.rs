@@ -0,0 +1,151 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rust and C++ symbol demangling. Symbols keep their raw (mangled) name next to the demangled
+//! one, so the style only changes what is shown; lookups accept either form.
+
+use clap::ValueEnum;
+
+/// How demangled names are presented
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum DemangleStyle {
+    /// Rust names with their `::h<hash>` suffix, C++ names with the full signature
+    #[default]
+    Full,
+    /// Rust names without the hash, C++ names with the full signature
+    NoHash,
+    /// Rust names without the hash and with paths inside generics cut to their last segment
+    /// (`drop_in_place<alloc::vec::Vec<u8>>` -> `drop_in_place<Vec<u8>>`); C++ names without
+    /// parameter list or return type
+    Short,
+    /// No demangling at all
+    Raw,
+}
+
+/// Demangle `raw` as Rust, then as C++. Names that are neither are returned unchanged.
+pub fn demangle(raw: &str, style: DemangleStyle) -> String {
+    if style == DemangleStyle::Raw {
+        return raw.to_string();
+    }
+    if let Ok(rust) = rustc_demangle::try_demangle(raw) {
+        return match style {
+            DemangleStyle::Full => rust.to_string(),
+            DemangleStyle::NoHash => format!("{:#}", rust),
+            DemangleStyle::Short => shorten_generic_paths(&format!("{:#}", rust)),
+            DemangleStyle::Raw => unreachable!(),
+        };
+    }
+    // Only try C++ on Itanium-mangled names; cpp_demangle accepts some plain C identifiers
+    if raw.starts_with("_Z") {
+        if let Ok(sym) = cpp_demangle::Symbol::new(raw.as_bytes()) {
+            let options = match style {
+                DemangleStyle::Short => cpp_demangle::DemangleOptions::new().no_params().no_return_type(),
+                _ => cpp_demangle::DemangleOptions::new(),
+            };
+            if let Ok(name) = sym.demangle_with_options(&options) {
+                return name;
+            }
+        }
+    }
+    raw.to_string()
+}
+
+/// Drop the leading path segments of every path nested inside `<...>`, leaving the outer path
+/// alone: `<impl core::fmt::Debug for app::Foo>::fmt` -> `<impl Debug for Foo>::fmt`.
+fn shorten_generic_paths(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut depth = 0usize;
+    // Where the path segment being written started
+    let mut segment_start = 0;
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '<' => depth += 1,
+            '>' if !out.ends_with('-') => depth = depth.saturating_sub(1),
+            ':' if chars.peek() == Some(&':') => {
+                chars.next();
+                if depth > 0 && chars.peek() != Some(&'<') {
+                    out.truncate(segment_start);
+                } else {
+                    out.push_str("::");
+                    segment_start = out.len();
+                }
+                continue;
+            }
+            _ => {}
+        }
+        out.push(c);
+        if !(c.is_alphanumeric() || c == '_' || c == '$' || c == '{' || c == '}' || c == '#') {
+            segment_start = out.len();
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUST_LEGACY: &str = "_ZN4core3ptr46drop_in_place$LT$alloc..vec..Vec$LT$u8$GT$$GT$17h0123456789abcdefE";
+    const RUST_IMPL: &str = "_ZN45_$LT$app..Foo$u20$as$u20$core..fmt..Debug$GT$3fmt17h0123456789abcdefE";
+    const CPP: &str = "_ZN5Motor8setSpeedEi";
+
+    #[test]
+    fn rust_styles() {
+        assert_eq!(
+            demangle(RUST_LEGACY, DemangleStyle::Full),
+            "core::ptr::drop_in_place<alloc::vec::Vec<u8>>::h0123456789abcdef"
+        );
+        assert_eq!(
+            demangle(RUST_LEGACY, DemangleStyle::NoHash),
+            "core::ptr::drop_in_place<alloc::vec::Vec<u8>>"
+        );
+        assert_eq!(
+            demangle(RUST_LEGACY, DemangleStyle::Short),
+            "core::ptr::drop_in_place<Vec<u8>>"
+        );
+        assert_eq!(demangle(RUST_IMPL, DemangleStyle::Short), "<Foo as Debug>::fmt");
+        assert_eq!(demangle(RUST_LEGACY, DemangleStyle::Raw), RUST_LEGACY);
+    }
+
+    #[test]
+    fn cpp_styles() {
+        assert_eq!(demangle(CPP, DemangleStyle::Full), "Motor::setSpeed(int)");
+        assert_eq!(demangle(CPP, DemangleStyle::NoHash), "Motor::setSpeed(int)");
+        assert_eq!(demangle(CPP, DemangleStyle::Short), "Motor::setSpeed");
+        assert_eq!(demangle(CPP, DemangleStyle::Raw), CPP);
+    }
+
+    #[test]
+    fn plain_names_are_unchanged() {
+        for style in [DemangleStyle::Full, DemangleStyle::Short] {
+            assert_eq!(demangle("main", style), "main");
+            assert_eq!(demangle("SEGGER_RTT", style), "SEGGER_RTT");
+            assert_eq!(demangle("_Zfoo", style), "_Zfoo");
+        }
+    }
+
+    #[test]
+    fn short_form_keeps_turbofish_and_fn_types() {
+        assert_eq!(
+            shorten_generic_paths("embassy_executor::raw::TaskStorage<app::main_task::{{closure}}>::poll"),
+            "embassy_executor::raw::TaskStorage<{{closure}}>::poll"
+        );
+        assert_eq!(
+            shorten_generic_paths("core::iter::Iterator::map::<core::option::Option<fn() -> u8>>"),
+            "core::iter::Iterator::map::<Option<fn() -> u8>>"
+        );
+        assert_eq!(shorten_generic_paths("<T as core::ops::Fn>::call"), "<T as Fn>::call");
+    }
+}
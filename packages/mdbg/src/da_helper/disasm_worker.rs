@@ -12,28 +12,46 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::common::transport;
-use crate::da_helper::elf_items::{LineInfoEntry, ObjectInfo};
-use crate::da_helper::get_assembly::{get_disasm_from_objdump, AssemblyLine, AssemblyListing};
-use crate::da_helper::helper_requests::{DisasmResponse, SerInstruction};
-use crate::da_helper::protocol::{disassembly_ready_notification, DisasmRequest};
+use crate::da_helper::cfg::ControlFlowGraph;
+use crate::da_helper::elf_items::{FileTable, LineInfoEntry, ObjectInfo};
+use crate::da_helper::get_assembly::{
+    get_disasm_from_objdump_with_progress, AssemblyBlock, AssemblyLine, AssemblyListing,
+};
+use crate::da_helper::helper_requests::{
+    CfgRequest, CfgResponse, DisasmResponse, SerInstruction, SourceListingRequest, SourceListingResponse,
+};
+use crate::da_helper::protocol::{disassembly_ready_notification, DisasmRequest, WorkerJob, WorkerRequest};
+use crate::da_helper::helper_requests::{ErrorCode, RequestError};
+use crate::da_helper::readiness::{Admission, Capability, Readiness};
+use crate::da_helper::request_handler::{parse_hex_address, send_response, HandlerResult};
+use crate::da_helper::responder::{CancelSet, Responder};
+use crate::da_helper::source_listing::{build_source_listing, SourceCache};
+use crate::da_helper::symbols::SymbolTable;
 /// Disassembly worker thread - loads objdump output and serves requests.
 use crate::debug_println;
-use serde_json;
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::sync::{mpsc::Receiver, Arc};
 use std::time::Instant;
 
 /// Run the disassembly worker: load objdump, wait for ObjectInfo, serve requests.
+/// Requests queue up in `req_rx` until then; if objdump fails they are rejected as not ready.
+/// The ELF loader sends the size of the code to disassemble on `text_bytes_rx` once it has
+/// parsed the file, for progress; nothing if it couldn't.
 pub fn run_disassembly_worker(
     objdump_path: &str,
     elf_path: &str,
-    req_rx: Receiver<DisasmRequest>,
+    req_rx: Receiver<WorkerJob>,
     obj_info_rx: Receiver<Arc<ObjectInfo>>,
+    text_bytes_rx: Receiver<u64>,
+    cancels: Arc<CancelSet>,
+    readiness: Arc<Readiness>,
 ) {
     let now = Instant::now();
 
-    match get_disasm_from_objdump(objdump_path, elf_path) {
+    let total_bytes = text_bytes_rx.recv().unwrap_or(0);
+    let mut progress = |done, total| readiness.progress(Capability::Disassembly, done, total);
+    match get_disasm_from_objdump_with_progress(objdump_path, elf_path, total_bytes, &mut progress) {
         Ok(listing) => {
             use crate::info_println;
             info_println!(
@@ -44,13 +62,11 @@ pub fn run_disassembly_worker(
             );
 
             // Send DisassemblyReady notification
-            let notify =
-                disassembly_ready_notification("local-session", listing.lines.len() as u64);
-            if let Err(e) = transport::write_json_locked(&notify) {
-                eprintln!("Failed to write DisassemblyReady: {}", e);
-            } else {
-                eprintln!("Worker sent DisassemblyReady");
-            }
+            readiness.notify(&disassembly_ready_notification(
+                "local-session",
+                listing.lines.len() as u64,
+            ));
+            eprintln!("Worker sent DisassemblyReady");
 
             // Wait for ObjectInfo from main thread (blocks until available)
             debug_println!("Worker waiting for ObjectInfo...");
@@ -62,25 +78,7 @@ pub fn run_disassembly_worker(
                     );
                     // We have to take info from the FileTable and the addr-to-line mapping and add that to
                     // the disassembly instructions before we can serve requests.
-                    for addr2line in &info.addr_to_line.entries {
-                        let addr = addr2line.0;
-                        let entry: &LineInfoEntry = addr2line.1;
-                        if let Some(line_info) = listing.get_line_by_addr(*addr) {
-                            // For simplicity, we just take the first line info entry if there are multiple
-                            let mut min = i32::MAX;
-                            let mut max = i32::MIN;
-                            for line in &entry.line {
-                                let line_num = line.get() as i32;
-                                if line_num < min {
-                                    min = line_num;
-                                }
-                                if line_num > max {
-                                    max = line_num;
-                                }
-                            }
-                            line_info.set_source_info(entry.file_id as i32, min, -1, max, -1);
-                        }
-                    }
+                    apply_line_info(&listing, &info);
                     Some(info)
                 }
                 Err(_) => {
@@ -90,105 +88,280 @@ pub fn run_disassembly_worker(
             };
 
             // Serve disassemble requests from main thread
-            // TODO: Use obj_info for symbol/line info enrichment
-            serve_disassembly_requests(listing, req_rx, obj_info);
+            readiness.set_ready(Capability::Disassembly);
+            serve_disassembly_requests(listing, req_rx, obj_info, &cancels);
         }
         Err(e) => {
             eprintln!("Failed to load disassembly: {}", e);
+            readiness.set_failed(Capability::Disassembly, &e.to_string());
+            reject_disassembly_requests(req_rx, &readiness);
+        }
+    }
+}
+
+/// Answer every queued and future request with the not_ready error from a failed load
+fn reject_disassembly_requests(req_rx: Receiver<WorkerJob>, readiness: &Readiness) {
+    while let Ok(WorkerJob { responder, .. }) = req_rx.recv() {
+        if let Admission::Reject(e) = readiness.admit(Capability::Disassembly) {
+            responder.send_error(e);
+        }
+    }
+}
+
+/// Attach DWARF line info to each disassembled instruction. When several rows map to the
+/// same address we record the lowest and highest line so the instruction spans all of them.
+pub fn apply_line_info(listing: &AssemblyListing, info: &ObjectInfo) {
+    for addr2line in &info.addr_to_line.entries {
+        let addr = addr2line.0;
+        let entry: &LineInfoEntry = addr2line.1;
+        if let Some(line_info) = listing.get_line_by_addr(*addr) {
+            let mut min = i32::MAX;
+            let mut max = i32::MIN;
+            for line in &entry.line {
+                let line_num = line.get() as i32;
+                if line_num < min {
+                    min = line_num;
+                }
+                if line_num > max {
+                    max = line_num;
+                }
+            }
+            line_info.set_source_info(entry.file_id as i32, min, -1, max, -1);
         }
     }
 }
 
-/// Process incoming disassemble requests and send responses.
-fn serve_disassembly_requests(
+/// Process incoming worker requests and send responses. Requests cancelled while they
+/// sat in the queue are dropped without running; failures become error responses.
+pub(crate) fn serve_disassembly_requests(
     listing: AssemblyListing,
-    req_rx: Receiver<DisasmRequest>,
-    obj_info_: Option<Arc<ObjectInfo>>,
+    req_rx: Receiver<WorkerJob>,
+    obj_info: Option<Arc<ObjectInfo>>,
+    cancels: &CancelSet,
 ) {
-    // TODO: Use obj_info to enrich responses:
-    // - obj_info.dwarf_symbols / elf_symbols for function names
-    // - obj_info.addr_to_line for source line mapping
-    // - obj_info.file_table for file paths
-
-    while let Ok(req) = req_rx.recv() {
-        debug_println!("Worker processing request: {:?}", req);
-        let obj_info = obj_info_.as_ref();
-        let global_file_table = obj_info.map(|info| &info.file_table);
-
-        let before = if req.instr_offset < 0 {
-            req.instr_offset.unsigned_abs() as usize
-        } else {
-            0
+    while let Some(WorkerJob { request, responder }) = cancels.next_job(&req_rx) {
+        if responder.is_cancelled() {
+            debug_println!("Worker skipping cancelled request seq {}", responder.seq());
+            continue;
+        }
+        debug_println!("Worker processing request: {:?}", request);
+        let obj_info = obj_info.as_deref();
+        let result = match request {
+            WorkerRequest::Disasm(req) => handle_disasm_request(&listing, req, obj_info, &responder),
+            WorkerRequest::Cfg(req) => handle_cfg_request(&listing, req, obj_info, &responder),
+            WorkerRequest::SourceListing(req) => {
+                handle_source_listing_request(&listing, req, obj_info, &responder)
+            }
         };
+        responder.finish(result);
+    }
+}
 
-        // Calculate after section size. If before >= instr_count, we need to expand
-        // the window to include the target instruction. This can happen when VSCode
-        // makes an invalid request (e.g., instructionCount=50, offset=-250).
-        // We expand the window rather than clamping to maintain correct scroll behavior.
-        let after = if before >= req.instr_count as usize {
-            // Invalid request: target would be outside the window
-            // Expand window to include target at correct position
-            debug_println!(
-                "WARNING: instructionOffset {} exceeds instructionCount {}, expanding window",
-                req.instr_offset,
-                req.instr_count
-            );
-            // Give them the full instructionCount for the after section
-            req.instr_count as usize
+/// Find the function (objdump block) a cfg request refers to, by name first, then by address.
+/// A name objdump doesn't show (a raw mangled name, or one in another demangle style) is
+/// resolved to its address through `symbols`.
+pub fn find_function_block<'a>(
+    listing: &'a AssemblyListing,
+    name: Option<&str>,
+    address: Option<u64>,
+    symbols: Option<&SymbolTable>,
+) -> Option<&'a AssemblyBlock> {
+    if let Some(name) = name {
+        if let Some(block) = listing.blocks.iter().find(|b| b.name == name) {
+            return Some(block);
+        }
+    }
+    let by_symbol = || Some(symbols?.get_by_name(name?)?.address);
+    let addr = address.or_else(by_symbol)?;
+    let line = listing.get_line_by_addr(addr).or_else(|| {
+        let (_, &ix) = listing.addr_map.range(..=addr).next_back()?;
+        listing.lines.get(ix).map(|l| l.as_ref())
+    })?;
+    let func_id = line.function_id.get();
+    if func_id < 0 {
+        return None;
+    }
+    listing.blocks.get(func_id as usize)
+}
+
+fn handle_cfg_request(
+    listing: &AssemblyListing,
+    req: CfgRequest,
+    obj_info: Option<&ObjectInfo>,
+    responder: &Responder,
+) -> HandlerResult {
+    let address = req.address.as_deref().and_then(parse_hex_address);
+    let symbols = obj_info.map(|info| &info.elf_symbols);
+    let Some(block) = find_function_block(listing, req.function.as_deref(), address, symbols) else {
+        return Err(RequestError::new(
+            ErrorCode::AddressNotFound,
+            format!(
+                "No function found (function: {:?}, address: {:?})",
+                req.function, req.address
+            ),
+        ));
+    };
+    let cfg = ControlFlowGraph::from_assembly_block(block);
+    let response = CfgResponse {
+        req: "cfg".to_string(),
+        seq: req.seq,
+        graph: cfg.to_graph(obj_info.map(|info| &info.file_table)),
+        dot: if req.dot.unwrap_or(false) {
+            Some(cfg.to_dot())
         } else {
-            req.instr_count as usize - before
-        };
+            None
+        },
+    };
+    send_response(responder, &response)
+}
 
+fn handle_disasm_request(
+    listing: &AssemblyListing,
+    req: DisasmRequest,
+    obj_info: Option<&ObjectInfo>,
+    responder: &Responder,
+) -> HandlerResult {
+    let global_file_table = obj_info.map(|info| &info.file_table);
+
+    let before = if req.instr_offset < 0 {
+        req.instr_offset.unsigned_abs() as usize
+    } else {
+        0
+    };
+
+    // Calculate after section size. If before >= instr_count, we need to expand
+    // the window to include the target instruction. This can happen when VSCode
+    // makes an invalid request (e.g., instructionCount=50, offset=-250).
+    // We expand the window rather than clamping to maintain correct scroll behavior.
+    let after = if before >= req.instr_count as usize {
+        // Invalid request: target would be outside the window
+        // Expand window to include target at correct position
         debug_println!(
-            "DEBUG: target=0x{:x}, before={}, after={}, total_returned={}",
-            req.start_addr,
-            before,
-            after,
-            before + after
+            "WARNING: instructionOffset {} exceeds instructionCount {}, expanding window",
+            req.instr_offset,
+            req.instr_count
         );
-        let window = listing.get_window(req.start_addr, before, after);
-        debug_println!(
-            "DEBUG: window.len()={}, first={}, last={}",
-            window.len(),
-            window
-                .first()
-                .map(|i| format!("0x{:x}", i.address))
-                .unwrap_or_else(|| "none".to_string()),
-            window
-                .last()
-                .map(|i| format!("0x{:x}", i.address))
-                .unwrap_or_else(|| "none".to_string())
-        );
-        let mut func_table: HashMap<u32, String> = HashMap::new();
-        let mut file_table: HashMap<u32, String> = HashMap::new();
-        for instr in &window {
-            let func_id = instr.function_id.get();
-            let file_id = instr.file_id.get();
-            if func_id >= 0 && !func_table.contains_key(&(func_id as u32)) {
-                let function_name = listing.blocks[func_id as usize].name.clone();
-                func_table.insert(func_id as u32, function_name);
-            }
-            if file_id >= 0 && !file_table.contains_key(&(file_id as u32)) {
-                let file_name = global_file_table
-                    .and_then(|ft| ft.get_by_id(file_id as u32))
-                    .cloned()
-                    .unwrap_or_else(|| format!("file_{}", file_id));
-                file_table.insert(file_id as u32, file_name);
-            }
+        // Give them the full instructionCount for the after section
+        req.instr_count as usize
+    } else {
+        req.instr_count as usize - before
+    };
+
+    debug_println!(
+        "DEBUG: target=0x{:x}, before={}, after={}, total_returned={}",
+        req.start_addr,
+        before,
+        after,
+        before + after
+    );
+    let window = listing.get_window(req.start_addr, before, after);
+    debug_println!(
+        "DEBUG: window.len()={}, first={}, last={}",
+        window.len(),
+        window
+            .first()
+            .map(|i| format!("0x{:x}", i.address))
+            .unwrap_or_else(|| "none".to_string()),
+        window
+            .last()
+            .map(|i| format!("0x{:x}", i.address))
+            .unwrap_or_else(|| "none".to_string())
+    );
+    let (file_table, func_table) = build_id_tables(listing, window.iter(), global_file_table);
+    let ser_instructions: Vec<SerInstruction> = window
+        .iter()
+        .map(SerInstruction::from_assembly_line)
+        .collect();
+    let response = DisasmResponse::new(req.seq_id, file_table, func_table, ser_instructions);
+    send_response(responder, &response)?;
+    debug_println!("Worker sent disasm response for seq_id {}", req.seq_id);
+    Ok(())
+}
+
+/// Build the file and function id -> name tables that accompany a set of instructions.
+fn build_id_tables<'a>(
+    listing: &AssemblyListing,
+    instrs: impl Iterator<Item = &'a AssemblyLine>,
+    global_file_table: Option<&FileTable>,
+) -> (HashMap<u32, String>, HashMap<u32, String>) {
+    let mut func_table: HashMap<u32, String> = HashMap::new();
+    let mut file_table: HashMap<u32, String> = HashMap::new();
+    for instr in instrs {
+        let func_id = instr.function_id.get();
+        let file_id = instr.file_id.get();
+        if func_id >= 0 && !func_table.contains_key(&(func_id as u32)) {
+            let function_name = listing.blocks[func_id as usize].name.clone();
+            func_table.insert(func_id as u32, function_name);
         }
-        let ser_instructions: Vec<SerInstruction> = window
-            .iter()
-            .map(SerInstruction::from_assembly_line)
-            .collect();
-        let response = DisasmResponse::new(req.seq_id, file_table, func_table, ser_instructions);
-        let response_json = serde_json::to_string(&response).unwrap();
-        if let Err(e) = transport::write_json_locked(&serde_json::from_str(&response_json).unwrap())
-        {
-            eprintln!("Worker failed to write disasm response: {}", e);
-        } else {
-            debug_println!("Worker sent disasm response for seq_id {}", req.seq_id);
+        if file_id >= 0 && !file_table.contains_key(&(file_id as u32)) {
+            let file_name = global_file_table
+                .and_then(|ft| ft.get_by_id(file_id as u32))
+                .cloned()
+                .unwrap_or_else(|| format!("file_{}", file_id));
+            file_table.insert(file_id as u32, file_name);
         }
     }
+    (file_table, func_table)
+}
+
+/// Collect the instructions a sourceListing request covers: a whole function, or an address range.
+pub fn source_listing_lines(
+    listing: &AssemblyListing,
+    function: Option<&str>,
+    start: Option<u64>,
+    end: Option<u64>,
+    symbols: Option<&SymbolTable>,
+) -> Option<Vec<Rc<AssemblyLine>>> {
+    if let Some(block) = find_function_block(listing, function, None, symbols) {
+        return Some(block.lines.clone());
+    }
+    let (start, end) = (start?, end?);
+    if start >= end {
+        return None;
+    }
+    let lines: Vec<Rc<AssemblyLine>> = listing
+        .addr_map
+        .range(start..end)
+        .take(MAX_SOURCE_LISTING_INSTRUCTIONS)
+        .map(|(_, &ix)| listing.lines[ix].clone())
+        .collect();
+    Some(lines)
+}
+
+/// Upper bound for address-range listings, so a bad range can't serialize the whole image
+const MAX_SOURCE_LISTING_INSTRUCTIONS: usize = 20_000;
+
+fn handle_source_listing_request(
+    listing: &AssemblyListing,
+    req: SourceListingRequest,
+    obj_info: Option<&ObjectInfo>,
+    responder: &Responder,
+) -> HandlerResult {
+    let start = req.start.as_deref().and_then(parse_hex_address);
+    let end = req.end.as_deref().and_then(parse_hex_address);
+    let symbols = obj_info.map(|info| &info.elf_symbols);
+    let function = req.function.as_deref();
+    let Some(lines) = source_listing_lines(listing, function, start, end, symbols) else {
+        return Err(RequestError::new(
+            ErrorCode::AddressNotFound,
+            format!(
+                "Nothing to list (function: {:?}, start: {:?}, end: {:?})",
+                req.function, req.start, req.end
+            ),
+        ));
+    };
+    let global_file_table = obj_info.map(|info| &info.file_table);
+    let (file_table, func_table) =
+        build_id_tables(listing, lines.iter().map(|l| l.as_ref()), global_file_table);
+    let mut cache = SourceCache::new();
+    let response = SourceListingResponse {
+        req: "sourceListing".to_string(),
+        seq: req.seq,
+        file_table,
+        func_table,
+        chunks: build_source_listing(&lines, global_file_table, &mut cache),
+    };
+    send_response(responder, &response)
 }
 
 impl DisasmResponse {
@@ -228,7 +401,7 @@ mod tests {
     use std::{fs, io::Write};
 
     use super::*;
-    use crate::da_helper::get_assembly::{AssemblyLine, AssemblyListing};
+    use crate::da_helper::get_assembly::{get_disasm_from_objdump, AssemblyLine, AssemblyListing};
 
     #[test]
     fn serialize_compact_basic() {
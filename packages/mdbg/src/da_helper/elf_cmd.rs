@@ -0,0 +1,438 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `mdbg elf ...` — one-shot, offline queries against an ELF file using the same
+//! analysis the da-helper serves to the Debug Adapter. Output goes to stdout.
+
+use anyhow::{anyhow, Result};
+use clap::{Args, Subcommand};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use crate::common::utils::encode_hex;
+use crate::da_helper::capstone::Disassembler;
+use crate::da_helper::cfg::ControlFlowGraph;
+use crate::da_helper::demangle::DemangleStyle;
+use crate::da_helper::disasm_worker::{apply_line_info, find_function_block, source_listing_lines};
+use crate::da_helper::elf_items::ObjectInfo;
+use crate::da_helper::get_assembly::{get_disasm_from_objdump, AssemblyLine, AssemblyListing};
+use crate::da_helper::helper_requests::VerifyDump;
+use crate::da_helper::image::{ImageFormat, MemoryImage};
+use crate::da_helper::memory_map::{build_memory_map, format_memory_map, load_regions, ElfLayout};
+use crate::da_helper::request_handler::parse_hex_address;
+use crate::da_helper::run::load_elf_info;
+use crate::da_helper::source_listing::{build_source_listing, format_source_listing, SourceCache};
+use crate::da_helper::source_map::SourceMap;
+use crate::da_helper::verify::{format_verify_report, load_dumps, verify_image};
+
+#[derive(Args, Debug)]
+pub struct ElfArgs {
+    /// Path to the ELF file to analyze
+    #[arg(short = 'e', long = "elf", global = true)]
+    pub elf_file: Option<String>,
+
+    #[arg(
+        short = 'o',
+        long = "objdump-path",
+        global = true,
+        default_value = "arm-none-eabi-objdump"
+    )]
+    pub objdump_path: String,
+
+    /// Rewrite source paths recorded in DWARF: FROM=TO or re:PATTERN=REPLACEMENT (repeatable)
+    #[arg(long = "source-map", value_name = "RULE", global = true)]
+    pub source_map: Vec<String>,
+
+    /// How Rust and C++ symbol names are shown; lookups accept both demangled and raw names
+    #[arg(
+        long = "demangle",
+        value_enum,
+        value_name = "STYLE",
+        global = true,
+        default_value_t = DemangleStyle::Full
+    )]
+    pub demangle: DemangleStyle,
+
+    /// Print in json format for machine parsing
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub command: ElfCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ElfCommand {
+    /// Control-flow graph of a function, as Graphviz DOT (or JSON with --json)
+    #[command(name = "cfg")]
+    Cfg(CfgArgs),
+
+    /// Disassembly of a function or address range, interleaved with its source lines
+    #[command(name = "listing")]
+    Listing(ListingArgs),
+
+    /// Memory regions and how full they are, copied-down sections, overflows and overlaps
+    #[command(name = "memory-map")]
+    MemoryMap(MemoryMapArgs),
+
+    /// Flash image (Intel HEX, S-record or binary) built from the load segments, at their load addresses
+    #[command(name = "export")]
+    Export(ExportArgs),
+
+    /// Compare memory read from the target (HEX, S-record or binary dumps) with the ELF's contents
+    #[command(name = "verify")]
+    Verify(VerifyArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct CfgArgs {
+    /// Function name (demangled or raw), or a hex address inside the function
+    pub function: String,
+}
+
+#[derive(Args, Debug)]
+pub struct ListingArgs {
+    /// Function name, or a hex address inside the function
+    pub function: Option<String>,
+
+    /// Start of an address range to list instead of a function (hex)
+    #[arg(long, requires = "end", conflicts_with = "function")]
+    pub start: Option<String>,
+
+    /// End of the address range, exclusive (hex)
+    #[arg(long, requires = "start")]
+    pub end: Option<String>,
+
+    /// Disassemble a HEX, S-record or binary image instead of the ELF (no --elf needed), as
+    /// FILE or FILE@ADDR (hex) for a binary; lists the --start/--end range
+    #[arg(long, value_name = "FILE[@ADDR]", requires = "start")]
+    pub image: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct MemoryMapArgs {
+    /// Linker script whose MEMORY block defines the regions (default: the ELF's load segments)
+    #[arg(long = "ld", value_name = "FILE")]
+    pub linker_script: Option<PathBuf>,
+
+    /// CMSIS-SVD file to add the peripheral regions from
+    #[arg(long, value_name = "FILE")]
+    pub svd: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// Output file
+    #[arg(short = 'O', long, value_name = "FILE")]
+    pub output: PathBuf,
+
+    /// Output format (default: from the output file's extension)
+    #[arg(long, value_enum)]
+    pub format: Option<ImageFormat>,
+
+    /// Byte to fill gaps with in binary output and checksums (hex)
+    #[arg(long, default_value = "0xff")]
+    pub gap_fill: String,
+
+    /// Only export from this address on (hex)
+    #[arg(long, conflicts_with = "region")]
+    pub start: Option<String>,
+
+    /// Only export below this address (hex)
+    #[arg(long, conflicts_with = "region")]
+    pub end: Option<String>,
+
+    /// Only export what lies in this memory region (see memory-map)
+    #[arg(long)]
+    pub region: Option<String>,
+
+    /// Linker script whose MEMORY block defines the regions for --region
+    #[arg(long = "ld", value_name = "FILE", requires = "region")]
+    pub linker_script: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    /// Dump files; a binary dump is given as FILE@ADDR (hex), HEX and S-record dumps carry addresses
+    #[arg(value_name = "DUMP")]
+    pub dumps: Vec<String>,
+
+    /// Inline bytes read from the target, as ADDR=HEXBYTES (e.g. 0x08000000=00500020)
+    #[arg(long = "bytes", value_name = "ADDR=HEX")]
+    pub bytes: Vec<String>,
+}
+
+pub fn run(args: ElfArgs) -> Result<()> {
+    if let ElfCommand::Listing(listing_args @ ListingArgs { image: Some(spec), .. }) = &args.command {
+        return run_image_listing(&args, spec, listing_args);
+    }
+    let elf_file = args.elf_file.clone().ok_or_else(|| anyhow!("--elf is required"))?;
+    match &args.command {
+        ElfCommand::Cfg(cfg_args) => run_cfg(&args, &elf_file, cfg_args),
+        ElfCommand::Listing(listing_args) => run_listing(&args, &elf_file, listing_args),
+        ElfCommand::MemoryMap(map_args) => run_memory_map(&args, &elf_file, map_args),
+        ElfCommand::Export(export_args) => run_export(&args, &elf_file, export_args),
+        ElfCommand::Verify(verify_args) => run_verify(&args, &elf_file, verify_args),
+    }
+}
+
+/// Load the disassembly and symbol/line info, and merge them the way the worker does.
+fn load_listing(args: &ElfArgs, elf_file: &str) -> Result<(AssemblyListing, ObjectInfo)> {
+    let listing = get_disasm_from_objdump(&args.objdump_path, elf_file)
+        .map_err(|e| anyhow!("Failed to disassemble {} with {}: {}", elf_file, args.objdump_path, e))?;
+    let info = load_elf_info(
+        elf_file,
+        None,
+        false,
+        SourceMap::from_specs(&args.source_map)?,
+        args.demangle,
+    )?;
+    apply_line_info(&listing, &info);
+    Ok((listing, info))
+}
+
+fn run_cfg(args: &ElfArgs, elf_file: &str, cfg_args: &CfgArgs) -> Result<()> {
+    let (listing, info) = load_listing(args, elf_file)?;
+    let block = find_function_block(
+        &listing,
+        Some(&cfg_args.function),
+        parse_hex_address(&cfg_args.function),
+        Some(&info.elf_symbols),
+    )
+    .ok_or_else(|| anyhow!("No function named or containing '{}'", cfg_args.function))?;
+    let cfg = ControlFlowGraph::from_assembly_block(block);
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&cfg.to_graph(Some(&info.file_table)))?
+        );
+    } else {
+        print!("{}", cfg.to_dot());
+    }
+    Ok(())
+}
+
+fn run_listing(args: &ElfArgs, elf_file: &str, listing_args: &ListingArgs) -> Result<()> {
+    let (listing, info) = load_listing(args, elf_file)?;
+    let lines = match &listing_args.function {
+        Some(function) => find_function_block(
+            &listing,
+            Some(function),
+            parse_hex_address(function),
+            Some(&info.elf_symbols),
+        )
+        .map(|block| block.lines.clone())
+        .ok_or_else(|| anyhow!("No function named or containing '{}'", function))?,
+        None => {
+            let start = listing_args.start.as_deref().and_then(parse_hex_address);
+            let end = listing_args.end.as_deref().and_then(parse_hex_address);
+            if start.is_none() || end.is_none() {
+                return Err(anyhow!("Give a function, or a hex --start and --end"));
+            }
+            source_listing_lines(&listing, None, start, end, None)
+                .ok_or_else(|| anyhow!("Empty or invalid address range"))?
+        }
+    };
+    let mut cache = SourceCache::new();
+    let chunks = build_source_listing(&lines, Some(&info.file_table), &mut cache);
+    let file_table: HashMap<u32, String> = chunks
+        .iter()
+        .flat_map(|c| c.source.iter())
+        .filter_map(|s| Some((s.F, info.file_table.get_by_id(s.F)?.clone())))
+        .collect();
+    if args.json {
+        let out = serde_json::json!({ "file_table": file_table, "chunks": chunks });
+        println!("{}", serde_json::to_string_pretty(&out)?);
+    } else {
+        print!("{}", format_source_listing(&chunks, &file_table));
+    }
+    Ok(())
+}
+
+/// Disassemble part of a loaded image with Capstone; there is no source to interleave
+fn run_image_listing(args: &ElfArgs, spec: &str, listing_args: &ListingArgs) -> Result<()> {
+    let image = load_dumps(&[dump_spec(spec)])?;
+    let (Some(start), Some(end)) = (
+        listing_args.start.as_deref().and_then(parse_hex_address),
+        listing_args.end.as_deref().and_then(parse_hex_address),
+    ) else {
+        return Err(anyhow!("Give a hex --start and --end to list"));
+    };
+    let instructions = Disassembler::new()
+        .and_then(|d| d.disassemble_image(&image, start, end))
+        .map_err(|e| anyhow!("Failed to disassemble {}: {}", spec, e))?;
+    if instructions.is_empty() {
+        return Err(anyhow!("{} holds nothing in 0x{:08x}-0x{:08x}", spec, start, end));
+    }
+    let lines: Vec<Rc<AssemblyLine>> = instructions
+        .iter()
+        .map(|i| {
+            let text = format!("{}\t{}", i.mnemonic, i.op_str).trim_end().to_string();
+            Rc::new(AssemblyLine::new(i.address, i.objdump_bytes(), text, String::new(), -1, 0))
+        })
+        .collect();
+    let chunks = build_source_listing(&lines, None, &mut SourceCache::new());
+    if args.json {
+        let out = serde_json::json!({ "file_table": {}, "chunks": chunks });
+        println!("{}", serde_json::to_string_pretty(&out)?);
+    } else {
+        print!("{}", format_source_listing(&chunks, &HashMap::new()));
+    }
+    Ok(())
+}
+
+fn run_memory_map(args: &ElfArgs, elf_file: &str, map_args: &MemoryMapArgs) -> Result<()> {
+    let info = load_elf_info(
+        elf_file,
+        None,
+        false,
+        SourceMap::from_specs(&args.source_map)?,
+        args.demangle,
+    )?;
+    let regions = load_regions(
+        &info.layout,
+        map_args.linker_script.as_deref(),
+        map_args.svd.as_deref(),
+    )?;
+    let map = build_memory_map(&info.layout, &regions);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&map)?);
+    } else {
+        print!("{}", format_memory_map(&map));
+    }
+    Ok(())
+}
+
+fn run_export(args: &ElfArgs, elf_file: &str, export_args: &ExportArgs) -> Result<()> {
+    let data = std::fs::read(elf_file).map_err(|e| anyhow!("Failed to read {}: {}", elf_file, e))?;
+    let mut image = MemoryImage::from_elf_data(&data)?;
+    let gap_fill = parse_hex_address(&export_args.gap_fill)
+        .and_then(|v| u8::try_from(v).ok())
+        .ok_or_else(|| anyhow!("--gap-fill must be a byte value"))?;
+
+    if let Some(name) = &export_args.region {
+        let obj_file = object::File::parse(&*data)?;
+        let layout = ElfLayout::from_object(&obj_file, &data);
+        let regions = load_regions(&layout, export_args.linker_script.as_deref(), None)?;
+        let region = regions
+            .iter()
+            .find(|r| &r.name == name)
+            .ok_or_else(|| anyhow!("No memory region named '{}'", name))?;
+        image = image.clip(region.origin, region.end());
+    } else if export_args.start.is_some() || export_args.end.is_some() {
+        let parse = |arg: &Option<String>, default: u64| match arg {
+            Some(text) => parse_hex_address(text).ok_or_else(|| anyhow!("Bad address '{}'", text)),
+            None => Ok(default),
+        };
+        image = image.clip(parse(&export_args.start, 0)?, parse(&export_args.end, u64::MAX)?);
+    }
+    if image.is_empty() {
+        return Err(anyhow!("Nothing to export in the selected range"));
+    }
+
+    let format = export_args
+        .format
+        .unwrap_or_else(|| ImageFormat::from_path(&export_args.output));
+    image.save(&export_args.output, format, gap_fill)?;
+
+    let start = image.start().unwrap_or(0);
+    let end = image.end().unwrap_or(0);
+    let crc32 = image.crc32(gap_fill);
+    let sha256 = encode_hex(&image.sha256(gap_fill));
+    if args.json {
+        let out = serde_json::json!({
+            "output": export_args.output,
+            "start": format!("0x{:08x}", start),
+            "end": format!("0x{:08x}", end),
+            "size": end - start,
+            "data_size": image.data_len(),
+            "crc32": format!("0x{:08x}", crc32),
+            "sha256": sha256,
+        });
+        println!("{}", serde_json::to_string_pretty(&out)?);
+    } else {
+        println!(
+            "Wrote {} (0x{:08x}-0x{:08x}, {} bytes, {} with data)",
+            export_args.output.display(),
+            start,
+            end,
+            end - start,
+            image.data_len()
+        );
+        println!("CRC32:   0x{:08x}", crc32);
+        println!("SHA-256: {}", sha256);
+    }
+    Ok(())
+}
+
+/// A dump file given as FILE, or FILE@ADDR (hex) for a binary
+fn dump_spec(spec: &str) -> VerifyDump {
+    let (file, address) = match spec.rsplit_once('@') {
+        Some((file, address)) => (file, Some(address.to_string())),
+        None => (spec, None),
+    };
+    VerifyDump {
+        file: Some(file.to_string()),
+        address,
+        data: None,
+    }
+}
+
+fn run_verify(args: &ElfArgs, elf_file: &str, verify_args: &VerifyArgs) -> Result<()> {
+    let mut dumps: Vec<VerifyDump> = verify_args
+        .dumps
+        .iter()
+        .map(|spec| dump_spec(spec))
+        .collect();
+    for spec in &verify_args.bytes {
+        let (address, data) = spec
+            .split_once('=')
+            .ok_or_else(|| anyhow!("--bytes takes ADDR=HEX, not '{}'", spec))?;
+        dumps.push(VerifyDump {
+            file: None,
+            address: Some(address.to_string()),
+            data: Some(data.to_string()),
+        });
+    }
+    if dumps.is_empty() {
+        return Err(anyhow!("Give at least one dump file or --bytes"));
+    }
+    let actual = load_dumps(&dumps)?;
+    let info = load_elf_info(
+        elf_file,
+        None,
+        false,
+        SourceMap::from_specs(&args.source_map)?,
+        args.demangle,
+    )?;
+    let report = verify_image(info.load_image()?, &actual, &info.layout, &info.elf_symbols);
+    if args.json {
+        let out = serde_json::json!({
+            "matched": report.matched(),
+            "compared": report.compared,
+            "ignored": report.ignored,
+            "not_dumped": report.not_dumped,
+            "mismatches": report.mismatches,
+        });
+        println!("{}", serde_json::to_string_pretty(&out)?);
+    } else {
+        print!("{}", format_verify_report(&report));
+    }
+    if report.matched() {
+        Ok(())
+    } else {
+        Err(anyhow!("Target memory does not match {}", elf_file))
+    }
+}
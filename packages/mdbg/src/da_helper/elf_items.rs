@@ -12,18 +12,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::num::{NonZero, NonZeroU64};
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
 
+use anyhow::{anyhow, Context, Result};
+
+use crate::common::sync::MutexExt;
 use crate::common::utils::canonicalize_path;
 use crate::common::utils::CanonicalPath;
+use crate::da_helper::demangle::DemangleStyle;
+use crate::da_helper::image::MemoryImage;
+use crate::da_helper::memory_map::ElfLayout;
+use crate::da_helper::run::load_dwarf;
+use crate::da_helper::source_map::SourceMap;
 use crate::da_helper::symbols::Symbol;
+use crate::da_helper::unwind::UnwindTables;
+use crate::da_helper::value_type::{resolve_types_at, ValueType};
 
 pub struct FileTable {
-    // Map from file index to file path
+    // Map from file index to file path, after source map rules (what clients and the disk see)
     files_by_id: std::collections::BTreeMap<u32, String>,
+    // Canonical paths as recorded by the compiler, plus their remapped form, to file index
     id_by_file: std::collections::BTreeMap<String, u32>,
     next_id: u32,
+    source_map: SourceMap,
 }
 
 impl Default for FileTable {
@@ -38,9 +52,20 @@ impl FileTable {
             files_by_id: std::collections::BTreeMap::new(),
             id_by_file: std::collections::BTreeMap::new(),
             next_id: 1,
+            source_map: SourceMap::new(),
         }
     }
 
+    /// Install source map rules. Must be called before any paths are interned.
+    pub fn set_source_map(&mut self, source_map: SourceMap) {
+        debug_assert!(self.files_by_id.is_empty());
+        self.source_map = source_map;
+    }
+
+    pub fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+
     //
     pub fn intern(&mut self, path: String) -> u32 {
         let fp = canonicalize_path(&path);
@@ -49,7 +74,12 @@ impl FileTable {
         }
         let id = self.next_id;
         self.next_id += 1;
-        self.files_by_id.insert(id, fp.clone());
+        let local = match self.source_map.to_local(&fp) {
+            Some(mapped) => canonicalize_path(&mapped),
+            None => fp.clone(),
+        };
+        self.id_by_file.entry(local.clone()).or_insert(id);
+        self.files_by_id.insert(id, local);
         self.id_by_file.insert(fp, id);
         id
     }
@@ -58,13 +88,28 @@ impl FileTable {
         self.files_by_id.get(&id)
     }
 
+    /// Canonical local form of a compiler-recorded path, after source map rules.
+    pub fn local_path(&self, path: &str) -> CanonicalPath {
+        let canon = CanonicalPath::new(path);
+        match self.source_map.to_local(canon.as_str()) {
+            Some(mapped) => CanonicalPath::new(&mapped),
+            None => canon,
+        }
+    }
+
+    /// Look up a file by either its local path or the path the compiler recorded.
     pub fn get_by_path(&self, path: &str) -> Option<u32> {
         let id = self.id_by_file.get(path);
         if id.is_some() {
             return id.copied();
         }
         let canon_path = canonicalize_path(path);
-        self.id_by_file.get(&canon_path).copied()
+        if let Some(&id) = self.id_by_file.get(&canon_path) {
+            return Some(id);
+        }
+        // A local path the compiler never saw: map it back to the compiled form
+        let compiled = self.source_map.to_compiled(&canon_path)?;
+        self.id_by_file.get(&canonicalize_path(&compiled)).copied()
     }
 }
 
@@ -166,6 +211,15 @@ pub struct ObjectInfo {
     pub file_table: FileTable,
     /// Memory regions/sections from ELF (e.g., .text, .data, .bss)
     pub memory_ranges: Vec<crate::da_helper::memory::MemoryRegion>,
+    /// PT_LOAD segments and allocated sections with their load addresses, for the memory map
+    pub layout: ElfLayout,
+    /// The ELF file all this was loaded from
+    pub elf_source: Option<ElfSource>,
+    /// Contents of the loadable sections at their load addresses, read on first use; see
+    /// [`ObjectInfo::load_image`]
+    load_image: OnceLock<MemoryImage>,
+    /// CFI and EHABI tables for unwinding stacks
+    pub unwind_tables: UnwindTables,
     /// Symbol table extracted from ELF symbol table (for cross-checking)
     pub elf_symbols: crate::da_helper::symbols::SymbolTable,
     /// Static file to symbols mapping for quick lookup of which symbols are defined in which files
@@ -175,6 +229,12 @@ pub struct ObjectInfo {
     pub global_symbols: Vec<Arc<Symbol>>, // List of global symbols for quick access
 
     pub rtt_symbol_address: Option<u64>, // Address of RTT control block if found
+
+    /// How symbol names were demangled when they were loaded
+    pub demangle_style: DemangleStyle,
+
+    /// DWARF types resolved so far, by `Symbol::type_offset`; `None` if there was none to resolve
+    value_types: Mutex<HashMap<u64, Option<ValueType>>>,
 }
 
 impl ObjectInfo {
@@ -184,21 +244,147 @@ impl ObjectInfo {
             dwarf_symbols: crate::da_helper::symbols::SymbolTable::new(),
             file_table: FileTable::new(),
             memory_ranges: Vec::new(),
+            layout: ElfLayout::default(),
+            elf_source: None,
+            load_image: OnceLock::new(),
+            unwind_tables: UnwindTables::default(),
             elf_symbols: crate::da_helper::symbols::SymbolTable::new(),
             static_file_mapping: StaticFileMapping::new(),
             global_symbols: Vec::new(),
             rtt_symbol_address: None,
+            demangle_style: DemangleStyle::default(),
+            value_types: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Contents of the loadable sections at their load addresses, for flash verification and
+    /// core dumps. Only a few requests need it, so it is read from the ELF when first asked for
+    /// rather than held from load time. Empty when nothing was loaded from a file.
+    pub fn load_image(&self) -> Result<&MemoryImage> {
+        if let Some(image) = self.load_image.get() {
+            return Ok(image);
+        }
+        let image = self
+            .with_elf(|obj_file| Ok(MemoryImage::from_sections(obj_file, &self.layout)))?
+            .unwrap_or_default();
+        Ok(self.load_image.get_or_init(|| image))
+    }
+
+    /// Types of the variables whose `Symbol::type_offset`s are given, for formatting their values.
+    /// Few variables are ever sampled, so types are resolved from the ELF's DWARF when first
+    /// asked for rather than for every variable at load time. Offsets with no type are left out.
+    /// The offsets point into the DWARF that was loaded, so this fails, caching nothing, once the
+    /// ELF has been rebuilt.
+    pub fn value_types(&self, type_offsets: &[u64]) -> Result<HashMap<u64, ValueType>> {
+        let mut cache = self.value_types.lock_recover();
+        let missing: Vec<u64> = type_offsets
+            .iter()
+            .copied()
+            .filter(|offset| !cache.contains_key(offset))
+            .collect();
+        if !missing.is_empty() {
+            let mut resolved = self
+                .with_elf(|obj_file| Ok(resolve_types_at(&load_dwarf(obj_file)?, &missing)?))?
+                .unwrap_or_default();
+            for offset in missing {
+                cache.insert(offset, resolved.remove(&offset));
+            }
+        }
+        Ok(type_offsets
+            .iter()
+            .filter_map(|offset| Some((*offset, cache.get(offset)?.clone()?)))
+            .collect())
+    }
+
+    /// Run `f` on the ELF the symbols were loaded from; `None` when they weren't loaded from a file.
+    /// Fails if the file has been rebuilt since, as its contents wouldn't match the rest of this.
+    fn with_elf<T>(&self, f: impl FnOnce(&object::File) -> Result<T>) -> Result<Option<T>> {
+        let Some(source) = &self.elf_source else {
+            return Ok(None);
+        };
+        let data = source.read()?;
+        let obj_file =
+            object::File::parse(&*data).with_context(|| format!("Parsing {}", source.path.display()))?;
+        f(&obj_file).map(Some)
+    }
+
     pub fn sort_globals_and_statics(&mut self) {
         self.global_symbols.sort_by_key(|s| s.name.clone());
         self.static_file_mapping.sort_symbols();
     }
 }
 
+/// The ELF file an ObjectInfo was loaded from, with a fingerprint of what it held then
+#[derive(Debug, Clone)]
+pub struct ElfSource {
+    pub path: PathBuf,
+    len: usize,
+    crc32: u32,
+}
+
+impl ElfSource {
+    /// `data` is the file's contents as they were loaded
+    pub fn new(path: PathBuf, data: &[u8]) -> Self {
+        Self {
+            path,
+            len: data.len(),
+            crc32: crc32fast::hash(data),
+        }
+    }
+
+    /// Read the file again, failing if it no longer holds what was loaded
+    pub fn read(&self) -> Result<Vec<u8>> {
+        let data = std::fs::read(&self.path).with_context(|| format!("Reading {}", self.path.display()))?;
+        if data.len() != self.len || crc32fast::hash(&data) != self.crc32 {
+            return Err(anyhow!(
+                "{} has changed since it was loaded; restart the session to use the new build",
+                self.path.display()
+            ));
+        }
+        Ok(data)
+    }
+}
+
 impl Default for ObjectInfo {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_rebuilt_elf_is_not_read_against_the_old_symbols() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.elf");
+        std::fs::write(&path, b"first build").unwrap();
+        let mut info = ObjectInfo::new();
+        info.elf_source = Some(ElfSource::new(path.clone(), b"first build"));
+        assert_eq!(info.elf_source.as_ref().unwrap().read().unwrap(), b"first build");
+
+        // Same size, different contents
+        std::fs::write(&path, b"later build").unwrap();
+        let err = info.load_image().unwrap_err().to_string();
+        assert!(err.contains("has changed since it was loaded"), "{}", err);
+
+        std::fs::write(&path, b"first build").unwrap();
+        assert_eq!(info.elf_source.as_ref().unwrap().read().unwrap(), b"first build");
+    }
+
+    #[test]
+    fn types_are_not_resolved_from_a_rebuilt_elf() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.elf");
+        std::fs::write(&path, b"later build").unwrap();
+        let mut info = ObjectInfo::new();
+        info.elf_source = Some(ElfSource::new(path, b"first build"));
+
+        let err = info.value_types(&[0x40, 0x80]).unwrap_err().to_string();
+        assert!(err.contains("has changed since it was loaded"), "{}", err);
+        assert!(info.value_types.lock_recover().is_empty());
+        // Nothing to resolve needs no file
+        assert!(info.value_types(&[]).unwrap().is_empty());
+    }
+}
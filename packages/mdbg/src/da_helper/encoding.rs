@@ -0,0 +1,306 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Wire formats negotiated with `hello`. Each client's sink holds a `FormatCell`; everything it
+//! writes goes through `WireFormat::encode`, which produces a Content-Length frame labelled with
+//! `Content-Type` / `Content-Encoding` so readers can decode any frame on its own.
+
+use std::io::{Read, Write};
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+use crate::common::framing::{Frame, FrameCodec, FrameError, DEFAULT_MAX_MESSAGE_SIZE};
+use crate::common::sync::MutexExt;
+use crate::da_helper::helper_requests::{Compression, Encoding, HelloRequest};
+
+/// Bodies smaller than this are sent uncompressed; it would cost more than it saves
+pub const COMPRESS_MIN_BYTES: usize = 1024;
+
+const ZSTD_LEVEL: i32 = 3;
+
+impl Encoding {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(Encoding::Json),
+            "msgpack" | "messagepack" => Some(Encoding::Msgpack),
+            "cbor" => Some(Encoding::Cbor),
+            _ => None,
+        }
+    }
+
+    /// The `Content-Type` header for this encoding; JSON frames don't carry one
+    pub fn content_type(self) -> Option<&'static str> {
+        match self {
+            Encoding::Json => None,
+            Encoding::Msgpack => Some("application/msgpack"),
+            Encoding::Cbor => Some("application/cbor"),
+        }
+    }
+
+    fn from_content_type(content_type: Option<&str>) -> Result<Self, FrameError> {
+        let Some(content_type) = content_type else {
+            return Ok(Encoding::Json);
+        };
+        let media = content_type.split(';').next().unwrap_or("").trim();
+        match media.to_ascii_lowercase().as_str() {
+            "application/json" | "application/vscode-jsonrpc" => Ok(Encoding::Json),
+            "application/msgpack" | "application/x-msgpack" => Ok(Encoding::Msgpack),
+            "application/cbor" => Ok(Encoding::Cbor),
+            _ => Err(FrameError::UnsupportedContentType(content_type.to_string())),
+        }
+    }
+}
+
+impl Compression {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" | "identity" => Some(Compression::None),
+            "zstd" => Some(Compression::Zstd),
+            "deflate" => Some(Compression::Deflate),
+            _ => None,
+        }
+    }
+
+    pub fn content_encoding(self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Zstd => Some("zstd"),
+            Compression::Deflate => Some("deflate"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WireFormat {
+    pub encoding: Encoding,
+    pub compression: Compression,
+}
+
+impl WireFormat {
+    /// The first supported entry of each of the client's preference lists
+    pub fn negotiate(req: &HelloRequest) -> Self {
+        Self {
+            encoding: req
+                .encodings
+                .iter()
+                .find_map(|n| Encoding::parse(n))
+                .unwrap_or_default(),
+            compression: req
+                .compression
+                .iter()
+                .find_map(|n| Compression::parse(n))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// `msg` as a complete frame in this format
+    pub fn encode(&self, codec: &FrameCodec, msg: &Value) -> Result<Vec<u8>, FrameError> {
+        let (body, compression) = self.encode_body(msg)?;
+        codec.encode_body(&body, self.encoding.content_type(), compression.content_encoding())
+    }
+
+    /// The body alone, and the compression actually applied to it
+    pub fn encode_body(&self, msg: &Value) -> Result<(Vec<u8>, Compression), FrameError> {
+        let body = match self.encoding {
+            Encoding::Json => serde_json::to_vec(msg)?,
+            Encoding::Msgpack => rmp_serde::to_vec_named(msg).map_err(encoding_error)?,
+            Encoding::Cbor => {
+                let mut body = Vec::new();
+                ciborium::into_writer(msg, &mut body).map_err(encoding_error)?;
+                body
+            }
+        };
+        if body.len() < COMPRESS_MIN_BYTES {
+            return Ok((body, Compression::None));
+        }
+        let compressed = match self.compression {
+            Compression::None => return Ok((body, Compression::None)),
+            Compression::Zstd => zstd::encode_all(body.as_slice(), ZSTD_LEVEL)?,
+            Compression::Deflate => {
+                let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&body)?;
+                encoder.finish()?
+            }
+        };
+        Ok((compressed, self.compression))
+    }
+}
+
+/// Turn a frame in any supported format back into JSON. Decompressed bodies are held to the
+/// same limit as frames, so a small compressed frame can't expand without bound.
+pub fn decode(frame: &Frame) -> Result<Value, FrameError> {
+    let encoding = Encoding::from_content_type(frame.content_type.as_deref())?;
+    let compression = match frame.content_encoding.as_deref() {
+        None => Compression::None,
+        Some(name) => Compression::parse(name)
+            .ok_or_else(|| FrameError::Encoding(format!("unsupported Content-Encoding '{}'", name)))?,
+    };
+    let decompressed;
+    let body = match compression {
+        Compression::None => frame.body.as_slice(),
+        Compression::Zstd => {
+            decompressed = read_bounded(zstd::Decoder::new(frame.body.as_slice())?)?;
+            decompressed.as_slice()
+        }
+        Compression::Deflate => {
+            decompressed = read_bounded(flate2::read::ZlibDecoder::new(frame.body.as_slice()))?;
+            decompressed.as_slice()
+        }
+    };
+    match encoding {
+        Encoding::Json => Ok(serde_json::from_slice(body)?),
+        Encoding::Msgpack => rmp_serde::from_slice(body).map_err(encoding_error),
+        Encoding::Cbor => ciborium::from_reader(body).map_err(encoding_error),
+    }
+}
+
+fn read_bounded<R: Read>(reader: R) -> Result<Vec<u8>, FrameError> {
+    let mut out = Vec::new();
+    reader.take(DEFAULT_MAX_MESSAGE_SIZE as u64 + 1).read_to_end(&mut out)?;
+    if out.len() > DEFAULT_MAX_MESSAGE_SIZE {
+        return Err(FrameError::TooLarge {
+            size: out.len(),
+            max: DEFAULT_MAX_MESSAGE_SIZE,
+        });
+    }
+    Ok(out)
+}
+
+fn encoding_error(e: impl std::fmt::Display) -> FrameError {
+    FrameError::Encoding(e.to_string())
+}
+
+/// A sink's current format; switched once the HelloResponse has gone out
+#[derive(Default)]
+pub struct FormatCell(Mutex<WireFormat>);
+
+impl FormatCell {
+    pub fn get(&self) -> WireFormat {
+        *self.0.lock_recover()
+    }
+
+    pub fn set(&self, format: WireFormat) {
+        *self.0.lock_recover() = format;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    fn sample(instructions: usize) -> Value {
+        let instructions: Vec<Value> = (0..instructions)
+            .map(|n| {
+                json!({"a": format!("{:x}", 0x0800_0000 + n * 2), "b": "00 20", "i": "movs r0, #0",
+                       "f": 3, "o": n * 2, "F": 1, "sl": 10 + n, "el": 10 + n})
+            })
+            .collect();
+        json!({"req": "disasm", "seq": 7, "file_table": {"1": "main.c"}, "func_table": {"3": "main"},
+               "instructions": instructions})
+    }
+
+    fn round_trip(format: WireFormat, msg: &Value) -> Frame {
+        let codec = FrameCodec::new();
+        let bytes = format.encode(&codec, msg).unwrap();
+        let frame = codec.read_message(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(&decode(&frame).unwrap(), msg, "{:?}", format);
+        frame
+    }
+
+    #[test]
+    fn every_format_round_trips() {
+        let msg = sample(200);
+        let json_size = serde_json::to_vec(&msg).unwrap().len();
+        for encoding in [Encoding::Json, Encoding::Msgpack, Encoding::Cbor] {
+            for compression in [Compression::None, Compression::Zstd, Compression::Deflate] {
+                let frame = round_trip(WireFormat { encoding, compression }, &msg);
+                assert_eq!(frame.content_type.as_deref(), encoding.content_type());
+                assert_eq!(frame.content_encoding.as_deref(), compression.content_encoding());
+                if encoding != Encoding::Json || compression != Compression::None {
+                    assert!(
+                        frame.body.len() < json_size,
+                        "{:?}/{:?} is not smaller",
+                        encoding,
+                        compression
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn small_messages_are_not_compressed() {
+        let format = WireFormat {
+            encoding: Encoding::Msgpack,
+            compression: Compression::Zstd,
+        };
+        let frame = round_trip(format, &json!({"method": "HelperEvent", "args": {"type": "Progress"}}));
+        assert_eq!(frame.content_type.as_deref(), Some("application/msgpack"));
+        assert_eq!(frame.content_encoding, None);
+
+        // The default format writes exactly what the plain JSON codec does
+        let codec = FrameCodec::new();
+        let msg = sample(100);
+        assert_eq!(
+            WireFormat::default().encode(&codec, &msg).unwrap(),
+            codec.encode(&msg).unwrap()
+        );
+    }
+
+    #[test]
+    fn negotiation_takes_the_first_supported_preference() {
+        let hello: HelloRequest = serde_json::from_value(json!({
+            "req": "hello", "seq": 1, "encodings": ["protobuf", "cbor", "msgpack"], "compression": ["br", "deflate"]
+        }))
+        .unwrap();
+        assert_eq!(
+            WireFormat::negotiate(&hello),
+            WireFormat {
+                encoding: Encoding::Cbor,
+                compression: Compression::Deflate
+            }
+        );
+        let bare: HelloRequest = serde_json::from_value(json!({"req": "hello", "seq": 2})).unwrap();
+        assert_eq!(WireFormat::negotiate(&bare), WireFormat::default());
+    }
+
+    #[test]
+    fn decode_rejects_unknown_or_corrupt_bodies() {
+        let frame = |content_type: Option<&str>, content_encoding: Option<&str>, body: &[u8]| Frame {
+            content_type: content_type.map(str::to_string),
+            content_encoding: content_encoding.map(str::to_string),
+            body: body.to_vec(),
+        };
+        assert!(matches!(
+            decode(&frame(Some("text/plain"), None, b"{}")),
+            Err(FrameError::UnsupportedContentType(_))
+        ));
+        assert!(matches!(
+            decode(&frame(None, Some("br"), b"{}")),
+            Err(FrameError::Encoding(_))
+        ));
+        assert!(decode(&frame(None, Some("zstd"), b"not zstd")).is_err());
+        assert!(matches!(
+            decode(&frame(Some("application/cbor"), None, &[0xff])),
+            Err(FrameError::Encoding(_))
+        ));
+        assert_eq!(
+            decode(&frame(Some("application/json; charset=utf-8"), None, b"[1]")).unwrap(),
+            json!([1])
+        );
+    }
+}
@@ -356,6 +356,32 @@ pub fn get_disasm_from_objdump(
     objdump_path: &str,
     elf_path: &str,
 ) -> Result<AssemblyListing, Box<dyn Error>> {
+    get_disasm_from_objdump_with_progress(objdump_path, elf_path, 0, &mut |_, _| {})
+}
+
+/// Total size of the executable sections objdump will disassemble, used as the progress total.
+pub(crate) fn executable_bytes(obj_file: &object::File) -> u64 {
+    use object::{Object, ObjectSection, SectionKind};
+    obj_file
+        .sections()
+        .filter(|s| s.kind() == SectionKind::Text)
+        .map(|s| s.size())
+        .sum()
+}
+
+/// Same as `get_disasm_from_objdump`, calling `progress(bytes_done, total_bytes)` each time
+/// another 1% of `total_bytes` (see `executable_bytes`) has been parsed. `total_bytes` is 0 if
+/// it is unknown.
+pub fn get_disasm_from_objdump_with_progress(
+    objdump_path: &str,
+    elf_path: &str,
+    total_bytes: u64,
+    progress: &mut dyn FnMut(u64, u64),
+) -> Result<AssemblyListing, Box<dyn Error>> {
+    let report_step = (total_bytes / 100).max(1);
+    let mut done_bytes: u64 = 0;
+    let mut next_report: u64 = 0;
+
     // Spawn objdump and stream its stdout to avoid allocating the whole output
     let mut command = Command::new(objdump_path);
     command.args(["-Cd", elf_path]).stdout(Stdio::piped());
@@ -460,6 +486,12 @@ pub fn get_disasm_from_objdump(
         listing.addr_map.insert(address, listing.lines.len());
         listing.lines.push(rc_line.clone());
         current_block.lines.push(rc_line.clone());
+
+        done_bytes += rc_line.bytes.len() as u64 / 2;
+        if done_bytes >= next_report {
+            progress(done_bytes, total_bytes);
+            next_report = done_bytes + report_step;
+        }
     }
 
     // ensure child finishes
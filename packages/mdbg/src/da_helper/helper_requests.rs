@@ -63,6 +63,9 @@ pub struct DisassembleRequest {
 pub struct GlobalsRequest {
     pub req: String, // e.g. "globals"
     pub seq: u64,
+    /** When true, the response carries `variables` instead of the (name, address) tuples */
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detailed: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
@@ -71,7 +74,9 @@ pub struct GlobalsRequest {
 pub struct GlobalsResponse {
     pub req: String, // e.g. "globals"
     pub seq: u64,
-    pub globals: Vec<(String, String)>, // (name, address)
+    pub globals: Vec<(String, String)>, // (name, address), empty for detailed requests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables: Option<Vec<VariableInfo>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
@@ -81,6 +86,9 @@ pub struct StaticsRequest {
     pub req: String, // e.g. "statics"
     pub seq: u64,
     pub file_name: String,
+    /** When true, the response carries `variables` instead of the (name, address) tuples */
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detailed: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
@@ -89,7 +97,30 @@ pub struct StaticsRequest {
 pub struct StaticsResponse {
     pub req: String, // e.g. "statics"
     pub seq: u64,
-    pub statics: Vec<(String, String)>, // (name, address)
+    pub statics: Vec<(String, String)>, // (name, address), empty for detailed requests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables: Option<Vec<VariableInfo>>,
+}
+
+/**
+ * A global or static variable, as returned by detailed globals/statics requests.
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct VariableInfo {
+    pub name: String,
+    /** Mangled name when it differs from `name` (C++, Rust), e.g. for breakpoints by linkage name */
+    pub raw_name: Option<String>,
+    pub address: String, // hex string
+    /** Size in bytes, from the ELF symbol */
+    pub size: u64,
+    /** ELF section, e.g. ".data", ".bss", ".rodata" or a custom one */
+    pub section: Option<String>,
+    /** Declaring file (after source map rules) and 1-based line, from DWARF */
+    pub decl_file: Option<String>,
+    pub decl_line: Option<u32>,
+    /** Hex offset of the variable's type DIE in .debug_info, usable as a type reference */
+    pub type_ref: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
@@ -171,6 +202,667 @@ pub struct DisasmResponse {
     pub instructions: Vec<SerInstruction>, // (addr_hex, bytes, instr)
 }
 
+/**
+ * CfgRequest asks for the control-flow graph of a single function. The function is selected either by
+ * name (as shown in the disassembly, in any demangle style, or the raw mangled name) or by any address
+ * inside it. Set `dot` to also get a Graphviz rendering of the graph in the response.
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+#[allow(non_snake_case)]
+pub struct CfgRequest {
+    pub req: String, // e.g. "cfg"
+    pub seq: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>, // hex address anywhere inside the function
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dot: Option<bool>,
+}
+
+/// Kind of a control-flow edge leaving a basic block.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+#[serde(rename_all = "snake_case")]
+pub enum CfgEdgeKind {
+    /// Execution falls into the next block (no branch, or a conditional branch not taken)
+    Fallthrough,
+    /// Unconditional branch. A target outside the function is a tail call
+    Jump,
+    /// Conditional branch, taken
+    Conditional,
+    /// Subroutine call. Calls do not end a basic block
+    Call,
+    /// Return from the function
+    Return,
+    /// Branch through a register or a table (`bx r3`, `tbb`, `ldr pc, ...`); target unknown
+    Indirect,
+}
+
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct CfgEdge {
+    pub from: u32,
+    /** Destination block id, or null when the edge leaves the function (call, return, tail call, indirect) */
+    pub to: Option<u32>,
+    pub kind: CfgEdgeKind,
+    /** Branch/call target address in hex, when it is known statically */
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /** Symbol objdump printed for the target, e.g. "HAL_Init" */
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct CfgBlock {
+    pub id: u32,
+    /** Address of the first instruction in hex */
+    pub start: String,
+    /** Address just past the last instruction in hex */
+    pub end: String,
+    pub instructions: Vec<SerInstruction>,
+}
+
+/**
+ * Control-flow graph of one function. Instructions carry the same compact source info as in
+ * a DisasmResponse, and `file_table` resolves their `F` ids.
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct CfgGraph {
+    pub function: String,
+    /** Function start address in hex */
+    pub start: String,
+    pub file_table: HashMap<u32, String>,
+    pub blocks: Vec<CfgBlock>,
+    pub edges: Vec<CfgEdge>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct CfgResponse {
+    pub req: String, // e.g. "cfg"
+    pub seq: u64,
+    pub graph: CfgGraph,
+    /** Graphviz DOT rendering of `graph`, only when the request asked for it */
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dot: Option<String>,
+}
+
+/**
+ * SourceListingRequest asks for a mixed source/assembly listing of a function (by name) or of the
+ * address range [start, end). The helper reads the source files itself, so the DA does not have to
+ * rebuild the listing from the `F`/`sl`/`el` fields.
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct SourceListingRequest {
+    pub req: String, // e.g. "sourceListing"
+    pub seq: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>, // hex address, used with `end` when `function` is absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>, // hex address, exclusive
+}
+
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+#[allow(non_snake_case)]
+pub struct SourceLine {
+    /** File ID, resolved through the response's file_table */
+    pub F: u32,
+    /** 1-based line number */
+    pub l: u32,
+    /** Line text, or null if the file could not be read */
+    pub text: Option<String>,
+    /** True if this line was already shown earlier in the listing (code reordered by the optimizer) */
+    pub repeat: bool,
+}
+
+/**
+ * One group of a source-interleaved listing: zero or more source lines followed by the
+ * instructions generated for them. A chunk with no source lines holds instructions without line info.
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct SourceListingChunk {
+    pub source: Vec<SourceLine>,
+    pub instructions: Vec<SerInstruction>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct SourceListingResponse {
+    pub req: String, // e.g. "sourceListing"
+    pub seq: u64,
+    pub file_table: HashMap<u32, String>,
+    pub func_table: HashMap<u32, String>,
+    pub chunks: Vec<SourceListingChunk>,
+}
+
+/**
+ * BatchRequest carries several requests in one message. Each entry is a complete request with its own
+ * `req` and `seq`; batches can't be nested. The helper answers with a single BatchResponse.
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct BatchRequest {
+    pub req: String, // e.g. "batch"
+    pub seq: u64,
+    #[ts(type = "Array<unknown>")]
+    pub requests: Vec<serde_json::Value>,
+}
+
+/**
+ * Responses in the same order as the batch's requests. Failed requests have an ErrorResponse
+ * in their slot; an entry is null only when that request was cancelled.
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct BatchResponse {
+    pub req: String, // e.g. "batch"
+    pub seq: u64,
+    #[ts(type = "Array<unknown>")]
+    pub responses: Vec<serde_json::Value>,
+}
+
+/**
+ * CancelRequest drops queued or in-flight work for the request (or batch) with this `seq`;
+ * no response is sent for it, or for the cancelled request. Cancelling finished work is a no-op.
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct CancelRequest {
+    pub req: String, // e.g. "cancel"
+    pub seq: u64,
+}
+
+/**
+ * MemoryMapRequest asks how the image fills the device's memory. Regions come from the MEMORY block of
+ * `linker_script` when given, else from the ELF's PT_LOAD segments; `svd` adds the peripheral blocks of
+ * a CMSIS-SVD file. Both are paths the helper reads itself.
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct MemoryMapRequest {
+    pub req: String, // e.g. "memoryMap"
+    pub seq: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linker_script: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub svd: Option<String>,
+}
+
+/// Where a memory region's definition came from
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+#[serde(rename_all = "lowercase")]
+pub enum RegionSource {
+    Elf,
+    Linker,
+    Svd,
+}
+
+/**
+ * One physical memory region and how much of it the image uses. `used` counts run-time placement
+ * (VMA) and, for linker regions, load images (LMA) such as the initial values of `.data` in flash.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct MemoryRegionUsage {
+    pub name: String,
+    pub origin: String, // hex address
+    pub length: u64,
+    /** Linker attributes such as "rx" or "rwx", or the segment's flags; null for SVD peripherals */
+    pub attributes: Option<String>,
+    pub source: RegionSource,
+    pub used: u64,
+    pub free: u64,
+    /** Bytes of sections that start in this region but run past its end */
+    pub overflow: u64,
+    /** Sections placed in this region; a copy-down section is also listed in its load region */
+    pub sections: Vec<String>,
+}
+
+/**
+ * An allocated ELF section. `vma` is where it lives at run time, `lma` where its contents are stored in
+ * the image; they differ for sections the startup code copies down (`copy_down`), such as `.data`.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct SectionPlacement {
+    pub name: String,
+    pub vma: String, // hex address
+    pub lma: String, // hex address
+    pub size: u64,
+    /** False for sections without contents in the file, such as `.bss` */
+    pub has_data: bool,
+    pub copy_down: bool,
+    pub region: Option<String>,
+    pub load_region: Option<String>,
+}
+
+/// Two regions, or two sections, that share addresses
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct MemoryOverlap {
+    pub first: String,
+    pub second: String,
+    pub start: String, // hex address
+    pub size: u64,
+    /** True for two sections, false for two regions */
+    pub sections: bool,
+}
+
+/**
+ * A section that doesn't fit: `bytes` of it lie past the end of `region` (the region it starts in), or
+ * outside every region when `region` is null. `load` is set when it is the section's load image.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct MemoryOverflow {
+    pub section: String,
+    pub region: Option<String>,
+    pub bytes: u64,
+    pub load: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct MemoryMapResponse {
+    pub req: String, // e.g. "memoryMap"
+    pub seq: u64,
+    pub regions: Vec<MemoryRegionUsage>,
+    pub sections: Vec<SectionPlacement>,
+    pub overlaps: Vec<MemoryOverlap>,
+    pub overflows: Vec<MemoryOverflow>,
+}
+
+/**
+ * Target memory to compare against the ELF: either a HEX, S-record or binary `file` the helper reads
+ * (binary files are placed at `address`), or inline `data` at `address`. `data` is hex, two digits per
+ * byte, as gdb's `-data-read-memory-bytes` returns it.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct VerifyDump {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>, // hex address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+/**
+ * VerifyRequest asks whether target memory holds what the ELF would load there. Only the dumped
+ * addresses are compared, against the contents of the loadable sections at their load addresses.
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct VerifyRequest {
+    pub req: String, // e.g. "verify"
+    pub seq: u64,
+    pub dumps: Vec<VerifyDump>,
+}
+
+/**
+ * A range of load addresses where target memory differs from the ELF. Differences a few bytes apart
+ * are reported as one range; `differing` counts the bytes that actually differ. `symbols` are the
+ * symbols at the matching run-time addresses (they differ from the load addresses for `.data`).
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct VerifyMismatch {
+    pub start: String, // hex address
+    pub size: u64,
+    pub differing: u64,
+    pub section: Option<String>,
+    pub symbols: Vec<String>,
+    /** The first bytes of the range, in hex, from the ELF and from the target */
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct VerifyResponse {
+    pub req: String, // e.g. "verify"
+    pub seq: u64,
+    /** True when every compared byte matched */
+    pub matched: bool,
+    /** Bytes that were in both the dumps and the ELF */
+    pub compared: u64,
+    /** Dumped bytes at addresses the ELF has no contents for; not compared */
+    pub ignored: u64,
+    /** ELF contents at addresses that weren't dumped */
+    pub not_dumped: u64,
+    pub mismatches: Vec<VerifyMismatch>,
+}
+
+/**
+ * UnwindRequest asks for the call stack of a halted Cortex-M. `registers` maps names (r0-r12, sp, lr,
+ * pc, xpsr, msp, psp, control) to hex values; `memory` holds the stack the client read, in the same
+ * form as verify dumps. Alternatively `core` is the path of a core-dump manifest to unwind. Memory that
+ * isn't supplied is read from the ELF's load image.
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct UnwindRequest {
+    pub req: String, // e.g. "unwind"
+    pub seq: u64,
+    #[serde(default)]
+    pub registers: HashMap<String, String>,
+    #[serde(default)]
+    pub memory: Vec<VerifyDump>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub core: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_frames: Option<u32>,
+}
+
+/**
+ * One frame, innermost first. `method` says which table recovered it from the frame below (absent
+ * for the innermost frame); `exception` is set on code that an exception interrupted. `registers`
+ * holds the values known in this frame, for evaluating its locals.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct UnwindFrame {
+    pub pc: String, // hex address
+    pub sp: String, // hex address
+    pub function: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u64>,
+    pub method: Option<String>, // ".debug_frame", ".eh_frame", ".ARM.exidx", "lr" or "exception frame"
+    pub exception: Option<String>, // e.g. "SysTick"
+    pub exc_return: Option<String>,
+    pub registers: Vec<(String, String)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct UnwindResponse {
+    pub req: String, // e.g. "unwind"
+    pub seq: u64,
+    pub frames: Vec<UnwindFrame>,
+    /** Why unwinding stopped after the last frame */
+    pub stop_reason: String,
+}
+
+/**
+ * One thing to sample. Give either `symbol` (a global or static variable, typed from DWARF) or
+ * `address` with `size`. `type_ref` (from `VariableInfo`) types an address; without a type the value
+ * is shown as hex.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct LiveItem {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>, // hex address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_ref: Option<String>,
+    pub interval_ms: u32,
+}
+
+/**
+ * LiveStartRequest starts sampling `items` over a connection of its own to the gdb-server at
+ * `gdb_server` (`host:port`, e.g. a port the proxy duplicated the GDB stream to). It replaces any
+ * sampling this client already started. Changed values arrive as `LiveSamples` events.
+ * `max_busy_percent` (default 25) caps the share of time spent reading; `max_gap` (default 32) is how
+ * many unused bytes may be read to join two items into one read.
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct LiveStartRequest {
+    pub req: String, // e.g. "liveStart"
+    pub seq: u64,
+    pub gdb_server: String,
+    pub items: Vec<LiveItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_busy_percent: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_gap: Option<u32>,
+}
+
+/** Where each item was resolved to, in request order */
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct LiveItemInfo {
+    pub id: String,
+    pub address: String, // hex address
+    pub size: u32,
+    pub type_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct LiveStartResponse {
+    pub req: String, // e.g. "liveStart"
+    pub seq: u64,
+    pub items: Vec<LiveItemInfo>,
+}
+
+/** LiveStopRequest stops this client's sampling; `stopped` is false if none was running */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct LiveStopRequest {
+    pub req: String, // e.g. "liveStop"
+    pub seq: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct LiveStopResponse {
+    pub req: String, // e.g. "liveStop"
+    pub seq: u64,
+    pub stopped: bool,
+}
+
+/** A sampled value that changed since it was last reported */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct LiveSample {
+    pub id: String,
+    pub value: String,
+    pub bytes: String, // hex, in target memory order
+}
+
+/**
+ * HistoryStartRequest records the values of running live sampling (see `LiveStartRequest`): the items
+ * named in `ids`, or all of them. The last `capacity` samples (default 100000) are kept in memory, or
+ * with `file` every sample is appended to that JSON-lines file. It replaces any earlier recording.
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct HistoryStartRequest {
+    pub req: String, // e.g. "historyStart"
+    pub seq: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ids: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct HistoryStartResponse {
+    pub req: String, // e.g. "historyStart"
+    pub seq: u64,
+    /** Ids of the recorded items */
+    pub signals: Vec<String>,
+}
+
+/** HistoryStopRequest stops recording. What was recorded can still be exported. */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct HistoryStopRequest {
+    pub req: String, // e.g. "historyStop"
+    pub seq: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct HistoryStopResponse {
+    pub req: String, // e.g. "historyStop"
+    pub seq: u64,
+    pub samples: u64,
+    /** Samples the in-memory ring overwrote because it was full */
+    pub dropped: u64,
+}
+
+/**
+ * Variable history formats. CSV has one row per sample (`time_s,id,value,bytes`); JSON holds the
+ * signals and samples; VCD opens in waveform viewers such as GTKWave.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryFormat {
+    Csv,
+    Json,
+    Vcd,
+}
+
+/**
+ * HistoryExportRequest exports the recorded history, to `path` if given, else in the response's
+ * `data`. Recording may still be running.
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct HistoryExportRequest {
+    pub req: String, // e.g. "historyExport"
+    pub seq: u64,
+    pub format: HistoryFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct HistoryExportResponse {
+    pub req: String, // e.g. "historyExport"
+    pub seq: u64,
+    pub samples: u64,
+    pub path: Option<String>,
+    pub data: Option<String>,
+}
+
+/**
+ * Body encodings for messages the helper sends. JSON is the default; MessagePack and CBOR carry the
+ * same objects (same field names and values), only smaller and cheaper to parse.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    #[default]
+    Json,
+    Msgpack,
+    Cbor,
+}
+
+/**
+ * Compression applied on top of the encoding. Only bodies of at least 1 KiB are compressed, so a
+ * frame's Content-Encoding header says whether this one was.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    None,
+    Zstd,
+    Deflate,
+}
+
+/**
+ * HelloRequest negotiates how the helper encodes what it sends to this client, e.g. to shrink large
+ * disassembly responses over slow links. List the names you accept in order of preference; the helper
+ * picks the first one it supports from each list, falling back to "json" and "none". The choice applies
+ * to everything sent after the HelloResponse, which itself is still JSON. Every frame also names its
+ * encoding in `Content-Type` (absent for JSON) and `Content-Encoding` headers. Requests stay JSON.
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct HelloRequest {
+    pub req: String, // e.g. "hello"
+    pub seq: u64,
+    /** e.g. ["msgpack", "cbor", "json"]; unknown names are skipped */
+    #[serde(default)]
+    pub encodings: Vec<String>,
+    /** e.g. ["zstd", "deflate", "none"]; unknown names are skipped */
+    #[serde(default)]
+    pub compression: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct HelloResponse {
+    pub req: String,
+    pub seq: u64,
+    pub version: String,
+    pub encoding: Encoding,
+    pub compression: Compression,
+}
+
+/**
+ * Stable error codes for ErrorResponse. Clients may switch on these; new codes may be added.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /** The request JSON did not match the schema for its `req` */
+    ParseError,
+    /** `req` is missing or not a request this helper knows */
+    UnknownRequest,
+    /** The data the request needs is still loading; retry after the matching Ready event */
+    NotReady,
+    /** No function, symbol or instruction at the given name/address */
+    AddressNotFound,
+    /** The ELF file is being reloaded; retry after the next SymbolTableReady. Reserved: this
+     * helper does not reload yet */
+    ReloadInProgress,
+    /** Recognized but not implemented by this helper version */
+    NotImplemented,
+    /** The gdb-server could not be reached, or stopped answering */
+    TargetUnavailable,
+    /** Something went wrong inside the helper */
+    Internal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct RequestError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+/**
+ * Sent instead of the normal response when a request fails. Every request that carries a `seq`
+ * gets exactly one of the two (except `cancel`, and requests it cancelled).
+ */
+#[derive(Serialize, Deserialize, Debug, ts_rs::TS)]
+#[ts(export, export_to = "dasm-helper/")]
+pub struct ErrorResponse {
+    pub req: String,
+    pub seq: u64,
+    pub error: RequestError,
+}
+
 /**
  * Events generated by the helper process and sent to the DA.
  * Uses internally-tagged enum serialization so each variant has a 'type' field.
@@ -223,6 +915,14 @@ pub enum HelperEvent {
         details: Option<String>,
     },
 
+    /// Live values that changed; `slowdown` is the factor sampling intervals are stretched by
+    /// while the probe can't keep up (1 when it can)
+    LiveSamples {
+        session_id: String,
+        samples: Vec<LiveSample>,
+        slowdown: u32,
+    },
+
     /// Diagnostic/log message (typically only shown if verbose logging enabled)
     Log {
         session_id: String,
@@ -251,9 +951,94 @@ mod tests {
         GlobalsResponse::export(&config).unwrap();
         StaticsRequest::export(&config).unwrap();
         StaticsResponse::export(&config).unwrap();
+        VariableInfo::export(&config).unwrap();
         SymbolLookupAddressRequest::export(&config).unwrap();
         SymbolLookupNameRequest::export(&config).unwrap();
         SymbolLookupResponse::export(&config).unwrap();
+        CfgRequest::export(&config).unwrap();
+        CfgEdgeKind::export(&config).unwrap();
+        CfgEdge::export(&config).unwrap();
+        CfgBlock::export(&config).unwrap();
+        CfgGraph::export(&config).unwrap();
+        CfgResponse::export(&config).unwrap();
+        SourceListingRequest::export(&config).unwrap();
+        SourceLine::export(&config).unwrap();
+        SourceListingChunk::export(&config).unwrap();
+        SourceListingResponse::export(&config).unwrap();
+        BatchRequest::export(&config).unwrap();
+        BatchResponse::export(&config).unwrap();
+        CancelRequest::export(&config).unwrap();
+        MemoryMapRequest::export(&config).unwrap();
+        RegionSource::export(&config).unwrap();
+        MemoryRegionUsage::export(&config).unwrap();
+        SectionPlacement::export(&config).unwrap();
+        MemoryOverlap::export(&config).unwrap();
+        MemoryOverflow::export(&config).unwrap();
+        MemoryMapResponse::export(&config).unwrap();
+        VerifyDump::export(&config).unwrap();
+        VerifyRequest::export(&config).unwrap();
+        VerifyMismatch::export(&config).unwrap();
+        VerifyResponse::export(&config).unwrap();
+        UnwindRequest::export(&config).unwrap();
+        UnwindFrame::export(&config).unwrap();
+        UnwindResponse::export(&config).unwrap();
+        LiveItem::export(&config).unwrap();
+        LiveStartRequest::export(&config).unwrap();
+        LiveItemInfo::export(&config).unwrap();
+        LiveStartResponse::export(&config).unwrap();
+        LiveStopRequest::export(&config).unwrap();
+        LiveStopResponse::export(&config).unwrap();
+        LiveSample::export(&config).unwrap();
+        HistoryStartRequest::export(&config).unwrap();
+        HistoryStartResponse::export(&config).unwrap();
+        HistoryStopRequest::export(&config).unwrap();
+        HistoryStopResponse::export(&config).unwrap();
+        HistoryFormat::export(&config).unwrap();
+        HistoryExportRequest::export(&config).unwrap();
+        HistoryExportResponse::export(&config).unwrap();
+        ErrorCode::export(&config).unwrap();
+        RequestError::export(&config).unwrap();
+        ErrorResponse::export(&config).unwrap();
         HelperEvent::export(&config).unwrap();
+        Encoding::export(&config).unwrap();
+        Compression::export(&config).unwrap();
+        HelloRequest::export(&config).unwrap();
+        HelloResponse::export(&config).unwrap();
+    }
+
+    #[test]
+    fn globals_tuple_form_stays_the_default() {
+        let req: GlobalsRequest = serde_json::from_str(r#"{"req":"globals","seq":3}"#).unwrap();
+        assert_eq!(req.detailed, None);
+
+        let compact = GlobalsResponse {
+            req: "globals".to_string(),
+            seq: 3,
+            globals: vec![("counter".to_string(), "0x20000000".to_string())],
+            variables: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&compact).unwrap(),
+            r#"{"req":"globals","seq":3,"globals":[["counter","0x20000000"]]}"#
+        );
+
+        let detailed = StaticsResponse {
+            req: "statics".to_string(),
+            seq: 4,
+            statics: Vec::new(),
+            variables: Some(vec![VariableInfo {
+                name: "state".to_string(),
+                raw_name: None,
+                address: "0x20000010".to_string(),
+                size: 4,
+                section: Some(".bss".to_string()),
+                decl_file: Some("/src/main.c".to_string()),
+                decl_line: Some(12),
+                type_ref: Some("0x1a2".to_string()),
+            }]),
+        };
+        let json = serde_json::to_value(&detailed).unwrap();
+        assert_eq!(json["variables"][0]["section"], ".bss");
+        assert_eq!(json["variables"][0]["decl_line"], 12);
     }
 }
@@ -0,0 +1,570 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Flash images: a sparse memory image built from an ELF's PT_LOAD segments (at their load
+//! addresses) or read from Intel HEX, Motorola S-record or raw binary files, and written back out
+//! in any of those formats.
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::common::utils::decode_hex;
+use crate::da_helper::memory_map::{elf_load_segments, ElfLayout};
+
+/// Data bytes per HEX / S-record line, as objcopy writes them
+const RECORD_BYTES: usize = 16;
+
+/// Largest span a flat binary may cover. Wider images hold several memories far apart (flash and
+/// option bytes, or flash and RAM), and filling the gaps between them would take gigabytes.
+const MAX_BINARY_SPAN: u64 = 256 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImageFormat {
+    /// Intel HEX
+    Hex,
+    /// Motorola S-record
+    Srec,
+    /// Raw binary, from the lowest address to the highest
+    Bin,
+}
+
+impl ImageFormat {
+    /// Guess from a file extension: .hex/.ihex, .srec/.s19/.s28/.s37/.mot, anything else is binary
+    pub fn from_path(path: &Path) -> Self {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        match ext.as_str() {
+            "hex" | "ihex" | "ihx" => ImageFormat::Hex,
+            "srec" | "s19" | "s28" | "s37" | "mot" => ImageFormat::Srec,
+            _ => ImageFormat::Bin,
+        }
+    }
+}
+
+/// Bytes at addresses, as contiguous runs. Writes over existing bytes replace them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryImage {
+    /// Start address -> bytes; runs never overlap or touch
+    runs: BTreeMap<u64, Vec<u8>>,
+    /// Entry point, from the ELF header or a HEX/S-record start address record
+    pub entry: Option<u64>,
+}
+
+impl MemoryImage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The PT_LOAD segments of an ELF file, placed at their physical (load) addresses. Only the
+    /// file contents are included; the zero-filled tail of a segment (.bss) is not.
+    pub fn from_elf(path: &Path) -> Result<Self> {
+        let data = std::fs::read(path).with_context(|| format!("Reading {}", path.display()))?;
+        Self::from_elf_data(&data)
+    }
+
+    pub fn from_elf_data(data: &[u8]) -> Result<Self> {
+        use object::Object;
+        let obj_file = object::File::parse(data)?;
+        let segments = elf_load_segments(&obj_file, data);
+        if segments.is_empty() {
+            return Err(anyhow!("No loadable segments"));
+        }
+        let mut image = Self::new();
+        for seg in segments.iter().filter(|s| s.filesz > 0) {
+            let start = seg.offset as usize;
+            let bytes = start
+                .checked_add(seg.filesz as usize)
+                .and_then(|end| data.get(start..end))
+                .ok_or_else(|| anyhow!("Segment at 0x{:x} runs past the end of the file", seg.paddr))?;
+            image.write(seg.paddr, bytes);
+        }
+        image.entry = Some(obj_file.entry());
+        Ok(image)
+    }
+
+    /// The contents of the allocated sections, at their load addresses. Unlike `from_elf_data` this
+    /// leaves out the headers and padding that PT_LOAD segments may also cover.
+    pub fn from_sections(obj_file: &object::File, layout: &ElfLayout) -> Self {
+        use object::{Object, ObjectSection};
+        let mut image = Self::new();
+        for section in obj_file.sections() {
+            let Some(placed) = layout.sections.iter().find(|s| {
+                s.has_data && s.vma == section.address() && section.name().is_ok_and(|n| n == s.name)
+            }) else {
+                continue;
+            };
+            if let Ok(bytes) = section.data() {
+                image.write(placed.lma, bytes);
+            }
+        }
+        image.entry = Some(obj_file.entry());
+        image
+    }
+
+    pub fn from_binary(data: &[u8], base: u64) -> Self {
+        let mut image = Self::new();
+        image.write(base, data);
+        image
+    }
+
+    /// Load a HEX, S-record or binary file; the format comes from the contents, then the
+    /// extension. Binary files are placed at `base`.
+    pub fn load(path: &Path, base: u64) -> Result<Self> {
+        let data = std::fs::read(path).with_context(|| format!("Reading {}", path.display()))?;
+        let text = std::str::from_utf8(&data).ok().map(str::trim_start);
+        let image = match text {
+            Some(t) if t.starts_with(':') => Self::from_ihex(t),
+            Some(t) if t.starts_with('S') && ImageFormat::from_path(path) != ImageFormat::Bin => Self::from_srec(t),
+            Some(t) if t.starts_with("S0") => Self::from_srec(t),
+            _ => Ok(Self::from_binary(&data, base)),
+        };
+        image.with_context(|| format!("In {}", path.display()))
+    }
+
+    /// Store `data` at `addr`, replacing anything already there. Bytes that would lie past
+    /// the end of the 64-bit address space are dropped, so every run ends within it.
+    pub fn write(&mut self, addr: u64, data: &[u8]) {
+        let room = usize::try_from(u64::MAX - addr).unwrap_or(usize::MAX);
+        let data = &data[..data.len().min(room)];
+        if data.is_empty() {
+            return;
+        }
+        let end = addr + data.len() as u64;
+        // Runs that overlap or touch [addr, end) merge into one
+        let touching: Vec<u64> = self
+            .runs
+            .range(..=end)
+            .filter(|(&start, bytes)| start + bytes.len() as u64 >= addr)
+            .map(|(&start, _)| start)
+            .collect();
+        let new_start = touching.first().map_or(addr, |&s| s.min(addr));
+        let mut merged = Vec::new();
+        for start in touching {
+            let bytes = self.runs.remove(&start).unwrap();
+            let offset = (start - new_start) as usize;
+            if merged.len() < offset + bytes.len() {
+                merged.resize(offset + bytes.len(), 0);
+            }
+            merged[offset..offset + bytes.len()].copy_from_slice(&bytes);
+        }
+        let offset = (addr - new_start) as usize;
+        if merged.len() < offset + data.len() {
+            merged.resize(offset + data.len(), 0);
+        }
+        merged[offset..offset + data.len()].copy_from_slice(data);
+        self.runs.insert(new_start, merged);
+    }
+
+    /// `len` bytes at `addr`, if the image holds all of them
+    pub fn read(&self, addr: u64, len: usize) -> Option<&[u8]> {
+        let (&start, bytes) = self.runs.range(..=addr).next_back()?;
+        let offset = (addr - start) as usize;
+        bytes.get(offset..offset.checked_add(len)?)
+    }
+
+    /// The run holding `addr`, with its start address
+    pub fn run_at(&self, addr: u64) -> Option<(u64, &[u8])> {
+        let (&start, bytes) = self.runs.range(..=addr).next_back()?;
+        (addr < start + bytes.len() as u64).then_some((start, bytes.as_slice()))
+    }
+
+    /// The contiguous runs, in address order
+    pub fn runs(&self) -> impl Iterator<Item = (u64, &[u8])> {
+        self.runs.iter().map(|(&start, bytes)| (start, bytes.as_slice()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Lowest address held
+    pub fn start(&self) -> Option<u64> {
+        self.runs.keys().next().copied()
+    }
+
+    /// One past the highest address held
+    pub fn end(&self) -> Option<u64> {
+        self.runs
+            .iter()
+            .next_back()
+            .map(|(&start, bytes)| start + bytes.len() as u64)
+    }
+
+    /// Number of bytes held, not counting gaps
+    pub fn data_len(&self) -> u64 {
+        self.runs.values().map(|b| b.len() as u64).sum()
+    }
+
+    /// Only the bytes in [start, end)
+    pub fn clip(&self, start: u64, end: u64) -> Self {
+        let mut out = Self {
+            runs: BTreeMap::new(),
+            entry: self.entry,
+        };
+        for (run_start, bytes) in self.runs() {
+            let lo = run_start.max(start);
+            let hi = (run_start + bytes.len() as u64).min(end);
+            if lo < hi {
+                out.write(lo, &bytes[(lo - run_start) as usize..(hi - run_start) as usize]);
+            }
+        }
+        out
+    }
+
+    /// Fill the gaps between runs with `fill`, leaving a single run. Fails like `to_binary`.
+    pub fn fill_gaps(&mut self, fill: u8) -> Result<()> {
+        if let Some(start) = self.start() {
+            let flat = self.to_binary(fill)?;
+            self.runs.clear();
+            self.runs.insert(start, flat);
+        }
+        Ok(())
+    }
+
+    /// The image from its lowest to its highest address, gaps filled with `fill`. Fails if that
+    /// spans more than `MAX_BINARY_SPAN` bytes.
+    pub fn to_binary(&self, fill: u8) -> Result<Vec<u8>> {
+        let (Some(start), Some(end)) = (self.start(), self.end()) else {
+            return Ok(Vec::new());
+        };
+        if end - start > MAX_BINARY_SPAN {
+            return Err(anyhow!(
+                "The image spans 0x{:08x}-0x{:08x} ({} MiB), too far apart for a binary file; \
+                 export one memory with --region (or --start/--end), or use HEX or S-record",
+                start,
+                end,
+                (end - start) >> 20
+            ));
+        }
+        let mut out = vec![fill; (end - start) as usize];
+        for (run_start, bytes) in self.runs() {
+            let offset = (run_start - start) as usize;
+            out[offset..offset + bytes.len()].copy_from_slice(bytes);
+        }
+        Ok(out)
+    }
+
+    /// CRC-32 (IEEE, as zlib and most bootloaders compute it) of `to_binary(fill)`
+    pub fn crc32(&self, fill: u8) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        self.for_each_binary_piece(fill, |piece| hasher.update(piece));
+        hasher.finalize()
+    }
+
+    /// SHA-256 of `to_binary(fill)`
+    pub fn sha256(&self, fill: u8) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        self.for_each_binary_piece(fill, |piece| hasher.update(piece));
+        hasher.finalize().into()
+    }
+
+    /// Feed the flattened image to `f` without building it in memory
+    fn for_each_binary_piece(&self, fill: u8, mut f: impl FnMut(&[u8])) {
+        let gap = [fill; 4096];
+        let mut next = self.start().unwrap_or(0);
+        for (start, bytes) in self.runs() {
+            let mut missing = start - next;
+            while missing > 0 {
+                let n = missing.min(gap.len() as u64);
+                f(&gap[..n as usize]);
+                missing -= n;
+            }
+            f(bytes);
+            next = start + bytes.len() as u64;
+        }
+    }
+
+    pub fn to_ihex(&self) -> String {
+        let mut out = String::new();
+        let mut upper: Option<u64> = None;
+        for (start, bytes) in self.runs() {
+            let mut addr = start;
+            for line in split_at_boundaries(start, bytes, 0x1_0000) {
+                if upper != Some(addr >> 16) {
+                    upper = Some(addr >> 16);
+                    ihex_record(&mut out, 0, 0x04, &((addr >> 16) as u16).to_be_bytes());
+                }
+                ihex_record(&mut out, (addr & 0xffff) as u16, 0x00, line);
+                addr += line.len() as u64;
+            }
+        }
+        if let Some(entry) = self.entry {
+            ihex_record(&mut out, 0, 0x05, &(entry as u32).to_be_bytes());
+        }
+        ihex_record(&mut out, 0, 0x01, &[]);
+        out
+    }
+
+    pub fn from_ihex(text: &str) -> Result<Self> {
+        let mut image = Self::new();
+        let mut base: u64 = 0;
+        for (ix, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let line_no = ix + 1;
+            let record = line
+                .trim()
+                .strip_prefix(':')
+                .ok_or_else(|| anyhow!("Line {}: missing ':'", line_no))?;
+            let bytes = decode_hex(record).ok_or_else(|| anyhow!("Line {}: bad hex digits", line_no))?;
+            if bytes.len() < 5 || bytes.len() != bytes[0] as usize + 5 {
+                return Err(anyhow!("Line {}: bad record length", line_no));
+            }
+            if bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) != 0 {
+                return Err(anyhow!("Line {}: bad checksum", line_no));
+            }
+            let offset = u16::from_be_bytes([bytes[1], bytes[2]]) as u64;
+            let data = &bytes[4..bytes.len() - 1];
+            match bytes[3] {
+                0x00 => image.write(base + offset, data),
+                0x01 => break,
+                0x02 if data.len() == 2 => base = (u16::from_be_bytes([data[0], data[1]]) as u64) << 4,
+                0x03 if data.len() == 4 => {
+                    let cs = u16::from_be_bytes([data[0], data[1]]) as u64;
+                    let ip = u16::from_be_bytes([data[2], data[3]]) as u64;
+                    image.entry = Some((cs << 4) + ip);
+                }
+                0x04 if data.len() == 2 => base = (u16::from_be_bytes([data[0], data[1]]) as u64) << 16,
+                0x05 if data.len() == 4 => {
+                    image.entry = Some(u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as u64)
+                }
+                kind => return Err(anyhow!("Line {}: unsupported record type {:02X}", line_no, kind)),
+            }
+        }
+        Ok(image)
+    }
+
+    /// S-records with the smallest address size that fits (S1/S2/S3), and `header` in the S0 record
+    pub fn to_srec(&self, header: &str) -> String {
+        let max_addr = self.end().unwrap_or(0).saturating_sub(1).max(self.entry.unwrap_or(0));
+        let (data_type, addr_len) = match max_addr {
+            0..=0xffff => (1, 2),
+            0x1_0000..=0xff_ffff => (2, 3),
+            _ => (3, 4),
+        };
+        let mut out = String::new();
+        srec_record(&mut out, 0, 2, 0, header.as_bytes());
+        for (start, bytes) in self.runs() {
+            let mut addr = start;
+            for line in bytes.chunks(RECORD_BYTES) {
+                srec_record(&mut out, data_type, addr_len, addr, line);
+                addr += line.len() as u64;
+            }
+        }
+        srec_record(&mut out, 10 - data_type, addr_len, self.entry.unwrap_or(0), &[]);
+        out
+    }
+
+    pub fn from_srec(text: &str) -> Result<Self> {
+        let mut image = Self::new();
+        for (ix, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let line_no = ix + 1;
+            let line = line.trim();
+            let kind = line
+                .strip_prefix('S')
+                .and_then(|rest| rest.chars().next())
+                .and_then(|c| c.to_digit(10))
+                .ok_or_else(|| anyhow!("Line {}: not an S-record", line_no))?;
+            let bytes = decode_hex(&line[2..]).ok_or_else(|| anyhow!("Line {}: bad hex digits", line_no))?;
+            if bytes.is_empty() || bytes.len() != bytes[0] as usize + 1 {
+                return Err(anyhow!("Line {}: bad record length", line_no));
+            }
+            if bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) != 0xff {
+                return Err(anyhow!("Line {}: bad checksum", line_no));
+            }
+            let addr_len = match kind {
+                0 | 1 | 5 | 9 => 2,
+                2 | 6 | 8 => 3,
+                3 | 7 => 4,
+                _ => return Err(anyhow!("Line {}: unsupported record type S{}", line_no, kind)),
+            };
+            if bytes.len() < addr_len + 2 {
+                return Err(anyhow!("Line {}: record too short", line_no));
+            }
+            let addr = bytes[1..=addr_len].iter().fold(0u64, |a, b| (a << 8) | *b as u64);
+            let data = &bytes[addr_len + 1..bytes.len() - 1];
+            match kind {
+                1..=3 => image.write(addr, data),
+                7..=9 => image.entry = Some(addr),
+                _ => {}
+            }
+        }
+        Ok(image)
+    }
+
+    /// Write in `format`; binary output fills gaps with `fill`
+    pub fn save(&self, path: &Path, format: ImageFormat, fill: u8) -> Result<()> {
+        let bytes = match format {
+            ImageFormat::Hex => self.to_ihex().into_bytes(),
+            ImageFormat::Srec => {
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                self.to_srec(name).into_bytes()
+            }
+            ImageFormat::Bin => self.to_binary(fill)?,
+        };
+        std::fs::write(path, bytes).with_context(|| format!("Writing {}", path.display()))
+    }
+}
+
+/// Split `bytes` (starting at `start`) into record-sized lines that don't cross a multiple of `boundary`
+fn split_at_boundaries(start: u64, bytes: &[u8], boundary: u64) -> Vec<&[u8]> {
+    let mut lines = Vec::new();
+    let mut addr = start;
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let to_boundary = (boundary - addr % boundary) as usize;
+        let n = rest.len().min(RECORD_BYTES).min(to_boundary);
+        lines.push(&rest[..n]);
+        rest = &rest[n..];
+        addr += n as u64;
+    }
+    lines
+}
+
+fn ihex_record(out: &mut String, offset: u16, kind: u8, data: &[u8]) {
+    let mut bytes = vec![data.len() as u8];
+    bytes.extend_from_slice(&offset.to_be_bytes());
+    bytes.push(kind);
+    bytes.extend_from_slice(data);
+    let checksum = bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)).wrapping_neg();
+    bytes.push(checksum);
+    out.push(':');
+    for b in bytes {
+        let _ = write!(out, "{:02X}", b);
+    }
+    out.push('\n');
+}
+
+fn srec_record(out: &mut String, kind: u8, addr_len: usize, addr: u64, data: &[u8]) {
+    let mut bytes = vec![(addr_len + data.len() + 1) as u8];
+    bytes.extend_from_slice(&addr.to_be_bytes()[8 - addr_len..]);
+    bytes.extend_from_slice(data);
+    let checksum = !bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+    bytes.push(checksum);
+    let _ = write!(out, "S{}", kind);
+    for b in bytes {
+        let _ = write!(out, "{:02X}", b);
+    }
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> MemoryImage {
+        let mut image = MemoryImage::new();
+        image.write(0x0800_fff0, &(0..40u8).collect::<Vec<_>>());
+        image.write(0x0801_0100, &[0xde, 0xad, 0xbe, 0xef]);
+        image.entry = Some(0x0800_0101);
+        image
+    }
+
+    #[test]
+    fn writes_merge_and_overwrite() {
+        let mut image = MemoryImage::new();
+        image.write(0x10, &[1, 2, 3, 4]);
+        image.write(0x20, &[9]);
+        image.write(0x14, &[5, 6]);
+        image.write(0x12, &[7, 7]);
+        let runs: Vec<_> = image.runs().map(|(a, b)| (a, b.to_vec())).collect();
+        assert_eq!(runs, vec![(0x10, vec![1, 2, 7, 7, 5, 6]), (0x20, vec![9])]);
+        assert_eq!(image.read(0x11, 3), Some(&[2, 7, 7][..]));
+        assert_eq!(image.read(0x14, 4), None);
+        assert_eq!(image.to_binary(0xff).unwrap().len(), 0x11);
+        assert_eq!(image.to_binary(0xff).unwrap()[6], 0xff);
+        assert_eq!(image.clip(0x13, 0x21).runs().count(), 2);
+        assert_eq!(image.clip(0x13, 0x21).start(), Some(0x13));
+
+        // Nothing is kept past the top of the address space
+        image.write(u64::MAX - 2, &[1, 2, 3, 4]);
+        assert_eq!(image.read(u64::MAX - 2, 2), Some(&[1, 2][..]));
+        assert_eq!(image.end(), Some(u64::MAX));
+    }
+
+    #[test]
+    fn ihex_round_trip_crosses_64k_pages() {
+        let image = sample();
+        let hex = image.to_ihex();
+        assert!(hex.starts_with(":020000040800F2\n"));
+        assert!(hex.contains(":020000040801F1\n"));
+        assert!(hex.ends_with(":0400000508000101ED\n:00000001FF\n"));
+        assert_eq!(MemoryImage::from_ihex(&hex).unwrap(), image);
+
+        let bad = hex.replacen(":020000040800F2", ":020000040800F3", 1);
+        assert!(MemoryImage::from_ihex(&bad).is_err());
+    }
+
+    #[test]
+    fn srec_round_trip() {
+        let image = sample();
+        let srec = image.to_srec("fw.srec");
+        assert!(srec.starts_with("S00A000066772E737265633D\n"));
+        assert!(srec.lines().nth(1).unwrap().starts_with("S3150800FFF0"));
+        assert!(srec.ends_with("S70508000101F0\n"));
+        assert_eq!(MemoryImage::from_srec(&srec).unwrap(), image);
+
+        let small = MemoryImage::from_binary(&[1, 2, 3], 0x100);
+        let srec = small.to_srec("");
+        assert_eq!(srec, "S0030000FC\nS1060100010203F2\nS9030000FC\n");
+        assert_eq!(
+            MemoryImage::from_srec(&srec).unwrap().read(0x100, 3),
+            Some(&[1, 2, 3][..])
+        );
+    }
+
+    #[test]
+    fn checksums_cover_the_filled_image() {
+        let image = MemoryImage::from_binary(b"123456789", 0);
+        assert_eq!(image.crc32(0xff), 0xcbf4_3926);
+
+        let mut gappy = MemoryImage::from_binary(b"abc", 0x1000);
+        gappy.write(0x2000, b"xyz");
+        let flat = gappy.to_binary(0xff).unwrap();
+        assert_eq!(gappy.crc32(0xff), crc32fast::hash(&flat));
+        assert_eq!(gappy.sha256(0xff), <[u8; 32]>::from(Sha256::digest(&flat)));
+        gappy.fill_gaps(0xff).unwrap();
+        assert_eq!(gappy.runs().count(), 1);
+        assert_eq!(gappy.to_binary(0).unwrap(), flat);
+    }
+
+    #[test]
+    fn images_spanning_distant_memories_are_not_flattened() {
+        // Flash plus STM32F4 option bytes: the gap between them is most of 400 MiB
+        let mut image = MemoryImage::from_binary(&[0; 16], 0x0800_0000);
+        image.write(0x1fff_c000, &[0xaa, 0xf8]);
+        let err = image.to_binary(0xff).unwrap_err().to_string();
+        assert!(err.contains("--region"), "{}", err);
+        assert!(image.clone().fill_gaps(0xff).is_err());
+        let out = tempfile::tempdir().unwrap();
+        assert!(image.save(&out.path().join("fw.bin"), ImageFormat::Bin, 0xff).is_err());
+
+        // HEX keeps the image sparse, and one memory of it still flattens
+        assert!(image.save(&out.path().join("fw.hex"), ImageFormat::Hex, 0xff).is_ok());
+        assert_eq!(image.clip(0x0800_0000, 0x0810_0000).to_binary(0xff).unwrap().len(), 16);
+    }
+
+    #[test]
+    fn format_from_extension() {
+        assert_eq!(ImageFormat::from_path(Path::new("fw.HEX")), ImageFormat::Hex);
+        assert_eq!(ImageFormat::from_path(Path::new("fw.s19")), ImageFormat::Srec);
+        assert_eq!(ImageFormat::from_path(Path::new("fw.bin")), ImageFormat::Bin);
+        assert_eq!(ImageFormat::from_path(Path::new("fw")), ImageFormat::Bin);
+    }
+}
@@ -0,0 +1,756 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Device memory map: the physical regions of the part (from a linker script's MEMORY block,
+//! the ELF's PT_LOAD segments, or an SVD's peripheral blocks) and how the image's sections fill
+//! them, at their run-time (VMA) and load (LMA) addresses.
+
+use anyhow::{anyhow, Context, Result};
+use object::elf::{FileHeader32, FileHeader64, PF_R, PF_W, PF_X, PT_LOAD, SHF_ALLOC, SHF_TLS};
+use object::read::elf::{FileHeader, ProgramHeader};
+use object::{Endianness, Object, ObjectSection, SectionFlags, SectionKind};
+use regex::Regex;
+use serde::Serialize;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::da_helper::helper_requests::{
+    MemoryOverflow, MemoryOverlap, MemoryRegionUsage, RegionSource, SectionPlacement,
+};
+
+/// A PT_LOAD program header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadSegment {
+    /// File offset of the segment's contents
+    pub offset: u64,
+    pub vaddr: u64,
+    pub paddr: u64,
+    pub memsz: u64,
+    pub filesz: u64,
+    pub flags: u32,
+}
+
+/// An allocated section with its load address worked out from the segment that holds it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllocSection {
+    pub name: String,
+    pub vma: u64,
+    pub lma: u64,
+    pub size: u64,
+    pub has_data: bool,
+}
+
+impl AllocSection {
+    pub fn copy_down(&self) -> bool {
+        self.has_data && self.lma != self.vma
+    }
+}
+
+/// What the ELF says about where things go in memory
+#[derive(Debug, Default)]
+pub struct ElfLayout {
+    pub segments: Vec<LoadSegment>,
+    pub sections: Vec<AllocSection>,
+}
+
+impl ElfLayout {
+    pub fn from_object(obj_file: &object::File, data: &[u8]) -> Self {
+        let segments = elf_load_segments(obj_file, data);
+        let mut sections = Vec::new();
+        for section in obj_file.sections() {
+            let SectionFlags::Elf { sh_flags } = section.flags() else {
+                continue;
+            };
+            let kind = section.kind();
+            // TLS templates for .tbss take no space of their own
+            let tls_bss = sh_flags & SHF_TLS as u64 != 0 && kind == SectionKind::UninitializedTls;
+            if sh_flags & SHF_ALLOC as u64 == 0 || section.size() == 0 || tls_bss {
+                continue;
+            }
+            let vma = section.address();
+            let lma = segments
+                .iter()
+                .find(|seg| vma >= seg.vaddr && vma < seg.vaddr + seg.memsz)
+                .map_or(vma, |seg| seg.paddr + (vma - seg.vaddr));
+            sections.push(AllocSection {
+                name: section.name().unwrap_or("").to_string(),
+                vma,
+                lma,
+                size: section.size(),
+                has_data: !matches!(kind, SectionKind::UninitializedData | SectionKind::UninitializedTls),
+            });
+        }
+        sections.sort_by_key(|s| s.vma);
+        Self { segments, sections }
+    }
+}
+
+/// The PT_LOAD segments of an ELF file (none for other formats). `data` is the file `obj_file`
+/// was parsed from; `object` doesn't expose physical addresses in its generic API.
+pub fn elf_load_segments(obj_file: &object::File, data: &[u8]) -> Vec<LoadSegment> {
+    match (obj_file.format(), obj_file.is_64()) {
+        (object::BinaryFormat::Elf, false) => load_segments::<FileHeader32<Endianness>>(data),
+        (object::BinaryFormat::Elf, true) => load_segments::<FileHeader64<Endianness>>(data),
+        _ => Vec::new(),
+    }
+}
+
+fn load_segments<Elf: FileHeader<Endian = Endianness>>(data: &[u8]) -> Vec<LoadSegment> {
+    let Ok(header) = Elf::parse(data) else {
+        return Vec::new();
+    };
+    let Ok(endian) = header.endian() else {
+        return Vec::new();
+    };
+    let Ok(headers) = header.program_headers(endian, data) else {
+        return Vec::new();
+    };
+    headers
+        .iter()
+        .filter(|ph| ph.p_type(endian) == PT_LOAD && ph.p_memsz(endian).into() > 0)
+        .map(|ph| LoadSegment {
+            offset: ph.p_offset(endian).into(),
+            vaddr: ph.p_vaddr(endian).into(),
+            paddr: ph.p_paddr(endian).into(),
+            memsz: ph.p_memsz(endian).into(),
+            filesz: ph.p_filesz(endian).into(),
+            flags: ph.p_flags(endian),
+        })
+        .collect()
+}
+
+/// A physical memory region of the device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhysicalRegion {
+    pub name: String,
+    pub origin: u64,
+    pub length: u64,
+    pub attributes: Option<String>,
+    pub source: RegionSource,
+}
+
+impl PhysicalRegion {
+    pub fn end(&self) -> u64 {
+        self.origin.saturating_add(self.length)
+    }
+
+    pub fn contains(&self, addr: u64) -> bool {
+        addr >= self.origin && addr < self.end()
+    }
+}
+
+/// One region per PT_LOAD segment, for when there is no linker script
+pub fn segment_regions(layout: &ElfLayout) -> Vec<PhysicalRegion> {
+    layout
+        .segments
+        .iter()
+        .enumerate()
+        .map(|(ix, seg)| {
+            let flags = [(PF_R, 'r'), (PF_W, 'w'), (PF_X, 'x')]
+                .iter()
+                .filter(|(bit, _)| seg.flags & bit != 0)
+                .map(|(_, c)| *c)
+                .collect();
+            PhysicalRegion {
+                name: format!("LOAD{}", ix),
+                origin: seg.vaddr,
+                length: seg.memsz,
+                attributes: Some(flags),
+                source: RegionSource::Elf,
+            }
+        })
+        .collect()
+}
+
+/// The regions of the MEMORY block(s) in a GNU ld script, e.g.
+/// `FLASH (rx) : ORIGIN = 0x08000000, LENGTH = 512K`. Expressions may use `+ - *`, parentheses,
+/// K/M/G suffixes and ORIGIN()/LENGTH() of regions defined before.
+pub fn parse_linker_memory(script: &str) -> Result<Vec<PhysicalRegion>> {
+    let comments = Regex::new(r"(?s)/\*.*?\*/").unwrap();
+    let script = comments.replace_all(script, " ");
+    let block = Regex::new(r"\bMEMORY\s*\{([^}]*)\}").unwrap();
+    let entry = Regex::new(r"([A-Za-z_][\w.$]*)\s*(?:\(([^)]*)\))?\s*:\s*(?i:ORIGIN|org|o)\s*=").unwrap();
+    let length = Regex::new(r"(?s)^\s*(?i:LENGTH|len|l)\s*=(.*)$").unwrap();
+
+    let mut regions: Vec<PhysicalRegion> = Vec::new();
+    for body in block.captures_iter(&script) {
+        let body = body.get(1).unwrap().as_str();
+        let starts: Vec<_> = entry.captures_iter(body).collect();
+        for (ix, caps) in starts.iter().enumerate() {
+            let name = caps[1].to_string();
+            let rest_end = starts
+                .get(ix + 1)
+                .map_or(body.len(), |next| next.get(0).unwrap().start());
+            let rest = &body[caps.get(0).unwrap().end()..rest_end];
+            let comma = top_level_comma(rest).ok_or_else(|| anyhow!("MEMORY region {} has no LENGTH", name))?;
+            let len_expr = length
+                .captures(&rest[comma + 1..])
+                .ok_or_else(|| anyhow!("MEMORY region {} has no LENGTH", name))?;
+            let origin = eval_expr(&rest[..comma], &regions).with_context(|| format!("ORIGIN of {}", name))?;
+            let len = eval_expr(&len_expr[1], &regions).with_context(|| format!("LENGTH of {}", name))?;
+            regions.push(PhysicalRegion {
+                name,
+                origin,
+                length: len,
+                attributes: caps.get(2).map(|a| a.as_str().trim().to_string()),
+                source: RegionSource::Linker,
+            });
+        }
+    }
+    if regions.is_empty() {
+        return Err(anyhow!("No MEMORY regions found"));
+    }
+    Ok(regions)
+}
+
+fn top_level_comma(text: &str) -> Option<usize> {
+    let mut depth = 0i32;
+    for (ix, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => return Some(ix),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Evaluate a MEMORY expression. `regions` are those defined so far, for ORIGIN()/LENGTH().
+fn eval_expr(text: &str, regions: &[PhysicalRegion]) -> Result<u64> {
+    let tokens = tokenize(text)?;
+    let mut pos = 0;
+    let value = parse_sum(&tokens, &mut pos, regions)?;
+    if pos != tokens.len() {
+        return Err(anyhow!("Unexpected '{}' in '{}'", tokens[pos], text.trim()));
+    }
+    Ok(value)
+}
+
+fn tokenize(text: &str) -> Result<Vec<String>> {
+    let token = Regex::new(r"\s*([A-Za-z_][\w.$]*|[0-9][0-9A-Za-z]*|[-+*()])").unwrap();
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_end();
+    while !rest.trim_start().is_empty() {
+        let caps = token
+            .captures(rest)
+            .filter(|c| c.get(0).unwrap().start() == 0)
+            .ok_or_else(|| anyhow!("Can't parse '{}'", rest.trim()))?;
+        tokens.push(caps[1].to_string());
+        rest = &rest[caps.get(0).unwrap().end()..];
+    }
+    Ok(tokens)
+}
+
+fn parse_sum(tokens: &[String], pos: &mut usize, regions: &[PhysicalRegion]) -> Result<u64> {
+    let mut value = parse_product(tokens, pos, regions)?;
+    while let Some(op) = tokens.get(*pos).map(String::as_str).filter(|t| *t == "+" || *t == "-") {
+        *pos += 1;
+        let rhs = parse_product(tokens, pos, regions)?;
+        value = if op == "+" {
+            value.wrapping_add(rhs)
+        } else {
+            value.wrapping_sub(rhs)
+        };
+    }
+    Ok(value)
+}
+
+fn parse_product(tokens: &[String], pos: &mut usize, regions: &[PhysicalRegion]) -> Result<u64> {
+    let mut value = parse_factor(tokens, pos, regions)?;
+    while tokens.get(*pos).map(String::as_str) == Some("*") {
+        *pos += 1;
+        value = value.wrapping_mul(parse_factor(tokens, pos, regions)?);
+    }
+    Ok(value)
+}
+
+fn parse_factor(tokens: &[String], pos: &mut usize, regions: &[PhysicalRegion]) -> Result<u64> {
+    let token = tokens.get(*pos).ok_or_else(|| anyhow!("Expression ends early"))?;
+    *pos += 1;
+    if token == "(" {
+        let value = parse_sum(tokens, pos, regions)?;
+        expect(tokens, pos, ")")?;
+        return Ok(value);
+    }
+    let upper = token.to_ascii_uppercase();
+    if upper == "ORIGIN" || upper == "LENGTH" {
+        expect(tokens, pos, "(")?;
+        let name = tokens.get(*pos).ok_or_else(|| anyhow!("{}() needs a region", upper))?;
+        *pos += 1;
+        expect(tokens, pos, ")")?;
+        let region = regions
+            .iter()
+            .find(|r| &r.name == name)
+            .ok_or_else(|| anyhow!("Unknown region '{}'", name))?;
+        return Ok(if upper == "ORIGIN" {
+            region.origin
+        } else {
+            region.length
+        });
+    }
+    parse_number(token).ok_or_else(|| anyhow!("Not a number: '{}'", token))
+}
+
+fn expect(tokens: &[String], pos: &mut usize, want: &str) -> Result<()> {
+    if tokens.get(*pos).map(String::as_str) != Some(want) {
+        return Err(anyhow!("Expected '{}'", want));
+    }
+    *pos += 1;
+    Ok(())
+}
+
+/// `0x...` or decimal, with an optional K/M/G suffix (ld) or `#` binary prefix (SVD)
+pub(crate) fn parse_number(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let (digits, scale) = match text.chars().last()? {
+        'k' | 'K' => (&text[..text.len() - 1], 1 << 10),
+        'm' | 'M' => (&text[..text.len() - 1], 1 << 20),
+        'g' | 'G' => (&text[..text.len() - 1], 1 << 30),
+        _ => (text, 1),
+    };
+    let value = if let Some(hex) = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()?
+    } else if let Some(bin) = digits.strip_prefix('#') {
+        u64::from_str_radix(bin, 2).ok()?
+    } else {
+        digits.parse::<u64>().ok()?
+    };
+    value.checked_mul(scale)
+}
+
+struct SvdPeripheral {
+    name: String,
+    base: u64,
+    derived_from: Option<String>,
+    /// (offset, size) of each address block
+    blocks: Vec<(u64, u64)>,
+}
+
+/// One region per peripheral of a CMSIS-SVD file, spanning its address blocks. Peripherals
+/// `derivedFrom` another one without blocks of their own reuse the other's block layout.
+pub fn parse_svd_peripherals(svd: &str) -> Result<Vec<PhysicalRegion>> {
+    let peripheral = Regex::new(r"(?s)<peripheral(\s[^>]*)?>(.*?)</peripheral>").unwrap();
+    let derived = Regex::new(r#"derivedFrom\s*=\s*"([^"]*)""#).unwrap();
+    let name = Regex::new(r"<name>\s*([^<]*?)\s*</name>").unwrap();
+    let base = Regex::new(r"<baseAddress>\s*([^<]*?)\s*</baseAddress>").unwrap();
+    let block = Regex::new(r"(?s)<addressBlock>(.*?)</addressBlock>").unwrap();
+    let offset = Regex::new(r"<offset>\s*([^<]*?)\s*</offset>").unwrap();
+    let size = Regex::new(r"<size>\s*([^<]*?)\s*</size>").unwrap();
+
+    let mut found: Vec<SvdPeripheral> = Vec::new();
+    for caps in peripheral.captures_iter(svd) {
+        // Only look at the peripheral's own elements, not its registers'
+        let body = caps.get(2).unwrap().as_str();
+        let head = body.split("<registers>").next().unwrap_or(body);
+        let Some(pname) = name.captures(head).map(|c| c[1].to_string()) else {
+            continue;
+        };
+        let base_addr = base
+            .captures(head)
+            .and_then(|c| parse_number(&c[1]))
+            .ok_or_else(|| anyhow!("Peripheral {} has no valid baseAddress", pname))?;
+        let from = caps
+            .get(1)
+            .and_then(|attrs| derived.captures(attrs.as_str()))
+            .map(|c| c[1].to_string());
+        let blocks = block
+            .captures_iter(head)
+            .filter_map(|b| {
+                let off = parse_number(&offset.captures(&b[1])?[1])?;
+                let len = parse_number(&size.captures(&b[1])?[1])?;
+                Some((off, len))
+            })
+            .collect();
+        found.push(SvdPeripheral {
+            name: pname,
+            base: base_addr,
+            derived_from: from,
+            blocks,
+        });
+    }
+
+    let mut regions = Vec::new();
+    for p in &found {
+        let blocks = match (p.blocks.is_empty(), &p.derived_from) {
+            (true, Some(from)) => found
+                .iter()
+                .find(|base| &base.name == from)
+                .map_or(&p.blocks, |base| &base.blocks),
+            _ => &p.blocks,
+        };
+        let start = blocks.iter().map(|(off, _)| off).min();
+        let end = blocks.iter().map(|(off, len)| off + len).max();
+        if let (Some(start), Some(end)) = (start, end) {
+            regions.push(PhysicalRegion {
+                name: p.name.clone(),
+                origin: p.base + start,
+                length: end - start,
+                attributes: None,
+                source: RegionSource::Svd,
+            });
+        }
+    }
+    Ok(regions)
+}
+
+/// The regions a memory map is built on: the linker script's if given, else the ELF segments,
+/// plus the SVD's peripherals.
+pub fn load_regions(
+    layout: &ElfLayout,
+    linker_script: Option<&Path>,
+    svd: Option<&Path>,
+) -> Result<Vec<PhysicalRegion>> {
+    let mut regions = match linker_script {
+        Some(path) => {
+            let text = std::fs::read_to_string(path).with_context(|| format!("Reading {}", path.display()))?;
+            parse_linker_memory(&text).with_context(|| format!("In {}", path.display()))?
+        }
+        None => segment_regions(layout),
+    };
+    if let Some(path) = svd {
+        let text = std::fs::read_to_string(path).with_context(|| format!("Reading {}", path.display()))?;
+        regions.extend(parse_svd_peripherals(&text).with_context(|| format!("In {}", path.display()))?);
+    }
+    Ok(regions)
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemoryMap {
+    pub regions: Vec<MemoryRegionUsage>,
+    pub sections: Vec<SectionPlacement>,
+    pub overlaps: Vec<MemoryOverlap>,
+    pub overflows: Vec<MemoryOverflow>,
+}
+
+/// Place every section in `regions` and add up what each region holds. Load images of copied-down
+/// sections are only counted against linker regions; ELF segments describe run-time addresses only.
+pub fn build_memory_map(layout: &ElfLayout, regions: &[PhysicalRegion]) -> MemoryMap {
+    let memory: Vec<usize> = (0..regions.len())
+        .filter(|&ix| regions[ix].source != RegionSource::Svd)
+        .collect();
+    let region_at = |addr: u64| memory.iter().copied().find(|&ix| regions[ix].contains(addr));
+    let count_load = regions.iter().any(|r| r.source == RegionSource::Linker);
+
+    let mut used = vec![0u64; regions.len()];
+    let mut overflow = vec![0u64; regions.len()];
+    let mut members: Vec<Vec<String>> = vec![Vec::new(); regions.len()];
+    let mut overflows = Vec::new();
+    let mut sections = Vec::new();
+    for section in &layout.sections {
+        let region = region_at(section.vma);
+        let load_region = region_at(section.lma);
+        let mut placements = vec![(section.vma, region, false)];
+        if section.copy_down() && count_load {
+            placements.push((section.lma, load_region, true));
+        }
+        for (start, ix, load) in placements {
+            let end = start.saturating_add(section.size);
+            match ix {
+                Some(ix) => {
+                    let r = &regions[ix];
+                    used[ix] += end.min(r.end()) - start;
+                    members[ix].push(section.name.clone());
+                    if end > r.end() {
+                        overflow[ix] += end - r.end();
+                        overflows.push(MemoryOverflow {
+                            section: section.name.clone(),
+                            region: Some(r.name.clone()),
+                            bytes: end - r.end(),
+                            load,
+                        });
+                    }
+                }
+                None if !memory.is_empty() => overflows.push(MemoryOverflow {
+                    section: section.name.clone(),
+                    region: None,
+                    bytes: section.size,
+                    load,
+                }),
+                None => {}
+            }
+        }
+        sections.push(SectionPlacement {
+            name: section.name.clone(),
+            vma: format!("0x{:x}", section.vma),
+            lma: format!("0x{:x}", section.lma),
+            size: section.size,
+            has_data: section.has_data,
+            copy_down: section.copy_down(),
+            region: region.map(|ix| regions[ix].name.clone()),
+            load_region: load_region.map(|ix| regions[ix].name.clone()),
+        });
+    }
+
+    let mut overlaps = find_overlaps(regions.iter().map(|r| (r.name.as_str(), r.origin, r.length)), false);
+    overlaps.extend(find_overlaps(
+        layout.sections.iter().map(|s| (s.name.as_str(), s.vma, s.size)),
+        true,
+    ));
+
+    let regions = regions
+        .iter()
+        .enumerate()
+        .map(|(ix, r)| MemoryRegionUsage {
+            name: r.name.clone(),
+            origin: format!("0x{:x}", r.origin),
+            length: r.length,
+            attributes: r.attributes.clone(),
+            source: r.source,
+            used: used[ix],
+            free: r.length.saturating_sub(used[ix]),
+            overflow: overflow[ix],
+            sections: std::mem::take(&mut members[ix]),
+        })
+        .collect();
+    MemoryMap {
+        regions,
+        sections,
+        overlaps,
+        overflows,
+    }
+}
+
+/// Every pair of (name, start, size) ranges that share addresses
+fn find_overlaps<'a>(ranges: impl Iterator<Item = (&'a str, u64, u64)>, sections: bool) -> Vec<MemoryOverlap> {
+    let mut ranges: Vec<_> = ranges.filter(|r| r.2 > 0).collect();
+    ranges.sort_by_key(|r| r.1);
+    let mut overlaps = Vec::new();
+    for (ix, &(first, start, size)) in ranges.iter().enumerate() {
+        let end = start.saturating_add(size);
+        for &(second, other_start, other_size) in ranges[ix + 1..].iter().take_while(|r| r.1 < end) {
+            let shared_end = end.min(other_start.saturating_add(other_size));
+            overlaps.push(MemoryOverlap {
+                first: first.to_string(),
+                second: second.to_string(),
+                start: format!("0x{:x}", other_start),
+                size: shared_end - other_start,
+                sections,
+            });
+        }
+    }
+    overlaps
+}
+
+/// Human-readable summary for `mdbg elf memory-map`
+pub fn format_memory_map(map: &MemoryMap) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<16} {:>12} {:>10} {:>10} {:>10} {:>6}  Attr",
+        "Region", "Origin", "Length", "Used", "Free", "Use%"
+    );
+    let mut peripherals = 0;
+    for r in &map.regions {
+        if r.source == RegionSource::Svd {
+            peripherals += 1;
+            continue;
+        }
+        let percent = if r.length > 0 {
+            100.0 * r.used as f64 / r.length as f64
+        } else {
+            0.0
+        };
+        let _ = writeln!(
+            out,
+            "{:<16} {:>12} {:>10} {:>10} {:>10} {:>5.1}%  {}",
+            r.name,
+            r.origin,
+            r.length,
+            r.used,
+            r.free,
+            percent,
+            r.attributes.as_deref().unwrap_or("")
+        );
+    }
+    if peripherals > 0 {
+        let _ = writeln!(out, "(and {} peripheral regions from the SVD)", peripherals);
+    }
+    for s in map.sections.iter().filter(|s| s.copy_down) {
+        let _ = writeln!(
+            out,
+            "{} is copied from {} to {} at startup ({} bytes)",
+            s.name, s.lma, s.vma, s.size
+        );
+    }
+    for o in &map.overflows {
+        let image = if o.load { " (load image)" } else { "" };
+        let _ = match &o.region {
+            Some(region) => writeln!(
+                out,
+                "{} overflowed by {} bytes: {}{}",
+                region, o.bytes, o.section, image
+            ),
+            None => writeln!(
+                out,
+                "{}{} ({} bytes) is outside every region",
+                o.section, image, o.bytes
+            ),
+        };
+    }
+    for o in &map.overlaps {
+        let _ = writeln!(
+            out,
+            "{} and {} overlap at {} ({} bytes)",
+            o.first, o.second, o.start, o.size
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINKER_SCRIPT: &str = r#"
+/* STM32F4 */
+ENTRY(Reset_Handler)
+MEMORY
+{
+  FLASH (rx)      : ORIGIN = 0x08000000, LENGTH = 512K
+  RAM (xrw)       : ORIGIN = 0x20000000, LENGTH = 128K - 4K /* top reserved */
+  BACKUP (rw)     : org = ORIGIN(RAM) + LENGTH(RAM), len = 0x1000
+}
+SECTIONS { .text : { *(.text*) } > FLASH }
+"#;
+
+    fn section(name: &str, vma: u64, lma: u64, size: u64, has_data: bool) -> AllocSection {
+        AllocSection {
+            name: name.to_string(),
+            vma,
+            lma,
+            size,
+            has_data,
+        }
+    }
+
+    #[test]
+    fn parses_linker_memory_block() {
+        let regions = parse_linker_memory(LINKER_SCRIPT).unwrap();
+        let summary: Vec<_> = regions
+            .iter()
+            .map(|r| (r.name.as_str(), r.origin, r.length, r.attributes.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("FLASH", 0x0800_0000, 512 * 1024, Some("rx")),
+                ("RAM", 0x2000_0000, 124 * 1024, Some("xrw")),
+                ("BACKUP", 0x2001_f000, 0x1000, Some("rw")),
+            ]
+        );
+        assert!(parse_linker_memory("SECTIONS {}").is_err());
+        assert!(parse_linker_memory("MEMORY { RAM : ORIGIN = 0x20000000, LENGTH = LENGTH(ROM) }").is_err());
+    }
+
+    #[test]
+    fn parses_svd_peripherals() {
+        let svd = r#"<device><peripherals>
+            <peripheral>
+              <name>GPIOA</name><baseAddress>0x40020000</baseAddress>
+              <addressBlock><offset>0x0</offset><size>0x400</size><usage>registers</usage></addressBlock>
+              <registers><register><name>MODER</name><addressOffset>0x0</addressOffset></register></registers>
+            </peripheral>
+            <peripheral derivedFrom="GPIOA">
+              <name>GPIOB</name><baseAddress>0x40020400</baseAddress>
+            </peripheral>
+          </peripherals></device>"#;
+        let regions = parse_svd_peripherals(svd).unwrap();
+        let summary: Vec<_> = regions.iter().map(|r| (r.name.as_str(), r.origin, r.length)).collect();
+        assert_eq!(
+            summary,
+            vec![("GPIOA", 0x4002_0000, 0x400), ("GPIOB", 0x4002_0400, 0x400)]
+        );
+    }
+
+    #[test]
+    fn usage_copy_down_and_overflow() {
+        let layout = ElfLayout {
+            segments: Vec::new(),
+            sections: vec![
+                section(".text", 0x0800_0000, 0x0800_0000, 0x3000, true),
+                section(".data", 0x2000_0000, 0x0800_3000, 0x100, true),
+                section(".bss", 0x2000_0100, 0x2000_0100, 124 * 1024, false),
+            ],
+        };
+        let regions = parse_linker_memory(LINKER_SCRIPT).unwrap();
+        let map = build_memory_map(&layout, &regions);
+
+        let flash = &map.regions[0];
+        assert_eq!(flash.used, 0x3100);
+        assert_eq!(flash.sections, vec![".text", ".data"]);
+        let ram = &map.regions[1];
+        assert_eq!(ram.used, 124 * 1024);
+        assert_eq!(ram.free, 0);
+        assert_eq!(ram.overflow, 0x100);
+        assert_eq!(map.overflows.len(), 1);
+        assert_eq!(map.overflows[0].section, ".bss");
+        assert_eq!(map.overflows[0].bytes, 0x100);
+
+        let data = &map.sections[1];
+        assert!(data.copy_down);
+        assert_eq!(data.region.as_deref(), Some("RAM"));
+        assert_eq!(data.load_region.as_deref(), Some("FLASH"));
+        assert!(map.overlaps.is_empty());
+        assert!(format_memory_map(&map).contains("RAM overflowed by 256 bytes: .bss"));
+    }
+
+    #[test]
+    fn reports_overlaps_and_unplaced_sections() {
+        let layout = ElfLayout {
+            segments: Vec::new(),
+            sections: vec![
+                section(".a", 0x100, 0x100, 0x20, true),
+                section(".b", 0x110, 0x110, 0x20, true),
+                section(".c", 0x9000, 0x9000, 4, true),
+            ],
+        };
+        let regions = vec![
+            PhysicalRegion {
+                name: "ROM".to_string(),
+                origin: 0,
+                length: 0x1000,
+                attributes: None,
+                source: RegionSource::Linker,
+            },
+            PhysicalRegion {
+                name: "ALIAS".to_string(),
+                origin: 0x800,
+                length: 0x1000,
+                attributes: None,
+                source: RegionSource::Linker,
+            },
+        ];
+        let map = build_memory_map(&layout, &regions);
+        let overlaps: Vec<_> = map
+            .overlaps
+            .iter()
+            .map(|o| (o.first.as_str(), o.second.as_str(), o.size, o.sections))
+            .collect();
+        assert_eq!(overlaps, vec![("ROM", "ALIAS", 0x800, false), (".a", ".b", 0x10, true)]);
+        assert_eq!(map.overflows.len(), 1);
+        assert_eq!(map.overflows[0].region, None);
+        assert_eq!(map.overflows[0].section, ".c");
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse_number("0x20000000"), Some(0x2000_0000));
+        assert_eq!(parse_number("64K"), Some(65536));
+        assert_eq!(parse_number("1M"), Some(1 << 20));
+        assert_eq!(parse_number("#101"), Some(5));
+        assert_eq!(parse_number("0x2K"), Some(2048));
+        assert_eq!(parse_number("12x"), None);
+    }
+}
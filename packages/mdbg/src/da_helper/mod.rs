@@ -15,18 +15,39 @@
 //! Debug Adapter helper — ELF parsing, disassembly, symbol lookup.
 //! This is the existing mdbg da-helper functionality, now behind the `da-helper` subcommand.
 
+pub mod capstone;
+pub mod cfg;
+pub mod core_cmd;
+pub mod core_dump;
+pub mod cortex_m;
+pub mod demangle;
 pub mod disasm_worker;
+pub mod elf_cmd;
 pub mod elf_items;
+pub mod encoding;
 pub mod get_assembly;
 pub mod helper_requests;
+pub mod image;
 pub mod memory;
+pub mod memory_map;
 pub mod protocol;
+pub mod readiness;
+pub mod recorder;
 pub mod request_handler;
+pub mod responder;
 pub mod run;
+pub mod sampler;
+pub mod server;
+pub mod sim_cmd;
+pub mod sim_target;
+pub mod source_listing;
+pub mod source_map;
 pub mod symbols;
+pub mod unwind;
+pub mod value_type;
+pub mod verify;
 
 // These modules are experimental/incomplete and not yet wired up:
-// pub mod capstone;
 // pub mod instr;
 // pub mod instrdb;
 // pub mod serice;
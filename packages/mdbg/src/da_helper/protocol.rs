@@ -13,7 +13,8 @@
 // limitations under the License.
 
 /// Protocol message types and helpers for the helper ↔ DA communication.
-use crate::da_helper::helper_requests::HelperEvent;
+use crate::da_helper::helper_requests::{CfgRequest, HelperEvent, LiveSample, SourceListingRequest};
+use crate::da_helper::responder::Responder;
 use serde_json::{json, Value};
 
 /// Request from main thread to disassembly worker. This is our internal representation of a disassemble request,
@@ -30,6 +31,22 @@ pub struct DisasmRequest {
     pub seq_id: u64,
 }
 
+/// Work the main thread hands to the disassembly worker. Anything that needs the
+/// `AssemblyListing` has to go through the worker since the listing never leaves that thread.
+#[derive(Debug)]
+pub enum WorkerRequest {
+    Disasm(DisasmRequest),
+    Cfg(CfgRequest),
+    SourceListing(SourceListingRequest),
+}
+
+/// A worker request plus where its response should go.
+#[derive(Debug)]
+pub struct WorkerJob {
+    pub request: WorkerRequest,
+    pub responder: Responder,
+}
+
 /// Wrap an event in a JSON-RPC notification envelope for sending to the DA.
 pub fn wrap_event_as_notification(event: &HelperEvent) -> Value {
     json!({
@@ -64,3 +81,40 @@ pub fn rtt_found_notification(session_id: &str, address: &str) -> Value {
     };
     wrap_event_as_notification(&event)
 }
+
+/// Build a Progress event notification. `percentage` is None when the total is unknown.
+pub fn progress_notification(
+    session_id: &str,
+    operation: &str,
+    percentage: Option<u32>,
+    message: Option<String>,
+) -> Value {
+    let event = HelperEvent::Progress {
+        session_id: session_id.to_string(),
+        operation: operation.to_string(),
+        percentage,
+        message,
+    };
+    wrap_event_as_notification(&event)
+}
+
+/// Build an Error event notification.
+pub fn error_notification(session_id: &str, code: &str, message: &str) -> Value {
+    let event = HelperEvent::Error {
+        session_id: session_id.to_string(),
+        code: Some(code.to_string()),
+        message: message.to_string(),
+        details: None,
+    };
+    wrap_event_as_notification(&event)
+}
+
+/// Build a LiveSamples event notification.
+pub fn live_samples_notification(session_id: &str, samples: Vec<LiveSample>, slowdown: u32) -> Value {
+    let event = HelperEvent::LiveSamples {
+        session_id: session_id.to_string(),
+        samples,
+        slowdown,
+    };
+    wrap_event_as_notification(&event)
+}
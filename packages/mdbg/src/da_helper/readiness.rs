@@ -0,0 +1,226 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Startup readiness. The ELF symbols (main thread) and the objdump listing (worker) load in
+//! parallel while requests are already arriving. Each request needs one of these
+//! capabilities; while it is loading the request waits, and if loading failed the request
+//! is rejected with `not_ready`. Loaders report progress here, which goes out as
+//! `HelperEvent::Progress`.
+
+use std::sync::{Arc, Mutex};
+
+use crate::common::sync::MutexExt;
+use crate::da_helper::helper_requests::{ErrorCode, RequestError};
+use crate::da_helper::protocol::{error_notification, progress_notification};
+use crate::da_helper::responder::Outbox;
+
+/// Progress events are sent each time a load advances by at least this many percent
+const PROGRESS_STEP: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    /// ELF symbols and DWARF info: globals, statics, symbolLookup
+    Symbols,
+    /// The objdump listing: disasm, cfg, sourceListing
+    Disassembly,
+}
+
+impl Capability {
+    fn index(self) -> usize {
+        match self {
+            Capability::Symbols => 0,
+            Capability::Disassembly => 1,
+        }
+    }
+
+    /// The `operation` reported in Progress events
+    pub fn operation(self) -> &'static str {
+        match self {
+            Capability::Symbols => "Loading symbols",
+            Capability::Disassembly => "Parsing disassembly",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadState {
+    Loading { percentage: u32 },
+    Ready,
+    Failed(String),
+}
+
+/// What to do with a request, given the state of the capability it needs
+#[derive(Debug, PartialEq)]
+pub enum Admission {
+    Run,
+    Wait,
+    Reject(RequestError),
+}
+
+struct Tracker {
+    state: LoadState,
+    reported: Option<u32>,
+}
+
+pub struct Readiness {
+    session_id: String,
+    outbox: Arc<Outbox>,
+    trackers: Mutex<[Tracker; 2]>,
+}
+
+impl Readiness {
+    pub fn new(session_id: &str, outbox: Arc<Outbox>) -> Arc<Self> {
+        let loading = || Tracker {
+            state: LoadState::Loading { percentage: 0 },
+            reported: None,
+        };
+        Arc::new(Self {
+            session_id: session_id.to_string(),
+            outbox,
+            trackers: Mutex::new([loading(), loading()]),
+        })
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    pub fn state(&self, cap: Capability) -> LoadState {
+        self.trackers.lock_recover()[cap.index()].state.clone()
+    }
+
+    pub fn admit(&self, cap: Capability) -> Admission {
+        match self.state(cap) {
+            LoadState::Ready => Admission::Run,
+            LoadState::Loading { .. } => Admission::Wait,
+            LoadState::Failed(reason) => Admission::Reject(RequestError::new(
+                ErrorCode::NotReady,
+                format!("{} failed: {}", cap.operation(), reason),
+            )),
+        }
+    }
+
+    /// Record that `done` of `total` units have been loaded. A zero total reports an
+    /// indeterminate progress event once.
+    pub fn progress(&self, cap: Capability, done: u64, total: u64) {
+        let percentage = done
+            .saturating_mul(100)
+            .checked_div(total)
+            .map(|pct| pct.min(100) as u32);
+        {
+            let mut trackers = self.trackers.lock_recover();
+            let tracker = &mut trackers[cap.index()];
+            if !matches!(tracker.state, LoadState::Loading { .. }) {
+                return;
+            }
+            let pct = percentage.unwrap_or(0);
+            let due = match tracker.reported {
+                None => true,
+                Some(last) => percentage.is_some() && pct >= last + PROGRESS_STEP,
+            };
+            tracker.state = LoadState::Loading { percentage: pct };
+            if !due {
+                return;
+            }
+            tracker.reported = Some(pct);
+        }
+        self.outbox.notify(&progress_notification(
+            &self.session_id,
+            cap.operation(),
+            percentage,
+            None,
+        ));
+    }
+
+    /// Mark `cap` loaded, finishing its progress at 100% if it wasn't there already
+    pub fn set_ready(&self, cap: Capability) {
+        let reported = {
+            let mut trackers = self.trackers.lock_recover();
+            let tracker = &mut trackers[cap.index()];
+            tracker.state = LoadState::Ready;
+            tracker.reported.replace(100)
+        };
+        if reported == Some(100) {
+            return;
+        }
+        self.outbox.notify(&progress_notification(
+            &self.session_id,
+            cap.operation(),
+            Some(100),
+            None,
+        ));
+    }
+
+    pub fn set_failed(&self, cap: Capability, reason: &str) {
+        eprintln!("{} failed: {}", cap.operation(), reason);
+        self.trackers.lock_recover()[cap.index()].state = LoadState::Failed(reason.to_string());
+        let message = format!("{} failed: {}", cap.operation(), reason);
+        self.outbox
+            .notify(&error_notification(&self.session_id, "not_ready", &message));
+    }
+
+    /// Send an event discovered while loading (e.g. RTTFound)
+    pub fn notify(&self, msg: &serde_json::Value) {
+        self.outbox.notify(msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::da_helper::responder::FakeTransport;
+
+    #[test]
+    fn progress_is_throttled_and_ends_at_100() {
+        let transport = FakeTransport::default();
+        let readiness = Readiness::new("s", transport.outbox());
+        for done in 0..=50 {
+            readiness.progress(Capability::Symbols, done, 50);
+        }
+        readiness.set_ready(Capability::Symbols);
+        readiness.progress(Capability::Symbols, 10, 50); // ignored once ready
+
+        let percentages: Vec<u64> = transport
+            .take()
+            .iter()
+            .map(|m| {
+                assert_eq!(m["args"]["type"], "Progress");
+                assert_eq!(m["args"]["operation"], "Loading symbols");
+                m["args"]["percentage"].as_u64().unwrap()
+            })
+            .collect();
+        assert_eq!(percentages, vec![0, 10, 20, 30, 40, 50, 60, 70, 80, 90, 100]);
+        assert_eq!(readiness.admit(Capability::Symbols), Admission::Run);
+    }
+
+    #[test]
+    fn capabilities_are_independent() {
+        let transport = FakeTransport::default();
+        let readiness = Readiness::new("s", transport.outbox());
+        assert_eq!(readiness.admit(Capability::Disassembly), Admission::Wait);
+        readiness.set_failed(Capability::Disassembly, "objdump not found");
+        assert_eq!(
+            readiness.state(Capability::Symbols),
+            LoadState::Loading { percentage: 0 }
+        );
+        let Admission::Reject(error) = readiness.admit(Capability::Disassembly) else {
+            panic!("expected a rejection");
+        };
+        assert_eq!(error.code, ErrorCode::NotReady);
+        assert!(error.message.contains("objdump not found"));
+        let sent = transport.take();
+        assert_eq!(sent[0]["args"]["type"], "Error");
+        assert_eq!(sent[0]["args"]["code"], "not_ready");
+    }
+}
@@ -0,0 +1,462 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Variable history: a time series of the values the live sampler reports, for plotting or a
+//! waveform viewer. Samples are kept in a bounded ring like the serial [`RingBuffer`], the oldest
+//! overwritten when it is full, or, for long captures, appended to a JSON-lines file on disk.
+//! Either can be exported as CSV, JSON or VCD.
+//!
+//! [`RingBuffer`]: crate::serial::ring::RingBuffer
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::common::sync::MutexExt;
+use crate::common::utils::{decode_hex, encode_hex};
+use crate::da_helper::helper_requests::HistoryFormat;
+use crate::da_helper::sampler::{Sample, SampleItem};
+use crate::da_helper::value_type::ValueKind;
+
+/// Samples kept in memory when no capacity is given
+pub const DEFAULT_HISTORY_CAPACITY: usize = 100_000;
+
+/// One recorded value. `bytes` is hex, in target memory order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistorySample {
+    /// Microseconds since recording started
+    pub t_us: u64,
+    pub id: String,
+    pub value: String,
+    pub bytes: String,
+}
+
+/// A recorded variable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Signal {
+    pub id: String,
+    pub size: usize,
+    pub type_name: Option<String>,
+    /// Shown as a real number in VCD
+    pub float: bool,
+}
+
+impl Signal {
+    pub fn from_item(item: &SampleItem) -> Self {
+        Self {
+            id: item.id.clone(),
+            size: item.size,
+            type_name: item.value_type.as_ref().map(|t| t.name.clone()),
+            float: item.value_type.as_ref().is_some_and(|t| t.kind == ValueKind::Float),
+        }
+    }
+}
+
+struct RingInner {
+    samples: VecDeque<HistorySample>,
+    /// Samples overwritten since recording started
+    dropped: u64,
+}
+
+/// Bounded, thread-safe ring of samples. When full, new samples overwrite the oldest.
+pub struct SampleRing {
+    capacity: usize,
+    inner: Mutex<RingInner>,
+}
+
+impl SampleRing {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        SampleRing {
+            capacity,
+            inner: Mutex::new(RingInner {
+                samples: VecDeque::with_capacity(capacity.min(DEFAULT_HISTORY_CAPACITY)),
+                dropped: 0,
+            }),
+        }
+    }
+
+    pub fn push(&self, sample: HistorySample) {
+        let mut g = self.inner.lock_recover();
+        if g.samples.len() == self.capacity {
+            g.samples.pop_front();
+            g.dropped += 1;
+        }
+        g.samples.push_back(sample);
+    }
+
+    /// All samples, oldest first
+    pub fn snapshot(&self) -> Vec<HistorySample> {
+        self.inner.lock_recover().samples.iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock_recover().samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock_recover().samples.is_empty()
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.inner.lock_recover().dropped
+    }
+}
+
+enum Storage {
+    Ring(SampleRing),
+    /// JSON lines, one sample per line; `count` lines written so far
+    Disk {
+        path: PathBuf,
+        writer: Mutex<BufWriter<File>>,
+        count: Mutex<u64>,
+    },
+}
+
+/// Records the samples of selected signals. Thread-safe: the sampler thread records while
+/// requests stop and export.
+pub struct Recorder {
+    signals: Vec<Signal>,
+    start: Instant,
+    start_unix_ms: u64,
+    storage: Storage,
+    recording: AtomicBool,
+}
+
+impl Recorder {
+    /// Record `signals` in memory, keeping the last `capacity` samples
+    pub fn in_memory(signals: Vec<Signal>, capacity: usize) -> Self {
+        Self::with_storage(signals, Storage::Ring(SampleRing::new(capacity)))
+    }
+
+    /// Record `signals` to a JSON-lines file at `path`, without a bound
+    pub fn on_disk(signals: Vec<Signal>, path: &Path) -> Result<Self> {
+        let file = File::create(path).with_context(|| format!("Creating {}", path.display()))?;
+        Ok(Self::with_storage(
+            signals,
+            Storage::Disk {
+                path: path.to_path_buf(),
+                writer: Mutex::new(BufWriter::new(file)),
+                count: Mutex::new(0),
+            },
+        ))
+    }
+
+    fn with_storage(signals: Vec<Signal>, storage: Storage) -> Self {
+        Self {
+            signals,
+            start: Instant::now(),
+            start_unix_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis() as u64),
+            storage,
+            recording: AtomicBool::new(true),
+        }
+    }
+
+    pub fn signals(&self) -> &[Signal] {
+        &self.signals
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.load(Ordering::Relaxed)
+    }
+
+    /// Stop taking samples; what was recorded stays available for export
+    pub fn stop(&self) {
+        self.recording.store(false, Ordering::Relaxed);
+        if let Storage::Disk { writer, .. } = &self.storage {
+            if let Err(e) = writer.lock_recover().flush() {
+                eprintln!("Failed to flush variable history: {}", e);
+            }
+        }
+    }
+
+    /// Keep the samples of recorded signals. Disk errors are only logged, so a full disk
+    /// doesn't stop the sampler.
+    pub fn record(&self, samples: &[Sample]) {
+        if !self.is_recording() {
+            return;
+        }
+        for sample in samples {
+            if !self.signals.iter().any(|s| s.id == sample.id) {
+                continue;
+            }
+            let record = HistorySample {
+                t_us: sample.time.saturating_duration_since(self.start).as_micros() as u64,
+                id: sample.id.clone(),
+                value: sample.value.clone(),
+                bytes: encode_hex(&sample.bytes),
+            };
+            match &self.storage {
+                Storage::Ring(ring) => ring.push(record),
+                Storage::Disk { path, writer, count } => {
+                    let line = serde_json::to_string(&record).unwrap_or_default();
+                    if let Err(e) = writeln!(writer.lock_recover(), "{}", line) {
+                        eprintln!("Failed to write variable history to {}: {}", path.display(), e);
+                    } else {
+                        *count.lock_recover() += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Samples recorded and still held
+    pub fn len(&self) -> u64 {
+        match &self.storage {
+            Storage::Ring(ring) => ring.len() as u64,
+            Storage::Disk { count, .. } => *count.lock_recover(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Samples the ring had to overwrite
+    pub fn dropped(&self) -> u64 {
+        match &self.storage {
+            Storage::Ring(ring) => ring.dropped(),
+            Storage::Disk { .. } => 0,
+        }
+    }
+
+    /// Everything held, oldest first
+    pub fn samples(&self) -> Result<Vec<HistorySample>> {
+        match &self.storage {
+            Storage::Ring(ring) => Ok(ring.snapshot()),
+            Storage::Disk { path, writer, .. } => {
+                writer.lock_recover().flush()?;
+                let file = File::open(path).with_context(|| format!("Opening {}", path.display()))?;
+                BufReader::new(file)
+                    .lines()
+                    .map(|line| Ok(serde_json::from_str(&line?)?))
+                    .collect()
+            }
+        }
+    }
+
+    pub fn export(&self, format: HistoryFormat) -> Result<String> {
+        let samples = self.samples()?;
+        Ok(match format {
+            HistoryFormat::Csv => to_csv(&samples),
+            HistoryFormat::Json => to_json(&self.signals, self.start_unix_ms, &samples),
+            HistoryFormat::Vcd => to_vcd(&self.signals, self.start_unix_ms, &samples),
+        })
+    }
+}
+
+/// Quote a CSV field if it needs it
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// One row per sample: time in seconds, signal, formatted value, raw bytes
+pub fn to_csv(samples: &[HistorySample]) -> String {
+    let mut out = String::from("time_s,id,value,bytes\n");
+    for s in samples {
+        out.push_str(&format!(
+            "{}.{:06},{},{},{}\n",
+            s.t_us / 1_000_000,
+            s.t_us % 1_000_000,
+            csv_field(&s.id),
+            csv_field(&s.value),
+            s.bytes
+        ));
+    }
+    out
+}
+
+pub fn to_json(signals: &[Signal], start_unix_ms: u64, samples: &[HistorySample]) -> String {
+    serde_json::json!({
+        "start_unix_ms": start_unix_ms,
+        "signals": signals,
+        "samples": samples,
+    })
+    .to_string()
+}
+
+/// VCD identifier codes: printable ASCII from `!`, in base 94
+fn vcd_code(mut index: usize) -> String {
+    let mut code = String::new();
+    loop {
+        code.push((b'!' + (index % 94) as u8) as char);
+        index /= 94;
+        if index == 0 {
+            return code;
+        }
+        index -= 1;
+    }
+}
+
+/// A VCD signal name: no whitespace allowed
+fn vcd_name(id: &str) -> String {
+    id.chars().map(|c| if c.is_whitespace() { '_' } else { c }).collect()
+}
+
+/// Little-endian bytes as a VCD binary vector, most significant bit first
+fn vcd_binary(hex: &str) -> Option<String> {
+    let bytes = decode_hex(hex)?;
+    let bits: String = bytes.iter().rev().map(|b| format!("{:08b}", b)).collect();
+    let trimmed = bits.trim_start_matches('0');
+    Some(if trimmed.is_empty() {
+        "0".to_string()
+    } else {
+        trimmed.to_string()
+    })
+}
+
+/// Value Change Dump with a 1 us timescale. Scalars are bit vectors as wide as the variable;
+/// floats are reals.
+pub fn to_vcd(signals: &[Signal], start_unix_ms: u64, samples: &[HistorySample]) -> String {
+    let mut out = String::new();
+    out.push_str(&format!(
+        "$comment recorded by mdbg, started at unix ms {} $end\n",
+        start_unix_ms
+    ));
+    out.push_str("$timescale 1us $end\n$scope module live $end\n");
+    for (ix, signal) in signals.iter().enumerate() {
+        let kind = if signal.float { "real 64" } else { "wire" };
+        let width = if signal.float {
+            String::new()
+        } else {
+            format!(" {}", signal.size * 8)
+        };
+        out.push_str(&format!(
+            "$var {}{} {} {} $end\n",
+            kind,
+            width,
+            vcd_code(ix),
+            vcd_name(&signal.id)
+        ));
+    }
+    out.push_str("$upscope $end\n$enddefinitions $end\n");
+    let mut time = None;
+    for sample in samples {
+        let Some(ix) = signals.iter().position(|s| s.id == sample.id) else {
+            continue;
+        };
+        if time != Some(sample.t_us) {
+            out.push_str(&format!("#{}\n", sample.t_us));
+            time = Some(sample.t_us);
+        }
+        let code = vcd_code(ix);
+        if signals[ix].float {
+            out.push_str(&format!("r{} {}\n", sample.value, code));
+        } else if let Some(bits) = vcd_binary(&sample.bytes) {
+            out.push_str(&format!("b{} {}\n", bits, code));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(id: &str, size: usize, float: bool) -> Signal {
+        Signal {
+            id: id.to_string(),
+            size,
+            type_name: None,
+            float,
+        }
+    }
+
+    fn sample(id: &str, value: &str, bytes: &[u8], time: Instant) -> Sample {
+        Sample {
+            id: id.to_string(),
+            value: value.to_string(),
+            bytes: bytes.to_vec(),
+            time,
+        }
+    }
+
+    #[test]
+    fn ring_keeps_the_newest_samples() {
+        let recorder = Recorder::in_memory(vec![signal("n", 1, false)], 2);
+        let now = Instant::now();
+        for v in 0..3u8 {
+            recorder.record(&[sample("n", &v.to_string(), &[v], now), sample("other", "0", &[0], now)]);
+        }
+        let kept: Vec<String> = recorder.samples().unwrap().into_iter().map(|s| s.value).collect();
+        assert_eq!(kept, vec!["1", "2"]);
+        assert_eq!(recorder.dropped(), 1);
+
+        recorder.stop();
+        recorder.record(&[sample("n", "9", &[9], now)]);
+        assert_eq!(recorder.len(), 2);
+    }
+
+    #[test]
+    fn exports_csv_json_and_vcd() {
+        let signals = vec![signal("count", 2, false), signal("temp c", 4, true)];
+        let samples = vec![
+            HistorySample {
+                t_us: 0,
+                id: "count".to_string(),
+                value: "258".to_string(),
+                bytes: "0201".to_string(),
+            },
+            HistorySample {
+                t_us: 0,
+                id: "temp c".to_string(),
+                value: "21.5".to_string(),
+                bytes: "0000ac41".to_string(),
+            },
+            HistorySample {
+                t_us: 1_500_000,
+                id: "count".to_string(),
+                value: "0".to_string(),
+                bytes: "0000".to_string(),
+            },
+        ];
+        assert_eq!(
+            to_csv(&samples),
+            "time_s,id,value,bytes\n0.000000,count,258,0201\n0.000000,temp c,21.5,0000ac41\n1.500000,count,0,0000\n"
+        );
+        let json: serde_json::Value = serde_json::from_str(&to_json(&signals, 5, &samples)).unwrap();
+        assert_eq!(json["samples"][2]["t_us"], 1_500_000);
+        assert_eq!(json["signals"][1]["float"], true);
+
+        let vcd = to_vcd(&signals, 5, &samples);
+        assert!(vcd.contains("$var wire 16 ! count $end\n$var real 64 \" temp_c $end\n"));
+        assert!(vcd.ends_with("#0\nb100000010 !\nr21.5 \"\n#1500000\nb0 !\n"), "{}", vcd);
+        assert_eq!(vcd_code(93), "~");
+        assert_eq!(vcd_code(94), "!!");
+    }
+
+    #[test]
+    fn disk_history_reads_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let recorder = Recorder::on_disk(vec![signal("x", 1, false)], &path).unwrap();
+        let now = Instant::now();
+        recorder.record(&[sample("x", "1", &[1], now), sample("x", "2", &[2], now)]);
+        assert_eq!(recorder.len(), 2);
+        assert!(recorder.export(HistoryFormat::Csv).unwrap().ends_with(",x,2,02\n"));
+    }
+}
@@ -14,166 +14,859 @@
 
 //! Request parsing and dispatch for the main request loop.
 
-use crate::common::transport;
-use crate::common::utils::CanonicalPath;
-use crate::da_helper::elf_items::ObjectInfo;
+use crate::common::sync::MutexExt;
+use crate::common::utils::encode_hex;
+use crate::da_helper::core_dump::{CoreDump, CoreTarget};
+use crate::da_helper::cortex_m::canonical_register_name;
+use crate::da_helper::elf_items::{FileTable, ObjectInfo};
+use crate::da_helper::encoding::WireFormat;
 use crate::da_helper::helper_requests::*;
-use crate::da_helper::protocol::DisasmRequest;
+use crate::da_helper::memory_map::{build_memory_map, load_regions};
+use crate::da_helper::protocol::{
+    error_notification, live_samples_notification, DisasmRequest, WorkerJob, WorkerRequest,
+};
+use crate::da_helper::readiness::{Admission, Capability, Readiness};
+use crate::da_helper::recorder::{Recorder, Signal, DEFAULT_HISTORY_CAPACITY};
+use crate::da_helper::responder::{Batch, Outbox, Responder};
+use crate::da_helper::sampler::{Budget, SampleItem, Sampler, SamplerHandle};
+use crate::da_helper::symbols::Symbol;
+use crate::da_helper::unwind::{locate_frame, unwind, DEFAULT_MAX_FRAMES};
+use crate::da_helper::value_type::ValueType;
+use crate::da_helper::verify::{load_dumps, verify_image};
+use crate::debug_println;
+use crate::rsp::client::Client;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
 use std::string;
-use std::sync::mpsc::Sender;
-use std::sync::Arc;
+use std::sync::mpsc::{SendError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-/// Parse and dispatch requests from the DA based on the 'req' discriminant.
+/// What a request handler returns. Errors become an ErrorResponse for the request's seq.
+pub(crate) type HandlerResult = Result<(), RequestError>;
+
+/// Requests held back while the symbols load, beyond which new ones are rejected
+const MAX_WAITING_REQUESTS: usize = 1000;
+
+/// How long the live sampler waits for the gdb-server to accept and answer
+const LIVE_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Parses and dispatches requests from the DA for the main request loop.
 ///
-/// All requests have a 'req' field that identifies the request type. We peek at this
-/// field, then deserialize into the appropriate typed struct.
-pub fn dispatch_request(
-    msg: &Value,
-    req_tx: &Sender<DisasmRequest>,
-    obj_info: Arc<ObjectInfo>,
-) -> bool {
-    // Peek at the 'req' discriminant to determine request type
-    let req_type = msg
-        .get("req")
-        .and_then(|v| v.as_str())
-        .or_else(|| msg.get("command").and_then(|v| v.as_str()));
+/// Requests that need the symbol table wait here until the ELF loader finishes, then run in
+/// arrival order. Disassembly requests go straight to the worker's queue, which it starts
+/// serving once the listing is loaded. If a load fails, requests needing it get `not_ready`.
+pub struct Dispatcher {
+    req_tx: Sender<WorkerJob>,
+    outbox: Arc<Outbox>,
+    readiness: Arc<Readiness>,
+    obj_info: Option<Arc<ObjectInfo>>,
+    symbols_done: bool,
+    waiting: VecDeque<Value>,
+    /// This client's live sampling, stopped when the client goes away
+    live: Mutex<Option<LiveSession>>,
+    /// Where live samples are recorded, if anywhere; shared with the sampler thread
+    history: HistorySlot,
+}
 
-    match req_type {
-        Some("disasm") | Some("disassemble") => handle_disassemble_request(msg, req_tx, obj_info),
-        Some("globals") => handle_globals_request(msg, obj_info),
-        Some("statics") => handle_statics_request(msg, obj_info),
-        Some("symbolLookup") => handle_symbol_lookup_request(msg, obj_info),
-        _ => {
-            eprintln!("Unknown request type: {:?}", req_type);
-            false
+/// Running live sampling and what it samples
+struct LiveSession {
+    _sampler: SamplerHandle,
+    items: Vec<SampleItem>,
+}
+
+type HistorySlot = Arc<Mutex<Option<Arc<Recorder>>>>;
+
+impl Dispatcher {
+    pub fn new(req_tx: Sender<WorkerJob>, outbox: Arc<Outbox>, readiness: Arc<Readiness>) -> Self {
+        Self {
+            req_tx,
+            outbox,
+            readiness,
+            obj_info: None,
+            symbols_done: false,
+            waiting: VecDeque::new(),
+            live: Mutex::new(None),
+            history: Arc::new(Mutex::new(None)),
         }
     }
-}
 
-/// Handle disassemble request - deserialize and forward to worker
-fn handle_disassemble_request(
-    msg: &Value,
-    req_tx: &Sender<DisasmRequest>,
-    _obj_info: Arc<ObjectInfo>,
-) -> bool {
-    // Try to deserialize as our typed DisassembleRequest struct
-    match serde_json::from_value::<DisassembleRequest>(msg.clone()) {
-        Ok(typed_req) => {
-            // Convert to internal DisasmRequest format for worker
-            if let Some(internal_req) = convert_to_internal_disasm_request(&typed_req) {
-                if req_tx.send(internal_req).is_err() {
-                    eprintln!("Failed to send request to worker");
-                    return false;
+    /// Handle one message from the DA. Returns false if it was rejected or failed right away.
+    pub fn handle(&mut self, msg: &Value) -> bool {
+        if request_type(msg) == Some("cancel") {
+            // A request still waiting for symbols is simply dropped
+            if let Some(seq) = request_seq(msg) {
+                self.waiting.retain(|m| request_seq(m) != Some(seq));
+            }
+        } else if !self.symbols_done && needs_symbols(msg) {
+            if self.waiting.len() >= MAX_WAITING_REQUESTS {
+                if let Some(seq) = request_seq(msg) {
+                    let req_name = request_type(msg).unwrap_or("unknown");
+                    Responder::direct(req_name, seq, Arc::clone(&self.outbox)).send_error(
+                        RequestError::new(
+                            ErrorCode::NotReady,
+                            "Symbols are still loading and too many requests are waiting",
+                        ),
+                    );
                 }
-                return true;
+                return false;
             }
-            false
+            debug_println!("Request waits for symbols: {}", msg);
+            self.waiting.push_back(msg.clone());
+            return true;
         }
-        Err(e) => {
-            eprintln!("Failed to parse DisassembleRequest: {}", e);
-            false
+        self.dispatch(msg)
+    }
+
+    /// The ELF loader finished (`None` if it failed): run everything that was waiting
+    pub fn symbols_loaded(&mut self, obj_info: Option<Arc<ObjectInfo>>) {
+        self.obj_info = obj_info;
+        self.symbols_done = true;
+        while let Some(msg) = self.waiting.pop_front() {
+            self.dispatch(&msg);
         }
     }
-}
 
-/// Handle globals request - query global symbols
-fn handle_globals_request(msg: &Value, obj_info: Arc<ObjectInfo>) -> bool {
-    match serde_json::from_value::<GlobalsRequest>(msg.clone()) {
-        Ok(typed_req) => {
-            let mut globals: Vec<(String, String)> = Vec::new();
-            for sym in &obj_info.global_symbols {
-                globals.push((sym.name.clone(), format!("0x{:x}", sym.address)));
+    /// Dispatch based on the 'req' discriminant.
+    ///
+    /// All requests have a 'req' field that identifies the request type. We peek at this
+    /// field, then deserialize into the appropriate typed struct. `batch` and `cancel` are
+    /// handled here; everything else goes through `dispatch_one` with its own responder.
+    /// Every request with a `seq` gets exactly one response: the normal one or an ErrorResponse.
+    fn dispatch(&self, msg: &Value) -> bool {
+        let Some(seq) = request_seq(msg) else {
+            eprintln!("Ignoring request without a numeric seq: {}", msg);
+            return false;
+        };
+        let req_name = request_type(msg).unwrap_or("unknown");
+        match request_type(msg) {
+            Some("batch") => self.dispatch_batch(msg),
+            Some("cancel") => {
+                self.outbox.cancel(seq);
+                true
             }
-            let response = GlobalsResponse {
-                req: "globals".to_string(),
-                seq: typed_req.seq,
-                globals,
-            };
-            let response_json = serde_json::to_string(&response).unwrap();
-            if let Err(e) =
-                transport::write_json_locked(&serde_json::from_str(&response_json).unwrap())
-            {
-                eprintln!("Failed to write globals response: {}", e);
+            Some("hello") => {
+                let responder = Responder::direct(req_name, seq, Arc::clone(&self.outbox));
+                responder.finish(handle_hello_request(msg, &responder, &self.outbox))
+            }
+            _ => {
+                let responder = Responder::direct(req_name, seq, Arc::clone(&self.outbox));
+                self.dispatch_one(msg, responder)
+            }
+        }
+    }
+
+    /// Dispatch a single (non-batch) request. Worker requests take the responder with them.
+    fn dispatch_one(&self, msg: &Value, responder: Responder) -> bool {
+        if let Some(cap) = required_capability(msg) {
+            if let Admission::Reject(e) = self.readiness.admit(cap) {
+                return responder.finish(Err(e));
+            }
+        }
+        let obj_info = || {
+            self.obj_info
+                .as_deref()
+                .ok_or_else(|| RequestError::new(ErrorCode::NotReady, "Symbols are still loading"))
+        };
+        let result = match request_type(msg) {
+            Some("disasm") | Some("disassemble") => {
+                return self.forward_to_worker(parse_disassemble_request(msg), responder);
+            }
+            Some("cfg") => return self.forward_to_worker(parse_cfg_request(msg), responder),
+            Some("sourceListing") => {
+                return self.forward_to_worker(parse_source_listing_request(msg), responder);
+            }
+            Some("globals") => obj_info().and_then(|info| handle_globals_request(msg, info, &responder)),
+            Some("statics") => obj_info().and_then(|info| handle_statics_request(msg, info, &responder)),
+            Some("symbolLookup") => {
+                obj_info().and_then(|info| handle_symbol_lookup_request(msg, info, &responder))
+            }
+            Some("memoryMap") => obj_info().and_then(|info| handle_memory_map_request(msg, info, &responder)),
+            Some("verify") => obj_info().and_then(|info| handle_verify_request(msg, info, &responder)),
+            Some("unwind") => obj_info().and_then(|info| handle_unwind_request(msg, info, &responder)),
+            Some("liveStart") => obj_info().and_then(|info| {
+                let context = LiveContext {
+                    outbox: &self.outbox,
+                    readiness: &self.readiness,
+                    live: &self.live,
+                    history: &self.history,
+                };
+                handle_live_start_request(msg, info, &responder, context)
+            }),
+            Some("liveStop") => handle_live_stop_request(msg, &responder, &self.live),
+            Some("historyStart") => handle_history_start_request(msg, &responder, &self.live, &self.history),
+            Some("historyStop") => handle_history_stop_request(msg, &responder, &self.history),
+            Some("historyExport") => handle_history_export_request(msg, &responder, &self.history),
+            other => Err(RequestError::new(
+                ErrorCode::UnknownRequest,
+                format!("Unknown request type: {}", other.unwrap_or("<missing>")),
+            )),
+        };
+        responder.finish(result)
+    }
+
+    /// Queue a parsed request for the worker, which sends the response (or error) itself
+    fn forward_to_worker(
+        &self,
+        request: Result<WorkerRequest, RequestError>,
+        responder: Responder,
+    ) -> bool {
+        let request = match request {
+            Ok(request) => request,
+            Err(e) => return responder.finish(Err(e)),
+        };
+        if let Err(SendError(job)) = self.req_tx.send(WorkerJob { request, responder }) {
+            job.responder.send_error(RequestError::new(
+                ErrorCode::Internal,
+                "Disassembly worker is not running",
+            ));
+            return false;
+        }
+        true
+    }
+
+    /// Handle batch request - dispatch each entry into its slot of one combined response
+    fn dispatch_batch(&self, msg: &Value) -> bool {
+        let typed_req = match parse_request::<BatchRequest>(msg, "BatchRequest") {
+            Ok(typed_req) => typed_req,
+            Err(e) => {
+                let seq = request_seq(msg).unwrap_or(0);
+                Responder::direct("batch", seq, Arc::clone(&self.outbox)).send_error(e);
                 return false;
             }
-            true
+        };
+        let batch = Batch::new(typed_req.seq, typed_req.requests.len(), Arc::clone(&self.outbox));
+        for (index, sub_req) in typed_req.requests.iter().enumerate() {
+            let req_name = request_type(sub_req).unwrap_or("unknown");
+            let seq = request_seq(sub_req).unwrap_or(0);
+            let responder = Responder::for_batch(req_name, seq, &batch, index);
+            if matches!(request_type(sub_req), Some("batch") | Some("cancel") | Some("hello")) {
+                responder.send_error(RequestError::new(
+                    ErrorCode::UnknownRequest,
+                    format!("'{}' is not allowed inside a batch", req_name),
+                ));
+                continue;
+            }
+            self.dispatch_one(sub_req, responder);
         }
-        Err(e) => {
-            eprintln!("Failed to parse GlobalsRequest: {}", e);
-            false
+        true
+    }
+}
+
+/// The load a request depends on, if any
+fn required_capability(msg: &Value) -> Option<Capability> {
+    match request_type(msg)? {
+        "disasm" | "disassemble" | "cfg" | "sourceListing" => Some(Capability::Disassembly),
+        "globals" | "statics" | "symbolLookup" | "memoryMap" | "verify" | "unwind" | "liveStart" => {
+            Some(Capability::Symbols)
+        }
+        _ => None,
+    }
+}
+
+/// True if `msg`, or any request in it if it is a batch, needs the symbol table
+fn needs_symbols(msg: &Value) -> bool {
+    match msg.get("requests").and_then(|r| r.as_array()) {
+        Some(requests) if request_type(msg) == Some("batch") => requests.iter().any(needs_symbols),
+        _ => required_capability(msg) == Some(Capability::Symbols),
+    }
+}
+
+fn request_type(msg: &Value) -> Option<&str> {
+    msg.get("req")
+        .and_then(|v| v.as_str())
+        .or_else(|| msg.get("command").and_then(|v| v.as_str()))
+}
+
+fn request_seq(msg: &Value) -> Option<u64> {
+    msg.get("seq").and_then(|v| v.as_u64())
+}
+
+/// Deserialize `msg` into its typed request, mapping failure to a ParseError
+fn parse_request<T: DeserializeOwned>(msg: &Value, type_name: &str) -> Result<T, RequestError> {
+    serde_json::from_value::<T>(msg.clone()).map_err(|e| {
+        RequestError::new(
+            ErrorCode::ParseError,
+            format!("Failed to parse {}: {}", type_name, e),
+        )
+    })
+}
+
+/// Map a failure to load a file the request names. Reading it is the helper's problem
+/// (Internal); only contents that don't parse say the request pointed at the wrong thing.
+fn file_error(e: anyhow::Error) -> RequestError {
+    let code = if e.chain().any(|cause| cause.is::<std::io::Error>()) {
+        ErrorCode::Internal
+    } else {
+        ErrorCode::ParseError
+    };
+    RequestError::new(code, format!("{:#}", e))
+}
+
+/// Map a failure to read the loaded ELF again, which is not the request's fault
+fn elf_error(e: anyhow::Error) -> RequestError {
+    RequestError::new(ErrorCode::Internal, format!("{:#}", e))
+}
+
+/// Serialize and send a successful response
+pub(crate) fn send_response<T: Serialize>(responder: &Responder, response: &T) -> HandlerResult {
+    let json = serde_json::to_value(response).map_err(|e| {
+        RequestError::new(ErrorCode::Internal, format!("Failed to serialize response: {}", e))
+    })?;
+    responder.send(&json).map_err(|e| {
+        RequestError::new(ErrorCode::Internal, format!("Failed to write response: {}", e))
+    })
+}
+
+/// Parse a disassemble request into the worker's internal format
+fn parse_disassemble_request(msg: &Value) -> Result<WorkerRequest, RequestError> {
+    let typed_req = parse_request::<DisassembleRequest>(msg, "DisassembleRequest")?;
+    let internal_req = convert_to_internal_disasm_request(&typed_req).ok_or_else(|| {
+        RequestError::new(
+            ErrorCode::ParseError,
+            format!(
+                "Invalid memoryReference/offset: {} {:?}",
+                typed_req.arguments.memoryReference, typed_req.arguments.offset
+            ),
+        )
+    })?;
+    Ok(WorkerRequest::Disasm(internal_req))
+}
+
+/// Parse a control-flow graph request - the listing lives in the worker, so it is answered there
+fn parse_cfg_request(msg: &Value) -> Result<WorkerRequest, RequestError> {
+    let typed_req = parse_request::<CfgRequest>(msg, "CfgRequest")?;
+    if typed_req.function.is_none() && typed_req.address.is_none() {
+        return Err(RequestError::new(
+            ErrorCode::ParseError,
+            "CfgRequest needs either a function name or an address",
+        ));
+    }
+    Ok(WorkerRequest::Cfg(typed_req))
+}
+
+/// Parse a sourceListing request and check it names a function or a complete range
+fn parse_source_listing_request(msg: &Value) -> Result<WorkerRequest, RequestError> {
+    let typed_req = parse_request::<SourceListingRequest>(msg, "SourceListingRequest")?;
+    if typed_req.function.is_none() && (typed_req.start.is_none() || typed_req.end.is_none()) {
+        return Err(RequestError::new(
+            ErrorCode::ParseError,
+            "SourceListingRequest needs either a function name or a start/end range",
+        ));
+    }
+    Ok(WorkerRequest::SourceListing(typed_req))
+}
+
+/// Handle hello request - reply in the current format, then switch this client to the negotiated one
+fn handle_hello_request(msg: &Value, responder: &Responder, outbox: &Outbox) -> HandlerResult {
+    let typed_req = parse_request::<HelloRequest>(msg, "HelloRequest")?;
+    let format = WireFormat::negotiate(&typed_req);
+    let response = HelloResponse {
+        req: typed_req.req,
+        seq: typed_req.seq,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        encoding: format.encoding,
+        compression: format.compression,
+    };
+    send_response(responder, &response)?;
+    outbox.set_format(format);
+    Ok(())
+}
+
+/// Handle globals request - query global symbols
+fn handle_globals_request(
+    msg: &Value,
+    obj_info: &ObjectInfo,
+    responder: &Responder,
+) -> HandlerResult {
+    let typed_req = parse_request::<GlobalsRequest>(msg, "GlobalsRequest")?;
+    let (globals, variables) = variable_lists(
+        &obj_info.global_symbols,
+        typed_req.detailed.unwrap_or(false),
+        &obj_info.file_table,
+    );
+    let response = GlobalsResponse {
+        req: "globals".to_string(),
+        seq: typed_req.seq,
+        globals,
+        variables,
+    };
+    send_response(responder, &response)
+}
+
+/// Build either the compact (name, address) tuples or, for detailed requests, the full variable records
+fn variable_lists(
+    symbols: &[Arc<Symbol>],
+    detailed: bool,
+    file_table: &FileTable,
+) -> (Vec<(String, String)>, Option<Vec<VariableInfo>>) {
+    if detailed {
+        let variables = symbols
+            .iter()
+            .map(|sym| VariableInfo::from_symbol(sym, file_table))
+            .collect();
+        (Vec::new(), Some(variables))
+    } else {
+        let tuples = symbols
+            .iter()
+            .map(|sym| (sym.name.clone(), format!("0x{:x}", sym.address)))
+            .collect();
+        (tuples, None)
+    }
+}
+
+impl VariableInfo {
+    pub fn from_symbol(sym: &Symbol, file_table: &FileTable) -> Self {
+        Self {
+            name: sym.name.clone(),
+            raw_name: (sym.raw_name != sym.name).then(|| sym.raw_name.clone()),
+            address: format!("0x{:x}", sym.address),
+            size: sym.size,
+            section: sym.section.clone(),
+            decl_file: sym
+                .decl_file
+                .and_then(|id| file_table.get_by_id(id))
+                .cloned(),
+            decl_line: sym.decl_line,
+            type_ref: sym.type_offset.map(|off| format!("0x{:x}", off)),
         }
     }
 }
 
 /// Handle statics request - query static symbols in a file
-fn handle_statics_request(msg: &Value, obj_info: Arc<ObjectInfo>) -> bool {
-    match serde_json::from_value::<StaticsRequest>(msg.clone()) {
-        Ok(typed_req) => {
-            let canonical_file_name = CanonicalPath::new(&typed_req.file_name);
-            let statics = obj_info
-                .static_file_mapping
-                .get_statics_for_file(&canonical_file_name);
-            let statics_ary: Vec<(String, String)> = statics
-                .iter()
-                .map(|sym| (sym.name.clone(), format!("0x{:x}", sym.address)))
-                .collect();
-            let response = StaticsResponse {
-                req: "statics".to_string(),
-                seq: typed_req.seq,
-                statics: statics_ary,
-            };
-            let response_json = serde_json::to_string(&response).unwrap();
-            if let Err(e) =
-                transport::write_json_locked(&serde_json::from_str(&response_json).unwrap())
-            {
-                eprintln!("Failed to write statics response: {}", e);
-                return false;
+fn handle_statics_request(
+    msg: &Value,
+    obj_info: &ObjectInfo,
+    responder: &Responder,
+) -> HandlerResult {
+    let typed_req = parse_request::<StaticsRequest>(msg, "StaticsRequest")?;
+    // Statics are keyed by the remapped path, so a compiler path needs mapping too
+    let canonical_file_name = obj_info.file_table.local_path(&typed_req.file_name);
+    let statics = obj_info
+        .static_file_mapping
+        .get_statics_for_file(&canonical_file_name);
+    let (statics_ary, variables) = variable_lists(
+        &statics,
+        typed_req.detailed.unwrap_or(false),
+        &obj_info.file_table,
+    );
+    let response = StaticsResponse {
+        req: "statics".to_string(),
+        seq: typed_req.seq,
+        statics: statics_ary,
+        variables,
+    };
+    send_response(responder, &response)
+}
+
+/// Handle memoryMap request - place the sections in the device's regions. The linker script
+/// and SVD are read on every request, so edits show up without restarting the helper.
+fn handle_memory_map_request(
+    msg: &Value,
+    obj_info: &ObjectInfo,
+    responder: &Responder,
+) -> HandlerResult {
+    let typed_req = parse_request::<MemoryMapRequest>(msg, "MemoryMapRequest")?;
+    let regions = load_regions(
+        &obj_info.layout,
+        typed_req.linker_script.as_deref().map(Path::new),
+        typed_req.svd.as_deref().map(Path::new),
+    )
+    .map_err(file_error)?;
+    let map = build_memory_map(&obj_info.layout, &regions);
+    let response = MemoryMapResponse {
+        req: "memoryMap".to_string(),
+        seq: typed_req.seq,
+        regions: map.regions,
+        sections: map.sections,
+        overlaps: map.overlaps,
+        overflows: map.overflows,
+    };
+    send_response(responder, &response)
+}
+
+/// Handle verify request - compare target memory dumps with the ELF's load image
+fn handle_verify_request(msg: &Value, obj_info: &ObjectInfo, responder: &Responder) -> HandlerResult {
+    let typed_req = parse_request::<VerifyRequest>(msg, "VerifyRequest")?;
+    let actual = load_dumps(&typed_req.dumps).map_err(file_error)?;
+    let expected = obj_info.load_image().map_err(elf_error)?;
+    let report = verify_image(expected, &actual, &obj_info.layout, &obj_info.elf_symbols);
+    let response = VerifyResponse {
+        req: "verify".to_string(),
+        seq: typed_req.seq,
+        matched: report.matched(),
+        compared: report.compared,
+        ignored: report.ignored,
+        not_dumped: report.not_dumped,
+        mismatches: report.mismatches,
+    };
+    send_response(responder, &response)
+}
+
+/// Handle unwind request - call stack from registers and stack memory, or from a core dump
+fn handle_unwind_request(msg: &Value, obj_info: &ObjectInfo, responder: &Responder) -> HandlerResult {
+    let typed_req = parse_request::<UnwindRequest>(msg, "UnwindRequest")?;
+    let mut dump = match &typed_req.core {
+        Some(path) => CoreDump::load(Path::new(path)).map_err(file_error)?,
+        None => CoreDump::default(),
+    };
+    // Registers and memory given in the request take precedence over the dump's
+    for (name, value) in &typed_req.registers {
+        let value = parse_hex_address(value).ok_or_else(|| {
+            RequestError::new(
+                ErrorCode::ParseError,
+                format!("Bad value '{}' for register {}", value, name),
+            )
+        })?;
+        dump.registers.insert(canonical_register_name(name), value);
+    }
+    for (addr, bytes) in load_dumps(&typed_req.memory).map_err(file_error)?.runs() {
+        dump.memory.write(addr, bytes);
+    }
+    let target = CoreTarget {
+        dump: &dump,
+        elf_image: obj_info.load_image().map_err(elf_error)?,
+    };
+    let read = |addr: u64, len: usize| target.read(addr, len);
+    let max_frames = typed_req.max_frames.map_or(DEFAULT_MAX_FRAMES, |n| n as usize);
+    let backtrace = unwind(
+        &obj_info.unwind_tables,
+        &|name| target.register(name),
+        &read,
+        max_frames,
+    );
+    let frames = backtrace
+        .frames
+        .iter()
+        .map(|frame| {
+            let location = locate_frame(obj_info, frame);
+            UnwindFrame {
+                pc: location.address,
+                sp: format!("0x{:08x}", frame.sp),
+                function: location.function,
+                file: location.file,
+                line: location.line,
+                method: frame.method.map(|m| m.as_str().to_string()),
+                exception: frame.exception.as_ref().and_then(|e| e.name.clone()),
+                exc_return: frame.exception.as_ref().map(|e| format!("0x{:08x}", e.exc_return)),
+                registers: frame
+                    .known_registers()
+                    .map(|(name, value)| (name.to_string(), format!("0x{:08x}", value)))
+                    .collect(),
             }
-            true
+        })
+        .collect();
+    let response = UnwindResponse {
+        req: "unwind".to_string(),
+        seq: typed_req.seq,
+        frames,
+        stop_reason: backtrace.stop_reason,
+    };
+    send_response(responder, &response)
+}
+
+/// Where a live item is, before its type is resolved
+struct LiveLocation {
+    address: u64,
+    symbol_size: Option<usize>,
+    type_offset: Option<u64>,
+}
+
+/// Find a live item by symbol or address
+fn locate_live_item(item: &LiveItem, obj_info: &ObjectInfo) -> Result<LiveLocation, RequestError> {
+    let parse_error = |message: String| RequestError::new(ErrorCode::ParseError, message);
+    match (&item.symbol, &item.address) {
+        (Some(name), None) => {
+            let sym = obj_info
+                .dwarf_symbols
+                .get_by_name(name)
+                .or_else(|| obj_info.elf_symbols.get_by_name(name))
+                .ok_or_else(|| RequestError::new(ErrorCode::AddressNotFound, format!("No symbol named '{}'", name)))?;
+            Ok(LiveLocation {
+                address: sym.address,
+                symbol_size: Some(sym.size as usize),
+                type_offset: sym.type_offset,
+            })
         }
-        Err(e) => {
-            eprintln!("Failed to parse StaticsRequest: {}", e);
-            false
+        (None, Some(address)) => {
+            let address =
+                parse_hex_address(address).ok_or_else(|| parse_error(format!("Bad address '{}'", address)))?;
+            let type_offset = match &item.type_ref {
+                Some(type_ref) => Some(
+                    parse_hex_address(type_ref).ok_or_else(|| parse_error(format!("Bad type_ref '{}'", type_ref)))?,
+                ),
+                None => None,
+            };
+            Ok(LiveLocation {
+                address,
+                symbol_size: None,
+                type_offset,
+            })
         }
+        _ => Err(parse_error(format!(
+            "Live item '{}' needs exactly one of symbol and address",
+            item.id
+        ))),
     }
 }
 
-/// Handle symbol lookup request - by name or address
-fn handle_symbol_lookup_request(msg: &Value, obj_info: Arc<ObjectInfo>) -> bool {
-    // Try to parse as name lookup first
-    if let Ok(_typed_req) = serde_json::from_value::<SymbolLookupNameRequest>(msg.clone()) {
-        let sym = obj_info.elf_symbols.get_by_name(&_typed_req.name);
-        if let Some(symbol) = sym {
-            let ary: Vec<(string::String, String)> =
-                vec![(symbol.name.clone(), format!("0x{:x}", symbol.address))];
-            let response = SymbolLookupResponse {
-                req: "symbolLookup".to_string(),
-                seq: _typed_req.seq,
-                symbols: ary,
-            };
-            let response_json = serde_json::to_string(&response).unwrap();
-            if let Err(e) =
-                transport::write_json_locked(&serde_json::from_str(&response_json).unwrap())
-            {
-                eprintln!("Failed to write symbol lookup response: {}", e);
-                return false;
+/// Where a live item is and how to show it, given the types resolved for the request's items
+fn resolve_live_item(
+    item: &LiveItem,
+    location: LiveLocation,
+    value_types: &HashMap<u64, ValueType>,
+) -> Result<SampleItem, RequestError> {
+    let parse_error = |message: String| RequestError::new(ErrorCode::ParseError, message);
+    let value_type = location.type_offset.and_then(|off| value_types.get(&off)).cloned();
+    let size = item
+        .size
+        .map(|n| n as usize)
+        .or(value_type.as_ref().map(|t| t.size))
+        .or(location.symbol_size)
+        .filter(|&n| n > 0)
+        .ok_or_else(|| parse_error(format!("Live item '{}' has no size", item.id)))?;
+    if item.interval_ms == 0 {
+        return Err(parse_error(format!("Live item '{}' needs an interval_ms of at least 1", item.id)));
+    }
+    Ok(SampleItem {
+        id: item.id.clone(),
+        address: location.address,
+        size,
+        value_type,
+        interval: Duration::from_millis(item.interval_ms as u64),
+    })
+}
+
+/// Dispatcher state a liveStart request works with
+struct LiveContext<'a> {
+    outbox: &'a Arc<Outbox>,
+    readiness: &'a Readiness,
+    live: &'a Mutex<Option<LiveSession>>,
+    history: &'a HistorySlot,
+}
+
+/// Handle liveStart request - sample memory over a connection of our own to the gdb-server
+fn handle_live_start_request(
+    msg: &Value,
+    obj_info: &ObjectInfo,
+    responder: &Responder,
+    context: LiveContext,
+) -> HandlerResult {
+    let typed_req = parse_request::<LiveStartRequest>(msg, "LiveStartRequest")?;
+    let locations = typed_req
+        .items
+        .iter()
+        .map(|item| locate_live_item(item, obj_info))
+        .collect::<Result<Vec<_>, _>>()?;
+    // One pass over the DWARF for all the types the items need
+    let type_offsets: Vec<u64> = locations.iter().filter_map(|l| l.type_offset).collect();
+    let value_types = obj_info.value_types(&type_offsets).map_err(elf_error)?;
+    let items = typed_req
+        .items
+        .iter()
+        .zip(locations)
+        .map(|(item, location)| resolve_live_item(item, location, &value_types))
+        .collect::<Result<Vec<_>, _>>()?;
+    let budget = Budget {
+        max_busy: typed_req.max_busy_percent.map_or(Budget::default().max_busy, |pct| {
+            pct.clamp(1, 100) as f64 / 100.0
+        }),
+        max_gap: typed_req.max_gap.map_or(Budget::default().max_gap, |gap| gap as usize),
+        ..Budget::default()
+    };
+    // Stop what this client had running before opening another connection
+    context.live.lock_recover().take();
+    let infos = items
+        .iter()
+        .map(|item| LiveItemInfo {
+            id: item.id.clone(),
+            address: format!("0x{:08x}", item.address),
+            size: item.size as u32,
+            type_name: item.value_type.as_ref().map(|t| t.name.clone()),
+        })
+        .collect();
+    let session_id = context.readiness.session_id().to_string();
+    let (emit_outbox, error_outbox) = (Arc::clone(context.outbox), Arc::clone(context.outbox));
+    let error_session = session_id.clone();
+    let history = Arc::clone(context.history);
+    let gdb_server = typed_req.gdb_server.clone();
+    // Connecting can take a while; the sampler thread does it so requests keep flowing
+    let handle = SamplerHandle::spawn(
+        Sampler::new(items.clone(), budget, Instant::now()),
+        move || {
+            Client::connect(gdb_server.as_str(), LIVE_CONNECT_TIMEOUT)
+                .map_err(|e| format!("Connecting to {}: {}", gdb_server, e))
+        },
+        move |samples, slowdown| {
+            // Take the recorder out of the slot so recording doesn't hold the lock
+            let recorder = history.lock_recover().clone();
+            if let Some(recorder) = recorder {
+                recorder.record(&samples);
             }
-            return true;
+            let samples = samples
+                .into_iter()
+                .map(|s| LiveSample {
+                    id: s.id,
+                    value: s.value,
+                    bytes: encode_hex(&s.bytes),
+                })
+                .collect();
+            emit_outbox.notify(&live_samples_notification(&session_id, samples, slowdown));
+        },
+        move |error| {
+            error_outbox.notify(&error_notification(&error_session, "target_unavailable", &error));
+        },
+    );
+    *context.live.lock_recover() = Some(LiveSession {
+        _sampler: handle,
+        items,
+    });
+    let response = LiveStartResponse {
+        req: "liveStart".to_string(),
+        seq: typed_req.seq,
+        items: infos,
+    };
+    send_response(responder, &response)
+}
+
+/// Handle liveStop request
+fn handle_live_stop_request(msg: &Value, responder: &Responder, live: &Mutex<Option<LiveSession>>) -> HandlerResult {
+    let typed_req = parse_request::<LiveStopRequest>(msg, "LiveStopRequest")?;
+    let response = LiveStopResponse {
+        req: "liveStop".to_string(),
+        seq: typed_req.seq,
+        stopped: live.lock_recover().take().is_some(),
+    };
+    send_response(responder, &response)
+}
+
+/// Handle historyStart request - record the values of selected live items
+fn handle_history_start_request(
+    msg: &Value,
+    responder: &Responder,
+    live: &Mutex<Option<LiveSession>>,
+    history: &HistorySlot,
+) -> HandlerResult {
+    let typed_req = parse_request::<HistoryStartRequest>(msg, "HistoryStartRequest")?;
+    let signals: Vec<Signal> = {
+        let live = live.lock_recover();
+        let session = live.as_ref().ok_or_else(|| {
+            RequestError::new(ErrorCode::NotReady, "No live sampling is running; send liveStart first")
+        })?;
+        match &typed_req.ids {
+            Some(ids) => ids
+                .iter()
+                .map(|id| {
+                    session
+                        .items
+                        .iter()
+                        .find(|item| item.id == *id)
+                        .map(Signal::from_item)
+                        .ok_or_else(|| RequestError::new(ErrorCode::ParseError, format!("No live item '{}'", id)))
+                })
+                .collect::<Result<_, _>>()?,
+            None => session.items.iter().map(Signal::from_item).collect(),
         }
-        eprintln!("Symbol lookup by name received but not yet implemented");
-        return true;
+    };
+    let ids = signals.iter().map(|s| s.id.clone()).collect();
+    let recorder = match &typed_req.file {
+        Some(file) => Recorder::on_disk(signals, Path::new(file))
+            .map_err(|e| RequestError::new(ErrorCode::Internal, format!("{:#}", e)))?,
+        None => Recorder::in_memory(
+            signals,
+            typed_req.capacity.map_or(DEFAULT_HISTORY_CAPACITY, |n| n as usize),
+        ),
+    };
+    if let Some(previous) = history.lock_recover().replace(Arc::new(recorder)) {
+        previous.stop();
     }
+    let response = HistoryStartResponse {
+        req: "historyStart".to_string(),
+        seq: typed_req.seq,
+        signals: ids,
+    };
+    send_response(responder, &response)
+}
 
-    // Try to parse as address lookup
-    if let Ok(_typed_req) = serde_json::from_value::<SymbolLookupAddressRequest>(msg.clone()) {
-        // TODO: Implement symbol lookup by address
-        eprintln!("Symbol lookup by address received but not yet implemented");
-        return true;
+fn no_history() -> RequestError {
+    RequestError::new(ErrorCode::NotReady, "Nothing is recorded; send historyStart first")
+}
+
+/// Handle historyStop request
+fn handle_history_stop_request(msg: &Value, responder: &Responder, history: &HistorySlot) -> HandlerResult {
+    let typed_req = parse_request::<HistoryStopRequest>(msg, "HistoryStopRequest")?;
+    let recorder = history.lock_recover().clone().ok_or_else(no_history)?;
+    recorder.stop();
+    let response = HistoryStopResponse {
+        req: "historyStop".to_string(),
+        seq: typed_req.seq,
+        samples: recorder.len(),
+        dropped: recorder.dropped(),
+    };
+    send_response(responder, &response)
+}
+
+/// Handle historyExport request - to a file, or inline in the response
+fn handle_history_export_request(msg: &Value, responder: &Responder, history: &HistorySlot) -> HandlerResult {
+    let typed_req = parse_request::<HistoryExportRequest>(msg, "HistoryExportRequest")?;
+    let recorder = history.lock_recover().clone().ok_or_else(no_history)?;
+    let internal = |e: anyhow::Error| RequestError::new(ErrorCode::Internal, format!("{:#}", e));
+    let data = recorder.export(typed_req.format).map_err(internal)?;
+    let data = match &typed_req.path {
+        Some(path) => {
+            std::fs::write(path, data)
+                .map_err(|e| RequestError::new(ErrorCode::Internal, format!("Writing {}: {}", path, e)))?;
+            None
+        }
+        None => Some(data),
+    };
+    let response = HistoryExportResponse {
+        req: "historyExport".to_string(),
+        seq: typed_req.seq,
+        samples: recorder.len(),
+        path: typed_req.path,
+        data,
+    };
+    send_response(responder, &response)
+}
+
+/// Handle symbol lookup request - by name or address
+fn handle_symbol_lookup_request(
+    msg: &Value,
+    obj_info: &ObjectInfo,
+    responder: &Responder,
+) -> HandlerResult {
+    // Try to parse as name lookup first
+    if let Ok(typed_req) = serde_json::from_value::<SymbolLookupNameRequest>(msg.clone()) {
+        let symbol = obj_info
+            .elf_symbols
+            .get_by_name(&typed_req.name)
+            .ok_or_else(|| {
+                RequestError::new(
+                    ErrorCode::AddressNotFound,
+                    format!("No symbol named '{}'", typed_req.name),
+                )
+            })?;
+        let ary: Vec<(string::String, String)> =
+            vec![(symbol.name.clone(), format!("0x{:x}", symbol.address))];
+        let response = SymbolLookupResponse {
+            req: "symbolLookup".to_string(),
+            seq: typed_req.seq,
+            symbols: ary,
+        };
+        return send_response(responder, &response);
     }
 
-    eprintln!("Failed to parse SymbolLookupRequest");
-    false
+    // Try to parse as address lookup
+    parse_request::<SymbolLookupAddressRequest>(msg, "SymbolLookupRequest")?;
+    // TODO: Implement symbol lookup by address
+    Err(RequestError::new(
+        ErrorCode::NotImplemented,
+        "Symbol lookup by address is not implemented yet",
+    ))
 }
 
 /// Convert from the typed DisassembleRequest to the internal DisasmRequest format
@@ -204,8 +897,386 @@ fn convert_to_internal_disasm_request(req: &DisassembleRequest) -> Option<Disasm
 }
 
 /// Parse hex address from string (supports "0x1234" or "1234" format)
-fn parse_hex_address(input: &str) -> Option<u64> {
+pub(crate) fn parse_hex_address(input: &str) -> Option<u64> {
     let trimmed = input.trim();
     let hex_str = trimmed.strip_prefix("0x").unwrap_or(trimmed);
     u64::from_str_radix(hex_str, 16).ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::da_helper::disasm_worker::{run_disassembly_worker, serve_disassembly_requests};
+    use crate::da_helper::get_assembly::{AssemblyBlock, AssemblyLine, AssemblyListing};
+    use crate::da_helper::responder::FakeTransport;
+    use crate::da_helper::symbols::{SymbolScope, SymbolType};
+    use serde_json::json;
+    use std::rc::Rc;
+    use std::sync::mpsc::{channel, Receiver};
+    use std::thread;
+
+    /// Drives a `Dispatcher` the way the main loop does, with a real worker thread serving a
+    /// tiny synthetic listing and every message captured by a fake transport. Loading is
+    /// under the test's control: nothing is ready until `load_symbols`/`start_worker`.
+    struct Harness {
+        transport: FakeTransport,
+        outbox: Arc<Outbox>,
+        readiness: Arc<Readiness>,
+        dispatcher: Dispatcher,
+        req_rx: Option<Receiver<WorkerJob>>,
+        worker: Option<thread::JoinHandle<()>>,
+    }
+
+    fn tiny_listing() -> AssemblyListing {
+        let mut listing = AssemblyListing::new();
+        let mut block = AssemblyBlock::new("main".to_string(), 0x1000, 0);
+        for (addr, bytes, text) in [(0x1000, "00 20", "movs\tr0, #0"), (0x1002, "70 47", "bx\tlr")] {
+            let line = Rc::new(AssemblyLine::new(
+                addr,
+                bytes.to_string(),
+                text.to_string(),
+                String::new(),
+                0,
+                (addr - 0x1000) as u32,
+            ));
+            listing.addr_map.insert(addr, listing.lines.len());
+            listing.lines.push(line.clone());
+            block.lines.push(line);
+        }
+        listing.blocks.push(block);
+        listing
+    }
+
+    fn tiny_object_info() -> Arc<ObjectInfo> {
+        let mut info = ObjectInfo::new();
+        let counter = info.elf_symbols.insert(Symbol {
+            name: "counter".to_string(),
+            raw_name: "counter".to_string(),
+            address: 0x2000_0000,
+            size: 4,
+            kind: SymbolType::Data,
+            scope: SymbolScope::Global,
+            section: Some(".bss".to_string()),
+            decl_file: None,
+            decl_line: None,
+            type_offset: None,
+        });
+        info.global_symbols.push(counter);
+        Arc::new(info)
+    }
+
+    impl Harness {
+        /// Nothing loaded yet
+        fn loading() -> Self {
+            let transport = FakeTransport::default();
+            let outbox = transport.outbox();
+            let readiness = Readiness::new("test", Arc::clone(&outbox));
+            let (req_tx, req_rx) = channel();
+            let dispatcher = Dispatcher::new(req_tx, Arc::clone(&outbox), Arc::clone(&readiness));
+            Self {
+                transport,
+                outbox,
+                readiness,
+                dispatcher,
+                req_rx: Some(req_rx),
+                worker: None,
+            }
+        }
+
+        /// Everything loaded
+        fn new() -> Self {
+            let mut h = Self::loading();
+            h.load_symbols();
+            h.start_worker();
+            h
+        }
+
+        fn load_symbols(&mut self) {
+            self.readiness.set_ready(Capability::Symbols);
+            self.dispatcher.symbols_loaded(Some(tiny_object_info()));
+        }
+
+        fn start_worker(&mut self) {
+            let req_rx = self.req_rx.take().unwrap();
+            let cancels = Arc::clone(&self.outbox.cancels);
+            let readiness = Arc::clone(&self.readiness);
+            self.worker = Some(thread::spawn(move || {
+                readiness.set_ready(Capability::Disassembly);
+                serve_disassembly_requests(tiny_listing(), req_rx, Some(tiny_object_info()), &cancels);
+            }));
+        }
+
+        /// Run the real worker with an objdump that doesn't exist
+        fn start_failing_worker(&mut self) {
+            let req_rx = self.req_rx.take().unwrap();
+            let cancels = Arc::clone(&self.outbox.cancels);
+            let readiness = Arc::clone(&self.readiness);
+            let (_, obj_info_rx) = channel();
+            let (_, text_bytes_rx) = channel();
+            self.worker = Some(thread::spawn(move || {
+                run_disassembly_worker(
+                    "/nonexistent/objdump",
+                    "/nonexistent/app.elf",
+                    req_rx,
+                    obj_info_rx,
+                    text_bytes_rx,
+                    cancels,
+                    readiness,
+                );
+            }));
+        }
+
+        fn send(&mut self, msg: Value) -> bool {
+            self.dispatcher.handle(&msg)
+        }
+
+        /// Responses sent so far, leaving out event notifications
+        fn responses(&self) -> Vec<Value> {
+            self.transport
+                .take()
+                .into_iter()
+                .filter(|m| m.get("method").is_none())
+                .collect()
+        }
+
+        /// Stop the worker once it has drained its queue and return the remaining responses
+        fn finish(self) -> Vec<Value> {
+            let Harness {
+                transport,
+                dispatcher,
+                req_rx,
+                worker,
+                ..
+            } = self;
+            drop(dispatcher);
+            drop(req_rx);
+            if let Some(worker) = worker {
+                worker.join().unwrap();
+            }
+            transport
+                .take()
+                .into_iter()
+                .filter(|m| m.get("method").is_none())
+                .collect()
+        }
+    }
+
+    fn error_code(response: &Value) -> &str {
+        response["error"]["code"].as_str().unwrap_or("<none>")
+    }
+
+    #[test]
+    fn successful_requests_get_typed_responses() {
+        let mut h = Harness::new();
+        assert!(h.send(json!({"req": "globals", "seq": 1})));
+        assert!(h.send(json!({"req": "symbolLookup", "seq": 2, "name": "counter"})));
+        assert!(h.send(json!({"req": "cfg", "seq": 3, "function": "main"})));
+        let sent = h.finish();
+        assert_eq!(sent.len(), 3);
+        assert_eq!(sent[0]["seq"], 1);
+        assert_eq!(sent[0]["globals"], json!([["counter", "0x20000000"]]));
+        assert_eq!(sent[1]["symbols"], json!([["counter", "0x20000000"]]));
+        assert_eq!(sent[2]["req"], "cfg");
+        assert_eq!(sent[2]["seq"], 3);
+        assert!(sent.iter().all(|r| r.get("error").is_none()));
+    }
+
+    #[test]
+    fn failures_get_stable_error_codes() {
+        let mut h = Harness::new();
+        assert!(!h.send(json!({"req": "bogus", "seq": 1})));
+        assert!(!h.send(json!({"req": "statics", "seq": 2})));
+        assert!(!h.send(json!({"req": "symbolLookup", "seq": 3, "name": "missing"})));
+        assert!(!h.send(json!({"req": "cfg", "seq": 4})));
+        // Parsed fine, so it is queued; the worker reports the missing function
+        assert!(h.send(json!({"req": "cfg", "seq": 5, "function": "nope"})));
+        // No seq means nobody could match a reply, so none is sent
+        assert!(!h.send(json!({"req": "globals"})));
+        let sent = h.finish();
+        let codes: Vec<(u64, &str)> = sent
+            .iter()
+            .map(|r| (r["seq"].as_u64().unwrap(), error_code(r)))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                (1, "unknown_request"),
+                (2, "parse_error"),
+                (3, "address_not_found"),
+                (4, "parse_error"),
+                (5, "address_not_found"),
+            ]
+        );
+        assert_eq!(sent[0]["req"], "bogus");
+        assert!(sent[2]["error"]["message"].as_str().unwrap().contains("missing"));
+    }
+
+    #[test]
+    fn live_sampling_feeds_the_history() {
+        use crate::da_helper::image::MemoryImage;
+        use crate::da_helper::sim_target::{SharedSimTarget, SimTarget};
+        use crate::rsp::packet::Connection;
+        use crate::rsp::stub::{serve_connection, StubTarget};
+        use std::net::TcpListener;
+
+        let mut h = Harness::new();
+        assert!(!h.send(json!({"req": "historyStart", "seq": 1})));
+        assert_eq!(error_code(&h.responses()[0]), "not_ready");
+
+        let mut memory = MemoryImage::new();
+        memory.write(0x2000_0000, &[0; 16]);
+        let target = SharedSimTarget(Arc::new(Mutex::new(SimTarget::from_image(memory, 0x2000_0010, 0))));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let gdb_server = listener.local_addr().unwrap().to_string();
+        let mut served = target.clone();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut conn = Connection::new(stream.try_clone().unwrap(), stream);
+            let _ = serve_connection(&mut conn, &mut served);
+        });
+
+        let items = json!([{"id": "c", "symbol": "counter", "interval_ms": 5}]);
+        assert!(h.send(json!({"req": "liveStart", "seq": 2, "gdb_server": gdb_server, "items": items})));
+        assert_eq!(h.responses()[0]["items"][0]["address"], "0x20000000");
+        assert!(h.send(json!({"req": "historyStart", "seq": 3})));
+        target.0.lock_recover().write_memory(0x2000_0000, &[7, 0, 0, 0]);
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        let mut seen = Vec::new();
+        while !seen.iter().any(|m: &Value| m["args"]["samples"][0]["bytes"] == "07000000") {
+            assert!(std::time::Instant::now() < deadline, "no sample of the new value: {:?}", seen);
+            thread::sleep(Duration::from_millis(5));
+            seen.extend(h.transport.take());
+        }
+        assert!(h.send(json!({"req": "liveStop", "seq": 4})));
+        assert!(h.send(json!({"req": "historyExport", "seq": 5, "format": "csv"})));
+        let sent: Vec<Value> = seen
+            .into_iter()
+            .chain(h.responses())
+            .filter(|m| m.get("method").is_none())
+            .collect();
+        assert_eq!(sent[0]["signals"], json!(["c"]));
+        assert_eq!(sent[1]["stopped"], true);
+        assert!(sent[2]["data"].as_str().unwrap().ends_with(",c,07000000,07000000\n"));
+    }
+
+    #[test]
+    fn an_unreachable_gdb_server_is_reported_after_live_start_answers() {
+        use std::net::TcpListener;
+
+        // Nothing ever accepts, so the sampler's handshake times out while requests keep flowing
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let gdb_server = listener.local_addr().unwrap().to_string();
+        let mut h = Harness::new();
+        let items = json!([{"id": "c", "symbol": "counter", "interval_ms": 5}]);
+        assert!(h.send(json!({"req": "liveStart", "seq": 1, "gdb_server": gdb_server, "items": items})));
+        assert_eq!(h.responses()[0]["items"][0]["id"], "c");
+        assert!(h.send(json!({"req": "globals", "seq": 2})));
+        assert_eq!(h.responses()[0]["seq"], 2);
+
+        let deadline = std::time::Instant::now() + LIVE_CONNECT_TIMEOUT * 3;
+        let mut seen = Vec::new();
+        while !seen.iter().any(|m: &Value| m["args"]["code"] == "target_unavailable") {
+            assert!(std::time::Instant::now() < deadline, "no target_unavailable event: {:?}", seen);
+            thread::sleep(Duration::from_millis(5));
+            seen.extend(h.transport.take());
+        }
+    }
+
+    #[test]
+    fn hello_negotiates_the_wire_format() {
+        let mut h = Harness::new();
+        assert!(h.send(json!({"req": "hello", "seq": 1, "encodings": ["bson", "msgpack"], "compression": ["zstd"]})));
+        assert!(h.send(json!({"req": "batch", "seq": 2, "requests": [{"req": "hello", "seq": 3}]})));
+        let sent = h.finish();
+        assert_eq!(sent[0]["encoding"], "msgpack");
+        assert_eq!(sent[0]["compression"], "zstd");
+        assert_eq!(sent[0]["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(error_code(&sent[1]["responses"][0]), "unknown_request");
+    }
+
+    #[test]
+    fn batch_slots_hold_responses_and_errors() {
+        let mut h = Harness::new();
+        assert!(h.send(json!({"req": "batch", "seq": 10, "requests": [
+            {"req": "globals", "seq": 11},
+            {"req": "cfg", "seq": 12, "function": "nope"},
+            {"req": "cancel", "seq": 11},
+            {"req": "nope", "seq": 13},
+        ]})));
+        assert!(!h.send(json!({"req": "batch", "seq": 20, "requests": "not a list"})));
+        let sent = h.finish();
+        assert_eq!(sent.len(), 2);
+        // The worker may complete the first batch before or after the second one fails
+        let by_seq = |seq: u64| sent.iter().find(|r| r["seq"] == seq).unwrap();
+        assert_eq!(error_code(by_seq(20)), "parse_error");
+
+        let responses = by_seq(10)["responses"].as_array().unwrap();
+        assert_eq!(responses[0]["seq"], 11);
+        assert!(responses[0]["globals"].is_array());
+        assert_eq!(error_code(&responses[1]), "address_not_found");
+        assert_eq!(error_code(&responses[2]), "unknown_request");
+        assert_eq!(error_code(&responses[3]), "unknown_request");
+    }
+
+    #[test]
+    fn requests_wait_until_their_capability_is_loaded() {
+        let mut h = Harness::loading();
+        assert!(h.send(json!({"req": "globals", "seq": 1})));
+        assert!(h.send(json!({"req": "cfg", "seq": 2, "function": "main"})));
+        assert!(h.send(json!({"req": "batch", "seq": 3, "requests": [
+            {"req": "cfg", "seq": 4, "function": "main"},
+            {"req": "symbolLookup", "seq": 5, "name": "counter"},
+        ]})));
+        assert!(h.send(json!({"req": "statics", "seq": 6, "file_name": "main.c"})));
+        assert!(h.send(json!({"req": "cancel", "seq": 6})));
+        assert!(h.responses().is_empty());
+
+        // Symbols first: the waiting requests run in order, cfg is still queued in the worker
+        h.load_symbols();
+        let sent = h.responses();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0]["seq"], 1);
+        assert!(sent[0]["globals"].is_array());
+
+        h.start_worker();
+        let sent = h.finish();
+        let mut seqs: Vec<u64> = sent.iter().map(|r| r["seq"].as_u64().unwrap()).collect();
+        seqs.sort();
+        assert_eq!(seqs, vec![2, 3]);
+        assert!(sent.iter().all(|r| r.get("error").is_none()));
+        let batch = sent.iter().find(|r| r["seq"] == 3).unwrap();
+        assert_eq!(batch["responses"][0]["req"], "cfg");
+        assert_eq!(batch["responses"][1]["symbols"], json!([["counter", "0x20000000"]]));
+    }
+
+    #[test]
+    fn failed_loads_reject_with_not_ready() {
+        let mut h = Harness::loading();
+        assert!(h.send(json!({"req": "globals", "seq": 1})));
+        h.readiness.set_failed(Capability::Symbols, "not an ELF file");
+        h.dispatcher.symbols_loaded(None);
+        assert!(!h.send(json!({"req": "symbolLookup", "seq": 2, "name": "counter"})));
+
+        // Queued before objdump fails, and sent after
+        assert!(h.send(json!({"req": "cfg", "seq": 3, "function": "main"})));
+        h.start_failing_worker();
+        while h.readiness.admit(Capability::Disassembly) == Admission::Wait {
+            thread::yield_now();
+        }
+        assert!(!h.send(json!({"req": "cfg", "seq": 4, "function": "main"})));
+        let sent = h.finish();
+
+        let mut codes: Vec<(u64, &str)> = sent
+            .iter()
+            .map(|r| (r["seq"].as_u64().unwrap(), error_code(r)))
+            .collect();
+        codes.sort();
+        assert_eq!(
+            codes,
+            vec![(1, "not_ready"), (2, "not_ready"), (3, "not_ready"), (4, "not_ready")]
+        );
+        let first = sent.iter().find(|r| r["seq"] == 1).unwrap();
+        assert!(first["error"]["message"].as_str().unwrap().contains("not an ELF file"));
+    }
+}
@@ -0,0 +1,530 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Where request results go: straight to the client, or into one slot of a `batch` response.
+//! Also tracks `cancel`led seqs so queued or in-flight work for them produces no output,
+//! and turns handler errors into `ErrorResponse`s. Each client has its own `Outbox`, so seqs
+//! (and cancels) are per client; notifications go to every client through `Broadcast`.
+
+use std::collections::HashSet;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+
+use crate::common::framing::{FrameCodec, FrameError};
+use crate::common::sync::MutexExt;
+use crate::common::transport;
+use crate::da_helper::encoding::{FormatCell, WireFormat};
+use crate::da_helper::helper_requests::{BatchResponse, ErrorCode, ErrorResponse, RequestError};
+use crate::da_helper::protocol::WorkerJob;
+use crate::debug_println;
+
+/// Cancelled (client, seq) pairs. Shared by the main thread (which records cancels) and the worker.
+#[derive(Default)]
+pub struct CancelSet {
+    seqs: Mutex<HashSet<(u64, u64)>>,
+}
+
+impl CancelSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self, client: u64, seq: u64) {
+        self.seqs.lock_recover().insert((client, seq));
+    }
+
+    pub fn is_cancelled(&self, client: u64, seq: u64) -> bool {
+        self.seqs.lock_recover().contains(&(client, seq))
+    }
+
+    /// Take the next job for the worker, blocking if there is none. Cancels only matter for
+    /// work that is queued or running, so we forget them whenever the queue is empty. The
+    /// emptiness check and the clear happen under the lock, so a cancel for a job that was
+    /// already sent can't be lost.
+    pub fn next_job(&self, rx: &Receiver<WorkerJob>) -> Option<WorkerJob> {
+        {
+            let mut seqs = self.seqs.lock_recover();
+            match rx.try_recv() {
+                Ok(job) => return Some(job),
+                Err(e) => {
+                    seqs.clear();
+                    if e == TryRecvError::Disconnected {
+                        return None;
+                    }
+                }
+            }
+        }
+        rx.recv().ok()
+    }
+}
+
+/// Where responses and notifications are written. The helper uses stdout; tests substitute a
+/// fake transport.
+pub trait ResponseSink: Send + Sync {
+    fn write_response(&self, msg: &Value) -> Result<(), FrameError>;
+
+    /// Encode everything written from now on in `format` (see `hello`). Sinks that aren't a
+    /// client connection ignore this.
+    fn set_format(&self, _format: WireFormat) {}
+}
+
+impl<T: ResponseSink + ?Sized> ResponseSink for Arc<T> {
+    fn write_response(&self, msg: &Value) -> Result<(), FrameError> {
+        (**self).write_response(msg)
+    }
+
+    fn set_format(&self, format: WireFormat) {
+        (**self).set_format(format)
+    }
+}
+
+/// Writes responses to stdout with Content-Length framing, sharing the lock with notifications
+#[derive(Default)]
+pub struct StdoutSink {
+    format: FormatCell,
+}
+
+impl ResponseSink for StdoutSink {
+    fn write_response(&self, msg: &Value) -> Result<(), FrameError> {
+        transport::write_frame_locked(&self.format.get().encode(&FrameCodec::new(), msg)?)
+    }
+
+    fn set_format(&self, format: WireFormat) {
+        self.format.set(format);
+    }
+}
+
+/// One client's view of the helper: where its responses go and which of its seqs are cancelled.
+pub struct Outbox {
+    client: u64,
+    pub cancels: Arc<CancelSet>,
+    sink: Box<dyn ResponseSink>,
+}
+
+impl Outbox {
+    /// An outbox for a lone client with its own cancel set
+    pub fn new(sink: Box<dyn ResponseSink>) -> Arc<Self> {
+        Self::for_client(0, Arc::new(CancelSet::new()), sink)
+    }
+
+    /// An outbox for `client`, sharing `cancels` with the worker
+    pub fn for_client(
+        client: u64,
+        cancels: Arc<CancelSet>,
+        sink: Box<dyn ResponseSink>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            client,
+            cancels,
+            sink,
+        })
+    }
+
+    pub fn stdout() -> Arc<Self> {
+        Self::new(Box::<StdoutSink>::default())
+    }
+
+    pub fn cancel(&self, seq: u64) {
+        self.cancels.cancel(self.client, seq);
+    }
+
+    pub fn is_cancelled(&self, seq: u64) -> bool {
+        self.cancels.is_cancelled(self.client, seq)
+    }
+
+    /// Switch this client to a negotiated wire format
+    pub fn set_format(&self, format: WireFormat) {
+        self.sink.set_format(format);
+    }
+
+    /// Send an unsolicited message (an event notification). Failures are only logged.
+    pub fn notify(&self, msg: &Value) {
+        if let Err(e) = self.sink.write_response(msg) {
+            eprintln!("Failed to write notification: {}", e);
+        }
+    }
+}
+
+impl RequestError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Collects the sub-responses of one batch request and sends the combined response once
+/// every slot is filled. Slots of failed, unknown or cancelled sub-requests are null.
+pub struct Batch {
+    seq: u64,
+    outbox: Arc<Outbox>,
+    slots: Mutex<BatchSlots>,
+}
+
+struct BatchSlots {
+    responses: Vec<Option<Value>>,
+    filled: Vec<bool>,
+    remaining: usize,
+}
+
+impl Batch {
+    pub fn new(seq: u64, count: usize, outbox: Arc<Outbox>) -> Arc<Self> {
+        let batch = Arc::new(Self {
+            seq,
+            outbox,
+            slots: Mutex::new(BatchSlots {
+                responses: vec![None; count],
+                filled: vec![false; count],
+                remaining: count,
+            }),
+        });
+        if count == 0 {
+            batch.send(Vec::new());
+        }
+        batch
+    }
+
+    /// Fill slot `index`; the first fill wins. Sends the batch response when it was the last one.
+    fn fill(&self, index: usize, response: Option<Value>) {
+        let mut slots = self.slots.lock_recover();
+        if slots.filled[index] {
+            return;
+        }
+        slots.filled[index] = true;
+        slots.responses[index] = response;
+        slots.remaining -= 1;
+        if slots.remaining == 0 {
+            let responses = std::mem::take(&mut slots.responses);
+            drop(slots);
+            self.send(responses);
+        }
+    }
+
+    fn send(&self, responses: Vec<Option<Value>>) {
+        if self.outbox.is_cancelled(self.seq) {
+            debug_println!("Dropping response for cancelled batch seq {}", self.seq);
+            return;
+        }
+        let response = BatchResponse {
+            req: "batch".to_string(),
+            seq: self.seq,
+            responses: responses.into_iter().map(|r| r.unwrap_or(Value::Null)).collect(),
+        };
+        match serde_json::to_value(&response) {
+            Ok(json) => {
+                if let Err(e) = self.outbox.sink.write_response(&json) {
+                    eprintln!("Failed to write batch response: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to serialize batch response: {}", e),
+        }
+    }
+}
+
+struct BatchSlot {
+    batch: Arc<Batch>,
+    index: usize,
+}
+
+impl Drop for BatchSlot {
+    // A sub-request that never produced a response (cancelled, or a handler that forgot to
+    // reply) still has to complete its slot, or the batch would never be sent.
+    fn drop(&mut self) {
+        self.batch.fill(self.index, None);
+    }
+}
+
+/// The reply channel for one request. Handlers call `send` or `send_error`; dropping a batch
+/// responder without either leaves a null in the batch.
+pub struct Responder {
+    req: String,
+    seq: u64,
+    outbox: Arc<Outbox>,
+    slot: Option<BatchSlot>,
+}
+
+impl Responder {
+    pub fn direct(req: &str, seq: u64, outbox: Arc<Outbox>) -> Self {
+        Self {
+            req: req.to_string(),
+            seq,
+            outbox,
+            slot: None,
+        }
+    }
+
+    pub fn for_batch(req: &str, seq: u64, batch: &Arc<Batch>, index: usize) -> Self {
+        Self {
+            req: req.to_string(),
+            seq,
+            outbox: Arc::clone(&batch.outbox),
+            slot: Some(BatchSlot {
+                batch: Arc::clone(batch),
+                index,
+            }),
+        }
+    }
+
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// True if this request, or the batch it belongs to, was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.outbox.is_cancelled(self.seq)
+            || self
+                .slot
+                .as_ref()
+                .is_some_and(|slot| self.outbox.is_cancelled(slot.batch.seq))
+    }
+
+    pub fn send(&self, response: &Value) -> Result<(), FrameError> {
+        if self.is_cancelled() {
+            debug_println!("Dropping response for cancelled seq {}", self.seq);
+            return Ok(());
+        }
+        match &self.slot {
+            Some(slot) => {
+                slot.batch.fill(slot.index, Some(response.clone()));
+                Ok(())
+            }
+            None => self.outbox.sink.write_response(response),
+        }
+    }
+
+    /// Reply with an ErrorResponse. Failures to write are logged; there is nobody else to tell.
+    pub fn send_error(&self, error: RequestError) {
+        eprintln!("Request '{}' (seq {}) failed: {:?}", self.req, self.seq, error);
+        let response = ErrorResponse {
+            req: self.req.clone(),
+            seq: self.seq,
+            error,
+        };
+        match serde_json::to_value(&response) {
+            Ok(json) => {
+                if let Err(e) = self.send(&json) {
+                    eprintln!("Failed to write error response: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to serialize error response: {}", e),
+        }
+    }
+
+    /// Send `result`'s error, if any
+    pub fn finish(&self, result: Result<(), RequestError>) -> bool {
+        match result {
+            Ok(()) => true,
+            Err(error) => {
+                self.send_error(error);
+                false
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Responder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Responder")
+            .field("req", &self.req)
+            .field("seq", &self.seq)
+            .field("batch", &self.slot.as_ref().map(|s| (s.batch.seq, s.index)))
+            .finish()
+    }
+}
+
+/// Sends notifications to every connected client. Load milestones (ready events, RTT found,
+/// load failures) are remembered and replayed to clients that connect later.
+#[derive(Default)]
+pub struct Broadcast {
+    state: Mutex<BroadcastState>,
+}
+
+#[derive(Default)]
+struct BroadcastState {
+    clients: Vec<(u64, Arc<dyn ResponseSink>)>,
+    milestones: Vec<Value>,
+}
+
+impl Broadcast {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Start sending notifications to `client`, beginning with the milestones so far
+    pub fn add_client(&self, client: u64, sink: Arc<dyn ResponseSink>) {
+        let mut state = self.state.lock_recover();
+        for msg in &state.milestones {
+            if let Err(e) = sink.write_response(msg) {
+                eprintln!("Failed to replay notification to client {}: {}", client, e);
+            }
+        }
+        state.clients.push((client, sink));
+    }
+
+    pub fn remove_client(&self, client: u64) {
+        self.state.lock_recover().clients.retain(|(id, _)| *id != client);
+    }
+}
+
+fn is_milestone(msg: &Value) -> bool {
+    let args = &msg["args"];
+    match args["type"].as_str() {
+        Some("SymbolTableReady") | Some("DisassemblyReady") | Some("RTTFound") => true,
+        Some("Error") => args["code"] == "not_ready",
+        _ => false,
+    }
+}
+
+impl ResponseSink for Broadcast {
+    fn write_response(&self, msg: &Value) -> Result<(), FrameError> {
+        // Hold the lock while writing so a client being added can't miss or duplicate a milestone
+        let mut state = self.state.lock_recover();
+        if is_milestone(msg) {
+            state.milestones.push(msg.clone());
+        }
+        for (client, sink) in &state.clients {
+            if let Err(e) = sink.write_response(msg) {
+                eprintln!("Failed to write notification to client {}: {}", client, e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Records responses instead of writing them, for protocol tests.
+#[cfg(test)]
+#[derive(Default, Clone)]
+pub(crate) struct FakeTransport {
+    sent: Arc<Mutex<Vec<Value>>>,
+}
+
+#[cfg(test)]
+impl FakeTransport {
+    pub(crate) fn outbox(&self) -> Arc<Outbox> {
+        Outbox::new(Box::new(self.clone()))
+    }
+
+    /// Everything written so far, oldest first; clears the record
+    pub(crate) fn take(&self) -> Vec<Value> {
+        std::mem::take(&mut *self.sent.lock_recover())
+    }
+}
+
+#[cfg(test)]
+impl ResponseSink for FakeTransport {
+    fn write_response(&self, msg: &Value) -> Result<(), FrameError> {
+        self.sent.lock_recover().push(msg.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::da_helper::helper_requests::CfgRequest;
+    use crate::da_helper::protocol::WorkerRequest;
+    use serde_json::json;
+    use std::sync::mpsc::channel;
+
+    fn job(seq: u64, outbox: &Arc<Outbox>) -> WorkerJob {
+        WorkerJob {
+            request: WorkerRequest::Cfg(CfgRequest {
+                req: "cfg".to_string(),
+                seq,
+                function: Some("main".to_string()),
+                address: None,
+                dot: None,
+            }),
+            responder: Responder::direct("cfg", seq, Arc::clone(outbox)),
+        }
+    }
+
+    #[test]
+    fn cancels_apply_to_queued_jobs_and_expire_when_idle() {
+        let outbox = FakeTransport::default().outbox();
+        let cancels = &outbox.cancels;
+        let (tx, rx) = channel();
+        tx.send(job(1, &outbox)).unwrap();
+        tx.send(job(2, &outbox)).unwrap();
+        outbox.cancel(2);
+
+        let first = cancels.next_job(&rx).unwrap();
+        assert!(!first.responder.is_cancelled());
+        let second = cancels.next_job(&rx).unwrap();
+        assert!(second.responder.is_cancelled());
+
+        // Queue is empty now: the next poll forgets old cancels
+        tx.send(job(3, &outbox)).unwrap();
+        assert_eq!(cancels.next_job(&rx).unwrap().responder.seq(), 3);
+        assert!(outbox.is_cancelled(2));
+        drop(tx);
+        assert!(cancels.next_job(&rx).is_none());
+        assert!(!outbox.is_cancelled(2));
+    }
+
+    #[test]
+    fn batch_collects_responses_errors_and_nulls() {
+        let transport = FakeTransport::default();
+        let outbox = transport.outbox();
+        let batch = Batch::new(10, 3, Arc::clone(&outbox));
+        {
+            let r0 = Responder::for_batch("globals", 1, &batch, 0);
+            let r1 = Responder::for_batch("cfg", 2, &batch, 1);
+            let _r2 = Responder::for_batch("cfg", 3, &batch, 2);
+            outbox.cancel(3);
+            r0.send(&json!({"seq": 1})).unwrap();
+            r0.send(&json!({"seq": 99})).unwrap(); // first fill wins
+            r1.send_error(RequestError::new(ErrorCode::AddressNotFound, "no such function"));
+            assert!(transport.take().is_empty());
+        }
+        let sent = transport.take();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0]["seq"], 10);
+        let responses = sent[0]["responses"].as_array().unwrap();
+        assert_eq!(responses[0], json!({"seq": 1}));
+        assert_eq!(responses[1]["error"]["code"], "address_not_found");
+        assert_eq!(responses[2], Value::Null);
+    }
+
+    #[test]
+    fn clients_have_separate_cancels_and_see_milestones() {
+        let cancels = Arc::new(CancelSet::new());
+        let first = FakeTransport::default();
+        let second = FakeTransport::default();
+        let first_outbox = Outbox::for_client(1, Arc::clone(&cancels), Box::new(first.clone()));
+        let second_outbox = Outbox::for_client(2, Arc::clone(&cancels), Box::new(second.clone()));
+        first_outbox.cancel(7);
+        assert!(first_outbox.is_cancelled(7));
+        assert!(!second_outbox.is_cancelled(7));
+
+        let broadcast = Broadcast::new();
+        broadcast.add_client(1, Arc::new(first.clone()));
+        let ready = json!({"method": "HelperEvent", "args": {"type": "SymbolTableReady"}});
+        let progress = json!({"method": "HelperEvent", "args": {"type": "Progress"}});
+        broadcast.write_response(&progress).unwrap();
+        broadcast.write_response(&ready).unwrap();
+        assert_eq!(first.take(), vec![progress, ready.clone()]);
+
+        // A late client only gets the milestones replayed
+        broadcast.add_client(2, Arc::new(second.clone()));
+        assert_eq!(second.take(), vec![ready]);
+        broadcast.remove_client(1);
+        broadcast.write_response(&json!({"method": "HelperEvent"})).unwrap();
+        assert!(first.take().is_empty());
+        assert_eq!(second.take().len(), 1);
+    }
+}
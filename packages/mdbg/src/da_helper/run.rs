@@ -15,25 +15,36 @@
 //! Entry point for the da-helper subcommand.
 //! Loads ELF/DWARF info, starts disassembly worker, and serves requests from the Debug Adapter.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Args;
 use gimli::Reader;
 use object::{Object, ObjectSection, ObjectSymbol};
-use std::process::exit;
-use std::sync::{mpsc::channel, Arc};
+use std::collections::HashMap;
+use std::io::{self, BufReader};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
 use std::{borrow::Cow, fs, rc::Rc};
 
 use crate::common::debug;
-use crate::common::transport::{StdioTransport, Transport};
 use crate::common::utils::{is_absolute_path, CanonicalPath};
+use crate::da_helper::demangle::{demangle, DemangleStyle};
 use crate::da_helper::disasm_worker;
-use crate::da_helper::elf_items::ObjectInfo;
+use crate::da_helper::elf_items::{ElfSource, FileTable, ObjectInfo};
+use crate::da_helper::get_assembly::executable_bytes;
 use crate::da_helper::memory::MemoryRegion;
+use crate::da_helper::memory_map::ElfLayout;
 use crate::da_helper::protocol::{self, rtt_found_notification};
-use crate::da_helper::request_handler;
+use crate::da_helper::readiness::{Capability, Readiness};
+use crate::da_helper::responder::{Broadcast, CancelSet, Outbox, ResponseSink, StdoutSink};
+use crate::da_helper::server::{
+    self, HelperEndpoint, ListenAddr, Listener, Session, SessionEvent, StateFileGuard,
+};
+use crate::da_helper::source_map::SourceMap;
 use crate::da_helper::symbols::{Symbol, SymbolScope, SymbolType};
+use crate::da_helper::unwind::UnwindTables;
 
 #[derive(Args, Debug)]
 pub struct DaHelperArgs {
@@ -56,6 +67,33 @@ pub struct DaHelperArgs {
     #[arg(short = 'd', long = "debug", default_value_t = false)]
     pub debug: bool,
 
+    /// Rewrite source paths recorded in DWARF: FROM=TO (path prefix) or re:PATTERN=REPLACEMENT.
+    /// May be repeated; rules are tried in order
+    #[arg(long = "source-map", value_name = "RULE")]
+    pub source_map: Vec<String>,
+
+    /// How Rust and C++ symbol names are shown; lookups accept both demangled and raw names
+    #[arg(
+        long = "demangle",
+        value_enum,
+        value_name = "STYLE",
+        default_value_t = DemangleStyle::Full
+    )]
+    pub demangle: DemangleStyle,
+
+    /// Serve any number of clients on HOST:PORT or unix:PATH instead of the DA on stdio.
+    /// Port 0 picks a free port; the address is printed as JSON and saved to the state file
+    #[arg(long = "listen", value_name = "ADDR")]
+    pub listen: Option<String>,
+
+    /// Where a server records its address (default: ~/.mcu-debug/da-helper/<hash>.json)
+    #[arg(long = "state-file", value_name = "PATH")]
+    pub state_file: Option<PathBuf>,
+
+    /// Print the address of a running server for the ELF file and exit; fails if none
+    #[arg(long = "find", default_value_t = false)]
+    pub find: bool,
+
     /// Path(s) to ELF file(s) to analyze
     #[arg(required = true, num_args = 1..)]
     pub elf_files: Vec<String>,
@@ -73,6 +111,40 @@ fn dwarf_attr_to_string(
         .and_then(|d_s| d_s.to_string_lossy().ok().map(|cow| cow.to_string()))
 }
 
+/// Map a CU-local file index (line program numbering, also used by DW_AT_decl_file) to a
+/// global FileTable id, interning the path the first time it is seen.
+fn resolve_file_index(
+    dwarf: &gimli::Dwarf<gimli::EndianRcSlice<gimli::RunTimeEndian>>,
+    unit: &gimli::Unit<gimli::EndianRcSlice<gimli::RunTimeEndian>>,
+    file_idx: u64,
+    file_map: &mut HashMap<u64, u32>,
+    file_table: &mut FileTable,
+) -> Option<u32> {
+    if let Some(&id) = file_map.get(&file_idx) {
+        return Some(id);
+    }
+    let header = unit.line_program.as_ref()?.header();
+    let fe = header.file(file_idx)?;
+    let mut p = String::new();
+
+    // Get directory path
+    if let Some(dir_attr) = header.directory(fe.directory_index()) {
+        if let Some(dir_str) = dwarf_attr_to_string(dwarf, unit, dir_attr) {
+            p.push_str(&dir_str);
+            p.push('/');
+        }
+    }
+
+    // Get file name
+    if let Some(file_str) = dwarf_attr_to_string(dwarf, unit, fe.path_name()) {
+        p.push_str(&file_str);
+    }
+
+    let id = file_table.intern(p);
+    file_map.insert(file_idx, id);
+    Some(id)
+}
+
 /// Statistics for processing DWARF compilation units
 struct ProcessingStats {
     total_line_rows: usize,
@@ -109,6 +181,7 @@ fn process_dwarf_entry(
     unit: &gimli::Unit<gimli::EndianRcSlice<gimli::RunTimeEndian>>,
     info: &mut ObjectInfo,
     unit_file_name: &CanonicalPath,
+    file_map: &mut HashMap<u64, u32>,
     stats: &mut ProcessingStats,
 ) -> Result<()> {
     match entry.tag() {
@@ -135,7 +208,8 @@ fn process_dwarf_entry(
                 }
             }
 
-            let name = demangle(raw_name_opt);
+            let raw_name = raw_name_opt.unwrap_or_else(|| "unknown".to_string());
+            let name = demangle(&raw_name, info.demangle_style);
 
             // 2. Extract Address Range
             let mut low_opt = None;
@@ -180,10 +254,15 @@ fn process_dwarf_entry(
 
                     info.dwarf_symbols.insert(Symbol {
                         name,
+                        raw_name,
                         address: low,
                         size,
                         kind: SymbolType::Function,
                         scope: SymbolScope::Global,
+                        section: None,
+                        decl_file: None,
+                        decl_line: None,
+                        type_offset: None,
                     });
                 }
             }
@@ -212,11 +291,31 @@ fn process_dwarf_entry(
                 }
             }
 
-            let name = demangle(raw_name_opt);
+            let raw_name = raw_name_opt.unwrap_or_else(|| "unknown".to_string());
+            let name = demangle(&raw_name, info.demangle_style);
 
             // Lookup by name in ELF symbols (avoids expensive DWARF expression evaluation)
-            if let Some(existing_sym) = info.elf_symbols.get_by_name(&name) {
-                let arc_sym = info.dwarf_symbols.insert(existing_sym.clone());
+            if let Some(existing_sym) = info.elf_symbols.get_by_name(&raw_name) {
+                let mut sym = existing_sym.clone();
+                if let Some(gimli::AttributeValue::FileIndex(ix)) =
+                    entry.attr_value(gimli::DW_AT_decl_file)?
+                {
+                    sym.decl_file =
+                        resolve_file_index(dwarf, unit, ix, file_map, &mut info.file_table);
+                }
+                sym.decl_line = entry
+                    .attr_value(gimli::DW_AT_decl_line)?
+                    .and_then(|v| v.udata_value())
+                    .map(|l| l as u32);
+                if let Some(gimli::AttributeValue::UnitRef(offset)) =
+                    entry.attr_value(gimli::DW_AT_type)?
+                {
+                    // The type itself is only resolved if the variable is sampled
+                    sym.type_offset = offset
+                        .to_debug_info_offset(&unit.header)
+                        .map(|o| o.0 as u64);
+                }
+                let arc_sym = info.dwarf_symbols.insert(sym);
                 if arc_sym.kind == SymbolType::Data {
                     if arc_sym.scope == SymbolScope::Static {
                         info.static_file_mapping.insert(unit_file_name, arc_sym);
@@ -248,23 +347,64 @@ fn process_dwarf_entry(
     Ok(())
 }
 
-fn load_elf_info(path: &str, transport: &mut impl Transport, timing: bool) -> Result<ObjectInfo> {
+/// The DWARF sections of `obj_file`, decompressed where needed
+pub(crate) fn load_dwarf(
+    obj_file: &object::File,
+) -> Result<gimli::Dwarf<gimli::EndianRcSlice<gimli::RunTimeEndian>>> {
+    let load_section =
+        |id: gimli::SectionId| -> Result<gimli::EndianRcSlice<gimli::RunTimeEndian>> {
+            let data = obj_file
+                .section_by_name(id.name())
+                .map(|s| s.uncompressed_data().unwrap_or_default())
+                .unwrap_or_default();
+
+            let data_rc: Rc<[u8]> = match data {
+                Cow::Borrowed(b) => Rc::from(b),
+                Cow::Owned(o) => Rc::from(o),
+            };
+            Ok(gimli::EndianRcSlice::new(
+                data_rc,
+                gimli::RunTimeEndian::Little,
+            ))
+        };
+    gimli::Dwarf::load(&load_section)
+}
+
+/// Load symbols, sections and DWARF line info from `path`. `readiness`, when given, receives
+/// load progress and notifications for things found along the way (currently the RTT control
+/// block); the offline `mdbg elf` commands pass `None`. File paths are rewritten with
+/// `source_map` as they are interned, and symbol names are shown in `demangle_style`.
+pub(crate) fn load_elf_info(
+    path: &str,
+    readiness: Option<&Readiness>,
+    timing: bool,
+    source_map: SourceMap,
+    demangle_style: DemangleStyle,
+) -> Result<ObjectInfo> {
+    load_elf_info_with(path, readiness, timing, source_map, demangle_style, |_| {})
+}
+
+/// `load_elf_info`, calling `on_parsed` with the object as soon as the file has been parsed
+fn load_elf_info_with(
+    path: &str,
+    readiness: Option<&Readiness>,
+    timing: bool,
+    source_map: SourceMap,
+    demangle_style: DemangleStyle,
+    on_parsed: impl FnOnce(&object::File),
+) -> Result<ObjectInfo> {
     let start = Instant::now();
-    let file_result = fs::File::open(path);
-    let file = match file_result {
-        Ok(f) => f,
-        Err(e) => {
-            eprintln!("Error opening ELF file '{}': {}", path, e);
-            exit(1);
-        }
-    };
-    let mmap = unsafe { memmap2::Mmap::map(&file)? };
-    let obj_file = object::File::parse(&*mmap)?;
+    let file = fs::File::open(path).with_context(|| format!("Opening ELF file '{}'", path))?;
+    let mmap = unsafe { memmap2::Mmap::map(&file) }.with_context(|| format!("Mapping ELF file '{}'", path))?;
+    let obj_file = object::File::parse(&*mmap).with_context(|| format!("Parsing ELF file '{}'", path))?;
+    on_parsed(&obj_file);
     if timing {
         eprintln!("  ⏱️  File open + mmap + parse: {:.2?}", start.elapsed());
     }
 
     let mut info = ObjectInfo::new();
+    info.file_table.set_source_map(source_map);
+    info.demangle_style = demangle_style;
 
     let step = Instant::now();
     for section in obj_file.sections() {
@@ -277,6 +417,9 @@ fn load_elf_info(path: &str, transport: &mut impl Transport, timing: bool) -> Re
             ));
         }
     }
+    info.layout = ElfLayout::from_object(&obj_file, &mmap);
+    info.elf_source = Some(ElfSource::new(PathBuf::from(path), &mmap));
+    info.unwind_tables = UnwindTables::from_object(&obj_file);
     if timing {
         eprintln!("  ⏱️  Process sections: {:.2?}", step.elapsed());
     }
@@ -299,21 +442,31 @@ fn load_elf_info(path: &str, transport: &mut impl Transport, timing: bool) -> Re
                 SymbolScope::Unknown
             };
             let is_data = kind == SymbolType::Data;
-            let dname = demangle(Some(name.to_string()));
+            let dname = demangle(name, info.demangle_style);
+            let section = symbol
+                .section_index()
+                .and_then(|ix| obj_file.section_by_index(ix).ok())
+                .and_then(|sec| sec.name().ok().map(str::to_string));
             info.elf_symbols.insert(Symbol {
                 name: dname.clone(),
+                raw_name: name.to_string(),
                 address: symbol.address(),
                 size: symbol.size(),
                 kind,
                 scope,
+                section,
+                decl_file: None,
+                decl_line: None,
+                type_offset: None,
             });
-            if (dname == "_SEGGER_RTT" || dname == "SEGGER_RTT") && is_data {
+            if (name == "_SEGGER_RTT" || name == "SEGGER_RTT") && is_data {
                 info.rtt_symbol_address = Some(symbol.address());
-                let notify =
-                    rtt_found_notification("local-session", &format!("0x{:x}", symbol.address()));
-                transport
-                    .write_message(&notify)
-                    .map_err(|e| anyhow::anyhow!("{}", e))?;
+                if let Some(readiness) = readiness {
+                    readiness.notify(&rtt_found_notification(
+                        "local-session",
+                        &format!("0x{:x}", symbol.address()),
+                    ));
+                }
                 eprintln!(
                     "Found RTT symbol '{}' at address 0x{:x}",
                     dname,
@@ -328,29 +481,9 @@ fn load_elf_info(path: &str, transport: &mut impl Transport, timing: bool) -> Re
 
     // Load DWARF sections
     let step = Instant::now();
-    let load_section =
-        |id: gimli::SectionId| -> Result<gimli::EndianRcSlice<gimli::RunTimeEndian>> {
-            let data = obj_file
-                .section_by_name(id.name())
-                .map(|s| {
-                    use object::ObjectSection;
-                    s.uncompressed_data().unwrap_or_default()
-                })
-                .unwrap_or_default();
-
-            let data_rc: Rc<[u8]> = match data {
-                Cow::Borrowed(b) => Rc::from(b),
-                Cow::Owned(o) => Rc::from(o),
-            };
-            Ok(gimli::EndianRcSlice::new(
-                data_rc,
-                gimli::RunTimeEndian::Little,
-            ))
-        };
-
     // If DWARF loading fails, we might still want to return symbols if possible,
     // but for now we propagate the error.
-    let dwarf = gimli::Dwarf::load(&load_section)?;
+    let dwarf = load_dwarf(&obj_file)?;
     if timing {
         eprintln!("  ⏱️  Load DWARF sections: {:.2?}", step.elapsed());
     }
@@ -360,8 +493,15 @@ fn load_elf_info(path: &str, transport: &mut impl Transport, timing: bool) -> Re
     let mut units = dwarf.units();
     let mut unit_count = 0;
     let mut stats = ProcessingStats::new();
+    // Progress is measured by how far into .debug_info the current unit starts
+    let debug_info_len = gimli::Section::reader(&dwarf.debug_info).len() as u64;
     while let Some(header) = units.next()? {
         unit_count += 1;
+        if let (Some(readiness), Some(offset)) =
+            (readiness, header.offset().as_debug_info_offset())
+        {
+            readiness.progress(Capability::Symbols, offset.0 as u64, debug_info_len);
+        }
         let unit = dwarf.unit(header)?;
 
         let mut unit_file_name = unit
@@ -379,7 +519,8 @@ fn load_elf_info(path: &str, transport: &mut impl Transport, timing: bool) -> Re
                 }
             }
         }
-        let canonical_unit_file_name: CanonicalPath = CanonicalPath::new(&unit_file_name);
+        let canonical_unit_file_name: CanonicalPath =
+            info.file_table.local_path(&unit_file_name);
 
         if timing {
             eprintln!(
@@ -390,7 +531,7 @@ fn load_elf_info(path: &str, transport: &mut impl Transport, timing: bool) -> Re
 
         // Mapping from CU-local file index to Global File ID
         // Shared between line program processing and symbol extraction
-        let mut file_map: std::collections::HashMap<u64, u32> = std::collections::HashMap::new();
+        let mut file_map: HashMap<u64, u32> = HashMap::new();
 
         // Process line program if present
         let line_start = Instant::now();
@@ -399,40 +540,21 @@ fn load_elf_info(path: &str, transport: &mut impl Transport, timing: bool) -> Re
 
             // Rows
             let mut rows = program.rows();
-            while let Some((header, row)) = rows.next_row()? {
+            while let Some((_, row)) = rows.next_row()? {
                 stats.total_line_rows += 1;
                 if row.is_stmt() {
                     if let Some(line) = row.line() {
                         let local_file_idx = row.file_index();
 
                         // Resolve file path lazy-ish
-                        let global_id = *file_map.entry(local_file_idx).or_insert_with(|| {
-                            if let Some(fe) = header.file(local_file_idx) {
-                                let mut p = String::new();
-                                let dir_idx = fe.directory_index();
-
-                                // Get directory path
-                                if let Some(dir_attr) = header.directory(dir_idx) {
-                                    if let Some(dir_str) =
-                                        dwarf_attr_to_string(&dwarf, &unit, dir_attr)
-                                    {
-                                        p.push_str(&dir_str);
-                                        p.push('/');
-                                    }
-                                }
-
-                                // Get file name
-                                if let Some(file_str) =
-                                    dwarf_attr_to_string(&dwarf, &unit, fe.path_name())
-                                {
-                                    p.push_str(&file_str);
-                                }
-
-                                info.file_table.intern(p)
-                            } else {
-                                0 // Unknown
-                            }
-                        });
+                        let global_id = resolve_file_index(
+                            &dwarf,
+                            &unit,
+                            local_file_idx,
+                            &mut file_map,
+                            &mut info.file_table,
+                        )
+                        .unwrap_or(0); // Unknown
 
                         info.addr_to_line
                             .append_or_insert(row.address(), global_id, line);
@@ -460,6 +582,7 @@ fn load_elf_info(path: &str, transport: &mut impl Transport, timing: bool) -> Re
                         &unit,
                         &mut info,
                         &canonical_unit_file_name,
+                        &mut file_map,
                         &mut stats,
                     )?;
                     first_entry_found = true;
@@ -479,6 +602,7 @@ fn load_elf_info(path: &str, transport: &mut impl Transport, timing: bool) -> Re
                     &unit,
                     &mut info,
                     &canonical_unit_file_name,
+                    &mut file_map,
                     &mut stats,
                 )?;
             }
@@ -513,109 +637,223 @@ fn load_elf_info(path: &str, transport: &mut impl Transport, timing: bool) -> Re
     Ok(info)
 }
 
-fn demangle(raw_name_opt: Option<String>) -> String {
-    let mut name = "unknown".to_string();
-    if let Some(raw_name) = raw_name_opt {
-        // DEMANGLE
-        // 1. Try Rust
-        let rust_demangled = rustc_demangle::demangle(&raw_name).to_string();
-        if rust_demangled != raw_name {
-            name = rust_demangled;
-        } else {
-            // 2. Try C++
-            name = raw_name.clone(); // Default to raw
-            if let Ok(sym) = cpp_demangle::Symbol::new(raw_name.as_bytes()) {
-                // cpp_demangle 0.5.1 does not take options in demangle() directly
-                if let Ok(d) = sym.demangle() {
-                    name = d;
-                }
+/// What the ELF loader hands the disassembly worker
+struct WorkerFeed {
+    /// The size of the code objdump will disassemble, as soon as the ELF is parsed
+    text_bytes: Sender<u64>,
+    /// The loaded symbols
+    obj_info: Sender<Arc<ObjectInfo>>,
+}
+
+/// Load the ELF symbols for the session and hand them to the worker. Runs on its own thread
+/// so requests keep being read (and queued) while it works.
+fn load_session_symbols(
+    path: &str,
+    readiness: &Readiness,
+    timing: bool,
+    source_map: SourceMap,
+    demangle_style: DemangleStyle,
+    worker: WorkerFeed,
+    now: Instant,
+) -> Option<Arc<ObjectInfo>> {
+    // The worker's progress total comes from the object parsed here rather than a second parse
+    let send_text_bytes = |obj_file: &object::File| {
+        let _ = worker.text_bytes.send(executable_bytes(obj_file));
+    };
+    let mut obj_info_data =
+        match load_elf_info_with(path, Some(readiness), timing, source_map, demangle_style, send_text_bytes) {
+            Ok(info) => info,
+            Err(e) => {
+                readiness.set_failed(Capability::Symbols, &format!("{:#}", e));
+                return None;
             }
-        }
+        };
+    if timing {
+        eprintln!(
+            "Loaded ELF info for: {} (elapsed: {:.2?})",
+            path,
+            now.elapsed()
+        );
+    }
+
+    let sort_start = Instant::now();
+    obj_info_data.sort_globals_and_statics(); // Sort symbols once so clients don't have to sort repeatedly
+    if timing {
+        eprintln!(
+            "  ⏱️  Sort globals and statics: {:.2?}",
+            sort_start.elapsed()
+        );
+    }
+
+    let obj_info = Arc::new(obj_info_data); // Now immutable and shareable across threads
+
+    // Send ObjectInfo to worker (Arc makes it cheap to send)
+    if worker.obj_info.send(Arc::clone(&obj_info)).is_err() {
+        eprintln!("Warning: Worker exited before receiving ObjectInfo");
+    }
+
+    // Notify DA that symbol table is ready
+    readiness.set_ready(Capability::Symbols);
+    readiness.notify(&protocol::symbol_table_ready_notification(
+        "local-session",
+        env!("CARGO_PKG_VERSION"),
+    ));
+    eprintln!(
+        "Sent SymbolTableReady notification to DA (elapsed: {:.2?})",
+        now.elapsed()
+    );
+    Some(obj_info)
+}
+
+/// Where this server's discovery record goes: `--state-file`, or one derived from the ELF path
+fn state_file_path(args: &DaHelperArgs) -> Result<PathBuf> {
+    match &args.state_file {
+        Some(path) => Ok(path.clone()),
+        None => server::default_state_file(&args.elf_files[0]),
     }
-    name
 }
 
 pub fn run(args: DaHelperArgs) -> Result<()> {
     // Initialize global debug flag
     debug::set_debug(args.debug);
 
+    if args.find {
+        let endpoint = server::find_server(&state_file_path(&args)?)?;
+        println!("{}", serde_json::to_string(&endpoint)?);
+        return Ok(());
+    }
+
     // TODO: Support multiple ELF files - for now just use the first one
     let path = args.elf_files[0].clone();
 
-    // Setup transport (uses stdout's built-in locking)
-    let mut transport = StdioTransport::new();
+    let source_map = SourceMap::from_specs(&args.source_map)?;
 
-    // Create channels: request dispatch + ObjectInfo delivery to worker
+    // Create channels: request dispatch + ObjectInfo delivery to worker + session events
     let (req_tx, req_rx) = channel();
     let (obj_info_tx, obj_info_rx) = channel();
+    let (text_bytes_tx, text_bytes_rx) = channel();
+    let (event_tx, event_rx) = channel();
+    let broadcast = Broadcast::new();
+    let cancels = Arc::new(CancelSet::new());
+    let readiness = Readiness::new(
+        "local-session",
+        Outbox::new(Box::new(Arc::clone(&broadcast))),
+    );
     let now = Instant::now();
 
+    // Clients must be attached before loading starts, or they would miss its progress events
+    let _state_guard = match &args.listen {
+        None => {
+            let sink: Arc<dyn ResponseSink> = Arc::new(StdoutSink::default());
+            broadcast.add_client(0, Arc::clone(&sink));
+            let _ = event_tx.send(SessionEvent::Connected(0, sink));
+            server::spawn_reader(0, Box::new(BufReader::new(io::stdin())), event_tx.clone());
+            None
+        }
+        Some(spec) => {
+            let listener = Listener::bind(&ListenAddr::parse(spec)?)?;
+            let address = listener.address()?;
+            let endpoint = HelperEndpoint::new(&path, &address);
+            let state_file = state_file_path(&args)?;
+            server::write_state_file(&state_file, &endpoint)?;
+            eprintln!(
+                "Listening on {} (state file {})",
+                endpoint.address,
+                state_file.display()
+            );
+            // The launcher reads this line to learn the real address
+            println!("{}", serde_json::to_string(&endpoint)?);
+            server::spawn_acceptor(listener, Arc::clone(&broadcast), event_tx.clone());
+            // A server has no stdin to close; a signal is how it is told to stop
+            server::spawn_shutdown_watcher(event_tx.clone());
+            Some(StateFileGuard::new(state_file).with_socket(&address))
+        }
+    };
+
     // Spawn disassembly worker immediately (loads objdump in parallel)
     let path_clone = path.clone();
     let objdump_path_clone = args.objdump_path.clone();
+    let worker_cancels = Arc::clone(&cancels);
+    let worker_readiness = Arc::clone(&readiness);
     thread::spawn(move || {
         disasm_worker::run_disassembly_worker(
             &objdump_path_clone,
             &path_clone,
             req_rx,
             obj_info_rx,
+            text_bytes_rx,
+            worker_cancels,
+            worker_readiness,
         );
     });
     if args.timing {
         eprintln!("Started reading ${} (elapsed: {:.2?})", path, now.elapsed());
     }
-    // Load ELF info in parallel with worker's disassembly loading
-    let mut obj_info_data = load_elf_info(&path, &mut transport, args.timing)?;
-    if args.timing {
-        eprintln!(
-            "Loaded ELF info for: {} (elapsed: {:.2?})",
-            path,
-            now.elapsed()
-        );
-    }
 
-    let sort_start = Instant::now();
-    obj_info_data.sort_globals_and_statics(); // Sort symbols once so clients don't have to sort repeatedly
-    if args.timing {
-        eprintln!(
-            "  ⏱️  Sort globals and statics: {:.2?}",
-            sort_start.elapsed()
+    // Load ELF info in parallel with worker's disassembly loading
+    let loader_readiness = Arc::clone(&readiness);
+    let timing = args.timing;
+    let demangle_style = args.demangle;
+    thread::spawn(move || {
+        let obj_info = load_session_symbols(
+            &path,
+            &loader_readiness,
+            timing,
+            source_map,
+            demangle_style,
+            WorkerFeed {
+                text_bytes: text_bytes_tx,
+                obj_info: obj_info_tx,
+            },
+            now,
         );
-    }
+        let _ = event_tx.send(SessionEvent::SymbolsLoaded(obj_info));
+    });
 
-    let obj_info = Arc::new(obj_info_data); // Now immutable and shareable across threads
+    // Serve requests until the DA's stdin closes (a server runs until SIGINT/SIGTERM),
+    // then let `_state_guard` remove the state file and socket
+    let session = Session {
+        req_tx,
+        cancels,
+        readiness,
+        broadcast,
+    };
+    server::run_session(session, event_rx, args.listen.is_none());
 
-    // Send ObjectInfo to worker (Arc makes it cheap to send)
-    if obj_info_tx.send(Arc::clone(&obj_info)).is_err() {
-        eprintln!("Warning: Worker exited before receiving ObjectInfo");
-    }
+    Ok(())
+}
 
-    // Notify DA that symbol table is ready
-    let notify =
-        protocol::symbol_table_ready_notification("local-session", env!("CARGO_PKG_VERSION"));
-    transport
-        .write_message(&notify)
-        .map_err(|e| anyhow::anyhow!("{}", e))?;
-    eprintln!(
-        "Sent SymbolTableReady notification to DA (elapsed: {:.2?})",
-        now.elapsed()
-    );
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::da_helper::readiness::LoadState;
+    use crate::da_helper::responder::FakeTransport;
+
+    #[test]
+    fn an_elf_that_cannot_be_opened_fails_the_symbols_load() {
+        let transport = FakeTransport::default();
+        let readiness = Readiness::new("s", transport.outbox());
+        let (obj_info_tx, obj_info_rx) = channel();
+        let (text_bytes_tx, text_bytes_rx) = channel();
+        let obj_info = load_session_symbols(
+            "/nonexistent/app.elf",
+            &readiness,
+            false,
+            SourceMap::default(),
+            DemangleStyle::default(),
+            WorkerFeed {
+                text_bytes: text_bytes_tx,
+                obj_info: obj_info_tx,
+            },
+            Instant::now(),
+        );
 
-    // Main request loop
-    loop {
-        match transport.read_message() {
-            Ok(msg) => {
-                eprintln!("Received request: {}", msg);
-                if !request_handler::dispatch_request(&msg, &req_tx, Arc::clone(&obj_info)) {
-                    eprintln!("Unknown request type: {}", msg);
-                }
-            }
-            Err(e) => {
-                eprintln!("Transport read error or EOF: {}", e);
-                break;
-            }
-        }
+        assert!(obj_info.is_none());
+        assert!(obj_info_rx.recv().is_err());
+        assert!(text_bytes_rx.recv().is_err());
+        let LoadState::Failed(reason) = readiness.state(Capability::Symbols) else {
+            panic!("symbols load did not fail");
+        };
+        assert!(reason.starts_with("Opening ELF file '/nonexistent/app.elf'"), "{}", reason);
     }
-
-    Ok(())
 }
@@ -0,0 +1,446 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Live memory sampling over a direct connection to the gdb-server, next to GDB's own (see the
+//! proxy's `DuplicateStream`). Each item is read at its own rate; items due together are read
+//! with as few `m` packets as possible, and only values that changed are reported.
+//!
+//! Sampling must not starve GDB of the probe. The time spent reading is kept to a fraction of
+//! the wall clock: when reads take longer than that, every interval is stretched (doubling, up to
+//! `MAX_SLOWDOWN`), and shrunk back once the probe keeps up again. Failed reads back off the same way.
+
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::da_helper::value_type::ValueType;
+use crate::rsp::client::{Client, RspResult};
+
+/// Largest factor intervals are stretched by when the probe can't keep up
+pub const MAX_SLOWDOWN: u32 = 64;
+
+/// Reads in a row that may fail before sampling stops
+pub const MAX_CONSECUTIVE_FAILURES: u32 = 8;
+
+/// Longest sleep between checks for a stop request
+const STOP_POLL: Duration = Duration::from_millis(50);
+
+/// Where samples are read from: in practice an RSP client, in tests a fake
+pub trait SampleSource {
+    /// Read up to `len` bytes; fewer means the rest isn't readable
+    fn read(&mut self, addr: u64, len: usize) -> RspResult<Vec<u8>>;
+}
+
+impl<R: Read, W: Write> SampleSource for Client<R, W> {
+    fn read(&mut self, addr: u64, len: usize) -> RspResult<Vec<u8>> {
+        self.read_memory(addr, len)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SampleItem {
+    /// Chosen by the client, echoed in every sample
+    pub id: String,
+    pub address: u64,
+    pub size: usize,
+    /// How to show the value; None shows hex
+    pub value_type: Option<ValueType>,
+    pub interval: Duration,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Budget {
+    /// Fraction of the time the link may spend on sampling reads
+    pub max_busy: f64,
+    /// Unused bytes that may be read to join two ranges into one read
+    pub max_gap: usize,
+    /// Largest single read
+    pub max_read: usize,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self {
+            max_busy: 0.25,
+            max_gap: 32,
+            max_read: 1024,
+        }
+    }
+}
+
+/// One read covering the ranges of `members` (indexes into the ranges given to `coalesce`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadSpan {
+    pub address: u64,
+    pub len: usize,
+    pub members: Vec<usize>,
+}
+
+/// Merge (address, size) ranges into reads. Ranges that overlap, or are at most `max_gap` bytes
+/// apart, share a read unless it would grow beyond `max_read`. A range bigger than `max_read`
+/// still gets a read of its own.
+pub fn coalesce(ranges: &[(u64, usize)], max_gap: usize, max_read: usize) -> Vec<ReadSpan> {
+    let mut order: Vec<usize> = (0..ranges.len()).collect();
+    order.sort_by_key(|&ix| ranges[ix]);
+    let mut spans: Vec<ReadSpan> = Vec::new();
+    for ix in order {
+        let (address, size) = ranges[ix];
+        let end = address + size as u64;
+        if let Some(span) = spans.last_mut() {
+            let span_end = span.address + span.len as u64;
+            let new_len = (end.max(span_end) - span.address) as usize;
+            if address <= span_end + max_gap as u64 && new_len <= max_read {
+                span.len = new_len;
+                span.members.push(ix);
+                continue;
+            }
+        }
+        spans.push(ReadSpan {
+            address,
+            len: size,
+            members: vec![ix],
+        });
+    }
+    spans
+}
+
+/// A value that changed (or was read for the first time)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub id: String,
+    /// The value formatted by its type, or hex
+    pub value: String,
+    pub bytes: Vec<u8>,
+    /// When the read that produced it finished
+    pub time: Instant,
+}
+
+/// What one round of reads produced
+#[derive(Debug, Default)]
+pub struct Poll {
+    pub samples: Vec<Sample>,
+    /// The first read that failed, if any
+    pub error: Option<String>,
+}
+
+struct ItemState {
+    item: SampleItem,
+    next_due: Instant,
+    last: Option<Vec<u8>>,
+}
+
+pub struct Sampler {
+    items: Vec<ItemState>,
+    budget: Budget,
+    slowdown: u32,
+    failures: u32,
+}
+
+impl Sampler {
+    /// Every item is due straight away
+    pub fn new(items: Vec<SampleItem>, budget: Budget, now: Instant) -> Self {
+        Self {
+            items: items
+                .into_iter()
+                .map(|item| ItemState {
+                    item,
+                    next_due: now,
+                    last: None,
+                })
+                .collect(),
+            budget,
+            slowdown: 1,
+            failures: 0,
+        }
+    }
+
+    /// Factor every interval is currently stretched by
+    pub fn slowdown(&self) -> u32 {
+        self.slowdown
+    }
+
+    /// Reads that failed in a row
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// When the next item is due, None if there are no items
+    pub fn next_due(&self) -> Option<Instant> {
+        self.items.iter().map(|s| s.next_due).min()
+    }
+
+    /// Read every item due at `now` and report those whose bytes changed
+    pub fn poll(&mut self, now: Instant, source: &mut dyn SampleSource) -> Poll {
+        let due: Vec<usize> = (0..self.items.len()).filter(|&ix| self.items[ix].next_due <= now).collect();
+        let mut poll = Poll::default();
+        if due.is_empty() {
+            return poll;
+        }
+        let ranges: Vec<(u64, usize)> = due
+            .iter()
+            .map(|&ix| (self.items[ix].item.address, self.items[ix].item.size))
+            .collect();
+        let started = Instant::now();
+        for span in coalesce(&ranges, self.budget.max_gap, self.budget.max_read) {
+            let bytes = match source.read(span.address, span.len) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    poll.error.get_or_insert_with(|| {
+                        format!("Reading {} bytes at 0x{:08x}: {}", span.len, span.address, e)
+                    });
+                    continue;
+                }
+            };
+            let read_at = Instant::now();
+            for member in span.members {
+                let state = &mut self.items[due[member]];
+                let from = (state.item.address - span.address) as usize;
+                // Items the read came up short for are left out until they can be read
+                let Some(value) = bytes.get(from..from + state.item.size) else {
+                    continue;
+                };
+                if state.last.as_deref() != Some(value) {
+                    poll.samples.push(Sample {
+                        id: state.item.id.clone(),
+                        value: match &state.item.value_type {
+                            Some(t) => t.format(value),
+                            None => value.iter().map(|b| format!("{:02x}", b)).collect(),
+                        },
+                        bytes: value.to_vec(),
+                        time: read_at,
+                    });
+                    state.last = Some(value.to_vec());
+                }
+            }
+        }
+        self.adjust(&due, started.elapsed(), poll.error.is_some());
+        for &ix in &due {
+            let state = &mut self.items[ix];
+            state.next_due = now + state.item.interval * self.slowdown;
+        }
+        poll
+    }
+
+    /// Stretch or relax the intervals after a round that took `busy` for the items in `due`
+    fn adjust(&mut self, due: &[usize], busy: Duration, failed: bool) {
+        if failed {
+            self.failures += 1;
+            self.slowdown = (self.slowdown * 2).min(MAX_SLOWDOWN);
+            return;
+        }
+        self.failures = 0;
+        let Some(shortest) = due.iter().map(|&ix| self.items[ix].item.interval).min() else {
+            return;
+        };
+        let allowed = (shortest * self.slowdown).mul_f64(self.budget.max_busy);
+        if busy > allowed {
+            self.slowdown = (self.slowdown * 2).min(MAX_SLOWDOWN);
+        } else if busy * 4 < allowed && self.slowdown > 1 {
+            // Well within budget even at half the stretch
+            self.slowdown /= 2;
+        }
+    }
+
+    /// Poll until `stop` is set or reads keep failing. Changes go to `emit` together with the
+    /// current slowdown; a persistent failure is returned as the error.
+    pub fn run(
+        &mut self,
+        source: &mut dyn SampleSource,
+        stop: &AtomicBool,
+        emit: &mut dyn FnMut(Vec<Sample>, u32),
+    ) -> Result<(), String> {
+        while !stop.load(Ordering::Relaxed) {
+            let Some(due) = self.next_due() else {
+                return Ok(());
+            };
+            let now = Instant::now();
+            if due > now {
+                std::thread::sleep((due - now).min(STOP_POLL));
+                continue;
+            }
+            let poll = self.poll(now, source);
+            if !poll.samples.is_empty() {
+                emit(poll.samples, self.slowdown);
+            }
+            if let Some(error) = poll.error {
+                if self.failures >= MAX_CONSECUTIVE_FAILURES {
+                    return Err(error);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A sampler running on a thread of its own. Dropping the handle stops it.
+pub struct SamplerHandle {
+    stop: Arc<AtomicBool>,
+}
+
+impl SamplerHandle {
+    /// Open a source with `connect` and run `sampler` against it until the handle is dropped,
+    /// both on the sampler's thread. `on_error` is told if the source could not be opened or the
+    /// sampler gave up because reads kept failing.
+    pub fn spawn<S: SampleSource>(
+        mut sampler: Sampler,
+        connect: impl FnOnce() -> Result<S, String> + Send + 'static,
+        mut emit: impl FnMut(Vec<Sample>, u32) + Send + 'static,
+        on_error: impl FnOnce(String) + Send + 'static,
+    ) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            let result = connect().and_then(|mut source| sampler.run(&mut source, &thread_stop, &mut emit));
+            if let Err(e) = result {
+                on_error(e);
+            }
+        });
+        Self { stop }
+    }
+}
+
+impl Drop for SamplerHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsp::client::RspError;
+
+    fn item(id: &str, address: u64, size: usize, interval_ms: u64) -> SampleItem {
+        SampleItem {
+            id: id.to_string(),
+            address,
+            size,
+            value_type: None,
+            interval: Duration::from_millis(interval_ms),
+        }
+    }
+
+    /// 256 bytes of RAM at 0x2000_0000; records each read
+    struct FakeRam {
+        memory: Vec<u8>,
+        reads: Vec<(u64, usize)>,
+        delay: Duration,
+        fail: bool,
+    }
+
+    impl FakeRam {
+        fn new() -> Self {
+            Self {
+                memory: (0..=255).collect(),
+                reads: Vec::new(),
+                delay: Duration::ZERO,
+                fail: false,
+            }
+        }
+    }
+
+    impl SampleSource for FakeRam {
+        fn read(&mut self, addr: u64, len: usize) -> RspResult<Vec<u8>> {
+            self.reads.push((addr, len));
+            std::thread::sleep(self.delay);
+            if self.fail {
+                return Err(RspError::Closed);
+            }
+            let from = (addr - 0x2000_0000) as usize;
+            Ok(self.memory[from..(from + len).min(self.memory.len())].to_vec())
+        }
+    }
+
+    #[test]
+    fn coalesces_nearby_ranges() {
+        let spans = coalesce(&[(0x100, 4), (0x2000, 4), (0x108, 2), (0x104, 4)], 8, 64);
+        assert_eq!(
+            spans,
+            vec![
+                ReadSpan {
+                    address: 0x100,
+                    len: 10,
+                    members: vec![0, 3, 2],
+                },
+                ReadSpan {
+                    address: 0x2000,
+                    len: 4,
+                    members: vec![1],
+                },
+            ]
+        );
+        // Too far apart, or too big together
+        assert_eq!(coalesce(&[(0x100, 4), (0x120, 4)], 8, 64).len(), 2);
+        assert_eq!(coalesce(&[(0x100, 40), (0x128, 40)], 8, 64).len(), 2);
+    }
+
+    #[test]
+    fn reports_changes_at_each_items_rate() {
+        let start = Instant::now();
+        let mut sampler = Sampler::new(
+            vec![item("fast", 0x2000_0000, 4, 10), item("slow", 0x2000_0008, 2, 100)],
+            Budget::default(),
+            start,
+        );
+        let mut ram = FakeRam::new();
+
+        let first = sampler.poll(start, &mut ram);
+        assert_eq!(ram.reads, vec![(0x2000_0000, 10)]);
+        assert_eq!(first.samples.len(), 2);
+        assert_eq!(first.samples[1].value, "0809");
+
+        // Only the fast item is due, and it hasn't changed
+        let next = sampler.next_due().unwrap();
+        assert_eq!(next, start + Duration::from_millis(10));
+        assert!(sampler.poll(next, &mut ram).samples.is_empty());
+        assert_eq!(ram.reads[1], (0x2000_0000, 4));
+
+        ram.memory[1] = 0xff;
+        let changed = sampler.poll(next + Duration::from_millis(10), &mut ram);
+        assert_eq!(changed.samples.len(), 1);
+        assert_eq!(changed.samples[0].id, "fast");
+        assert_eq!(changed.samples[0].bytes, vec![0, 0xff, 2, 3]);
+
+        // Past the end of memory: no value until it can be read
+        let mut beyond = Sampler::new(vec![item("edge", 0x2000_00fe, 4, 10)], Budget::default(), start);
+        let poll = beyond.poll(start, &mut ram);
+        assert!(poll.samples.is_empty() && poll.error.is_none());
+    }
+
+    #[test]
+    fn backs_off_when_the_probe_is_slow_or_failing() {
+        let start = Instant::now();
+        let budget = Budget {
+            max_busy: 0.5,
+            ..Budget::default()
+        };
+        let mut sampler = Sampler::new(vec![item("x", 0x2000_0000, 4, 4)], budget, start);
+        let mut ram = FakeRam::new();
+        ram.delay = Duration::from_millis(5);
+        sampler.poll(start, &mut ram);
+        assert_eq!(sampler.slowdown(), 2);
+        assert_eq!(sampler.next_due(), Some(start + Duration::from_millis(8)));
+
+        ram.delay = Duration::ZERO;
+        sampler.poll(start + Duration::from_millis(8), &mut ram);
+        assert_eq!(sampler.slowdown(), 1);
+
+        ram.fail = true;
+        let stop = AtomicBool::new(false);
+        let error = sampler.run(&mut ram, &stop, &mut |_, _| {}).unwrap_err();
+        assert!(error.contains("0x20000000"), "{}", error);
+        assert_eq!(sampler.failures(), MAX_CONSECUTIVE_FAILURES);
+    }
+}
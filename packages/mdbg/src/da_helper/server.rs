@@ -0,0 +1,690 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Client sessions for the da-helper. Normally the only client is the DA on stdio; with
+//! `--listen` the helper instead accepts any number of clients over TCP or a Unix socket,
+//! all served from the same `ObjectInfo` and disassembly worker.
+//!
+//! Each client gets its own `Dispatcher` and `Outbox`, so seqs and cancels are per client.
+//! Notifications go to everyone through `Broadcast`. A server records where it listens in a
+//! state file (by default `~/.mcu-debug/da-helper/<hash of ELF path>.json`), so other tools
+//! inspecting the same ELF can find it instead of starting their own helper:
+//!
+//! ```json
+//! { "v": 1, "pid": 4242, "version": "0.1.11", "elf": "/work/app.elf",
+//!   "address": "tcp:127.0.0.1:40123", "started_at_unix": 1760000000 }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::common::framing::FrameError;
+use crate::common::transport::{read_framed_json, LockedWriter};
+use crate::da_helper::elf_items::ObjectInfo;
+use crate::da_helper::encoding::{FormatCell, WireFormat};
+use crate::da_helper::protocol::WorkerJob;
+use crate::da_helper::readiness::Readiness;
+use crate::da_helper::request_handler::Dispatcher;
+use crate::da_helper::responder::{Broadcast, CancelSet, Outbox, ResponseSink};
+
+/// How long `find_server` waits when checking that a recorded server is alive
+const PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Where a server listens: `HOST:PORT` (or `tcp:HOST:PORT`), or `unix:PATH`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenAddr {
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+impl ListenAddr {
+    pub fn parse(spec: &str) -> Result<Self> {
+        if let Some(path) = spec.strip_prefix("unix:") {
+            if path.is_empty() {
+                bail!("Listen address '{}' is missing the socket path", spec);
+            }
+            return Ok(ListenAddr::Unix(PathBuf::from(path)));
+        }
+        let addr = spec.strip_prefix("tcp:").unwrap_or(spec);
+        match addr.rsplit_once(':') {
+            Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {
+                Ok(ListenAddr::Tcp(addr.to_string()))
+            }
+            _ => bail!("Listen address '{}' must be HOST:PORT or unix:PATH", spec),
+        }
+    }
+
+    /// Open a client connection, e.g. to check that a server is alive
+    pub fn connect(&self, timeout: Duration) -> io::Result<()> {
+        match self {
+            ListenAddr::Tcp(addr) => {
+                let sock_addr = addr
+                    .to_socket_addrs()?
+                    .next()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "address did not resolve"))?;
+                TcpStream::connect_timeout(&sock_addr, timeout).map(|_| ())
+            }
+            #[cfg(unix)]
+            ListenAddr::Unix(path) => std::os::unix::net::UnixStream::connect(path).map(|_| ()),
+            #[cfg(not(unix))]
+            ListenAddr::Unix(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Unix sockets are not supported on this platform",
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ListenAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListenAddr::Tcp(addr) => write!(f, "tcp:{}", addr),
+            ListenAddr::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// Writes framed responses and notifications to one connected client
+pub struct StreamSink {
+    writer: LockedWriter<Box<dyn Write + Send>>,
+    format: FormatCell,
+}
+
+impl StreamSink {
+    pub fn new(writer: Box<dyn Write + Send>) -> Arc<Self> {
+        Arc::new(Self {
+            writer: LockedWriter::new(writer),
+            format: FormatCell::default(),
+        })
+    }
+}
+
+impl ResponseSink for StreamSink {
+    fn write_response(&self, msg: &Value) -> Result<(), FrameError> {
+        self.writer
+            .write_frame(&self.format.get().encode(self.writer.codec(), msg)?)
+    }
+
+    fn set_format(&self, format: WireFormat) {
+        self.format.set(format);
+    }
+}
+
+/// An accepted client: where to read its requests and where to write to it
+pub struct Connection {
+    pub reader: Box<dyn BufRead + Send>,
+    pub sink: Arc<StreamSink>,
+    pub peer: String,
+}
+
+pub enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixListener, PathBuf),
+}
+
+impl Listener {
+    pub fn bind(addr: &ListenAddr) -> Result<Self> {
+        match addr {
+            ListenAddr::Tcp(a) => {
+                let listener = TcpListener::bind(a).with_context(|| format!("could not listen on {}", a))?;
+                Ok(Listener::Tcp(listener))
+            }
+            #[cfg(unix)]
+            ListenAddr::Unix(path) => {
+                // A socket file left by a server that died would make bind fail
+                if path.exists() && addr.connect(PROBE_TIMEOUT).is_err() {
+                    let _ = std::fs::remove_file(path);
+                }
+                let listener = std::os::unix::net::UnixListener::bind(path)
+                    .with_context(|| format!("could not listen on {}", path.display()))?;
+                Ok(Listener::Unix(listener, path.clone()))
+            }
+            #[cfg(not(unix))]
+            ListenAddr::Unix(_) => bail!("Unix sockets are not supported on this platform"),
+        }
+    }
+
+    /// The address clients should connect to, with the real port if 0 was requested
+    pub fn address(&self) -> Result<ListenAddr> {
+        match self {
+            Listener::Tcp(l) => Ok(ListenAddr::Tcp(l.local_addr()?.to_string())),
+            #[cfg(unix)]
+            Listener::Unix(_, path) => Ok(ListenAddr::Unix(path.clone())),
+        }
+    }
+
+    pub fn accept(&self) -> io::Result<Connection> {
+        match self {
+            Listener::Tcp(l) => {
+                let (stream, peer) = l.accept()?;
+                Ok(Connection {
+                    reader: Box::new(BufReader::new(stream.try_clone()?)),
+                    sink: StreamSink::new(Box::new(stream)),
+                    peer: peer.to_string(),
+                })
+            }
+            #[cfg(unix)]
+            Listener::Unix(l, path) => {
+                let (stream, _) = l.accept()?;
+                Ok(Connection {
+                    reader: Box::new(BufReader::new(stream.try_clone()?)),
+                    sink: StreamSink::new(Box::new(stream)),
+                    peer: path.display().to_string(),
+                })
+            }
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Listener::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// What the session loop reacts to
+pub enum SessionEvent {
+    Connected(u64, Arc<dyn ResponseSink>),
+    Request(u64, Value),
+    Disconnected(u64, String),
+    SymbolsLoaded(Option<Arc<ObjectInfo>>),
+    /// Stop serving, e.g. on SIGINT/SIGTERM, so the caller can clean up behind it
+    Shutdown(String),
+}
+
+/// Read framed requests from `client` until its input closes or can no longer be parsed
+pub fn spawn_reader(client: u64, mut reader: Box<dyn BufRead + Send>, events: Sender<SessionEvent>) {
+    thread::spawn(move || loop {
+        match read_framed_json(&mut reader) {
+            Ok(msg) => {
+                if events.send(SessionEvent::Request(client, msg)).is_err() {
+                    break;
+                }
+            }
+            // The frame was consumed whole, so the stream is still in step; without a seq
+            // there is nobody to send an error response to
+            Err(FrameError::Json(e)) => eprintln!("Ignoring unparsable request from client {}: {}", client, e),
+            Err(e) => {
+                let _ = events.send(SessionEvent::Disconnected(client, e.to_string()));
+                break;
+            }
+        }
+    });
+}
+
+/// Accept clients forever, numbering them from 1 (0 is the stdio client)
+pub fn spawn_acceptor(listener: Listener, broadcast: Arc<Broadcast>, events: Sender<SessionEvent>) {
+    thread::spawn(move || {
+        let mut next_client = 1;
+        loop {
+            match listener.accept() {
+                Ok(conn) => {
+                    let client = next_client;
+                    next_client += 1;
+                    eprintln!("Client {} connected from {}", client, conn.peer);
+                    let sink: Arc<dyn ResponseSink> = conn.sink;
+                    broadcast.add_client(client, Arc::clone(&sink));
+                    if events.send(SessionEvent::Connected(client, sink)).is_err() {
+                        break;
+                    }
+                    spawn_reader(client, conn.reader, events.clone());
+                }
+                Err(e) => eprintln!("Failed to accept a client: {}", e),
+            }
+        }
+    });
+}
+
+/// Turn SIGINT and SIGTERM (Ctrl-C on Windows) into a `Shutdown` event.
+///
+/// A server otherwise only stops by being killed, and a killed process runs no `Drop`: the
+/// state file and Unix socket would be left behind for the next `--find` to trip over.
+pub fn spawn_shutdown_watcher(events: Sender<SessionEvent>) {
+    thread::spawn(move || {
+        let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(rt) => rt,
+            Err(e) => {
+                eprintln!("Cannot watch for shutdown signals: {}", e);
+                return;
+            }
+        };
+        let reason = rt.block_on(async {
+            #[cfg(unix)]
+            {
+                use tokio::signal::unix::{signal, SignalKind};
+                let Ok(mut term) = signal(SignalKind::terminate()) else {
+                    return tokio::signal::ctrl_c().await.map(|_| "SIGINT");
+                };
+                tokio::select! {
+                    r = tokio::signal::ctrl_c() => r.map(|_| "SIGINT"),
+                    _ = term.recv() => Ok("SIGTERM"),
+                }
+            }
+            #[cfg(not(unix))]
+            tokio::signal::ctrl_c().await.map(|_| "Ctrl-C")
+        });
+        match reason {
+            Ok(reason) => {
+                let _ = events.send(SessionEvent::Shutdown(reason.to_string()));
+            }
+            Err(e) => eprintln!("Cannot watch for shutdown signals: {}", e),
+        }
+    });
+}
+
+/// Shared state for all clients, driven by `run_session`
+pub struct Session {
+    pub req_tx: Sender<WorkerJob>,
+    pub cancels: Arc<CancelSet>,
+    pub readiness: Arc<Readiness>,
+    pub broadcast: Arc<Broadcast>,
+}
+
+/// Serve client events until the channel closes, or (for stdio) until the last client leaves.
+pub fn run_session(session: Session, events: Receiver<SessionEvent>, stop_when_empty: bool) {
+    let mut clients: HashMap<u64, Dispatcher> = HashMap::new();
+    // Outer None: still loading. Inner None: loading failed.
+    let mut symbols: Option<Option<Arc<ObjectInfo>>> = None;
+    for event in events {
+        match event {
+            SessionEvent::Connected(client, sink) => {
+                let outbox = Outbox::for_client(client, Arc::clone(&session.cancels), Box::new(sink));
+                let mut dispatcher = Dispatcher::new(session.req_tx.clone(), outbox, Arc::clone(&session.readiness));
+                if let Some(obj_info) = &symbols {
+                    dispatcher.symbols_loaded(obj_info.clone());
+                }
+                clients.insert(client, dispatcher);
+            }
+            SessionEvent::Request(client, msg) => {
+                eprintln!("Received request from client {}: {}", client, msg);
+                if let Some(dispatcher) = clients.get_mut(&client) {
+                    dispatcher.handle(&msg);
+                }
+            }
+            SessionEvent::Disconnected(client, reason) => {
+                eprintln!("Client {} disconnected: {}", client, reason);
+                session.broadcast.remove_client(client);
+                clients.remove(&client);
+                if stop_when_empty && clients.is_empty() {
+                    break;
+                }
+            }
+            SessionEvent::SymbolsLoaded(obj_info) => {
+                for dispatcher in clients.values_mut() {
+                    dispatcher.symbols_loaded(obj_info.clone());
+                }
+                symbols = Some(obj_info);
+            }
+            SessionEvent::Shutdown(reason) => {
+                eprintln!("Shutting down: {}", reason);
+                break;
+            }
+        }
+    }
+}
+
+/// The discovery record a server writes to its state file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HelperEndpoint {
+    /// Schema version
+    pub v: u32,
+    pub pid: u32,
+    pub version: String,
+    /// Canonical path of the ELF file being served
+    pub elf: String,
+    /// `tcp:HOST:PORT` or `unix:PATH`
+    pub address: String,
+    pub started_at_unix: u64,
+}
+
+impl HelperEndpoint {
+    pub fn new(elf: &str, address: &ListenAddr) -> Self {
+        Self {
+            v: 1,
+            pid: std::process::id(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            elf: canonical_elf_path(elf),
+            address: address.to_string(),
+            started_at_unix: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        }
+    }
+}
+
+fn canonical_elf_path(elf: &str) -> String {
+    dunce::canonicalize(elf)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| elf.to_string())
+}
+
+/// Directory holding server state files: `MDBG_DA_HELPER_STATE_DIR` if set, else
+/// `~/.mcu-debug/da-helper`
+pub fn state_base() -> Result<PathBuf> {
+    Ok(match std::env::var_os("MDBG_DA_HELPER_STATE_DIR") {
+        Some(p) => PathBuf::from(p),
+        None => dirs::home_dir()
+            .context("could not determine the home directory")?
+            .join(".mcu-debug")
+            .join("da-helper"),
+    })
+}
+
+/// The state file for a server of `elf`, named by a stable hash of its canonical path
+pub fn default_state_file(elf: &str) -> Result<PathBuf> {
+    // FNV-1a: stable across runs and Rust versions, unlike DefaultHasher
+    let hash = canonical_elf_path(elf).bytes().fold(0xcbf2_9ce4_8422_2325u64, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    Ok(state_base()?.join(format!("{:016x}.json", hash)))
+}
+
+/// Write the state file atomically (temp file + rename) so a reader never sees half of it
+pub fn write_state_file(path: &Path, endpoint: &HelperEndpoint) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("could not create {}", dir.display()))?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(endpoint)?)
+        .with_context(|| format!("could not write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("could not rename {} -> {}", tmp.display(), path.display()))?;
+    Ok(())
+}
+
+/// The server recorded in `state_file`, if there is one and it accepts connections
+pub fn find_server(state_file: &Path) -> Result<HelperEndpoint> {
+    let bytes =
+        std::fs::read(state_file).with_context(|| format!("no da-helper server state at {}", state_file.display()))?;
+    let endpoint: HelperEndpoint =
+        serde_json::from_slice(&bytes).with_context(|| format!("could not parse {}", state_file.display()))?;
+    ListenAddr::parse(&endpoint.address)?
+        .connect(PROBE_TIMEOUT)
+        .map_err(|e| anyhow!("da-helper server at {} is not responding: {}", endpoint.address, e))?;
+    Ok(endpoint)
+}
+
+/// Removes the state file when the server stops, unless another server has replaced it,
+/// and the Unix socket the server listened on
+pub struct StateFileGuard {
+    path: PathBuf,
+    socket: Option<PathBuf>,
+}
+
+impl StateFileGuard {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, socket: None }
+    }
+
+    /// Also remove the socket file of `address`, if it is a Unix socket. The `Listener`
+    /// would remove it itself, but it lives on the acceptor thread, which is never joined.
+    pub fn with_socket(mut self, address: &ListenAddr) -> Self {
+        if let ListenAddr::Unix(path) = address {
+            self.socket = Some(path.clone());
+        }
+        self
+    }
+}
+
+impl Drop for StateFileGuard {
+    fn drop(&mut self) {
+        let ours = std::fs::read(&self.path)
+            .ok()
+            .and_then(|b| serde_json::from_slice::<HelperEndpoint>(&b).ok())
+            .is_some_and(|ep| ep.pid == std::process::id());
+        if ours {
+            let _ = std::fs::remove_file(&self.path);
+        }
+        // Bound by us, and no other server can bind a path that still answers
+        if let Some(socket) = &self.socket {
+            let _ = std::fs::remove_file(socket);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::framing::FrameCodec;
+    use crate::common::transport::write_framed_json;
+    use crate::da_helper::encoding::decode;
+    use crate::da_helper::readiness::Capability;
+    use crate::da_helper::symbols::{Symbol, SymbolScope, SymbolType};
+    use serde_json::json;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn listen_addresses() {
+        assert_eq!(
+            ListenAddr::parse("127.0.0.1:0").unwrap(),
+            ListenAddr::Tcp("127.0.0.1:0".to_string())
+        );
+        assert_eq!(
+            ListenAddr::parse("tcp:localhost:4711").unwrap().to_string(),
+            "tcp:localhost:4711"
+        );
+        assert_eq!(
+            ListenAddr::parse("unix:/tmp/h.sock").unwrap(),
+            ListenAddr::Unix(PathBuf::from("/tmp/h.sock"))
+        );
+        assert!(ListenAddr::parse("4711").is_err());
+        assert!(ListenAddr::parse("host:port").is_err());
+        assert!(ListenAddr::parse("unix:").is_err());
+    }
+
+    #[test]
+    fn state_file_discovery() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = dir.path().join("sub").join("app.json");
+        let listener = Listener::bind(&ListenAddr::parse("127.0.0.1:0").unwrap()).unwrap();
+        let endpoint = HelperEndpoint::new("/no/such/app.elf", &listener.address().unwrap());
+        write_state_file(&state, &endpoint).unwrap();
+        assert_eq!(find_server(&state).unwrap(), endpoint);
+
+        // Once the server is gone the record is stale
+        drop(listener);
+        assert!(find_server(&state).is_err());
+        drop(StateFileGuard::new(state.clone()));
+        assert!(!state.exists());
+
+        assert_eq!(
+            default_state_file("/work/app.elf").unwrap(),
+            default_state_file("/work/app.elf").unwrap()
+        );
+        assert_ne!(
+            default_state_file("/work/app.elf").unwrap(),
+            default_state_file("/work/boot.elf").unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shutdown_ends_a_listening_session_and_cleans_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = dir.path().join("app.json");
+        let socket = dir.path().join("helper.sock");
+        let listener = Listener::bind(&ListenAddr::Unix(socket.clone())).unwrap();
+        let address = listener.address().unwrap();
+        write_state_file(&state, &HelperEndpoint::new("/no/such/app.elf", &address)).unwrap();
+        let guard = StateFileGuard::new(state.clone()).with_socket(&address);
+
+        let broadcast = Broadcast::new();
+        let (req_tx, _req_rx) = channel();
+        let (event_tx, event_rx) = channel();
+        spawn_acceptor(listener, Arc::clone(&broadcast), event_tx.clone());
+        let session = Session {
+            req_tx,
+            cancels: Arc::new(CancelSet::new()),
+            readiness: Readiness::new("test", Outbox::new(Box::new(Arc::clone(&broadcast)))),
+            broadcast,
+        };
+        let served = thread::spawn(move || run_session(session, event_rx, false));
+        event_tx.send(SessionEvent::Shutdown("test".to_string())).unwrap();
+        served.join().unwrap();
+
+        drop(guard);
+        assert!(!state.exists());
+        assert!(!socket.exists());
+    }
+
+    /// Symbols with one global, `counter`
+    fn counter_info() -> ObjectInfo {
+        let mut info = ObjectInfo::new();
+        let counter = info.elf_symbols.insert(Symbol {
+            name: "counter".to_string(),
+            raw_name: "counter".to_string(),
+            address: 0x2000_0000,
+            size: 4,
+            kind: SymbolType::Data,
+            scope: SymbolScope::Global,
+            section: None,
+            decl_file: None,
+            decl_line: None,
+            type_offset: None,
+        });
+        info.global_symbols.push(counter);
+        info
+    }
+
+    struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl Client {
+        fn connect(address: &ListenAddr) -> Self {
+            let ListenAddr::Tcp(addr) = address else {
+                panic!("expected a TCP address");
+            };
+            let stream = TcpStream::connect(addr).unwrap();
+            stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+            Self {
+                reader: BufReader::new(stream.try_clone().unwrap()),
+                writer: stream,
+            }
+        }
+
+        fn send(&mut self, msg: Value) {
+            write_framed_json(&mut self.writer, &msg).unwrap();
+        }
+
+        /// Next message that isn't a Progress event
+        fn recv(&mut self) -> Value {
+            loop {
+                let msg = read_framed_json(&mut self.reader).unwrap();
+                if msg["args"]["type"] != "Progress" {
+                    return msg;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn clients_share_symbols_with_separate_seqs() {
+        let broadcast = Broadcast::new();
+        let readiness = Readiness::new("test", Outbox::new(Box::new(Arc::clone(&broadcast))));
+        let (req_tx, _req_rx) = channel();
+        let (event_tx, event_rx) = channel();
+        let listener = Listener::bind(&ListenAddr::parse("127.0.0.1:0").unwrap()).unwrap();
+        let address = listener.address().unwrap();
+        spawn_acceptor(listener, Arc::clone(&broadcast), event_tx.clone());
+        let session = Session {
+            req_tx,
+            cancels: Arc::new(CancelSet::new()),
+            readiness: Arc::clone(&readiness),
+            broadcast,
+        };
+        thread::spawn(move || run_session(session, event_rx, false));
+
+        // The first client asks before symbols are loaded; its request waits
+        let mut first = Client::connect(&address);
+        first.send(json!({"req": "globals", "seq": 1}));
+        readiness.set_ready(Capability::Symbols);
+        readiness.notify(&json!({"method": "HelperEvent", "args": {"type": "SymbolTableReady"}}));
+        event_tx
+            .send(SessionEvent::SymbolsLoaded(Some(Arc::new(counter_info()))))
+            .unwrap();
+        assert_eq!(first.recv()["args"]["type"], "SymbolTableReady");
+        let response = first.recv();
+        assert_eq!(response["seq"], 1);
+        assert_eq!(response["globals"], json!([["counter", "0x20000000"]]));
+
+        // A later client gets the milestone replayed and reuses the same seq
+        let mut second = Client::connect(&address);
+        assert_eq!(second.recv()["args"]["type"], "SymbolTableReady");
+        second.send(json!({"req": "cancel", "seq": 2}));
+        second.send(json!({"req": "symbolLookup", "seq": 1, "name": "counter"}));
+        first.send(json!({"req": "symbolLookup", "seq": 2, "name": "missing"}));
+
+        // The second client's cancel of seq 2 doesn't touch the first client's seq 2
+        let response = second.recv();
+        assert_eq!(response["seq"], 1);
+        assert_eq!(response["symbols"], json!([["counter", "0x20000000"]]));
+        let response = first.recv();
+        assert_eq!(response["seq"], 2);
+        assert_eq!(response["error"]["code"], "address_not_found");
+    }
+
+    #[test]
+    fn hello_switches_a_client_to_binary_frames() {
+        let broadcast = Broadcast::new();
+        let readiness = Readiness::new("test", Outbox::new(Box::new(Arc::clone(&broadcast))));
+        readiness.set_ready(Capability::Symbols);
+        let (req_tx, _req_rx) = channel();
+        let (event_tx, event_rx) = channel();
+        let listener = Listener::bind(&ListenAddr::parse("127.0.0.1:0").unwrap()).unwrap();
+        let address = listener.address().unwrap();
+        spawn_acceptor(listener, Arc::clone(&broadcast), event_tx.clone());
+        event_tx
+            .send(SessionEvent::SymbolsLoaded(Some(Arc::new(counter_info()))))
+            .unwrap();
+        let session = Session {
+            req_tx,
+            cancels: Arc::new(CancelSet::new()),
+            readiness,
+            broadcast,
+        };
+        thread::spawn(move || run_session(session, event_rx, false));
+
+        let mut client = Client::connect(&address);
+        client.send(json!({"req": "hello", "seq": 1, "encodings": ["cbor"], "compression": ["deflate"]}));
+        let hello = client.recv();
+        assert_eq!(
+            (hello["seq"].clone(), hello["encoding"].clone()),
+            (json!(1), json!("cbor"))
+        );
+
+        client.send(json!({"req": "globals", "seq": 2}));
+        let frame = FrameCodec::new().read_message(&mut client.reader).unwrap();
+        assert_eq!(frame.content_type.as_deref(), Some("application/cbor"));
+        let response = decode(&frame).unwrap();
+        assert_eq!(response["seq"], 2);
+        assert_eq!(response["globals"], json!([["counter", "0x20000000"]]));
+    }
+}
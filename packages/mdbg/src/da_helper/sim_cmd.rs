@@ -0,0 +1,125 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `mdbg sim-server`: a gdb-server with a simulated target behind it, for end-to-end tests of the
+//! proxy, the CLI and live watch without a probe or a board.
+
+use anyhow::{anyhow, Context, Result};
+use clap::Args;
+use std::io;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::da_helper::memory_map::parse_number;
+use crate::da_helper::sim_target::{SharedSimTarget, SimTarget, DEFAULT_HW_BREAKPOINTS, DEFAULT_WATCHPOINTS};
+use crate::rsp::packet::Connection;
+use crate::rsp::stub::serve_connection;
+
+#[derive(Args, Debug)]
+pub struct SimArgs {
+    /// Firmware ELF whose memory and vector table the target starts with
+    #[arg(short = 'e', long = "elf")]
+    pub elf_file: PathBuf,
+
+    /// Port to listen on (0 picks a free one)
+    #[arg(long, default_value_t = 3333)]
+    pub port: u16,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+
+    /// Extra zero-filled memory as ADDR:SIZE, e.g. 0x20000000:64K (repeatable)
+    #[arg(long = "region", value_name = "ADDR:SIZE")]
+    pub regions: Vec<String>,
+
+    /// Number of hardware breakpoints
+    #[arg(long, default_value_t = DEFAULT_HW_BREAKPOINTS)]
+    pub hw_breakpoints: usize,
+
+    /// Number of watchpoints
+    #[arg(long, default_value_t = DEFAULT_WATCHPOINTS)]
+    pub watchpoints: usize,
+}
+
+fn parse_region(spec: &str) -> Result<(u64, u64)> {
+    let (addr, size) = spec
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Region '{}' is not ADDR:SIZE", spec))?;
+    match (parse_number(addr), parse_number(size)) {
+        (Some(addr), Some(size)) => Ok((addr, size)),
+        _ => Err(anyhow!("Region '{}' is not ADDR:SIZE", spec)),
+    }
+}
+
+pub fn run(args: SimArgs) -> Result<()> {
+    let regions = args
+        .regions
+        .iter()
+        .map(|spec| parse_region(spec))
+        .collect::<Result<Vec<_>>>()?;
+    let mut sim = SimTarget::from_elf(&args.elf_file.to_string_lossy(), &regions)?;
+    sim.max_hw_breakpoints = args.hw_breakpoints;
+    sim.max_watchpoints = args.watchpoints;
+
+    let listener = TcpListener::bind((args.host.as_str(), args.port))
+        .with_context(|| format!("Listening on {}:{}", args.host, args.port))?;
+    let addr = listener.local_addr()?;
+    eprintln!(
+        "Simulating {} on {}; in GDB: target remote {}",
+        args.elf_file.display(),
+        addr,
+        addr
+    );
+    serve(listener, sim)
+}
+
+/// Serve GDB connections on `listener` until accepting fails. Like a real gdb-server, every
+/// connection sees the same target.
+pub fn serve(listener: TcpListener, sim: SimTarget) -> Result<()> {
+    let target = SharedSimTarget(Arc::new(Mutex::new(sim)));
+    loop {
+        let (stream, peer) = listener.accept()?;
+        eprintln!("GDB connected from {}", peer);
+        stream.set_nodelay(true)?;
+        let mut target = target.clone();
+        std::thread::spawn(move || {
+            let mut conn = match stream.try_clone() {
+                Ok(reader) => Connection::new(reader, stream),
+                Err(_) => return,
+            };
+            match serve_connection(&mut conn, &mut target) {
+                Ok(()) => eprintln!("GDB at {} detached", peer),
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof || e.kind() == io::ErrorKind::ConnectionReset => {
+                    eprintln!("GDB at {} disconnected", peer)
+                }
+                Err(e) => eprintln!("GDB at {}: {}", peer, e),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn region_specs() {
+        assert_eq!(parse_region("0x20000000:64K").unwrap(), (0x2000_0000, 0x10000));
+        assert_eq!(parse_region("4096:0x100").unwrap(), (4096, 0x100));
+        assert!(parse_region("0x20000000").is_err());
+        assert!(parse_region("ram:1K").is_err());
+    }
+}
@@ -0,0 +1,516 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A simulated Cortex-M for `mdbg sim-server`: registers, the memory an ELF occupies and
+//! breakpoints, without executing anything. It stands in for a probe and gdb-server in tests.
+//! A step moves PC over one instruction; a continue stops at the next breakpoint after PC as if the
+//! code had run to it, or runs until GDB interrupts it when no breakpoint is set.
+
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use object::{Object, ObjectSection};
+
+use crate::common::sync::MutexExt;
+use crate::da_helper::cortex_m::{target_xml, REGISTER_NAMES};
+use crate::da_helper::image::MemoryImage;
+use crate::da_helper::memory_map::ElfLayout;
+use crate::rsp::client::BreakpointKind;
+use crate::rsp::stub::{StubTarget, SIGINT, SIGTRAP};
+
+/// Comparators in a typical Cortex-M3/M4 FPB and DWT
+pub const DEFAULT_HW_BREAKPOINTS: usize = 6;
+pub const DEFAULT_WATCHPOINTS: usize = 4;
+
+/// Stack made available below the initial SP when no region covers it
+const DEFAULT_STACK_SIZE: u64 = 4096;
+
+/// Section names that linker scripts commonly give the vector table
+const VECTOR_SECTIONS: [&str; 4] = [".isr_vector", ".vectors", ".vector_table", ".intvecs"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Watchpoint {
+    kind: BreakpointKind,
+    addr: u64,
+    size: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimTarget {
+    registers: [u32; REGISTER_NAMES.len()],
+    memory: MemoryImage,
+    /// State after reset, for `monitor reset`
+    reset_registers: [u32; REGISTER_NAMES.len()],
+    reset_memory: MemoryImage,
+    breakpoints: BTreeSet<u64>,
+    hw_breakpoints: BTreeSet<u64>,
+    watchpoints: Vec<Watchpoint>,
+    pub max_hw_breakpoints: usize,
+    pub max_watchpoints: usize,
+    running: bool,
+}
+
+fn regnum(name: &str) -> usize {
+    REGISTER_NAMES
+        .iter()
+        .position(|n| *n == name)
+        .expect("a Cortex-M register name")
+}
+
+impl SimTarget {
+    /// A halted target as it would be after reset, except that `.data` and `.bss` already hold
+    /// their initial values. `regions` are extra (start, size) ranges of zeroed RAM.
+    pub fn from_elf(path: &str, regions: &[(u64, u64)]) -> Result<Self> {
+        let data = std::fs::read(path).with_context(|| format!("Reading {}", path))?;
+        let obj_file = object::File::parse(&*data).with_context(|| format!("Parsing {}", path))?;
+        let layout = ElfLayout::from_object(&obj_file, &data);
+
+        let mut memory = MemoryImage::new();
+        for &(start, size) in regions {
+            memory.write(start, &vec![0; size as usize]);
+        }
+        // Run-time placement, as the startup code leaves it; .bss reads as zeros
+        for placed in &layout.sections {
+            memory.write(placed.vma, &vec![0; placed.size as usize]);
+        }
+        for section in obj_file.sections() {
+            let Some(placed) = layout
+                .sections
+                .iter()
+                .find(|s| s.has_data && s.vma == section.address() && section.name().is_ok_and(|n| n == s.name))
+            else {
+                continue;
+            };
+            if let Ok(bytes) = section.data() {
+                memory.write(placed.vma, bytes);
+                if placed.copy_down() {
+                    memory.write(placed.lma, bytes);
+                }
+            }
+        }
+
+        // Initial SP and reset vector from the vector table
+        let vectors = VECTOR_SECTIONS
+            .iter()
+            .find_map(|name| layout.sections.iter().find(|s| s.name == *name))
+            .map(|s| s.lma)
+            .or_else(|| memory.start());
+        let word = |addr: u64| {
+            memory
+                .read(addr, 4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        };
+        let sp = vectors.and_then(word).unwrap_or(0);
+        let reset = vectors.and_then(|v| word(v + 4)).unwrap_or(0);
+        let pc = match obj_file.entry() {
+            0 => reset,
+            entry => entry as u32,
+        };
+        if sp >= DEFAULT_STACK_SIZE as u32 && memory.read((sp - 4) as u64, 4).is_none() {
+            memory.write(sp as u64 - DEFAULT_STACK_SIZE, &[0; DEFAULT_STACK_SIZE as usize]);
+        }
+
+        Ok(Self::from_image(memory, sp, pc))
+    }
+
+    /// A halted target with `memory` and the reset state given by `sp` and `pc`
+    pub fn from_image(memory: MemoryImage, sp: u32, pc: u32) -> Self {
+        let mut registers = [0u32; REGISTER_NAMES.len()];
+        registers[regnum("sp")] = sp;
+        registers[regnum("msp")] = sp;
+        registers[regnum("pc")] = pc & !1;
+        registers[regnum("lr")] = 0xffff_ffff;
+        // Thumb state
+        registers[regnum("xpsr")] = 0x0100_0000;
+        SimTarget {
+            registers,
+            reset_registers: registers,
+            reset_memory: memory.clone(),
+            memory,
+            breakpoints: BTreeSet::new(),
+            hw_breakpoints: BTreeSet::new(),
+            watchpoints: Vec::new(),
+            max_hw_breakpoints: DEFAULT_HW_BREAKPOINTS,
+            max_watchpoints: DEFAULT_WATCHPOINTS,
+            running: false,
+        }
+    }
+
+    pub fn register(&self, name: &str) -> Option<u32> {
+        REGISTER_NAMES
+            .iter()
+            .position(|n| *n == name)
+            .map(|ix| self.registers[ix])
+    }
+
+    pub fn memory(&self) -> &MemoryImage {
+        &self.memory
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn reset(&mut self) {
+        self.registers = self.reset_registers;
+        self.memory = self.reset_memory.clone();
+        self.running = false;
+    }
+
+    /// Size of the Thumb instruction at `pc`: 32-bit encodings start with 0b11101, 0b11110 or 0b11111
+    fn instruction_size(&self, pc: u32) -> u32 {
+        match self.memory.read(pc as u64, 2) {
+            Some(b) if u16::from_le_bytes([b[0], b[1]]) >= 0xe800 => 4,
+            _ => 2,
+        }
+    }
+
+    /// The breakpoint a continue from PC runs into: the next one after PC, wrapping around
+    fn next_breakpoint(&self, pc: u32) -> Option<u64> {
+        let all: BTreeSet<u64> = self.breakpoints.union(&self.hw_breakpoints).copied().collect();
+        all.range(pc as u64 + 1..).next().or_else(|| all.iter().next()).copied()
+    }
+}
+
+impl StubTarget for SimTarget {
+    fn target_xml(&self) -> Option<String> {
+        Some(target_xml())
+    }
+
+    fn register_count(&self) -> usize {
+        REGISTER_NAMES.len()
+    }
+
+    fn register_size(&self, _regnum: usize) -> usize {
+        4
+    }
+
+    fn read_register(&mut self, regnum: usize) -> Option<Vec<u8>> {
+        self.registers.get(regnum).map(|v| v.to_le_bytes().to_vec())
+    }
+
+    fn write_register(&mut self, regnum: usize, value: &[u8]) -> bool {
+        match (self.registers.get_mut(regnum), <[u8; 4]>::try_from(value)) {
+            (Some(reg), Ok(bytes)) => {
+                *reg = u32::from_le_bytes(bytes);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn read_memory(&mut self, addr: u64, len: usize) -> Vec<u8> {
+        let mut out = Vec::new();
+        while out.len() < len {
+            let Some(at) = addr.checked_add(out.len() as u64) else {
+                break;
+            };
+            let Some((start, bytes)) = self.memory.run_at(at) else {
+                break;
+            };
+            let from = (at - start) as usize;
+            let take = (len - out.len()).min(bytes.len() - from);
+            out.extend_from_slice(&bytes[from..from + take]);
+        }
+        out
+    }
+
+    /// Writes succeed only inside memory the target has
+    fn write_memory(&mut self, addr: u64, data: &[u8]) -> bool {
+        if self.memory.read(addr, data.len()).is_none() {
+            return false;
+        }
+        self.memory.write(addr, data);
+        true
+    }
+
+    fn resume(&mut self, step: bool) -> Option<u8> {
+        let pc = regnum("pc");
+        if step {
+            self.registers[pc] = self.registers[pc].wrapping_add(self.instruction_size(self.registers[pc]));
+            return Some(SIGTRAP);
+        }
+        match self.next_breakpoint(self.registers[pc]) {
+            Some(addr) => {
+                self.registers[pc] = addr as u32;
+                Some(SIGTRAP)
+            }
+            None => {
+                self.running = true;
+                None
+            }
+        }
+    }
+
+    fn halt(&mut self) -> u8 {
+        self.running = false;
+        SIGINT
+    }
+
+    fn insert_breakpoint(&mut self, kind: BreakpointKind, addr: u64, size: usize) -> Option<bool> {
+        Some(match kind {
+            BreakpointKind::Software => {
+                self.memory.read(addr, size.max(2)).is_some() && {
+                    self.breakpoints.insert(addr);
+                    true
+                }
+            }
+            BreakpointKind::Hardware => {
+                (self.hw_breakpoints.len() < self.max_hw_breakpoints || self.hw_breakpoints.contains(&addr)) && {
+                    self.hw_breakpoints.insert(addr);
+                    true
+                }
+            }
+            _ => {
+                self.watchpoints.len() < self.max_watchpoints && {
+                    self.watchpoints.push(Watchpoint { kind, addr, size });
+                    true
+                }
+            }
+        })
+    }
+
+    fn remove_breakpoint(&mut self, kind: BreakpointKind, addr: u64, size: usize) -> Option<bool> {
+        Some(match kind {
+            BreakpointKind::Software => self.breakpoints.remove(&addr),
+            BreakpointKind::Hardware => self.hw_breakpoints.remove(&addr),
+            _ => {
+                let before = self.watchpoints.len();
+                let removed = Watchpoint { kind, addr, size };
+                self.watchpoints.retain(|w| *w != removed);
+                self.watchpoints.len() < before
+            }
+        })
+    }
+
+    fn memory_map_xml(&self) -> Option<String> {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\"?>\n<!DOCTYPE memory-map PUBLIC \"+//IDN gnu.org//DTD GDB Memory Map V1.0//EN\" \
+             \"http://sourceware.org/gdb/gdb-memory-map.dtd\">\n<memory-map>\n",
+        );
+        // Everything is writable with M/X, flash included, so it is all presented as RAM
+        for (start, bytes) in self.memory.runs() {
+            xml.push_str(&format!(
+                "<memory type=\"ram\" start=\"0x{:x}\" length=\"0x{:x}\"/>\n",
+                start,
+                bytes.len()
+            ));
+        }
+        xml.push_str("</memory-map>\n");
+        Some(xml)
+    }
+
+    fn monitor(&mut self, command: &str) -> Option<String> {
+        match command.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["reset"] | ["reset", "halt"] | ["reset", "init"] => {
+                self.reset();
+                Some(String::new())
+            }
+            ["halt"] => {
+                self.running = false;
+                Some(String::new())
+            }
+            ["breakpoints"] => {
+                let list: Vec<String> = self
+                    .breakpoints
+                    .iter()
+                    .map(|a| format!("sw 0x{:08x}\n", a))
+                    .chain(self.hw_breakpoints.iter().map(|a| format!("hw 0x{:08x}\n", a)))
+                    .chain(
+                        self.watchpoints
+                            .iter()
+                            .map(|w| format!("{:?} 0x{:08x} {}\n", w.kind, w.addr, w.size)),
+                    )
+                    .collect();
+                Some(list.concat())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A simulated target shared by several GDB connections, as a gdb-server shares its probe
+#[derive(Clone)]
+pub struct SharedSimTarget(pub Arc<Mutex<SimTarget>>);
+
+impl StubTarget for SharedSimTarget {
+    fn target_xml(&self) -> Option<String> {
+        self.0.lock_recover().target_xml()
+    }
+
+    fn register_count(&self) -> usize {
+        self.0.lock_recover().register_count()
+    }
+
+    fn register_size(&self, regnum: usize) -> usize {
+        self.0.lock_recover().register_size(regnum)
+    }
+
+    fn read_register(&mut self, regnum: usize) -> Option<Vec<u8>> {
+        self.0.lock_recover().read_register(regnum)
+    }
+
+    fn write_register(&mut self, regnum: usize, value: &[u8]) -> bool {
+        self.0.lock_recover().write_register(regnum, value)
+    }
+
+    fn read_memory(&mut self, addr: u64, len: usize) -> Vec<u8> {
+        self.0.lock_recover().read_memory(addr, len)
+    }
+
+    fn write_memory(&mut self, addr: u64, data: &[u8]) -> bool {
+        self.0.lock_recover().write_memory(addr, data)
+    }
+
+    fn resume(&mut self, step: bool) -> Option<u8> {
+        self.0.lock_recover().resume(step)
+    }
+
+    fn halt(&mut self) -> u8 {
+        self.0.lock_recover().halt()
+    }
+
+    fn insert_breakpoint(&mut self, kind: BreakpointKind, addr: u64, size: usize) -> Option<bool> {
+        self.0.lock_recover().insert_breakpoint(kind, addr, size)
+    }
+
+    fn remove_breakpoint(&mut self, kind: BreakpointKind, addr: u64, size: usize) -> Option<bool> {
+        self.0.lock_recover().remove_breakpoint(kind, addr, size)
+    }
+
+    fn memory_map_xml(&self) -> Option<String> {
+        self.0.lock_recover().memory_map_xml()
+    }
+
+    fn monitor(&mut self, command: &str) -> Option<String> {
+        self.0.lock_recover().monitor(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsp::client::{Client, Resume, StopReply};
+    use crate::rsp::packet::Connection;
+    use crate::rsp::stub::serve_connection;
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    fn sim() -> SimTarget {
+        let mut memory = MemoryImage::new();
+        // movs r0, #1; bl <far>; nop; b .
+        memory.write(
+            0x0800_0000,
+            &[0x01, 0x20, 0x00, 0xf0, 0x02, 0xf8, 0x00, 0xbf, 0xfe, 0xe7],
+        );
+        memory.write(0x2000_0000, &[0; 0x100]);
+        SimTarget::from_image(memory, 0x2000_0100, 0x0800_0001)
+    }
+
+    #[test]
+    fn step_continue_and_breakpoints() {
+        let mut target = sim();
+        assert_eq!(target.register("pc"), Some(0x0800_0000));
+        assert_eq!(target.resume(true), Some(SIGTRAP));
+        assert_eq!(target.register("pc"), Some(0x0800_0002));
+        // bl is a 32-bit instruction
+        target.resume(true);
+        assert_eq!(target.register("pc"), Some(0x0800_0006));
+
+        assert_eq!(
+            target.insert_breakpoint(BreakpointKind::Software, 0x0800_0002, 2),
+            Some(true)
+        );
+        assert_eq!(
+            target.insert_breakpoint(BreakpointKind::Software, 0x0900_0000, 2),
+            Some(false)
+        );
+        // Wraps around to the only breakpoint
+        assert_eq!(target.resume(false), Some(SIGTRAP));
+        assert_eq!(target.register("pc"), Some(0x0800_0002));
+        assert_eq!(
+            target.remove_breakpoint(BreakpointKind::Software, 0x0800_0002, 2),
+            Some(true)
+        );
+        assert_eq!(target.resume(false), None);
+        assert!(target.is_running());
+        assert_eq!(target.halt(), SIGINT);
+
+        target.max_hw_breakpoints = 1;
+        assert_eq!(
+            target.insert_breakpoint(BreakpointKind::Hardware, 0x0800_0006, 2),
+            Some(true)
+        );
+        assert_eq!(
+            target.insert_breakpoint(BreakpointKind::Hardware, 0x0800_0008, 2),
+            Some(false)
+        );
+        target.max_watchpoints = 0;
+        assert_eq!(
+            target.insert_breakpoint(BreakpointKind::WriteWatch, 0x2000_0000, 4),
+            Some(false)
+        );
+
+        assert!(target.write_memory(0x2000_00fc, &[1, 2, 3, 4]));
+        assert!(!target.write_memory(0x2000_00fe, &[1, 2, 3, 4]));
+        assert_eq!(target.read_memory(0x2000_00fe, usize::MAX), vec![3, 4]);
+        assert_eq!(target.monitor("reset halt"), Some(String::new()));
+        assert_eq!(target.register("pc"), Some(0x0800_0000));
+        assert_eq!(target.memory().read(0x2000_00fc, 4), Some(&[0u8; 4][..]));
+        assert_eq!(target.monitor("frobnicate"), None);
+    }
+
+    #[test]
+    fn client_session_against_the_simulator() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut target = SharedSimTarget(Arc::new(Mutex::new(sim())));
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut conn = Connection::new(stream.try_clone().unwrap(), stream);
+            serve_connection(&mut conn, &mut target).unwrap();
+        });
+
+        let mut client = Client::connect(addr, Duration::from_secs(5)).unwrap();
+        client.handshake().unwrap();
+        let map = client.memory_map().unwrap().unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(client.read_memory(0x0800_0000, 2).unwrap(), vec![0x01, 0x20]);
+
+        client
+            .insert_breakpoint(BreakpointKind::Hardware, 0x0800_0008, 2)
+            .unwrap();
+        client.resume(Resume::Continue).unwrap();
+        assert!(matches!(
+            client.wait_stop().unwrap(),
+            StopReply::Signal { signal: SIGTRAP, .. }
+        ));
+        client
+            .remove_breakpoint(BreakpointKind::Hardware, 0x0800_0008, 2)
+            .unwrap();
+
+        // Nothing to stop at: runs until interrupted
+        client.resume(Resume::Continue).unwrap();
+        client.interrupt().unwrap();
+        assert!(matches!(
+            client.wait_stop().unwrap(),
+            StopReply::Signal { signal: SIGINT, .. }
+        ));
+
+        assert_eq!(client.monitor("reset").unwrap(), "");
+        client.detach().unwrap();
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,205 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Source-interleaved listings (the `objdump -S` view), built from the line info that
+//! `apply_line_info` attached to each instruction.
+//!
+//! The line table only has rows where the line changes, so an instruction without line
+//! info belongs to whatever line came before it. Optimized code jumps around in the
+//! source; a line we have already shown is shown again with `repeat` set so the UI can
+//! dim it, and we only fill in skipped lines when moving forward by a small amount.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+use std::rc::Rc;
+
+use crate::da_helper::elf_items::FileTable;
+use crate::da_helper::get_assembly::AssemblyLine;
+use crate::da_helper::helper_requests::{SerInstruction, SourceLine, SourceListingChunk};
+
+/// When the next line is at most this far ahead of the last one shown in the same file,
+/// the lines in between (comments, declarations, braces) are shown too.
+const MAX_GAP_FILL: u32 = 8;
+
+/// Lazily loaded source files, keyed by the path in the file table.
+#[derive(Default)]
+pub struct SourceCache {
+    files: HashMap<String, Option<Vec<String>>>,
+}
+
+impl SourceCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Text of a 1-based line, or None if the file can't be read or is too short.
+    pub fn line(&mut self, path: &str, line: u32) -> Option<String> {
+        let lines = self.files.entry(path.to_string()).or_insert_with(|| {
+            let bytes = std::fs::read(path).ok()?;
+            Some(
+                String::from_utf8_lossy(&bytes)
+                    .lines()
+                    .map(|l| l.trim_end_matches('\r').to_string())
+                    .collect(),
+            )
+        });
+        lines.as_ref()?.get((line as usize).checked_sub(1)?).cloned()
+    }
+}
+
+/// Group `lines` (in address order) into chunks of source lines followed by their instructions.
+pub fn build_source_listing(
+    lines: &[Rc<AssemblyLine>],
+    file_table: Option<&FileTable>,
+    cache: &mut SourceCache,
+) -> Vec<SourceListingChunk> {
+    let mut chunks: Vec<SourceListingChunk> = Vec::new();
+    let mut current: Option<(i32, i32, i32)> = None;
+    let mut shown: HashMap<u32, BTreeSet<u32>> = HashMap::new();
+    let mut last_line: HashMap<u32, u32> = HashMap::new();
+
+    for line in lines {
+        let file_id = line.file_id.get();
+        let start = line.start_line.get();
+        let key = (file_id >= 0 && start > 0).then(|| (file_id, start, line.end_line.get().max(start)));
+
+        // Instructions without their own row continue the current chunk
+        let new_chunk = match key {
+            Some(key) => current != Some(key),
+            None => chunks.is_empty(),
+        };
+        if new_chunk {
+            let mut source = Vec::new();
+            if let Some((file_id, start, end)) = key {
+                let file_id = file_id as u32;
+                let (start, end) = (start as u32, end as u32);
+                let path = file_table.and_then(|ft| ft.get_by_id(file_id)).cloned();
+                let seen = shown.entry(file_id).or_default();
+                let from = match last_line.get(&file_id) {
+                    Some(&last) if start > last && start - last <= MAX_GAP_FILL => last + 1,
+                    _ => start,
+                };
+                for l in from..=end {
+                    let repeat = seen.contains(&l);
+                    // Gap lines are context only; don't repeat them
+                    if repeat && l < start {
+                        continue;
+                    }
+                    seen.insert(l);
+                    source.push(SourceLine {
+                        F: file_id,
+                        l,
+                        text: path.as_deref().and_then(|p| cache.line(p, l)),
+                        repeat,
+                    });
+                }
+                last_line.insert(file_id, end);
+            }
+            current = key;
+            chunks.push(SourceListingChunk {
+                source,
+                instructions: Vec::new(),
+            });
+        }
+        if let Some(chunk) = chunks.last_mut() {
+            chunk.instructions.push(SerInstruction::from_assembly_line(line));
+        }
+    }
+    chunks
+}
+
+/// Render chunks as plain text, similar to `objdump -S`. Repeated lines are marked with `*`.
+pub fn format_source_listing(chunks: &[SourceListingChunk], file_table: &HashMap<u32, String>) -> String {
+    let mut out = String::new();
+    let mut last_file: Option<u32> = None;
+    for chunk in chunks {
+        for src in &chunk.source {
+            if last_file != Some(src.F) {
+                let name = file_table.get(&src.F).map(String::as_str).unwrap_or("<unknown>");
+                let _ = writeln!(out, "// {}", name);
+                last_file = Some(src.F);
+            }
+            let mark = if src.repeat { '*' } else { ' ' };
+            let text = src.text.as_deref().unwrap_or("<source not available>");
+            let _ = writeln!(out, "{:>6}{} {}", src.l, mark, text);
+        }
+        for instr in &chunk.instructions {
+            let _ = writeln!(out, "    {:>8}:\t{}\t{}", instr.a, instr.b, instr.i);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instr(addr: u64, line: i32) -> Rc<AssemblyLine> {
+        let l = AssemblyLine::new(addr, "00 bf".to_string(), "nop".to_string(), String::new(), 0, 0);
+        if line > 0 {
+            l.set_source_info(1, line, -1, line, -1);
+        }
+        Rc::new(l)
+    }
+
+    fn source_lines(chunk: &SourceListingChunk) -> Vec<(u32, bool)> {
+        chunk.source.iter().map(|s| (s.l, s.repeat)).collect()
+    }
+
+    #[test]
+    fn interleaves_and_handles_reordering() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.c");
+        let text: Vec<String> = (1..=30).map(|i| format!("line {}", i)).collect();
+        std::fs::write(&path, text.join("\n")).unwrap();
+        let mut ft = FileTable::new();
+        let id = ft.intern(path.to_string_lossy().to_string());
+        assert_eq!(id, 1);
+
+        let lines = vec![
+            instr(0x100, 10),
+            instr(0x102, -1), // continues line 10
+            instr(0x104, 12), // small gap: shows 11 and 12
+            instr(0x106, 10), // moved back by the optimizer
+            instr(0x108, 25), // large gap: only 25
+            instr(0x10a, 12),
+        ];
+        let mut cache = SourceCache::new();
+        let chunks = build_source_listing(&lines, Some(&ft), &mut cache);
+
+        assert_eq!(chunks.len(), 5);
+        assert_eq!(source_lines(&chunks[0]), vec![(10, false)]);
+        assert_eq!(chunks[0].instructions.len(), 2);
+        assert_eq!(chunks[0].source[0].text.as_deref(), Some("line 10"));
+        assert_eq!(source_lines(&chunks[1]), vec![(11, false), (12, false)]);
+        assert_eq!(source_lines(&chunks[2]), vec![(10, true)]);
+        assert_eq!(source_lines(&chunks[3]), vec![(25, false)]);
+        // Going back to 12 from 25 doesn't re-show 11
+        assert_eq!(source_lines(&chunks[4]), vec![(12, true)]);
+
+        let text = format_source_listing(&chunks, &HashMap::from([(1, "main.c".to_string())]));
+        assert!(text.starts_with("// main.c\n    10  line 10\n"));
+        assert!(text.contains("    10* line 10\n"));
+    }
+
+    #[test]
+    fn missing_source_and_line_info() {
+        let lines = vec![instr(0x100, -1), instr(0x102, 3)];
+        let mut cache = SourceCache::new();
+        let chunks = build_source_listing(&lines, None, &mut cache);
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[0].source.is_empty());
+        assert_eq!(chunks[1].source[0].text, None);
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Source maps: rewrite the file paths recorded by the compiler (often CI or container
+//! paths like `/build/...`) into paths that exist on this machine, and back.
+//!
+//! Rules are tried in order and the first match wins. A rule is either a path prefix
+//! (`/build=/home/me/proj`), matched on whole path components, or a regex
+//! (`re:^/ci/[^/]+/src=/home/me/src`) whose replacement may use `$1`-style groups.
+//! Regex rules only work in the compiled -> local direction.
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+
+#[derive(Debug, Clone)]
+enum RuleKind {
+    Prefix { from: String, to: String },
+    Regex { re: Regex, replacement: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceMapRule {
+    kind: RuleKind,
+}
+
+impl SourceMapRule {
+    pub fn prefix(from: &str, to: &str) -> Self {
+        Self {
+            kind: RuleKind::Prefix {
+                from: normalize_prefix(from),
+                to: normalize_prefix(to),
+            },
+        }
+    }
+
+    pub fn regex(pattern: &str, replacement: &str) -> Result<Self> {
+        let re = Regex::new(pattern).map_err(|e| anyhow!("Invalid source map regex '{}': {}", pattern, e))?;
+        Ok(Self {
+            kind: RuleKind::Regex {
+                re,
+                replacement: replacement.to_string(),
+            },
+        })
+    }
+
+    /// Parse a command line rule: `FROM=TO` for a prefix, `re:PATTERN=REPLACEMENT` for a regex.
+    /// Prefix rules split at the first `=`, regex rules at the last one.
+    pub fn parse(spec: &str) -> Result<Self> {
+        if let Some(rest) = spec.strip_prefix("re:") {
+            let (pattern, replacement) = rest
+                .rsplit_once('=')
+                .ok_or_else(|| anyhow!("Source map rule '{}' must look like re:PATTERN=REPLACEMENT", spec))?;
+            return Self::regex(pattern, replacement);
+        }
+        match spec.split_once('=') {
+            Some((from, to)) if !from.is_empty() => Ok(Self::prefix(from, to)),
+            _ => Err(anyhow!("Source map rule '{}' must look like FROM=TO", spec)),
+        }
+    }
+}
+
+/// An ordered list of path rewrite rules. The default map is empty and changes nothing.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    rules: Vec<SourceMapRule>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_specs<S: AsRef<str>>(specs: &[S]) -> Result<Self> {
+        let mut map = Self::new();
+        for spec in specs {
+            map.push(SourceMapRule::parse(spec.as_ref())?);
+        }
+        Ok(map)
+    }
+
+    pub fn push(&mut self, rule: SourceMapRule) {
+        self.rules.push(rule);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Map a path as recorded in DWARF to a local path. None if no rule matches.
+    pub fn to_local(&self, compiled: &str) -> Option<String> {
+        let path = compiled.replace('\\', "/");
+        self.rules.iter().find_map(|rule| match &rule.kind {
+            RuleKind::Prefix { from, to } => strip_path_prefix(&path, from).map(|rest| format!("{}{}", to, rest)),
+            RuleKind::Regex { re, replacement } => re
+                .is_match(&path)
+                .then(|| re.replacen(&path, 1, replacement.as_str()).into_owned()),
+        })
+    }
+
+    /// Map a local path back to the path recorded in DWARF, using the prefix rules only.
+    pub fn to_compiled(&self, local: &str) -> Option<String> {
+        let path = local.replace('\\', "/");
+        self.rules.iter().find_map(|rule| match &rule.kind {
+            RuleKind::Prefix { from, to } => strip_path_prefix(&path, to).map(|rest| format!("{}{}", from, rest)),
+            RuleKind::Regex { .. } => None,
+        })
+    }
+}
+
+fn normalize_prefix(prefix: &str) -> String {
+    let p = prefix.replace('\\', "/");
+    match p.trim_end_matches('/') {
+        "" if p.starts_with('/') => "/".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+fn is_drive_path(p: &str) -> bool {
+    let b = p.as_bytes();
+    b.len() >= 2 && b[0].is_ascii_alphabetic() && b[1] == b':'
+}
+
+/// If `path` starts with `prefix` on a component boundary, return the rest (starting with
+/// `/`, or empty). Windows drive paths compare case-insensitively.
+fn strip_path_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    if prefix == "/" {
+        return path.starts_with('/').then_some(path);
+    }
+    let head = path.get(..prefix.len())?;
+    let matches = if is_drive_path(prefix) {
+        head.eq_ignore_ascii_case(prefix)
+    } else {
+        head == prefix
+    };
+    let rest = &path[prefix.len()..];
+    (matches && (rest.is_empty() || rest.starts_with('/'))).then_some(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_rules_in_order() {
+        let map = SourceMap::from_specs(&["/build/app=/home/me/app", "/build=/opt/src"]).unwrap();
+        assert_eq!(
+            map.to_local("/build/app/main.c").as_deref(),
+            Some("/home/me/app/main.c")
+        );
+        assert_eq!(map.to_local("/build/lib/x.c").as_deref(), Some("/opt/src/lib/x.c"));
+        // Only whole components match
+        assert_eq!(map.to_local("/builder/x.c"), None);
+        assert_eq!(
+            map.to_compiled("/home/me/app/main.c").as_deref(),
+            Some("/build/app/main.c")
+        );
+        assert_eq!(map.to_compiled("/elsewhere/main.c"), None);
+    }
+
+    #[test]
+    fn windows_drive_letters() {
+        let map = SourceMap::from_specs(&["C:\\work\\proj=/mnt/c/Users/me/proj"]).unwrap();
+        assert_eq!(
+            map.to_local("c:/work/proj/src/a.c").as_deref(),
+            Some("/mnt/c/Users/me/proj/src/a.c")
+        );
+        assert_eq!(
+            map.to_local("C:\\Work\\Proj\\b.c").as_deref(),
+            Some("/mnt/c/Users/me/proj/b.c")
+        );
+        assert_eq!(
+            map.to_compiled("/mnt/c/Users/me/proj/b.c").as_deref(),
+            Some("C:/work/proj/b.c")
+        );
+    }
+
+    #[test]
+    fn regex_rules() {
+        let map = SourceMap::from_specs(&["re:^/ci/job-[0-9]+/(.*)$=/home/me/$1"]).unwrap();
+        assert_eq!(
+            map.to_local("/ci/job-1234/src/main.c").as_deref(),
+            Some("/home/me/src/main.c")
+        );
+        assert_eq!(map.to_compiled("/home/me/src/main.c"), None);
+        assert!(SourceMap::from_specs(&["re:(=x"]).is_err());
+        assert!(SourceMap::from_specs(&["no-equals"]).is_err());
+    }
+
+    #[test]
+    fn file_table_lookups_both_directions() {
+        use crate::da_helper::elf_items::FileTable;
+
+        let mut ft = FileTable::new();
+        ft.set_source_map(SourceMap::from_specs(&["/build=/home/me/proj"]).unwrap());
+        let id = ft.intern("/build/src/main.c".to_string());
+        assert_eq!(ft.get_by_id(id).map(String::as_str), Some("/home/me/proj/src/main.c"));
+        assert_eq!(ft.get_by_path("/home/me/proj/src/main.c"), Some(id));
+        assert_eq!(ft.get_by_path("/build/src/main.c"), Some(id));
+        assert_eq!(ft.local_path("/build/src/main.c").as_str(), "/home/me/proj/src/main.c");
+        assert_eq!(ft.get_by_path("/home/me/proj/src/other.c"), None);
+    }
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::hash_map::Entry;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -30,11 +31,21 @@ pub enum SymbolScope {
 
 #[derive(Debug, Clone)]
 pub struct Symbol {
+    /// Demangled name, in the session's demangle style
     pub name: String,
+    /// Name as it appears in the ELF/DWARF (mangled); same as `name` for C symbols
+    pub raw_name: String,
     pub address: u64,
     pub size: u64,
     pub kind: SymbolType,
     pub scope: SymbolScope,
+    /// ELF section the symbol lives in (e.g. ".bss"), if it has one
+    pub section: Option<String>,
+    /// Declaration site from DWARF: FileTable id and line
+    pub decl_file: Option<u32>,
+    pub decl_line: Option<u32>,
+    /// Offset of the DWARF type DIE in .debug_info, for variables
+    pub type_offset: Option<u64>,
 }
 
 pub struct SymbolTable {
@@ -42,7 +53,11 @@ pub struct SymbolTable {
     // BTreeMap in Rust is implemented as a B-Tree (conceptually almost identical to RB-Tree for this purpose)
     // It allows O(log n) lookups and range queries.
     symbols_by_addr: std::collections::BTreeMap<u64, Arc<Symbol>>,
+    // Keyed by both the demangled and the raw name
     symbols_by_name: std::collections::HashMap<String, Arc<Symbol>>,
+    // Demangled names shared by different symbols, such as overloads in a style that drops the
+    // parameters. They are left out of `symbols_by_name`; those symbols go by their raw names.
+    ambiguous_names: std::collections::HashSet<String>,
 }
 
 impl Default for SymbolTable {
@@ -56,6 +71,7 @@ impl SymbolTable {
         Self {
             symbols_by_addr: std::collections::BTreeMap::new(),
             symbols_by_name: std::collections::HashMap::new(),
+            ambiguous_names: std::collections::HashSet::new(),
         }
     }
 
@@ -63,11 +79,28 @@ impl SymbolTable {
         // If there are duplicate start addresses, this overwrites.
         // DWARF can have alias symbols; you might want to handle that differently if needed.
         let arc_symbol = Arc::new(symbol);
-        let name = arc_symbol.name.clone();
 
         self.symbols_by_addr
             .insert(arc_symbol.address, arc_symbol.clone());
-        self.symbols_by_name.insert(name, arc_symbol.clone());
+        if arc_symbol.raw_name == arc_symbol.name {
+            self.symbols_by_name.insert(arc_symbol.name.clone(), arc_symbol.clone());
+            return arc_symbol;
+        }
+        self.symbols_by_name.insert(arc_symbol.raw_name.clone(), arc_symbol.clone());
+        if self.ambiguous_names.contains(&arc_symbol.name) {
+            return arc_symbol;
+        }
+        match self.symbols_by_name.entry(arc_symbol.name.clone()) {
+            Entry::Occupied(entry) if entry.get().raw_name != arc_symbol.raw_name => {
+                self.ambiguous_names.insert(entry.remove().name.clone());
+            }
+            Entry::Occupied(mut entry) => {
+                entry.insert(arc_symbol.clone());
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(arc_symbol.clone());
+            }
+        }
         arc_symbol
     }
 
@@ -109,6 +142,7 @@ impl SymbolTable {
         result
     }
 
+    /// Find a symbol by its demangled or its raw (mangled) name
     pub fn get_by_name(&self, name: &str) -> Option<&Symbol> {
         self.symbols_by_name.get(name).map(|s| s.as_ref())
     }
@@ -119,3 +153,58 @@ impl SymbolTable {
         self.symbols_by_addr.contains_key(&addr)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_by_demangled_or_raw_name() {
+        let mut table = SymbolTable::new();
+        table.insert(Symbol {
+            name: "Motor::setSpeed(int)".to_string(),
+            raw_name: "_ZN5Motor8setSpeedEi".to_string(),
+            address: 0x0800_0100,
+            size: 0x20,
+            kind: SymbolType::Function,
+            scope: SymbolScope::Global,
+            section: Some(".text".to_string()),
+            decl_file: None,
+            decl_line: None,
+            type_offset: None,
+        });
+        let by_name = table.get_by_name("Motor::setSpeed(int)").unwrap();
+        let by_raw = table.get_by_name("_ZN5Motor8setSpeedEi").unwrap();
+        assert_eq!(by_name.address, 0x0800_0100);
+        assert_eq!(by_raw.name, by_name.name);
+        assert!(table.get_by_name("Motor::setSpeed").is_none());
+    }
+
+    #[test]
+    fn overloads_sharing_a_demangled_name_go_by_raw_name() {
+        let mut table = SymbolTable::new();
+        let overload = |raw_name: &str, address| Symbol {
+            name: "Motor::setSpeed".to_string(),
+            raw_name: raw_name.to_string(),
+            address,
+            size: 0x20,
+            kind: SymbolType::Function,
+            scope: SymbolScope::Global,
+            section: Some(".text".to_string()),
+            decl_file: None,
+            decl_line: None,
+            type_offset: None,
+        };
+        table.insert(overload("_ZN5Motor8setSpeedEi", 0x0800_0100));
+        // The same symbol again (from DWARF after the ELF symbols) is not a collision
+        table.insert(overload("_ZN5Motor8setSpeedEi", 0x0800_0100));
+        assert_eq!(table.get_by_name("Motor::setSpeed").unwrap().address, 0x0800_0100);
+
+        table.insert(overload("_ZN5Motor8setSpeedEf", 0x0800_0200));
+        table.insert(overload("_ZN5Motor8setSpeedEd", 0x0800_0300));
+        assert!(table.get_by_name("Motor::setSpeed").is_none());
+        assert_eq!(table.get_by_name("_ZN5Motor8setSpeedEi").unwrap().address, 0x0800_0100);
+        assert_eq!(table.get_by_name("_ZN5Motor8setSpeedEf").unwrap().address, 0x0800_0200);
+        assert_eq!(table.get_by_name("_ZN5Motor8setSpeedEd").unwrap().address, 0x0800_0300);
+    }
+}
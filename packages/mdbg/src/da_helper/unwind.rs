@@ -0,0 +1,724 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Stack unwinding for Cortex-M from a register snapshot and a memory-read callback. Each step uses
+//! the first table that covers the PC: `.debug_frame`, `.eh_frame`, then the ARM EHABI `.ARM.exidx`
+//! index. A return address holding EXC_RETURN is followed through the exception frame the processor
+//! pushed, onto the main or process stack, so backtraces continue past interrupt handlers.
+
+use gimli::{
+    BaseAddresses, CfaRule, DebugFrame, EhFrame, EndianSlice, LittleEndian, Register, RegisterRule, UnwindContext,
+    UnwindSection,
+};
+use object::{Object, ObjectSection};
+use serde::Serialize;
+
+use crate::da_helper::cortex_m::{exception_name, ExcReturn, ExceptionFrame, REGISTER_NAMES};
+use crate::da_helper::elf_items::ObjectInfo;
+use crate::da_helper::image::MemoryImage;
+
+/// Frames returned when the caller doesn't set a limit
+pub const DEFAULT_MAX_FRAMES: usize = 64;
+
+const SP: usize = 13;
+const LR: usize = 14;
+const PC: usize = 15;
+
+/// Reads `len` bytes at an address, or None if they aren't available
+pub type ReadMemory<'a> = &'a dyn Fn(u64, usize) -> Option<Vec<u8>>;
+
+/// What an `.ARM.exidx` entry says about its function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExidxAction {
+    CantUnwind,
+    /// Compact model with up to three opcodes in the entry itself
+    Inline(u32),
+    /// Address of the function's `.ARM.extab` entry
+    Table(u64),
+}
+
+/// The unwind tables of an ELF, copied out so they can live in `ObjectInfo`
+#[derive(Debug, Default)]
+pub struct UnwindTables {
+    address_size: u8,
+    debug_frame: Vec<u8>,
+    eh_frame: Vec<u8>,
+    eh_frame_address: u64,
+    text_address: u64,
+    /// Function start addresses, sorted
+    exidx: Vec<(u64, ExidxAction)>,
+    extab: MemoryImage,
+}
+
+/// Decode a 31-bit place-relative offset, as EHABI tables use
+fn prel31(word: u32, place: u64) -> u64 {
+    let offset = ((word << 1) as i32 >> 1) as i64;
+    (place as i64).wrapping_add(offset) as u64
+}
+
+fn word_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    let b = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+impl UnwindTables {
+    pub fn from_object(obj_file: &object::File) -> Self {
+        let data = |name: &str| {
+            obj_file
+                .section_by_name(name)
+                .and_then(|s| s.uncompressed_data().ok())
+                .map(|d| d.into_owned())
+                .unwrap_or_default()
+        };
+        let mut tables = UnwindTables {
+            address_size: if obj_file.is_64() { 8 } else { 4 },
+            debug_frame: data(".debug_frame"),
+            eh_frame: data(".eh_frame"),
+            eh_frame_address: obj_file.section_by_name(".eh_frame").map_or(0, |s| s.address()),
+            text_address: obj_file.section_by_name(".text").map_or(0, |s| s.address()),
+            ..Default::default()
+        };
+        for section in obj_file.sections() {
+            let Ok(name) = section.name() else {
+                continue;
+            };
+            let Ok(bytes) = section.data() else {
+                continue;
+            };
+            if name.starts_with(".ARM.exidx") {
+                tables.add_exidx(section.address(), bytes);
+            } else if name.starts_with(".ARM.extab") {
+                tables.extab.write(section.address(), bytes);
+            }
+        }
+        tables.exidx.sort_by_key(|(start, _)| *start);
+        tables
+    }
+
+    fn add_exidx(&mut self, address: u64, bytes: &[u8]) {
+        for offset in (0..bytes.len() / 8).map(|ix| ix * 8) {
+            let (Some(first), Some(second)) = (word_at(bytes, offset), word_at(bytes, offset + 4)) else {
+                break;
+            };
+            let place = address + offset as u64;
+            let action = match second {
+                1 => ExidxAction::CantUnwind,
+                w if w & 0x8000_0000 != 0 => ExidxAction::Inline(w),
+                w => ExidxAction::Table(prel31(w, place + 4)),
+            };
+            self.exidx.push((prel31(first, place), action));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.debug_frame.is_empty() && self.eh_frame.is_empty() && self.exidx.is_empty()
+    }
+
+    /// The registers of the caller of the frame in `regs`, found through the first table covering
+    /// `addr`. None when no table covers it.
+    fn step(&self, addr: u64, regs: &Registers, read: ReadMemory) -> Result<Option<(Registers, UnwindMethod)>, String> {
+        if !self.debug_frame.is_empty() {
+            let mut section = DebugFrame::new(&self.debug_frame, LittleEndian);
+            section.set_address_size(self.address_size);
+            let bases = BaseAddresses::default();
+            if let Some(caller) = dwarf_step(&section, &bases, addr, regs, read)? {
+                return Ok(Some((caller, UnwindMethod::DebugFrame)));
+            }
+        }
+        if !self.eh_frame.is_empty() {
+            let mut section = EhFrame::new(&self.eh_frame, LittleEndian);
+            section.set_address_size(self.address_size);
+            let bases = BaseAddresses::default()
+                .set_eh_frame(self.eh_frame_address)
+                .set_text(self.text_address);
+            if let Some(caller) = dwarf_step(&section, &bases, addr, regs, read)? {
+                return Ok(Some((caller, UnwindMethod::EhFrame)));
+            }
+        }
+        if let Some(caller) = self.exidx_step(addr, regs, read)? {
+            return Ok(Some((caller, UnwindMethod::Exidx)));
+        }
+        Ok(None)
+    }
+
+    fn exidx_step(&self, addr: u64, regs: &Registers, read: ReadMemory) -> Result<Option<Registers>, String> {
+        let ix = self.exidx.partition_point(|(start, _)| *start <= addr);
+        let Some(&(start, action)) = ix.checked_sub(1).and_then(|ix| self.exidx.get(ix)) else {
+            return Ok(None);
+        };
+        let opcodes = match action {
+            ExidxAction::CantUnwind => {
+                return Err(format!("The function at 0x{:08x} is marked as not unwindable", start));
+            }
+            ExidxAction::Inline(word) => compact_opcodes(word, &[])?,
+            ExidxAction::Table(entry) => self.extab_opcodes(entry)?,
+        };
+        run_exidx_opcodes(&opcodes, regs, read).map(Some)
+    }
+
+    /// Opcodes from an `.ARM.extab` entry: compact model, or a personality routine followed by
+    /// EHABI-format unwind data as GCC and Clang emit it
+    fn extab_opcodes(&self, entry: u64) -> Result<Vec<u8>, String> {
+        let word = |addr: u64| {
+            self.extab
+                .read(addr, 4)
+                .and_then(|b| word_at(b, 0))
+                .ok_or_else(|| format!("No .ARM.extab entry at 0x{:08x}", addr))
+        };
+        let first = word(entry)?;
+        if first & 0x8000_0000 != 0 {
+            // Compact model: personalities 1 and 2 give a count of extra opcode words
+            let extra = if (first >> 24) & 0x0f == 0 {
+                0
+            } else {
+                (first >> 16) & 0xff
+            };
+            let more = (0..extra as u64)
+                .map(|ix| word(entry + 4 + ix * 4))
+                .collect::<Result<Vec<_>, _>>()?;
+            return compact_opcodes(first, &more);
+        }
+        // Generic personality routine, then a word count in the top byte and three opcode bytes
+        let header = word(entry + 4)?;
+        let mut opcodes = header.to_be_bytes()[1..].to_vec();
+        for ix in 0..(header >> 24) as u64 {
+            opcodes.extend_from_slice(&word(entry + 8 + ix * 4)?.to_be_bytes());
+        }
+        Ok(opcodes)
+    }
+}
+
+/// Opcodes of a compact-model entry: personality 0 holds three, 1 and 2 hold two plus extra words
+fn compact_opcodes(word: u32, more: &[u32]) -> Result<Vec<u8>, String> {
+    let mut opcodes = match (word >> 24) & 0x0f {
+        0 => vec![(word >> 16) as u8, (word >> 8) as u8, word as u8],
+        1 | 2 => vec![(word >> 8) as u8, word as u8],
+        index => return Err(format!("Unknown ARM compact personality routine {}", index)),
+    };
+    for w in more {
+        opcodes.extend_from_slice(&w.to_be_bytes());
+    }
+    Ok(opcodes)
+}
+
+fn read_u32(read: ReadMemory, addr: u32) -> Option<u32> {
+    read(addr as u64, 4).and_then(|b| word_at(&b, 0))
+}
+
+/// Run EHABI unwind opcodes (ARM IHI 0038, section 10.3) against the registers of a frame
+fn run_exidx_opcodes(opcodes: &[u8], regs: &Registers, read: ReadMemory) -> Result<Registers, String> {
+    let mut caller = regs.for_caller();
+    let mut vsp = regs.r[SP].ok_or("SP is unknown")?;
+    let mut pc_popped = false;
+    let mut new_sp = None;
+    let mut pop = |caller: &mut Registers, vsp: &mut u32, reg: usize| -> Result<(), String> {
+        let value = read_u32(read, *vsp).ok_or_else(|| format!("Stack at 0x{:08x} is not readable", *vsp))?;
+        *vsp = vsp.wrapping_add(4);
+        match reg {
+            SP => new_sp = Some(value),
+            PC => {
+                pc_popped = true;
+                caller.r[PC] = Some(value);
+            }
+            _ => caller.r[reg] = Some(value),
+        }
+        Ok(())
+    };
+    let mut bytes = opcodes.iter().copied();
+    while let Some(op) = bytes.next() {
+        match op {
+            0x00..=0x3f => vsp = vsp.wrapping_add(((op as u32 & 0x3f) << 2) + 4),
+            0x40..=0x7f => vsp = vsp.wrapping_sub(((op as u32 & 0x3f) << 2) + 4),
+            0x80..=0x8f => {
+                let mask = ((op as u16 & 0x0f) << 8) | bytes.next().unwrap_or(0) as u16;
+                if mask == 0 {
+                    return Err("The unwind table refuses to unwind this function".to_string());
+                }
+                for bit in (0..12).filter(|bit| mask & (1 << bit) != 0) {
+                    pop(&mut caller, &mut vsp, 4 + bit)?;
+                }
+            }
+            0x90..=0x9f if op & 0x0f != 13 && op & 0x0f != 15 => {
+                vsp = regs.r[(op & 0x0f) as usize].ok_or("The unwind table sets SP from an unknown register")?;
+            }
+            0xa0..=0xaf => {
+                for reg in 4..=4 + (op & 0x07) as usize {
+                    pop(&mut caller, &mut vsp, reg)?;
+                }
+                if op & 0x08 != 0 {
+                    pop(&mut caller, &mut vsp, LR)?;
+                }
+            }
+            0xb0 => break,
+            0xb1 => {
+                let mask = bytes.next().unwrap_or(0);
+                if mask == 0 || mask & 0xf0 != 0 {
+                    return Err(format!("Reserved unwind opcode 0xb1 0x{:02x}", mask));
+                }
+                for reg in (0..4).filter(|reg| mask & (1 << reg) != 0) {
+                    pop(&mut caller, &mut vsp, reg)?;
+                }
+            }
+            0xb2 => {
+                let mut value = 0u32;
+                for (ix, byte) in bytes.by_ref().enumerate() {
+                    value |= ((byte & 0x7f) as u32) << (7 * ix);
+                    if byte & 0x80 == 0 {
+                        break;
+                    }
+                }
+                vsp = vsp.wrapping_add(0x204 + (value << 2));
+            }
+            // VFP registers: FSTMFDX (with a format word) or VPUSH
+            0xb3 => vsp = vsp.wrapping_add(((bytes.next().unwrap_or(0) as u32 & 0x0f) + 1) * 8 + 4),
+            0xb8..=0xbf => vsp = vsp.wrapping_add(((op as u32 & 0x07) + 1) * 8 + 4),
+            0xc8 | 0xc9 => vsp = vsp.wrapping_add(((bytes.next().unwrap_or(0) as u32 & 0x0f) + 1) * 8),
+            0xd0..=0xd7 => vsp = vsp.wrapping_add(((op as u32 & 0x07) + 1) * 8),
+            _ => return Err(format!("Unsupported unwind opcode 0x{:02x}", op)),
+        }
+    }
+    caller.r[SP] = Some(new_sp.unwrap_or(vsp));
+    if !pc_popped {
+        caller.r[PC] = caller.r[LR];
+    }
+    Ok(caller)
+}
+
+/// One step with DWARF CFI from `.debug_frame` or `.eh_frame`
+fn dwarf_step<'a, S: UnwindSection<EndianSlice<'a, LittleEndian>>>(
+    section: &S,
+    bases: &BaseAddresses,
+    addr: u64,
+    regs: &Registers,
+    read: ReadMemory,
+) -> Result<Option<Registers>, String> {
+    let fde = match section.fde_for_address(bases, addr, S::cie_from_offset) {
+        Ok(fde) => fde,
+        Err(gimli::Error::NoUnwindInfoForAddress) => return Ok(None),
+        Err(e) => return Err(format!("Bad unwind table: {}", e)),
+    };
+    let return_address = fde.cie().return_address_register().0 as usize;
+    let mut ctx = UnwindContext::new();
+    let row = match fde.unwind_info_for_address(section, bases, &mut ctx, addr) {
+        Ok(row) => row,
+        Err(gimli::Error::NoUnwindInfoForAddress) => return Ok(None),
+        Err(e) => return Err(format!("Bad unwind table: {}", e)),
+    };
+    let cfa = match row.cfa() {
+        CfaRule::RegisterAndOffset { register, offset } => regs
+            .r
+            .get(register.0 as usize)
+            .copied()
+            .flatten()
+            .ok_or_else(|| format!("The CFA is based on r{}, which is unknown", register.0))?
+            .wrapping_add(*offset as u32),
+        CfaRule::Expression(_) => return Err("CFA expressions are not supported".to_string()),
+    };
+    let restore = |reg: usize| -> Option<u32> {
+        match row.register(Register(reg as u16)) {
+            // Callee-saved registers and LR that the function doesn't touch keep their values
+            RegisterRule::Undefined => matches!(reg, 4..=11 | LR).then(|| regs.r[reg]).flatten(),
+            RegisterRule::SameValue => regs.r[reg],
+            RegisterRule::Offset(offset) => read_u32(read, cfa.wrapping_add(offset as u32)),
+            RegisterRule::ValOffset(offset) => Some(cfa.wrapping_add(offset as u32)),
+            RegisterRule::Register(other) => regs.r.get(other.0 as usize).copied().flatten(),
+            RegisterRule::Constant(value) => Some(value as u32),
+            _ => None,
+        }
+    };
+    let mut caller = regs.for_caller();
+    for reg in (0..PC).filter(|reg| *reg != SP) {
+        caller.r[reg] = restore(reg);
+    }
+    caller.r[SP] = Some(cfa);
+    caller.r[PC] = if return_address < PC {
+        restore(return_address)
+    } else {
+        None
+    };
+    Ok(Some(caller))
+}
+
+/// r0-r15 of one frame, plus the banked stack pointers and xPSR as far as they are known
+#[derive(Debug, Clone, Default)]
+struct Registers {
+    r: [Option<u32>; 16],
+    msp: Option<u32>,
+    psp: Option<u32>,
+    xpsr: Option<u32>,
+}
+
+impl Registers {
+    /// The caller starts out with the banked registers of this frame and nothing else
+    fn for_caller(&self) -> Self {
+        Registers {
+            r: [None; 16],
+            msp: self.msp,
+            psp: self.psp,
+            xpsr: self.xpsr,
+        }
+    }
+}
+
+/// How a frame's registers were recovered from the frame it called
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UnwindMethod {
+    DebugFrame,
+    EhFrame,
+    Exidx,
+    /// No table covers the innermost PC; assumed to be a leaf function that hasn't touched LR or SP
+    LinkRegister,
+    /// Popped from the frame the processor stacked on exception entry
+    ExceptionFrame,
+}
+
+impl UnwindMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnwindMethod::DebugFrame => ".debug_frame",
+            UnwindMethod::EhFrame => ".eh_frame",
+            UnwindMethod::Exidx => ".ARM.exidx",
+            UnwindMethod::LinkRegister => "lr",
+            UnwindMethod::ExceptionFrame => "exception frame",
+        }
+    }
+}
+
+/// The exception that interrupted a frame
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExceptionEntry {
+    /// Exception number and name, when xPSR was known
+    pub number: Option<u32>,
+    pub name: Option<String>,
+    pub exc_return: u32,
+    /// Where the processor stacked the interrupted registers
+    pub frame_address: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StackFrame {
+    /// Thumb bit cleared
+    pub pc: u32,
+    pub sp: u32,
+    /// How this frame was recovered; None for the innermost frame
+    pub method: Option<UnwindMethod>,
+    /// Set on the first frame of code that an exception interrupted
+    pub exception: Option<ExceptionEntry>,
+    /// r0-r15 as far as they could be recovered
+    pub registers: [Option<u32>; 16],
+}
+
+impl StackFrame {
+    /// Code addresses to look up: return addresses point after the call, so they are moved one
+    /// byte back into the calling instruction
+    pub fn lookup_address(&self) -> u32 {
+        match self.method {
+            None | Some(UnwindMethod::ExceptionFrame) => self.pc,
+            _ => self.pc.saturating_sub(1),
+        }
+    }
+
+    /// Recovered registers by name, as `REGISTER_NAMES` has them
+    pub fn known_registers(&self) -> impl Iterator<Item = (&'static str, u32)> + '_ {
+        self.registers
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, value)| value.map(|v| (REGISTER_NAMES[ix], v)))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Backtrace {
+    pub frames: Vec<StackFrame>,
+    /// Why unwinding stopped after the last frame
+    pub stop_reason: String,
+}
+
+/// Unwind from a register snapshot. `reg` returns a register by its `REGISTER_NAMES` name; `read`
+/// returns memory. Unwinding stops at the outermost frame, at a function no table covers, or at
+/// `max_frames`.
+pub fn unwind(
+    tables: &UnwindTables,
+    reg: &dyn Fn(&str) -> Option<u32>,
+    read: ReadMemory,
+    max_frames: usize,
+) -> Backtrace {
+    let mut regs = Registers {
+        r: std::array::from_fn(|ix| reg(REGISTER_NAMES[ix])),
+        msp: reg("msp"),
+        psp: reg("psp"),
+        xpsr: reg("xpsr"),
+    };
+    if regs.r[SP].is_none() {
+        // SP is whichever of MSP and PSP is active: handlers always run on MSP
+        let in_handler = regs.xpsr.is_some_and(|xpsr| xpsr & 0x1ff != 0);
+        let on_psp = !in_handler && reg("control").is_some_and(|control| control & 2 != 0);
+        regs.r[SP] = if on_psp { regs.psp } else { regs.msp };
+    }
+
+    let mut frames: Vec<StackFrame> = Vec::new();
+    let mut method = None;
+    let mut exception = None;
+    let stop_reason = loop {
+        let (Some(pc), Some(sp)) = (regs.r[PC], regs.r[SP]) else {
+            break if frames.is_empty() {
+                "PC or SP is unknown"
+            } else {
+                "The return address is unknown"
+            }
+            .to_string();
+        };
+        let pc = pc & !1;
+        if let Some(last) = frames.last() {
+            if last.pc == pc && last.sp == sp {
+                break "The unwound frame is the same as the previous one".to_string();
+            }
+        }
+        frames.push(StackFrame {
+            pc,
+            sp,
+            method,
+            exception: exception.take(),
+            registers: regs.r,
+        });
+        if frames.len() >= max_frames {
+            break format!("Stopped after {} frames", max_frames);
+        }
+
+        let frame = frames.last().expect("a frame was just pushed");
+        let mut caller = match tables.step(frame.lookup_address() as u64, &regs, read) {
+            Ok(Some((caller, how))) => {
+                method = Some(how);
+                caller
+            }
+            Ok(None) if frames.len() == 1 => {
+                // Without unwind info, assume the innermost function hasn't saved LR or moved SP yet
+                let mut caller = regs.clone();
+                caller.r[PC] = regs.r[LR];
+                method = Some(UnwindMethod::LinkRegister);
+                caller
+            }
+            Ok(None) => break format!("No unwind information for 0x{:08x}", pc),
+            Err(e) => break e,
+        };
+
+        match caller.r[PC] {
+            None | Some(0) | Some(0xffff_ffff) => break "Reached the outermost frame".to_string(),
+            Some(return_to) => {
+                if let Some(exc) = ExcReturn::from_lr(return_to) {
+                    match pop_exception_frame(exc, &regs, &caller, read) {
+                        Ok((interrupted, entry)) => {
+                            caller = interrupted;
+                            exception = Some(entry);
+                            method = Some(UnwindMethod::ExceptionFrame);
+                        }
+                        Err(e) => break e,
+                    }
+                }
+            }
+        }
+        regs = caller;
+    };
+    Backtrace { frames, stop_reason }
+}
+
+/// Registers of the code an exception interrupted. `handler` is the handler's innermost frame
+/// (for xPSR and PSP), `returned` the registers after unwinding the handler's own frame, whose SP
+/// is MSP as it was on exception entry.
+fn pop_exception_frame(
+    exc: ExcReturn,
+    handler: &Registers,
+    returned: &Registers,
+    read: ReadMemory,
+) -> Result<(Registers, ExceptionEntry), String> {
+    let stack = if exc.uses_psp() { handler.psp } else { returned.r[SP] };
+    let stack = stack.ok_or_else(|| {
+        format!(
+            "EXC_RETURN 0x{:08x} needs {}, which is unknown",
+            exc.0,
+            if exc.uses_psp() { "PSP" } else { "SP" }
+        )
+    })?;
+    let frame = ExceptionFrame::read(stack, exc.extended_frame(), read)
+        .ok_or_else(|| format!("The exception frame at 0x{:08x} is not readable", stack))?;
+
+    let number = handler.xpsr.map(|xpsr| xpsr & 0x1ff);
+    let entry = ExceptionEntry {
+        number,
+        name: number.map(exception_name),
+        exc_return: exc.0,
+        frame_address: frame.address,
+    };
+    let mut interrupted = returned.for_caller();
+    interrupted.r[4..12].copy_from_slice(&returned.r[4..12]);
+    interrupted.r[0] = Some(frame.r0);
+    interrupted.r[1] = Some(frame.r1);
+    interrupted.r[2] = Some(frame.r2);
+    interrupted.r[3] = Some(frame.r3);
+    interrupted.r[12] = Some(frame.r12);
+    interrupted.r[LR] = Some(frame.lr);
+    interrupted.r[PC] = Some(frame.pc);
+    interrupted.r[SP] = Some(frame.caller_sp());
+    interrupted.xpsr = Some(frame.xpsr);
+    if exc.uses_psp() {
+        interrupted.psp = Some(frame.caller_sp());
+        interrupted.msp = returned.r[SP];
+    } else {
+        interrupted.msp = Some(frame.caller_sp());
+    }
+    Ok((interrupted, entry))
+}
+
+/// A code address, as precisely as the ELF can place it
+#[derive(Debug, Clone, Serialize)]
+pub struct CodeLocation {
+    pub address: String,
+    pub function: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u64>,
+}
+
+impl CodeLocation {
+    pub fn describe(&self) -> String {
+        let mut out = self.address.clone();
+        if let Some(function) = &self.function {
+            out.push_str(&format!(" in {}", function));
+        }
+        if let (Some(file), Some(line)) = (&self.file, self.line) {
+            out.push_str(&format!(" at {}:{}", file, line));
+        }
+        out
+    }
+}
+
+/// Where `addr` is. Return addresses point after the call, so they are looked up one byte back.
+pub fn locate(info: &ObjectInfo, addr: u32, return_address: bool) -> CodeLocation {
+    let code = (addr & !1) as u64;
+    let lookup = if return_address { code.saturating_sub(1) } else { code };
+    let function = info.elf_symbols.lookup(lookup).map(|s| match code - s.address {
+        0 => s.name.clone(),
+        offset => format!("{}+0x{:x}", s.name, offset),
+    });
+    let line = info.addr_to_line.entries.range(..=lookup).next_back();
+    CodeLocation {
+        address: format!("0x{:08x}", addr),
+        function,
+        file: line.and_then(|(_, entry)| info.file_table.get_by_id(entry.file_id).cloned()),
+        line: line.and_then(|(_, entry)| entry.line.first()).map(|l| l.get()),
+    }
+}
+
+/// Where a frame is: the innermost frame and interrupted code at their PC, callers at the call
+pub fn locate_frame(info: &ObjectInfo, frame: &StackFrame) -> CodeLocation {
+    locate(info, frame.pc, frame.lookup_address() != frame.pc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn reader(memory: &HashMap<u64, u32>) -> impl Fn(u64, usize) -> Option<Vec<u8>> + '_ {
+        move |addr: u64, len: usize| {
+            (0..len as u64)
+                .step_by(4)
+                .map(|off| memory.get(&(addr + off)).map(|w| w.to_le_bytes()))
+                .collect::<Option<Vec<_>>>()
+                .map(|words| words.concat())
+        }
+    }
+
+    #[test]
+    fn exidx_through_an_exception_on_the_process_stack() {
+        let tables = UnwindTables {
+            exidx: vec![
+                // push {r7, lr}
+                (0x1000, ExidxAction::Inline(0x8084_08b0)),
+                // push {r4, lr}
+                (0x2000, ExidxAction::Inline(0x80a8_b0b0)),
+                (0x3000, ExidxAction::CantUnwind),
+            ],
+            ..Default::default()
+        };
+        let mut memory = HashMap::new();
+        memory.insert(0x2000_0ff8, 0x77);
+        memory.insert(0x2000_0ffc, 0xffff_fffd);
+        let frame = [10, 11, 12, 13, 14, 0x2105, 0x2010, 0x0100_0000];
+        for (ix, word) in frame.iter().enumerate() {
+            memory.insert(0x2000_0800 + ix as u64 * 4, *word);
+        }
+        memory.insert(0x2000_0820, 0x44);
+        memory.insert(0x2000_0824, 0x3005);
+        let regs: HashMap<&str, u32> = [
+            ("pc", 0x1004),
+            ("sp", 0x2000_0ff8),
+            ("lr", 0xffff_fffd),
+            ("xpsr", 15),
+            ("psp", 0x2000_0800),
+        ]
+        .into();
+        let read = reader(&memory);
+        let backtrace = unwind(&tables, &|name| regs.get(name).copied(), &read, DEFAULT_MAX_FRAMES);
+
+        let pcs: Vec<_> = backtrace.frames.iter().map(|f| f.pc).collect();
+        assert_eq!(pcs, vec![0x1004, 0x2010, 0x3004]);
+        let interrupted = &backtrace.frames[1];
+        assert_eq!(interrupted.method, Some(UnwindMethod::ExceptionFrame));
+        assert_eq!(interrupted.sp, 0x2000_0820);
+        let exception = interrupted.exception.as_ref().unwrap();
+        assert_eq!(exception.name.as_deref(), Some("SysTick"));
+        assert_eq!(exception.frame_address, 0x2000_0800);
+        assert_eq!(interrupted.registers[0], Some(10));
+        assert_eq!(interrupted.registers[7], Some(0x77));
+        assert_eq!(backtrace.frames[2].method, Some(UnwindMethod::Exidx));
+        assert_eq!(backtrace.frames[2].registers[4], Some(0x44));
+        assert!(backtrace.stop_reason.contains("not unwindable"));
+    }
+
+    #[test]
+    fn debug_frame_cfi() {
+        // CIE: version 1, code align 2, data align -4, RA r14, CFA = r13
+        let mut debug_frame = vec![12, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 1, 0, 2, 0x7c, 14, 0x0c, 13, 0];
+        // FDE for 0x4000-0x4020: after 2 bytes, CFA = sp + 8, r7 at CFA-8, lr at CFA-4
+        debug_frame.extend_from_slice(&[20, 0, 0, 0, 0, 0, 0, 0, 0, 0x40, 0, 0, 0x20, 0, 0, 0]);
+        debug_frame.extend_from_slice(&[0x41, 0x0e, 8, 0x87, 2, 0x8e, 1, 0]);
+        let tables = UnwindTables {
+            address_size: 4,
+            debug_frame,
+            ..Default::default()
+        };
+        let memory: HashMap<u64, u32> = [(0x2000_0100, 0x55), (0x2000_0104, 0x5001)].into();
+        let regs: HashMap<&str, u32> = [("pc", 0x4011), ("sp", 0x2000_0100), ("lr", 0x9999)].into();
+        let read = reader(&memory);
+        let backtrace = unwind(&tables, &|name| regs.get(name).copied(), &read, DEFAULT_MAX_FRAMES);
+
+        assert_eq!(backtrace.frames.len(), 2);
+        let caller = &backtrace.frames[1];
+        assert_eq!((caller.pc, caller.sp), (0x5000, 0x2000_0108));
+        assert_eq!(caller.method, Some(UnwindMethod::DebugFrame));
+        assert_eq!(caller.registers[7], Some(0x55));
+        assert_eq!(caller.lookup_address(), 0x4fff);
+        assert_eq!(backtrace.stop_reason, "No unwind information for 0x00005000");
+
+        // No table covers a leaf function: its caller is at LR
+        let leaf: HashMap<&str, u32> = [("pc", 0x6000), ("sp", 0x2000_0100), ("lr", 0x4011)].into();
+        let backtrace = unwind(&tables, &|name| leaf.get(name).copied(), &read, DEFAULT_MAX_FRAMES);
+        let methods: Vec<_> = backtrace.frames.iter().map(|f| f.method).collect();
+        assert_eq!(
+            methods,
+            vec![None, Some(UnwindMethod::LinkRegister), Some(UnwindMethod::DebugFrame)]
+        );
+    }
+}
@@ -0,0 +1,405 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! How to show a variable's bytes, from its DWARF type. Only scalars are decoded (integers,
+//! floats, booleans, characters, pointers and enums); aggregates are shown as hex. Values are
+//! little-endian, as on Cortex-M.
+
+use gimli::{AttributeValue, DebugInfoOffset, Reader, ReaderOffset, UnitOffset};
+use std::collections::HashMap;
+
+/// Typedefs and qualifiers followed before giving up on a type chain
+const MAX_TYPE_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueKind {
+    Unsigned,
+    Signed,
+    Float,
+    Bool,
+    /// A character type; `true` if signed
+    Char(bool),
+    Pointer,
+    /// Enumerators by value; `true` if the underlying type is signed
+    Enum(bool, Vec<(i64, String)>),
+    /// Structures, unions, arrays and anything else: raw bytes
+    Bytes,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueType {
+    /// Type name as written in C, e.g. `uint32_t`, `const char *` or `enum state`
+    pub name: String,
+    pub size: usize,
+    pub kind: ValueKind,
+}
+
+fn le_bits(bytes: &[u8]) -> u64 {
+    let mut word = [0u8; 8];
+    let n = bytes.len().min(8);
+    word[..n].copy_from_slice(&bytes[..n]);
+    u64::from_le_bytes(word)
+}
+
+fn sign_extend(value: u64, size: usize) -> i64 {
+    match size {
+        1..=7 => {
+            let shift = 64 - size * 8;
+            ((value << shift) as i64) >> shift
+        }
+        _ => value as i64,
+    }
+}
+
+impl ValueType {
+    /// Format `bytes` the way a debugger shows this type. Fewer bytes than the type's size, or a
+    /// size a scalar can't have, fall back to hex.
+    pub fn format(&self, bytes: &[u8]) -> String {
+        let scalar = bytes.len() >= self.size && matches!(self.size, 1 | 2 | 4 | 8);
+        if !scalar || self.kind == ValueKind::Bytes {
+            return bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        }
+        let bits = le_bits(&bytes[..self.size]);
+        match &self.kind {
+            ValueKind::Unsigned => bits.to_string(),
+            ValueKind::Signed => sign_extend(bits, self.size).to_string(),
+            ValueKind::Float if self.size == 4 => f32::from_bits(bits as u32).to_string(),
+            ValueKind::Float if self.size == 8 => f64::from_bits(bits).to_string(),
+            ValueKind::Float => format!("0x{:x}", bits),
+            ValueKind::Bool => (bits != 0).to_string(),
+            ValueKind::Char(signed) => {
+                let number = if *signed { sign_extend(bits, self.size) } else { bits as i64 };
+                match u8::try_from(bits).ok().filter(|c| c.is_ascii_graphic() || *c == b' ') {
+                    Some(c) => format!("{} '{}'", number, c as char),
+                    None => number.to_string(),
+                }
+            }
+            ValueKind::Pointer => format!("0x{:0width$x}", bits, width = self.size * 2),
+            ValueKind::Enum(signed, enumerators) => {
+                let value = if *signed { sign_extend(bits, self.size) } else { bits as i64 };
+                match enumerators.iter().find(|(v, _)| *v == value) {
+                    Some((_, name)) => name.clone(),
+                    None => value.to_string(),
+                }
+            }
+            ValueKind::Bytes => unreachable!(),
+        }
+    }
+}
+
+fn entry_name<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    entry: &gimli::DebuggingInformationEntry<R>,
+) -> Option<String> {
+    let attr = entry.attr_value(gimli::DW_AT_name).ok()??;
+    let name = dwarf.attr_string(unit, attr).ok()?;
+    name.to_string_lossy().ok().map(|s| s.to_string())
+}
+
+fn byte_size<R: Reader>(entry: &gimli::DebuggingInformationEntry<R>) -> Option<usize> {
+    entry
+        .attr_value(gimli::DW_AT_byte_size)
+        .ok()?
+        .and_then(|v| v.udata_value())
+        .map(|v| v as usize)
+}
+
+fn type_ref<R: Reader>(entry: &gimli::DebuggingInformationEntry<R>) -> Option<UnitOffset<R::Offset>> {
+    match entry.attr_value(gimli::DW_AT_type).ok()? {
+        Some(AttributeValue::UnitRef(offset)) => Some(offset),
+        _ => None,
+    }
+}
+
+fn enumerators<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    offset: UnitOffset<R::Offset>,
+) -> Vec<(i64, String)> {
+    let mut out = Vec::new();
+    let Ok(mut tree) = unit.entries_tree(Some(offset)) else {
+        return out;
+    };
+    let Ok(root) = tree.root() else {
+        return out;
+    };
+    let mut children = root.children();
+    while let Ok(Some(child)) = children.next() {
+        let entry = child.entry();
+        if entry.tag() != gimli::DW_TAG_enumerator {
+            continue;
+        }
+        let value = match entry.attr_value(gimli::DW_AT_const_value) {
+            Ok(Some(AttributeValue::Sdata(v))) => Some(v),
+            Ok(Some(v)) => v.udata_value().map(|v| v as i64),
+            _ => None,
+        };
+        if let (Some(value), Some(name)) = (value, entry_name(dwarf, unit, entry)) {
+            out.push((value, name));
+        }
+    }
+    out
+}
+
+/// Resolve the type DIE at `offset` in `unit`, following typedefs and qualifiers. None if the
+/// chain leaves the unit, is too deep, or has no size.
+pub fn resolve_type<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    offset: UnitOffset<R::Offset>,
+) -> Option<ValueType> {
+    resolve_at_depth(dwarf, unit, offset, 0)
+}
+
+/// Resolve the type DIEs at `offsets` into `.debug_info`, as in `Symbol::type_offset`. Offsets
+/// that resolve to no type are left out.
+pub fn resolve_types_at<R: Reader>(dwarf: &gimli::Dwarf<R>, offsets: &[u64]) -> gimli::Result<HashMap<u64, ValueType>> {
+    let mut types = HashMap::new();
+    let mut units = dwarf.units();
+    while let Some(header) = units.next()? {
+        let in_unit: Vec<(u64, UnitOffset<R::Offset>)> = offsets
+            .iter()
+            .filter_map(|&offset| {
+                let debug_info_offset = DebugInfoOffset(R::Offset::from_u64(offset).ok()?);
+                Some((offset, debug_info_offset.to_unit_offset(&header)?))
+            })
+            .collect();
+        if in_unit.is_empty() {
+            continue;
+        }
+        let unit = dwarf.unit(header)?;
+        for (offset, unit_offset) in in_unit {
+            if let Some(value_type) = resolve_type(dwarf, &unit, unit_offset) {
+                types.insert(offset, value_type);
+            }
+        }
+    }
+    Ok(types)
+}
+
+fn resolve_at_depth<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    offset: UnitOffset<R::Offset>,
+    depth: usize,
+) -> Option<ValueType> {
+    if depth > MAX_TYPE_DEPTH {
+        return None;
+    }
+    let entry = unit.entry(offset).ok()?;
+    let name = entry_name(dwarf, unit, &entry);
+    let inner = || type_ref(&entry).and_then(|t| resolve_at_depth(dwarf, unit, t, depth + 1));
+    match entry.tag() {
+        gimli::DW_TAG_base_type => {
+            let size = byte_size(&entry)?;
+            let encoding = match entry.attr_value(gimli::DW_AT_encoding).ok()? {
+                Some(AttributeValue::Encoding(e)) => e,
+                _ => return None,
+            };
+            let kind = match encoding {
+                gimli::DW_ATE_signed => ValueKind::Signed,
+                gimli::DW_ATE_unsigned => ValueKind::Unsigned,
+                gimli::DW_ATE_float => ValueKind::Float,
+                gimli::DW_ATE_boolean => ValueKind::Bool,
+                gimli::DW_ATE_signed_char => ValueKind::Char(true),
+                gimli::DW_ATE_unsigned_char | gimli::DW_ATE_UTF => ValueKind::Char(false),
+                _ => ValueKind::Bytes,
+            };
+            Some(ValueType {
+                name: name.unwrap_or_else(|| "?".to_string()),
+                size,
+                kind,
+            })
+        }
+        gimli::DW_TAG_typedef => inner().map(|t| ValueType {
+            name: name.unwrap_or(t.name),
+            ..t
+        }),
+        gimli::DW_TAG_const_type | gimli::DW_TAG_volatile_type => {
+            let qualifier = if entry.tag() == gimli::DW_TAG_const_type { "const" } else { "volatile" };
+            inner().map(|t| ValueType {
+                name: format!("{} {}", qualifier, t.name),
+                ..t
+            })
+        }
+        gimli::DW_TAG_restrict_type | gimli::DW_TAG_atomic_type => inner(),
+        gimli::DW_TAG_pointer_type | gimli::DW_TAG_reference_type => {
+            let target = match type_ref(&entry) {
+                Some(t) => type_name(dwarf, unit, t, depth + 1).unwrap_or_else(|| "?".to_string()),
+                None => "void".to_string(),
+            };
+            let sigil = if entry.tag() == gimli::DW_TAG_pointer_type { '*' } else { '&' };
+            Some(ValueType {
+                name: format!("{} {}", target, sigil),
+                size: byte_size(&entry).unwrap_or(unit.header.address_size() as usize),
+                kind: ValueKind::Pointer,
+            })
+        }
+        gimli::DW_TAG_enumeration_type => {
+            let signed = inner().is_some_and(|t| t.kind == ValueKind::Signed || t.kind == ValueKind::Char(true));
+            Some(ValueType {
+                name: format!("enum {}", name.as_deref().unwrap_or("<anonymous>")),
+                size: byte_size(&entry)?,
+                kind: ValueKind::Enum(signed, enumerators(dwarf, unit, offset)),
+            })
+        }
+        gimli::DW_TAG_structure_type | gimli::DW_TAG_union_type | gimli::DW_TAG_class_type => {
+            let keyword = match entry.tag() {
+                gimli::DW_TAG_union_type => "union",
+                gimli::DW_TAG_class_type => "class",
+                _ => "struct",
+            };
+            Some(ValueType {
+                name: format!("{} {}", keyword, name.as_deref().unwrap_or("<anonymous>")),
+                size: byte_size(&entry)?,
+                kind: ValueKind::Bytes,
+            })
+        }
+        gimli::DW_TAG_array_type => {
+            let element = inner()?;
+            let count = array_count(unit, offset);
+            let size = byte_size(&entry).or_else(|| count.map(|n| n * element.size))?;
+            Some(ValueType {
+                name: match count {
+                    Some(n) => format!("{}[{}]", element.name, n),
+                    None => format!("{}[]", element.name),
+                },
+                size,
+                kind: ValueKind::Bytes,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// A name for a pointer's target, which may be a type with no size (void, an incomplete struct)
+fn type_name<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    offset: UnitOffset<R::Offset>,
+    depth: usize,
+) -> Option<String> {
+    if let Some(t) = resolve_at_depth(dwarf, unit, offset, depth) {
+        return Some(t.name);
+    }
+    let entry = unit.entry(offset).ok()?;
+    let inner = type_ref(&entry).and_then(|t| type_name(dwarf, unit, t, depth + 1));
+    match entry.tag() {
+        gimli::DW_TAG_const_type => Some(format!("const {}", inner.unwrap_or_else(|| "void".to_string()))),
+        gimli::DW_TAG_volatile_type => Some(format!("volatile {}", inner.unwrap_or_else(|| "void".to_string()))),
+        gimli::DW_TAG_structure_type => Some(format!("struct {}", entry_name(dwarf, unit, &entry)?)),
+        gimli::DW_TAG_subroutine_type => Some("code".to_string()),
+        _ => entry_name(dwarf, unit, &entry),
+    }
+}
+
+/// Element count of a one-dimensional array, from its subrange
+fn array_count<R: Reader>(unit: &gimli::Unit<R>, offset: UnitOffset<R::Offset>) -> Option<usize> {
+    let mut tree = unit.entries_tree(Some(offset)).ok()?;
+    let root = tree.root().ok()?;
+    let mut children = root.children();
+    while let Ok(Some(child)) = children.next() {
+        let entry = child.entry();
+        if entry.tag() != gimli::DW_TAG_subrange_type {
+            continue;
+        }
+        if let Some(count) = entry.attr_value(gimli::DW_AT_count).ok()?.and_then(|v| v.udata_value()) {
+            return Some(count as usize);
+        }
+        return entry
+            .attr_value(gimli::DW_AT_upper_bound)
+            .ok()?
+            .and_then(|v| v.udata_value())
+            .map(|upper| upper as usize + 1);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scalar(name: &str, size: usize, kind: ValueKind) -> ValueType {
+        ValueType {
+            name: name.to_string(),
+            size,
+            kind,
+        }
+    }
+
+    #[test]
+    fn formats_scalars() {
+        assert_eq!(scalar("uint16_t", 2, ValueKind::Unsigned).format(&[0x34, 0x12]), "4660");
+        assert_eq!(scalar("int8_t", 1, ValueKind::Signed).format(&[0xfe]), "-2");
+        assert_eq!(scalar("int", 4, ValueKind::Signed).format(&[0xff, 0xff, 0xff, 0x7f]), "2147483647");
+        assert_eq!(scalar("float", 4, ValueKind::Float).format(&1.5f32.to_le_bytes()), "1.5");
+        assert_eq!(scalar("_Bool", 1, ValueKind::Bool).format(&[2]), "true");
+        assert_eq!(scalar("char", 1, ValueKind::Char(false)).format(b"A"), "65 'A'");
+        assert_eq!(scalar("char", 1, ValueKind::Char(true)).format(&[0xff]), "-1");
+        assert_eq!(
+            scalar("uint8_t *", 4, ValueKind::Pointer).format(&[0x00, 0x01, 0x00, 0x20]),
+            "0x20000100"
+        );
+        let state = scalar(
+            "enum state",
+            1,
+            ValueKind::Enum(true, vec![(-1, "FAILED".to_string()), (0, "IDLE".to_string())]),
+        );
+        assert_eq!(state.format(&[0xff]), "FAILED");
+        assert_eq!(state.format(&[3]), "3");
+        // Short reads and aggregates are hex
+        assert_eq!(scalar("uint32_t", 4, ValueKind::Unsigned).format(&[1, 2]), "0102");
+        assert_eq!(scalar("struct s", 3, ValueKind::Bytes).format(&[1, 2, 3]), "010203");
+    }
+
+    #[test]
+    fn resolves_types_by_debug_info_offset() {
+        use gimli::write::{AttributeValue as WriteValue, DwarfUnit, EndianVec, Sections};
+        use gimli::{Encoding, Format, LittleEndian};
+
+        let mut unit = DwarfUnit::new(Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 4,
+        });
+        let root = unit.unit.root();
+        let base = unit.unit.add(root, gimli::DW_TAG_base_type);
+        let entry = unit.unit.get_mut(base);
+        entry.set(gimli::DW_AT_name, WriteValue::String(b"uint16_t".to_vec()));
+        entry.set(gimli::DW_AT_byte_size, WriteValue::Udata(2));
+        entry.set(gimli::DW_AT_encoding, WriteValue::Encoding(gimli::DW_ATE_unsigned));
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        unit.write(&mut sections).unwrap();
+        let dwarf = gimli::Dwarf::load(|id| -> gimli::Result<_> {
+            let data = sections.get(id).map_or(&[][..], |s| s.slice());
+            Ok(gimli::EndianSlice::new(data, LittleEndian))
+        })
+        .unwrap();
+
+        // Find the type's offset the way the ELF loader records it
+        let unit = dwarf.unit(dwarf.units().next().unwrap().unwrap()).unwrap();
+        let mut entries = unit.entries();
+        let mut offset = None;
+        while let Some((_, entry)) = entries.next_dfs().unwrap() {
+            if entry.tag() == gimli::DW_TAG_base_type {
+                offset = entry.offset().to_debug_info_offset(&unit.header).map(|o| o.0 as u64);
+            }
+        }
+        let offset = offset.unwrap();
+        let types = resolve_types_at(&dwarf, &[offset, offset + 0x1000]).unwrap();
+        assert_eq!(types.len(), 1);
+        assert_eq!(types[&offset], scalar("uint16_t", 2, ValueKind::Unsigned));
+    }
+}
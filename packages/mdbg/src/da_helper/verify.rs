@@ -0,0 +1,320 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Flash verification: compare memory read from the target with what the ELF loads there, without
+//! relying on the gdb-server supporting `compare-sections`.
+
+use anyhow::{anyhow, Result};
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::common::utils::{decode_hex, encode_hex};
+use crate::da_helper::helper_requests::{VerifyDump, VerifyMismatch};
+use crate::da_helper::image::MemoryImage;
+use crate::da_helper::memory_map::ElfLayout;
+use crate::da_helper::request_handler::parse_hex_address;
+use crate::da_helper::symbols::SymbolTable;
+
+/// Differences at most this many matching bytes apart are reported as one range
+const MERGE_GAP: u64 = 8;
+
+/// Bytes of each mismatch shown in the report
+const SAMPLE_BYTES: usize = 16;
+
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    pub compared: u64,
+    pub ignored: u64,
+    pub not_dumped: u64,
+    pub mismatches: Vec<VerifyMismatch>,
+}
+
+impl VerifyReport {
+    pub fn matched(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Put the dumps together into one image; where dumps overlap, the later one wins
+pub fn load_dumps(dumps: &[VerifyDump]) -> Result<MemoryImage> {
+    let mut image = MemoryImage::new();
+    for dump in dumps {
+        let address = match &dump.address {
+            Some(text) => Some(parse_hex_address(text).ok_or_else(|| anyhow!("Bad address '{}'", text))?),
+            None => None,
+        };
+        let part = match (&dump.file, &dump.data) {
+            (Some(file), None) => MemoryImage::load(Path::new(file), address.unwrap_or(0))?,
+            (None, Some(data)) => {
+                let address = address.ok_or_else(|| anyhow!("Inline data needs an address"))?;
+                let bytes = decode_hex(data.trim()).ok_or_else(|| anyhow!("Bad hex data at 0x{:x}", address))?;
+                MemoryImage::from_binary(&bytes, address)
+            }
+            _ => return Err(anyhow!("Each dump needs either a file or inline data")),
+        };
+        for (start, bytes) in part.runs() {
+            image.write(start, bytes);
+        }
+    }
+    Ok(image)
+}
+
+/// Compare `actual` (target memory) with `expected` (the ELF's load image) wherever both have bytes.
+/// Mismatches are named after the section holding them and the symbols at the run-time addresses.
+pub fn verify_image(
+    expected: &MemoryImage,
+    actual: &MemoryImage,
+    layout: &ElfLayout,
+    symbols: &SymbolTable,
+) -> VerifyReport {
+    let mut report = VerifyReport::default();
+    for (dump_start, dump_bytes) in actual.runs() {
+        let dump_end = dump_start + dump_bytes.len() as u64;
+        for (elf_start, elf_bytes) in expected.runs() {
+            let lo = dump_start.max(elf_start);
+            let hi = dump_end.min(elf_start + elf_bytes.len() as u64);
+            if lo >= hi {
+                continue;
+            }
+            report.compared += hi - lo;
+            let want = &elf_bytes[(lo - elf_start) as usize..(hi - elf_start) as usize];
+            let got = &dump_bytes[(lo - dump_start) as usize..(hi - dump_start) as usize];
+            // (start, one past the last differing byte, differing bytes)
+            let mut current: Option<(u64, u64, u64)> = None;
+            for (ix, (w, g)) in want.iter().zip(got).enumerate() {
+                if w == g {
+                    continue;
+                }
+                let addr = lo + ix as u64;
+                current = match current {
+                    Some((start, end, count)) if addr - end <= MERGE_GAP => Some((start, addr + 1, count + 1)),
+                    Some((start, end, count)) => {
+                        report
+                            .mismatches
+                            .push(describe(expected, actual, layout, symbols, start, end, count));
+                        Some((addr, addr + 1, 1))
+                    }
+                    None => Some((addr, addr + 1, 1)),
+                };
+            }
+            if let Some((start, end, count)) = current {
+                report
+                    .mismatches
+                    .push(describe(expected, actual, layout, symbols, start, end, count));
+            }
+        }
+    }
+    report.ignored = actual.data_len() - report.compared;
+    report.not_dumped = expected.data_len() - report.compared;
+    report
+}
+
+fn describe(
+    expected: &MemoryImage,
+    actual: &MemoryImage,
+    layout: &ElfLayout,
+    symbols: &SymbolTable,
+    start: u64,
+    end: u64,
+    differing: u64,
+) -> VerifyMismatch {
+    let section = layout
+        .sections
+        .iter()
+        .find(|s| s.has_data && start >= s.lma && start < s.lma + s.size);
+    // Symbols are at run-time addresses, which differ from load addresses for copied-down sections
+    let run_start = section.map_or(start, |s| start - s.lma + s.vma);
+    let mut names: Vec<String> = Vec::new();
+    for symbol in symbols.lookup_range(run_start, run_start + (end - start)) {
+        if !names.contains(&symbol.name) {
+            names.push(symbol.name.clone());
+        }
+    }
+    let sample = ((end - start) as usize).min(SAMPLE_BYTES);
+    VerifyMismatch {
+        start: format!("0x{:08x}", start),
+        size: end - start,
+        differing,
+        section: section.map(|s| s.name.clone()),
+        symbols: names,
+        expected: expected.read(start, sample).map(encode_hex).unwrap_or_default(),
+        actual: actual.read(start, sample).map(encode_hex).unwrap_or_default(),
+    }
+}
+
+/// Plain-text report for `mdbg elf verify`
+pub fn format_verify_report(report: &VerifyReport) -> String {
+    let mut out = String::new();
+    let differing: u64 = report.mismatches.iter().map(|m| m.differing).sum();
+    if report.matched() {
+        let _ = writeln!(out, "OK: {} bytes compared, all match", report.compared);
+    } else {
+        let _ = writeln!(
+            out,
+            "MISMATCH: {} of {} bytes compared differ, in {} range(s)",
+            differing,
+            report.compared,
+            report.mismatches.len()
+        );
+    }
+    for m in &report.mismatches {
+        let _ = writeln!(
+            out,
+            "  {}  {:>6} bytes  {:<12} {}",
+            m.start,
+            m.size,
+            m.section.as_deref().unwrap_or("-"),
+            m.symbols.join(", ")
+        );
+        let _ = writeln!(out, "      expected {}", m.expected);
+        let _ = writeln!(out, "      actual   {}", m.actual);
+    }
+    if report.not_dumped > 0 {
+        let _ = writeln!(out, "Not dumped: {} bytes of ELF contents", report.not_dumped);
+    }
+    if report.ignored > 0 {
+        let _ = writeln!(
+            out,
+            "Ignored: {} dumped bytes outside the ELF's contents",
+            report.ignored
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::da_helper::memory_map::AllocSection;
+    use crate::da_helper::symbols::{Symbol, SymbolScope, SymbolType};
+
+    fn symbol(name: &str, address: u64, size: u64) -> Symbol {
+        Symbol {
+            name: name.to_string(),
+            raw_name: name.to_string(),
+            address,
+            size,
+            kind: SymbolType::Data,
+            scope: SymbolScope::Global,
+            section: None,
+            decl_file: None,
+            decl_line: None,
+            type_offset: None,
+        }
+    }
+
+    fn fixture() -> (MemoryImage, ElfLayout, SymbolTable) {
+        let mut expected = MemoryImage::new();
+        expected.write(0x0800_0000, &[0x11; 0x100]);
+        // .data: runs at 0x2000_0000, stored after .text
+        expected.write(0x0800_0100, &[0x22; 0x20]);
+        let layout = ElfLayout {
+            segments: Vec::new(),
+            sections: vec![
+                AllocSection {
+                    name: ".text".to_string(),
+                    vma: 0x0800_0000,
+                    lma: 0x0800_0000,
+                    size: 0x100,
+                    has_data: true,
+                },
+                AllocSection {
+                    name: ".data".to_string(),
+                    vma: 0x2000_0000,
+                    lma: 0x0800_0100,
+                    size: 0x20,
+                    has_data: true,
+                },
+            ],
+        };
+        let mut symbols = SymbolTable::new();
+        symbols.insert(symbol("main", 0x0800_0040, 0x40));
+        symbols.insert(symbol("counter", 0x2000_0010, 4));
+        (expected, layout, symbols)
+    }
+
+    #[test]
+    fn matching_dump_with_partial_coverage() {
+        let (expected, layout, symbols) = fixture();
+        let mut actual = MemoryImage::from_binary(&[0x11; 0x80], 0x0800_0000);
+        actual.write(0x0900_0000, &[0; 4]);
+        let report = verify_image(&expected, &actual, &layout, &symbols);
+        assert!(report.matched());
+        assert_eq!(report.compared, 0x80);
+        assert_eq!(report.ignored, 4);
+        assert_eq!(report.not_dumped, 0xa0);
+    }
+
+    #[test]
+    fn mismatches_merge_and_name_symbols() {
+        let (expected, layout, symbols) = fixture();
+        let mut actual = expected.clone();
+        actual.write(0x0800_0050, &[0]);
+        actual.write(0x0800_0055, &[0, 0]);
+        actual.write(0x0800_0070, &[0]);
+        // counter's initial value, in flash
+        actual.write(0x0800_0110, &[0x33]);
+        let report = verify_image(&expected, &actual, &layout, &symbols);
+        assert_eq!(report.compared, 0x120);
+        let found: Vec<_> = report
+            .mismatches
+            .iter()
+            .map(|m| {
+                (
+                    m.start.as_str(),
+                    m.size,
+                    m.differing,
+                    m.section.as_deref(),
+                    m.symbols.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("0x08000050", 7, 3, Some(".text"), vec!["main".to_string()]),
+                ("0x08000070", 1, 1, Some(".text"), vec!["main".to_string()]),
+                ("0x08000110", 1, 1, Some(".data"), vec!["counter".to_string()]),
+            ]
+        );
+        assert_eq!(report.mismatches[0].expected, "11111111111111");
+        assert_eq!(report.mismatches[0].actual, "00111111110000");
+        assert!(format_verify_report(&report).starts_with("MISMATCH: 5 of 288 bytes compared differ, in 3 range(s)"));
+    }
+
+    #[test]
+    fn dumps_from_inline_data() {
+        let dumps = vec![
+            VerifyDump {
+                file: None,
+                address: Some("0x100".to_string()),
+                data: Some("01020304".to_string()),
+            },
+            VerifyDump {
+                file: None,
+                address: Some("102".to_string()),
+                data: Some("ff".to_string()),
+            },
+        ];
+        let image = load_dumps(&dumps).unwrap();
+        assert_eq!(image.read(0x100, 4), Some(&[1, 2, 0xff, 4][..]));
+
+        let missing = vec![VerifyDump {
+            file: None,
+            address: None,
+            data: Some("00".to_string()),
+        }];
+        assert!(load_dumps(&missing).is_err());
+    }
+}
@@ -17,6 +17,7 @@ pub mod cockpit;
 pub mod common;
 pub mod da_helper;
 pub mod proxy_helper;
+pub mod rsp;
 pub mod serial;
 
 // Re-export commonly used API from the library for binaries/tests
@@ -17,7 +17,10 @@ use clap::{Parser, Subcommand};
 
 use mdbg::cockpit::run::AttachArgs;
 use mdbg::cockpit::run::DebugArgs;
+use mdbg::da_helper::core_cmd::CoreArgs;
+use mdbg::da_helper::elf_cmd::ElfArgs;
 use mdbg::da_helper::run::DaHelperArgs;
+use mdbg::da_helper::sim_cmd::SimArgs;
 use mdbg::proxy_helper::run::ProxyArgs;
 use mdbg::serial::cmd::SerialArgs;
 
@@ -46,6 +49,18 @@ enum Commands {
     #[command(name = "da-helper")]
     DaHelper(DaHelperArgs),
 
+    /// Offline ELF analysis: control-flow graphs and other one-shot queries
+    #[command(name = "elf")]
+    Elf(ElfArgs),
+
+    /// Post-mortem analysis of a core dump, and a GDB stub that serves it
+    #[command(name = "core")]
+    Core(CoreArgs),
+
+    /// GDB server with a simulated target loaded from an ELF, for tests without hardware
+    #[command(name = "sim-server")]
+    SimServer(SimArgs),
+
     /// Probe Agent: remote gdb-server orchestration via the Funnel Protocol
     #[command(name = "proxy")]
     Proxy(ProxyArgs),
@@ -83,7 +98,7 @@ fn main() -> Result<()> {
         let has_sub = args.get(1).is_some_and(|a| {
             matches!(
                 a.as_str(),
-                "debug" | "attach" | "da-helper" | "proxy" | "serial"
+                "debug" | "attach" | "da-helper" | "elf" | "core" | "sim-server" | "proxy" | "serial"
             )
         });
         if !has_sub {
@@ -97,6 +112,9 @@ fn main() -> Result<()> {
         Commands::Debug(args) => mdbg::cockpit::run::run(args),
         Commands::Attach(args) => mdbg::cockpit::run::attach(args),
         Commands::DaHelper(args) => mdbg::da_helper::run::run(args),
+        Commands::Elf(args) => mdbg::da_helper::elf_cmd::run(args),
+        Commands::Core(args) => mdbg::da_helper::core_cmd::run(args),
+        Commands::SimServer(args) => mdbg::da_helper::sim_cmd::run(args),
         Commands::Proxy(args) => mdbg::proxy_helper::run::run(args),
         Commands::Serial(args) => mdbg::serial::cmd::run(args),
     }
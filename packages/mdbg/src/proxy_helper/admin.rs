@@ -14,8 +14,8 @@
 
 //! Admin control channel (Tier 1, Phase C).
 //!
-//! Proxy-global operations — `status`, `shutdown`, `serialClose` — travel over the **same**
-//! listener as sessions. A newly accepted connection is discriminated by its
+//! Proxy-global operations — `status`, `shutdown`, `serialClose`, `sessions` — travel
+//! over the **same** listener as sessions. A newly accepted connection is discriminated by its
 //! first byte: a funnel session's first frame is a control message on stream 0
 //! (first byte `0x00`); an admin request is a single line of JSON (first byte
 //! `{`). See `discriminate` and `run.rs`'s accept loop.
@@ -33,6 +33,7 @@ use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 
 use crate::proxy_helper::lifetime::Lifetime;
+use crate::proxy_helper::session_log::{self, CaptureStream, SessionSummary};
 use crate::proxy_helper::singleton::{self, Endpoint};
 
 /// How a freshly accepted connection should be handled.
@@ -67,7 +68,8 @@ pub fn discriminate(stream: &TcpStream) -> Kind {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AdminRequest {
     pub v: u32,
-    /// `"status"` | `"shutdown"` | `"upgrade"` | `"serialClose"` | `"widen"` | `"narrow"`.
+    /// `"status"` | `"shutdown"` | `"upgrade"` | `"serialClose"` | `"widen"` | `"narrow"` |
+    /// `"sessions"` | `"sessionLog"`.
     pub cmd: String,
     #[serde(default)]
     pub token: String,
@@ -86,6 +88,15 @@ pub struct AdminRequest {
     /// Empty for every other command.
     #[serde(default)]
     pub host: String,
+    /// For `sessionLog`: the session id, or the client's session uid (newest match).
+    #[serde(default)]
+    pub session: String,
+    /// For `sessionLog`: `"stdout"` (the default when empty), `"stderr"` or `"control"`.
+    #[serde(default)]
+    pub stream: String,
+    /// For `sessionLog`: return only the last this many lines; 0 returns everything.
+    #[serde(default)]
+    pub tail: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -125,6 +136,12 @@ pub struct AdminResponse {
     /// For `widen`/`narrow`: every address the proxy accepts on after the change.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub hosts: Vec<String>,
+    /// For `sessions`: recorded sessions, newest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sessions: Vec<SessionSummary>,
+    /// For `sessionLog`: the captured text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log: Option<String>,
 }
 
 impl AdminResponse {
@@ -136,15 +153,24 @@ impl AdminResponse {
             message: None,
             closed: Vec::new(),
             hosts: Vec::new(),
+            sessions: Vec::new(),
+            log: None,
         }
     }
 }
 
 // ── Line-JSON framing ─────────────────────────────────────────────────────────
 
+/// Longest request line accepted. Requests are a handful of short fields.
+const MAX_REQUEST_LINE: usize = 64 * 1024;
+
+/// Longest reply line accepted. A `sessionLog` reply carries up to
+/// `session_log::MAX_FETCH_BYTES` of text, which JSON escaping can inflate several times.
+const MAX_REPLY_LINE: usize = 8 * 1024 * 1024;
+
 /// Read a single `\n`-terminated line without over-reading past it (so the
 /// funnel stream is never disturbed if we misclassify).
-fn read_line(stream: &mut TcpStream) -> Result<String> {
+fn read_line(stream: &mut TcpStream, max_len: usize) -> Result<String> {
     let mut buf = Vec::new();
     let mut byte = [0u8; 1];
     loop {
@@ -155,8 +181,8 @@ fn read_line(stream: &mut TcpStream) -> Result<String> {
                     break;
                 }
                 buf.push(byte[0]);
-                if buf.len() > 64 * 1024 {
-                    bail!("admin request line too long");
+                if buf.len() > max_len {
+                    bail!("admin line too long");
                 }
             }
         }
@@ -200,6 +226,8 @@ pub struct AdminContext {
     pub version: String,
     pub instance: String,
     pub started_at_unix: u64,
+    /// Where sessions record their gdb-server output, for `sessions`/`sessionLog`.
+    pub sessions_root: PathBuf,
 }
 
 /// Handle one admin connection: read the request line, act, reply, close.
@@ -211,7 +239,7 @@ pub fn handle(mut stream: TcpStream, ctx: &Arc<AdminContext>) {
         .peer_addr()
         .map(|a| a.ip().is_loopback())
         .unwrap_or(false);
-    let resp = match read_line(&mut stream).and_then(|l| {
+    let resp = match read_line(&mut stream, MAX_REQUEST_LINE).and_then(|l| {
         serde_json::from_str::<AdminRequest>(l.trim()).context("invalid admin request JSON")
     }) {
         Ok(req) if req.token != ctx.token => AdminResponse::err("bad token"),
@@ -247,12 +275,25 @@ fn dispatch(req: &AdminRequest, ctx: &Arc<AdminContext>, peer_is_loopback: bool)
             }),
             closed: Vec::new(),
             hosts: Vec::new(),
+            sessions: Vec::new(),
+            log: None,
         },
         "shutdown" => begin_drain(ctx),
         "upgrade" => begin_upgrade(req, ctx),
         "serialClose" => close_serial(req, ctx),
         "widen" => widen(req, ctx, peer_is_loopback),
         "narrow" => narrow(req, ctx, peer_is_loopback),
+        "sessions" => AdminResponse {
+            ok: true,
+            error: None,
+            status: None,
+            message: None,
+            closed: Vec::new(),
+            hosts: Vec::new(),
+            sessions: session_log::list_sessions(&ctx.sessions_root),
+            log: None,
+        },
+        "sessionLog" => fetch_session_log(req, ctx),
         other => AdminResponse::err(format!("unknown admin cmd: {other}")),
     }
 }
@@ -291,6 +332,8 @@ fn begin_upgrade(req: &AdminRequest, ctx: &Arc<AdminContext>) -> AdminResponse {
         )),
         closed: Vec::new(),
         hosts: Vec::new(),
+        sessions: Vec::new(),
+        log: None,
     }
 }
 
@@ -323,6 +366,8 @@ fn begin_drain(ctx: &Arc<AdminContext>) -> AdminResponse {
         )),
         closed: Vec::new(),
         hosts: Vec::new(),
+        sessions: Vec::new(),
+        log: None,
     }
 }
 
@@ -355,6 +400,8 @@ fn close_serial(req: &AdminRequest, ctx: &Arc<AdminContext>) -> AdminResponse {
         message: Some(message),
         closed,
         hosts: Vec::new(),
+        sessions: Vec::new(),
+        log: None,
     }
 }
 
@@ -386,6 +433,8 @@ fn widen(req: &AdminRequest, ctx: &Arc<AdminContext>, peer_is_loopback: bool) ->
                 message: Some(format!("accepting on {addr}")),
                 closed: Vec::new(),
                 hosts,
+                sessions: Vec::new(),
+                log: None,
             }
         }
         Err(e) => AdminResponse::err(e),
@@ -417,12 +466,47 @@ fn narrow(req: &AdminRequest, ctx: &Arc<AdminContext>, peer_is_loopback: bool) -
                 }),
                 closed: Vec::new(),
                 hosts,
+                sessions: Vec::new(),
+                log: None,
             }
         }
         Err(e) => AdminResponse::err(e),
     }
 }
 
+/// Return the captured gdb-server output (or control traffic) of a recorded session.
+///
+/// Read straight from disk, so it works as well for a session that died an hour ago
+/// as for one still running. A reply holds at most `MAX_FETCH_BYTES` of the newest
+/// text; `message` says when older output was left out.
+fn fetch_session_log(req: &AdminRequest, ctx: &Arc<AdminContext>) -> AdminResponse {
+    if req.session.is_empty() {
+        return AdminResponse::err("sessionLog requires a session id");
+    }
+    let stream = if req.stream.is_empty() { "stdout" } else { req.stream.as_str() };
+    let Some(stream) = CaptureStream::parse(stream) else {
+        return AdminResponse::err(format!(
+            "unknown stream '{}' (expected stdout, stderr or control)",
+            req.stream
+        ));
+    };
+    match session_log::read_capture(&ctx.sessions_root, &req.session, stream, req.tail) {
+        Ok(fetched) => AdminResponse {
+            ok: true,
+            error: None,
+            status: None,
+            message: fetched
+                .truncated
+                .then(|| format!("showing the end of session {}", fetched.session.id)),
+            closed: Vec::new(),
+            hosts: Vec::new(),
+            sessions: vec![fetched.session],
+            log: Some(fetched.text),
+        },
+        Err(e) => AdminResponse::err(format!("{e:#}")),
+    }
+}
+
 /// Republish the endpoint's host list so a later discovery read reflects reality.
 /// Best-effort: the proxy is already serving the new address either way, and failing
 /// the request over a file write would be worse than a stale anchor.
@@ -448,6 +532,9 @@ pub fn request_upgrade(endpoint: &Endpoint, my_version: &str) -> Result<AdminRes
         path: String::new(),
         host: String::new(),
         version: my_version.into(),
+        session: String::new(),
+        stream: String::new(),
+        tail: 0,
     };
     let resp = query(endpoint, &req)?;
     if !resp.ok {
@@ -468,6 +555,6 @@ pub fn query(endpoint: &Endpoint, req: &AdminRequest) -> Result<AdminResponse> {
     line.push('\n');
     stream.write_all(line.as_bytes())?;
     stream.flush()?;
-    let reply = read_line(&mut stream)?;
+    let reply = read_line(&mut stream, MAX_REPLY_LINE)?;
     serde_json::from_str(reply.trim()).context("invalid admin reply JSON")
 }
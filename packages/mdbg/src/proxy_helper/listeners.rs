@@ -682,6 +682,10 @@ mod tests {
             shutdown: false,
             all: false,
             close_serial: None,
+            sessions: false,
+            session_log: None,
+            log_stream: "stdout".to_string(),
+            tail: 0,
             daemonized: true,
         };
         let admin_ctx = Arc::new(AdminContext {
@@ -700,6 +704,7 @@ mod tests {
             version: "test".to_string(),
             instance: "default".to_string(),
             started_at_unix: 0,
+            sessions_root: std::path::PathBuf::from("/nonexistent/sessions"),
         });
         AcceptCtx {
             conn_args,
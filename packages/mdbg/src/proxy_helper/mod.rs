@@ -22,4 +22,5 @@ pub mod port_monitor;
 pub mod proxy_server;
 pub mod run;
 pub mod serial_available;
+pub mod session_log;
 pub mod singleton;
@@ -12,7 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-// For each platform we want to run a system command to wait for the ports to be open. This is needed because
+// On Linux we read the kernel's socket tables in /proc; elsewhere we run a system command to wait for the ports to
+// be open. This is needed because
 // the gdb-server if we want to do it in a non-invasive way (i.e. without connecting to it ourselves or creating
 // a serveron the port). For some gdb servers, we may be only allowed on connection and may not allow a reconnection
 // if we disconnect. Also, gdb-servers may timeout if we open on the server side and there is no client connection.
@@ -23,7 +24,7 @@ use std::sync::mpsc::Sender;
 
 use std::time::Duration;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::proxy_helper::proxy_server::ProxyEvent;
 
@@ -137,8 +138,8 @@ fn get_port_waiter_command() -> std::io::Result<(String, Vec<String>)> {
         format!(
             "no usable tool to detect listening ports (tried: {}). \
              mcu-debug needs one of these to tell when the gdb-server is ready; \
-             probing the port by connecting to it is not an option, because that is \
-             indistinguishable from gdb connecting and makes the server hang. \
+             without one it falls back to connecting to the port, which is \
+             indistinguishable from gdb connecting and can make the server hang. \
              Install one of them (on Linux, iproute2 provides `ss`).",
             if names.is_empty() {
                 "none — unsupported platform".to_string()
@@ -149,10 +150,190 @@ fn get_port_waiter_command() -> std::io::Result<(String, Vec<String>)> {
     ))
 }
 
-pub fn wait_for_ports(ports: Vec<(u8, u16)>, tx: Sender<ProxyEvent>, stop_rx: Receiver<()>) -> std::io::Result<()> {
-    // Resolved on this thread so a missing utility fails the caller loudly rather than
-    // dying quietly inside the monitor thread.
-    let (prog, args) = get_port_waiter_command()?;
+/// How the monitor learns which ports are listening.
+enum ListenDetector {
+    /// Read the kernel's socket tables under `/proc` (Linux). Needs no tools, so it
+    /// works in the minimal containers that ship without `ss` or `lsof`.
+    ProcNet { owner: Option<u32> },
+    /// Run a listing tool and search its output.
+    Command { prog: String, args: Vec<String> },
+    /// Connect to the port and hang up at once. The last resort, for a machine with
+    /// neither `/proc` nor any of the tools: a connect looks to the gdb-server like
+    /// gdb arriving, and openocd in particular may then wait for traffic that never
+    /// comes. A server that copes with that is better than a session that never
+    /// learns its server is up.
+    Connect,
+}
+
+impl ListenDetector {
+    fn for_this_platform(owner: Option<u32>) -> Self {
+        if cfg!(target_os = "linux") && std::fs::metadata(PROC_NET_TCP[0]).is_ok() {
+            eprintln!("Port monitor reading /proc/net to detect listening ports");
+            return ListenDetector::ProcNet { owner };
+        }
+        match get_port_waiter_command() {
+            Ok((prog, args)) => ListenDetector::Command { prog, args },
+            Err(e) => {
+                eprintln!("{}; probing ports by connecting to them instead", e);
+                ListenDetector::Connect
+            }
+        }
+    }
+
+    /// Which of `ports` are listening right now.
+    fn listening(&self, ports: &[u16]) -> std::io::Result<HashSet<u16>> {
+        match self {
+            ListenDetector::ProcNet { owner } => {
+                let mut sockets = Vec::new();
+                for path in PROC_NET_TCP {
+                    // tcp6 is absent on kernels built without IPv6.
+                    if let Ok(text) = std::fs::read_to_string(path) {
+                        sockets.extend(parse_proc_net_tcp(&text));
+                    }
+                }
+                // The owner's sockets are looked up only once a wanted port is
+                // listening, which keeps the walk over `/proc` off the idle polls.
+                if !sockets.iter().any(|(port, _)| ports.contains(port)) {
+                    return Ok(HashSet::new());
+                }
+                // Without sight of the owner's sockets (started through sudo or pkexec, or
+                // `/proc` mounted with hidepid) nothing would ever count as owned, and the
+                // session would wait forever; any listener is better than that.
+                let owned = owner.and_then(|pid| match socket_inodes_of_tree(pid) {
+                    Ok(owned) => Some(owned),
+                    Err(e) => {
+                        eprintln!(
+                            "Port monitor can't see the sockets of process {} ({}); accepting a listener from any process",
+                            pid, e
+                        );
+                        None
+                    }
+                });
+                Ok(sockets
+                    .into_iter()
+                    .filter(|(port, inode)| {
+                        ports.contains(port) && owned.as_ref().is_none_or(|owned| owned.contains(inode))
+                    })
+                    .map(|(port, _)| port)
+                    .collect())
+            }
+            ListenDetector::Command { prog, args } => {
+                let mut command = std::process::Command::new(prog);
+                command.args(args);
+                crate::common::process::suppress_console_window(&mut command);
+                let output = command.output().map_err(|e| {
+                    eprintln!("Failed to execute port waiter command '{}': {}", prog, e);
+                    e
+                })?;
+                if !output.status.success() {
+                    return Err(std::io::Error::other(format!(
+                        "port waiter command failed with status {}: {}",
+                        output.status,
+                        String::from_utf8_lossy(&output.stderr)
+                    )));
+                }
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                Ok(ports
+                    .iter()
+                    .copied()
+                    .filter(|port| stdout.lines().any(|line| is_port_listening_line(line, *port)))
+                    .collect())
+            }
+            ListenDetector::Connect => Ok(ports
+                .iter()
+                .copied()
+                .filter(|port| {
+                    let addr = std::net::SocketAddr::from((std::net::Ipv4Addr::LOCALHOST, *port));
+                    std::net::TcpStream::connect_timeout(&addr, Duration::from_millis(100)).is_ok()
+                })
+                .collect()),
+        }
+    }
+}
+
+const PROC_NET_TCP: [&str; 2] = ["/proc/net/tcp", "/proc/net/tcp6"];
+
+/// `(port, inode)` of every listening socket in a `/proc/net/tcp` or `tcp6` table.
+///
+/// Rows look like
+/// `0: 0100007F:0D05 00000000:0000 0A 00000000:00000000 00:00000000 00000000 1000 0 51234 ...`
+/// — the local endpoint is `<hex addr>:<hex port>`, state `0A` is LISTEN, and the
+/// inode is the tenth column.
+fn parse_proc_net_tcp(text: &str) -> Vec<(u16, u64)> {
+    const TCP_LISTEN: &str = "0A";
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if cols.len() < 10 || cols[3] != TCP_LISTEN {
+                return None;
+            }
+            let (_, port) = cols[1].rsplit_once(':')?;
+            let port = u16::from_str_radix(port, 16).ok()?;
+            let inode = cols[9].parse().ok()?;
+            Some((port, inode))
+        })
+        .collect()
+}
+
+/// Socket inodes held open by `root` and every process descended from it.
+///
+/// Descendants count because gdb-servers are often started through wrappers:
+/// `ST-LINK_gdbserver` is a shell script that runs the real server as its child,
+/// and that child is what binds the ports. Fails if the `fd` directory of any process
+/// in the tree cannot be read, since the sockets it holds are then unknown.
+fn socket_inodes_of_tree(root: u32) -> std::io::Result<HashSet<u64>> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    if let Ok(entries) = std::fs::read_dir("/proc") {
+        for entry in entries.flatten() {
+            let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+                continue;
+            };
+            // `pid (comm) state ppid ...`, where comm may itself contain ") ".
+            let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+                continue;
+            };
+            let ppid = stat
+                .rsplit_once(')')
+                .and_then(|(_, rest)| rest.split_whitespace().nth(1))
+                .and_then(|ppid| ppid.parse::<u32>().ok());
+            if let Some(ppid) = ppid {
+                children.entry(ppid).or_default().push(pid);
+            }
+        }
+    }
+
+    let mut inodes = HashSet::new();
+    let mut pending = vec![root];
+    while let Some(pid) = pending.pop() {
+        let dir = format!("/proc/{pid}/fd");
+        let fds = std::fs::read_dir(&dir).map_err(|e| std::io::Error::new(e.kind(), format!("{dir}: {e}")))?;
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            let inode = target
+                .to_str()
+                .and_then(|t| t.strip_prefix("socket:["))
+                .and_then(|t| t.strip_suffix(']'))
+                .and_then(|t| t.parse().ok());
+            if let Some(inode) = inode {
+                inodes.insert(inode);
+            }
+        }
+        pending.extend(children.remove(&pid).unwrap_or_default());
+    }
+    Ok(inodes)
+}
+
+/// Report each of `ports` as `PortReady` once something listens on it.
+///
+/// With an `owner`, and where ports can be attributed to processes (Linux), only
+/// sockets held by that process or its descendants count — a stranger that grabbed
+/// the port between our release of it and the gdb-server's bind is not mistaken for
+/// the server. If the owner's sockets can't be seen, any listener counts.
+pub fn wait_for_ports(ports: Vec<(u8, u16)>, owner: Option<u32>, tx: Sender<ProxyEvent>, stop_rx: Receiver<()>) {
+    let detector = ListenDetector::for_this_platform(owner);
     std::thread::spawn(move || {
         let mut port_map =
             HashMap::<u16, u8>::from_iter(ports.iter().cloned().map(|(stream_id, port)| (port, stream_id)));
@@ -172,31 +353,18 @@ pub fn wait_for_ports(ports: Vec<(u8, u16)>, tx: Sender<ProxyEvent>, stop_rx: Re
                 Err(std::sync::mpsc::TryRecvError::Empty) => {}
             }
 
-            let mut command = std::process::Command::new(&prog);
-            command.args(&args);
-            crate::common::process::suppress_console_window(&mut command);
-            let output = command.output().map_err(|e| {
-                eprintln!("Failed to execute port waiter command '{}': {}", prog, e);
-                e
-            });
-            let output = match output {
-                Ok(output) => output,
-                Err(_) => break,
+            let wanted: Vec<u16> = port_map.keys().copied().collect();
+            let listening = match detector.listening(&wanted) {
+                Ok(listening) => listening,
+                Err(e) => {
+                    eprintln!("Port monitor could not list listening ports: {}", e);
+                    break;
+                }
             };
-            if !output.status.success() {
-                eprintln!(
-                    "Port waiter command failed with status {}: {}",
-                    output.status,
-                    String::from_utf8_lossy(&output.stderr)
-                );
-                break;
-            }
-            let stdout = String::from_utf8_lossy(&output.stdout);
             let mut ready_ports = Vec::<u16>::new();
 
             for (port, stream_id) in &port_map {
-                let found = stdout.lines().any(|line| is_port_listening_line(line, *port));
-                if found {
+                if listening.contains(port) {
                     if tx
                         .send(ProxyEvent::PortReady {
                             stream_id: *stream_id,
@@ -258,7 +426,6 @@ pub fn wait_for_ports(ports: Vec<(u8, u16)>, tx: Sender<ProxyEvent>, stop_rx: Re
             }
         }
     });
-    Ok(())
 }
 
 #[cfg(test)]
@@ -322,9 +489,10 @@ mod tests {
         ));
     }
 
-    /// This machine must have one of the tools. If it does not, every session would hang
-    /// waiting for a port that is never reported ready — so the failure has to name
-    /// itself rather than being discovered as a timeout.
+    /// Off Linux this machine must have one of the tools. If it does not, sessions fall
+    /// back to connect probing, which some servers do not survive — so the gap has to
+    /// name itself here rather than be discovered as a hung openocd.
+    #[cfg(not(target_os = "linux"))]
     #[test]
     fn a_port_waiter_command_is_available_on_this_platform() {
         let cmd = get_port_waiter_command();
@@ -335,4 +503,76 @@ mod tests {
             cmd.err().map(|e| e.to_string()).unwrap_or_default()
         );
     }
+
+    /// Linux needs no tool at all: that is the point of reading `/proc`.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn linux_reads_proc_instead_of_running_a_tool() {
+        assert!(matches!(
+            ListenDetector::for_this_platform(None),
+            ListenDetector::ProcNet { owner: None }
+        ));
+    }
+
+    #[test]
+    fn parses_listening_rows_of_proc_net_tcp() {
+        let tcp = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0D05 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 51234 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:0D05 0100007F:D431 01 00000000:00000000 00:00000000 00000000  1000        0 51240 1 0000000000000000 20 4 30 10 -1
+";
+        let tcp6 = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:4E20 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 777 1 0000000000000000 100 0 0 10 0
+";
+        // The established connection on the same port is not a listener.
+        assert_eq!(parse_proc_net_tcp(tcp), [(3333, 51234)]);
+        assert_eq!(parse_proc_net_tcp(tcp6), [(20000, 777)]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn proc_net_sees_a_listener_and_attributes_it_to_its_owner() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let listening = |owner| ListenDetector::ProcNet { owner }.listening(&[port]).unwrap();
+
+        assert!(listening(None).contains(&port));
+        assert!(listening(Some(std::process::id())).contains(&port));
+
+        let mut stranger = std::process::Command::new("sleep").arg("5").spawn().unwrap();
+        let owned_by_stranger = listening(Some(stranger.id()));
+        stranger.kill().ok();
+        stranger.wait().ok();
+        assert!(!owned_by_stranger.contains(&port));
+    }
+
+    /// An owner whose `fd` directory can't be read (here, as under `hidepid=2`, because
+    /// it isn't there at all) must not leave the session waiting forever.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn proc_net_accepts_any_listener_when_the_owner_is_out_of_sight() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        // Above the kernel's largest pid_max, so no such process
+        let unseen = u32::MAX;
+
+        assert!(socket_inodes_of_tree(unseen).is_err());
+        let listening = ListenDetector::ProcNet { owner: Some(unseen) }.listening(&[port]).unwrap();
+        assert!(listening.contains(&port));
+    }
+
+    #[test]
+    fn connect_probing_finds_only_open_ports() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let open = listener.local_addr().unwrap().port();
+        let closed = {
+            let gone = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            gone.local_addr().unwrap().port()
+        };
+
+        let listening = ListenDetector::Connect.listening(&[open, closed]).unwrap();
+
+        assert_eq!(listening, HashSet::from([open]));
+    }
 }
@@ -16,7 +16,7 @@
 //! session initialization, port allocation, and file sync.
 
 use anyhow::{anyhow, Result};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
 use std::io::Read;
@@ -29,6 +29,9 @@ use std::time::{Duration, Instant};
 
 use crate::common::tcpports::reserve_free_ports;
 use crate::proxy_helper::port_monitor::wait_for_ports;
+use crate::proxy_helper::session_log;
+
+use super::presets::{self, ReadyScanner};
 
 use super::*;
 
@@ -36,7 +39,17 @@ use super::*;
 
 /// Read from `reader` in a loop and send each chunk to `tx` as a `StreamData`
 /// event. Sends `StreamClosed` on EOF or error.
-fn read_and_forward<R: Read>(stream_id: u8, mut reader: R, tx: Sender<ProxyEvent>) {
+fn read_and_forward<R: Read>(stream_id: u8, reader: R, tx: Sender<ProxyEvent>) {
+    read_and_forward_with(stream_id, reader, tx, |_| {});
+}
+
+/// [`read_and_forward`], calling `inspect` on each chunk after it is sent.
+fn read_and_forward_with<R: Read, F: FnMut(&[u8])>(
+    stream_id: u8,
+    mut reader: R,
+    tx: Sender<ProxyEvent>,
+    mut inspect: F,
+) {
     let mut buffer = [0; 4096];
     loop {
         match reader.read(&mut buffer) {
@@ -49,6 +62,7 @@ fn read_and_forward<R: Read>(stream_id: u8, mut reader: R, tx: Sender<ProxyEvent
                 if tx.send(ProxyEvent::StreamData { stream_id, data }).is_err() {
                     break;
                 }
+                inspect(&buffer[..n]);
             }
             Err(_) => {
                 tx.send(ProxyEvent::StreamClosed { stream_id }).ok();
@@ -58,6 +72,24 @@ fn read_and_forward<R: Read>(stream_id: u8, mut reader: R, tx: Sender<ProxyEvent
     }
 }
 
+/// [`read_and_forward`] for the gdb-server's stdout and stderr: when a `scanner` is
+/// given, each chunk is also searched for ready lines, and the ports they announce
+/// are reported as `PortReady` after the chunk itself is forwarded.
+fn forward_server_output<R: Read>(stream_id: u8, reader: R, tx: Sender<ProxyEvent>, scanner: Option<ReadyScanner>) {
+    let Some(mut scanner) = scanner else {
+        read_and_forward(stream_id, reader, tx);
+        return;
+    };
+    let ready_tx = tx.clone();
+    let inspect = move |data: &[u8]| {
+        for (stream_id, port) in scanner.feed(data) {
+            eprintln!("gdb-server output reports port {} (stream {}) ready", port, stream_id);
+            ready_tx.send(ProxyEvent::PortReady { stream_id, port }).ok();
+        }
+    };
+    read_and_forward_with(stream_id, reader, tx, inspect);
+}
+
 /// Return value from [`ProxyServer::wait_and_connect_sync`].
 pub enum WaitPortResult {
     /// A live TCP stream (when `keep_open == true`).
@@ -152,6 +184,7 @@ impl ProxyServer {
             } else {
                 eprintln!("Initialization successful");
                 self.server_cwd = dir.clone();
+                self.open_session_log(msg, workspace_uid, session_uid);
                 let data = ControlResponseData::Initialize {
                     version: CURRENT_VERSION.to_string(),
                     server_cwd: dir,
@@ -174,6 +207,23 @@ impl ProxyServer {
         }
     }
 
+    /// Start recording this session's server output and control traffic. A failure
+    /// costs only the record, so it is logged and the session carries on.
+    fn open_session_log(&self, msg: &ControlMessage, workspace_uid: &str, session_uid: &str) {
+        let root = session_log::sessions_root(&crate::proxy_helper::run::log_dir(&self.args));
+        match session_log::SessionLog::create(&root, workspace_uid, session_uid) {
+            Ok(log) => {
+                eprintln!("Recording session to {}", root.join(log.id()).display());
+                // The request that opened the log came in before it existed.
+                if let Ok(bytes) = serde_json::to_vec(msg) {
+                    log.control(session_log::Direction::Recv, &bytes);
+                }
+                self.writer.attach_session_log(Arc::new(log));
+            }
+            Err(e) => log::warn!("Session output will not be recorded: {e:#}"),
+        }
+    }
+
     pub(super) fn handle_allocate_ports(&mut self, msg: &ControlMessage) {
         if let ControlRequest::AllocatePorts { ports_spec } = &msg.request {
             let mut ret_vec: Vec<PortReserved> = Vec::new();
@@ -260,81 +310,22 @@ impl ProxyServer {
             server_path,
             server_args,
             server_env,
+            restart,
+            cwd,
+            limits,
         } = &msg.request
         {
-            self.stop_port_monitor();
-            let ports: Vec<(u8, u16)> = self.reserved_ports.drain(..).map(|p| (p.stream_id, p.port)).collect();
-            let dir = self.server_cwd.clone();
-            let mut command = Command::new(server_path);
-            command
-                .args(server_args)
-                .envs(server_env.as_ref().unwrap_or(&HashMap::new()))
-                .current_dir(dir)
-                .stdout(std::process::Stdio::piped())
-                .stderr(std::process::Stdio::piped());
-            crate::common::process::suppress_console_window(&mut command);
-            let child = match command.spawn() {
-                Ok(child) => child,
-                Err(e) => {
-                    eprintln!("Failed to launch gdb-server: {}: {}", server_path, e);
-                    ControlResponse::error(msg.seq, format!("Failed to launch gdb-server: {}: {}", server_path, e))
-                        .send(&self.writer)
-                        .ok();
-                    self.exit = true;
-                    return;
-                }
+            let mut launch = GdbLaunch::new(
+                server_path.clone(),
+                server_args.clone(),
+                server_env.clone(),
+                restart.clone(),
+            );
+            launch.cwd = cwd.clone();
+            launch.limits = limits.clone();
+            let Some(pid) = self.launch_gdb_server(msg.seq, launch) else {
+                return;
             };
-
-            let pid = child.id();
-            let child = Arc::new(Mutex::new(child));
-            self.process = Some(Arc::clone(&child));
-            // A fresh server means a fresh verdict: any earlier intentional stop must
-            // not silence the reaper for this one.
-            self.intentional_stop.store(false, Ordering::SeqCst);
-
-            // Take the pipes before the reaper can start reaping, so no read is racing
-            // a `wait()` that would close them.
-            let stdout = child.lock_recover().stdout.take();
-            let stderr = child.lock_recover().stderr.take();
-            if let Some(stdout) = stdout {
-                let tx = self.event_tx.clone();
-                spawn_session_thread(&self.event_tx, SessionThreadRole::GdbStdout, move || {
-                    read_and_forward(StreamId::Stdout.to_u8(), stdout, tx);
-                });
-            }
-            if let Some(stderr) = stderr {
-                let tx = self.event_tx.clone();
-                spawn_session_thread(&self.event_tx, SessionThreadRole::GdbStderr, move || {
-                    read_and_forward(StreamId::Stderr.to_u8(), stderr, tx);
-                });
-            }
-
-            // Watch for the server exiting on its own. Nothing did this before, so a
-            // crashed openocd left a zombie, and the client learned of it only
-            // indirectly when gdb's RSP connection dropped — with no exit code.
-            self.spawn_gdb_reaper(pid, Arc::clone(&child));
-
-            // Readiness is detected by *observing* listening sockets, never by
-            // connecting to them. Two connect-based strategies used to be selectable
-            // here and neither could work: a TCP connect to a gdb RSP port is a client
-            // connection as far as openocd is concerned, so probing it makes the server
-            // believe gdb has arrived and then hang or fail when no RSP traffic follows.
-            // That is inherent to the protocol, not a bug awaiting a fix, so the modes
-            // and the `--port-wait-mode` flag that chose between them are gone.
-            {
-                {
-                    self.stop_port_monitor();
-                    let (stop_tx, stop_rx) = std::sync::mpsc::channel();
-                    self.monitor_stop_tx = Some(stop_tx);
-                    let event_tx = self.event_tx.clone();
-                    spawn_session_thread(&self.event_tx, SessionThreadRole::PortMonitor, move || {
-                        if let Err(e) = wait_for_ports(ports, event_tx, stop_rx) {
-                            eprintln!("Port monitor exited with error: {}", e);
-                        }
-                    });
-                }
-            }
-
             let data = ControlResponseData::StartGdbServer { pid };
             ControlResponse::success(msg.seq, Some(data))
                 .send(&self.writer)
@@ -353,6 +344,329 @@ impl ProxyServer {
         }
     }
 
+    /// Reserve the preset's port block, build its command line and launch it.
+    ///
+    /// The ports are reserved the way `AllocatePorts` reserves them and join any the
+    /// client allocated itself, so the port monitor watches them all. Ready lines in
+    /// the server's output usually report them first.
+    pub(super) fn handle_start_gdb_server_preset(&mut self, seq: u64, preset: GdbServerPreset) {
+        let layout = preset.kind.port_layout();
+        let args = crate::common::tcpports::TcpPortFinderArgs {
+            consecutive: true,
+            count: layout.len() as u16,
+            start_port: preset.start_port.unwrap_or(preset.kind.default_start_port()),
+        };
+        let Some(listeners) = reserve_free_ports(&args) else {
+            let err = format!(
+                "Failed to allocate {} ports for {:?} from {}",
+                args.count, preset.kind, args.start_port
+            );
+            eprintln!("{}", err);
+            ControlResponse::error(seq, err).send(&self.writer).ok();
+            return;
+        };
+        let mut reserved = Vec::new();
+        let mut ports = HashMap::new();
+        for (role, listener) in layout.iter().zip(listeners) {
+            let Ok(port) = listener.local_addr().map(|a| a.port()) else {
+                continue;
+            };
+            let stream_id = self.next_stream_id;
+            self.next_stream_id += 1;
+            self.reserved_ports.push(PortInfoListner {
+                port,
+                stream_id,
+                listener: Some(listener),
+            });
+            reserved.push(PortReserved {
+                port,
+                stream_id,
+                stream_id_str: role.as_str().to_string(),
+            });
+            ports.insert(*role, (stream_id, port));
+        }
+
+        let port_numbers = ports.iter().map(|(role, (_, port))| (*role, *port)).collect();
+        let server_args = presets::build_args(&preset, &port_numbers);
+        let server_path = preset
+            .server_path
+            .clone()
+            .unwrap_or_else(|| preset.kind.default_server_path().to_string());
+        eprintln!("Starting {:?} preset: {} {:?}", preset.kind, server_path, server_args);
+        let mut launch = GdbLaunch::new(
+            server_path.clone(),
+            server_args.clone(),
+            preset.server_env.clone(),
+            preset.restart.clone(),
+        );
+        launch.scanner = Some(ReadyScanner::new(preset.kind, &ports));
+        launch.cwd = preset.cwd.clone();
+        launch.limits = preset.limits.clone();
+        let Some(pid) = self.launch_gdb_server(seq, launch) else {
+            return;
+        };
+        let data = ControlResponseData::StartGdbServerPreset {
+            pid,
+            server_path,
+            server_args,
+            ports: reserved,
+        };
+        ControlResponse::success(seq, Some(data))
+            .send(&self.writer)
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to send success response: {}", e);
+                self.exit = true;
+            });
+    }
+
+    /// Launch the gdb-server on every reserved port and keep `launch` for restarts.
+    ///
+    /// Returns the pid, or `None` after sending the error response for `seq`.
+    fn launch_gdb_server(&mut self, seq: u64, mut launch: GdbLaunch) -> Option<u32> {
+        launch.ports = self.reserved_ports.drain(..).map(|p| (p.stream_id, p.port)).collect();
+        match self.spawn_gdb_server(&mut launch) {
+            Ok(pid) => {
+                self.gdb_launch = Some(launch);
+                Some(pid)
+            }
+            Err(e) => {
+                eprintln!("Failed to launch gdb-server: {}: {}", launch.server_path, e);
+                ControlResponse::error(
+                    seq,
+                    format!("Failed to launch gdb-server: {}: {}", launch.server_path, e),
+                )
+                .send(&self.writer)
+                .ok();
+                self.exit = true;
+                None
+            }
+        }
+    }
+
+    /// Spawn the gdb-server `launch` describes and start forwarding its output,
+    /// reaping it and monitoring its ports. With a scanner, the server's stdout and
+    /// stderr are also searched for ready lines.
+    fn spawn_gdb_server(&mut self, launch: &mut GdbLaunch) -> std::io::Result<u32> {
+        self.stop_port_monitor();
+        let ports = launch.ports.clone();
+        // `join` keeps an absolute `cwd` as it is.
+        let dir = match &launch.cwd {
+            Some(cwd) => Path::new(&self.server_cwd).join(cwd),
+            None => PathBuf::from(&self.server_cwd),
+        };
+        let mut command = Command::new(&launch.server_path);
+        command
+            .args(&launch.server_args)
+            .envs(launch.server_env.as_ref().unwrap_or(&HashMap::new()))
+            .current_dir(dir)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        crate::common::process::suppress_console_window(&mut command);
+        // Its own group, so teardown can kill the helpers it forks along with it.
+        crate::common::process::own_process_group(&mut command);
+        if let Some(limits) = &launch.limits {
+            let applied = crate::common::process::limit_resources(
+                &mut command,
+                limits.cpu_secs.map(u64::from),
+                limits.max_memory_mb.map(|mb| u64::from(mb) * 1024 * 1024),
+            );
+            if !applied {
+                log::warn!(
+                    "Resource limits for {} are not supported on this platform; ignoring them",
+                    launch.server_path
+                );
+            }
+        }
+        let child = command.spawn()?;
+
+        let pid = child.id();
+        launch.pid = pid;
+        let child = Arc::new(Mutex::new(child));
+        self.process = Some(Arc::clone(&child));
+        // A fresh server means a fresh verdict: any earlier intentional stop must
+        // not silence the reaper for this one.
+        self.intentional_stop.store(false, Ordering::SeqCst);
+
+        // Take the pipes before the reaper can start reaping, so no read is racing
+        // a `wait()` that would close them.
+        let stdout = child.lock_recover().stdout.take();
+        let stderr = child.lock_recover().stderr.take();
+        let scanner = launch.scanner.clone();
+        if let Some(stdout) = stdout {
+            let tx = self.event_tx.clone();
+            let scanner = scanner.clone();
+            spawn_session_thread(&self.event_tx, SessionThreadRole::GdbStdout, move || {
+                forward_server_output(StreamId::Stdout.to_u8(), stdout, tx, scanner);
+            });
+        }
+        if let Some(stderr) = stderr {
+            let tx = self.event_tx.clone();
+            spawn_session_thread(&self.event_tx, SessionThreadRole::GdbStderr, move || {
+                forward_server_output(StreamId::Stderr.to_u8(), stderr, tx, scanner);
+            });
+        }
+
+        // Watch for the server exiting on its own. Nothing did this before, so a
+        // crashed openocd left a zombie, and the client learned of it only
+        // indirectly when gdb's RSP connection dropped — with no exit code.
+        self.spawn_gdb_reaper(pid, Arc::clone(&child));
+
+        // Wait for the server's ports to listen. The port monitor reads the kernel's
+        // socket tables under /proc where it can, runs a listing tool such as `ss`
+        // or `lsof` where it can't, and connects to the port only as a last resort
+        // on a machine with neither. A connect looks like gdb arriving, so openocd
+        // may then hang waiting for RSP traffic; observing the sockets avoids that.
+        {
+            self.stop_port_monitor();
+            let (stop_tx, stop_rx) = std::sync::mpsc::channel();
+            self.monitor_stop_tx = Some(stop_tx);
+            let event_tx = self.event_tx.clone();
+            spawn_session_thread(&self.event_tx, SessionThreadRole::PortMonitor, move || {
+                wait_for_ports(ports, Some(pid), event_tx, stop_rx);
+            });
+        }
+        Ok(pid)
+    }
+
+    /// Decide what the gdb-server exiting on its own means for the session.
+    ///
+    /// Returns `true` when its restart policy allows another launch, which is then
+    /// scheduled after the policy's backoff; `false` when the exit ends the session.
+    pub(super) fn schedule_gdb_restart(&mut self, previous_pid: u32, exit_code: i32) -> bool {
+        let Some(launch) = &self.gdb_launch else {
+            return false;
+        };
+        let Some(policy) = &launch.restart else {
+            return false;
+        };
+        if launch.restarts >= policy.max_restarts {
+            eprintln!("gdb-server has used all {} restarts", policy.max_restarts);
+            return false;
+        }
+        let delay = restart_backoff(policy, launch.restarts);
+        eprintln!(
+            "gdb-server pid {} exited (code {}); restart {} of {} in {:?}",
+            previous_pid,
+            exit_code,
+            launch.restarts + 1,
+            policy.max_restarts,
+            delay
+        );
+        self.stop_port_monitor();
+        let event_tx = self.event_tx.clone();
+        let cancel = self.cancel.clone();
+        spawn_session_thread(&self.event_tx, SessionThreadRole::GdbRestartTimer, move || {
+            std::thread::sleep(delay);
+            if !cancel.load(Ordering::SeqCst) {
+                event_tx
+                    .send(ProxyEvent::RestartGdbServer {
+                        previous_pid,
+                        exit_code,
+                    })
+                    .ok();
+            }
+        });
+        true
+    }
+
+    /// Relaunch the crashed gdb-server `previous_pid` on its original command line
+    /// and ports, and reconnect the streams that were connected to it.
+    ///
+    /// Returns the new pid and the attempt number, or `None` when the relaunch is
+    /// stale: the session ended the server or started another during the backoff.
+    pub(super) fn restart_gdb_server(&mut self, previous_pid: u32) -> Option<std::io::Result<(u32, u32)>> {
+        if self.process.is_some() || self.gdb_launch.as_ref().is_none_or(|l| l.pid != previous_pid) {
+            return None;
+        }
+        let mut launch = self.gdb_launch.take()?;
+        launch.restarts += 1;
+        // Every stream to the old server is dead, connected or not. Forgetting them
+        // lets the port monitor report the ports ready again; the ones that had been
+        // connected are connected again below.
+        let ports: Vec<u16> = launch.ports.iter().map(|(_, port)| *port).collect();
+        self.streams.retain(|_, pinfo| !ports.contains(&pinfo.port));
+        let reconnect: Vec<(u8, u16)> = launch.connected.drain().collect();
+        let result = self.spawn_gdb_server(&mut launch);
+        let attempt = launch.restarts;
+        self.gdb_launch = Some(launch);
+        let pid = match result {
+            Ok(pid) => pid,
+            Err(e) => return Some(Err(e)),
+        };
+        for (stream_id, port) in &reconnect {
+            self.streams.insert(
+                *stream_id,
+                PortInfo {
+                    port: *port,
+                    stream_id: *stream_id,
+                    stream: None,
+                },
+            );
+        }
+        self.spawn_port_waiters(reconnect, true, 0);
+        Some(Ok((pid, attempt)))
+    }
+
+    /// The gdb-server `pid` exited on its own, as the reaper found.
+    ///
+    /// Returns `Ok(true)` while the session goes on, which it does only when the
+    /// restart policy relaunches the server; an error means the client could not be told.
+    pub(super) fn gdb_server_exited(&mut self, pid: u32, exit_code: i32) -> std::io::Result<bool> {
+        // Already reaped by the reaper, along with any helpers it forked; drop the
+        // handle so `end_process` does not kill a pid the OS may have reused.
+        self.process = None;
+        if let Some(log) = self.writer.session_log() {
+            log.server_exited(exit_code);
+        }
+        // A server launched with a restart policy gets relaunched, and the
+        // client hears of it as `gdbServerRestarted` once it is back.
+        if self.schedule_gdb_restart(pid, exit_code) {
+            return Ok(true);
+        }
+        // The server died on its own — crashed, was signalled, or returned
+        // (openocd exits when it finds no probe). Tell the client, then end
+        // the session: that is what a native launch does, and leaving a
+        // session alive around a dead server serves nobody.
+        eprintln!("gdb-server pid {pid} exited (code {exit_code}) — ending session");
+        let sent = ProxyServerEvents::GdbServerExited { pid, exit_code }.send(&self.writer);
+        self.end_process();
+        sent.map(|()| false)
+    }
+
+    /// The backoff before relaunching the crashed gdb-server `previous_pid` is over.
+    ///
+    /// Returns `Ok(true)` while the session goes on, as for [`Self::gdb_server_exited`].
+    pub(super) fn relaunch_gdb_server(&mut self, previous_pid: u32, exit_code: i32) -> std::io::Result<bool> {
+        match self.restart_gdb_server(previous_pid) {
+            None => {
+                eprintln!("Dropping stale restart of gdb-server pid {previous_pid}");
+                Ok(true)
+            }
+            Some(Ok((pid, attempt))) => {
+                eprintln!("gdb-server restarted as pid {pid} (attempt {attempt})");
+                let event = ProxyServerEvents::GdbServerRestarted {
+                    previous_pid,
+                    pid,
+                    exit_code,
+                    attempt,
+                };
+                event.send(&self.writer).map(|()| true)
+            }
+            Some(Err(e)) => {
+                // Could not even relaunch it: report the original exit and end
+                // the session, exactly as without a restart policy.
+                eprintln!("gdb-server restart failed: {e} — ending session");
+                let event = ProxyServerEvents::GdbServerExited {
+                    pid: previous_pid,
+                    exit_code,
+                };
+                let sent = event.send(&self.writer);
+                self.end_process();
+                sent.map(|()| false)
+            }
+        }
+    }
+
     pub(super) fn spawn_port_waiters(&mut self, ports: Vec<(u8, u16)>, keep_open: bool, msg_seq: u64) {
         for (stream_id, port) in ports {
             let event_tx = self.event_tx.clone();
@@ -571,6 +885,68 @@ impl ProxyServer {
     }
 }
 
+/// How the running gdb-server was launched, kept so that a crashed one can be
+/// launched again the same way.
+pub(super) struct GdbLaunch {
+    server_path: String,
+    server_args: Vec<String>,
+    server_env: Option<HashMap<String, String>>,
+    /// `(stream_id, port)` of every port handed to the server.
+    ports: Vec<(u8, u16)>,
+    scanner: Option<ReadyScanner>,
+    restart: Option<RestartPolicy>,
+    /// Working directory requested by the client, as sent.
+    cwd: Option<String>,
+    limits: Option<ResourceLimits>,
+    /// Relaunches so far.
+    restarts: u32,
+    /// Pid of the latest launch.
+    pid: u32,
+    /// Streams connected to the latest launch, reconnected after a restart.
+    connected: HashSet<(u8, u16)>,
+}
+
+impl GdbLaunch {
+    fn new(
+        server_path: String,
+        server_args: Vec<String>,
+        server_env: Option<HashMap<String, String>>,
+        restart: Option<RestartPolicy>,
+    ) -> Self {
+        Self {
+            server_path,
+            server_args,
+            server_env,
+            ports: Vec::new(),
+            scanner: None,
+            restart,
+            cwd: None,
+            limits: None,
+            restarts: 0,
+            pid: 0,
+            connected: HashSet::new(),
+        }
+    }
+
+    /// Note a stream connected to `port`, if the port is one of the server's.
+    pub(super) fn note_connected(&mut self, stream_id: u8, port: u16) {
+        if self.ports.iter().any(|(_, p)| *p == port) {
+            self.connected.insert((stream_id, port));
+        }
+    }
+}
+
+const DEFAULT_RESTART_BACKOFF_MS: u32 = 500;
+const DEFAULT_MAX_RESTART_BACKOFF_MS: u32 = 8000;
+
+/// The wait before relaunch number `restarts + 1`: the policy's backoff, doubled
+/// for each relaunch already made, up to its ceiling.
+fn restart_backoff(policy: &RestartPolicy, restarts: u32) -> Duration {
+    let base = policy.backoff_ms.unwrap_or(DEFAULT_RESTART_BACKOFF_MS) as u64;
+    let cap = (policy.max_backoff_ms.unwrap_or(DEFAULT_MAX_RESTART_BACKOFF_MS) as u64).max(base);
+    Duration::from_millis(base.saturating_mul(1 << restarts.min(16)).min(cap))
+}
+
 /// Collapse an `ExitStatus` into the single `i32` the wire carries.
 ///
 /// `code()` is `None` on Unix when the child was killed by a signal, which is exactly
@@ -633,8 +1009,10 @@ fn reap_gdb_server(
         // thread, for up to a full poll interval.
         //
         // `try_wait` also reaps, so a self-exited child never lingers as a zombie the
-        // way it used to until session teardown.
-        let polled = child.lock_recover().try_wait();
+        // way it used to until session teardown. Helpers it forked may still be
+        // running and holding the probe, which a restart would then find busy; they
+        // are killed first, while the group's id cannot yet have been reused.
+        let polled = crate::common::process::try_wait_group(&mut child.lock_recover());
         let status = match polled {
             Ok(Some(status)) => status,
             Ok(None) => {
@@ -808,6 +1186,27 @@ mod reaper_tests {
         );
     }
 
+    /// Restarts back off from the policy's wait, doubling up to its ceiling, so a
+    /// server that dies on every launch does not spin.
+    #[test]
+    fn restart_backoff_doubles_up_to_the_ceiling() {
+        let policy = RestartPolicy {
+            max_restarts: 10,
+            backoff_ms: Some(100),
+            max_backoff_ms: Some(1000),
+        };
+        let waits: Vec<u64> = (0..6).map(|n| restart_backoff(&policy, n).as_millis() as u64).collect();
+        assert_eq!(waits, [100, 200, 400, 800, 1000, 1000]);
+
+        let defaults = RestartPolicy {
+            max_restarts: 1,
+            backoff_ms: None,
+            max_backoff_ms: None,
+        };
+        assert_eq!(restart_backoff(&defaults, 0), Duration::from_millis(500));
+        assert_eq!(restart_backoff(&defaults, 40), Duration::from_secs(8));
+    }
+
     /// The reaper must not hold the child's lock while it sleeps between polls.
     ///
     /// `end_process` runs on the message loop and needs that same lock to `kill()`.
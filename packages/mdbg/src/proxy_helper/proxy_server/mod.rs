@@ -18,6 +18,7 @@
 use crate::common::sync::MutexExt;
 use crate::proxy_helper::run::ProxyArgs;
 use crate::proxy_helper::serial_available::SerialAvailabilityHub;
+use crate::proxy_helper::session_log::{Direction, SessionLog};
 use crate::serial::port::PortHandle;
 use anyhow::Result;
 use std::collections::HashMap;
@@ -27,7 +28,7 @@ use std::net::{TcpListener, TcpStream};
 use std::process::Child;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{channel, Receiver, Sender};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 
 // IMPORTANT: The `eprintln!` macro override MUST be declared before all `mod`
 // statements so that child modules see the redefinition when they `use super::*`.
@@ -41,6 +42,7 @@ pub mod protocol;
 pub use protocol::*;
 
 mod gdb_server;
+mod presets;
 mod serial;
 pub use serial::{
     force_close_serial, serial_status, FunnelWriter, OpenPort, SerialPortRegistry, SerialStatus, CLOSE_ALL_SERIAL,
@@ -90,6 +92,10 @@ pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
 #[derive(Clone)]
 pub struct FrameWriter {
     stream: Arc<Mutex<TcpStream>>,
+    /// Where outgoing control frames and gdb-server output are also recorded, once
+    /// the session has initialized. Shared by every clone, so a background thread
+    /// holding a writer from before `initialize` still records after it.
+    session_log: Arc<OnceLock<Arc<SessionLog>>>,
 }
 
 impl FrameWriter {
@@ -102,12 +108,30 @@ impl FrameWriter {
         stream.set_write_timeout(Some(std::time::Duration::from_secs(5))).ok();
         Self {
             stream: Arc::new(Mutex::new(stream)),
+            session_log: Arc::new(OnceLock::new()),
         }
     }
 
+    /// Start recording this session's traffic to `log`. Only the first call counts.
+    pub fn attach_session_log(&self, log: Arc<SessionLog>) {
+        let _ = self.session_log.set(log);
+    }
+
+    pub fn session_log(&self) -> Option<&Arc<SessionLog>> {
+        self.session_log.get()
+    }
+
     /// Write `bytes` as a single Funnel-protocol frame with the given `stream_id`.
     /// Acquires the internal lock for the duration so header + payload are atomic.
     pub fn write_frame(&self, stream_id: u8, bytes: &[u8]) -> io::Result<()> {
+        // Recorded before the write, so the frame is kept even when the client is
+        // the thing that failed.
+        if let Some(log) = self.session_log.get() {
+            match stream_id {
+                0 => log.control(Direction::Send, bytes),
+                _ => log.server_output(stream_id, bytes),
+            }
+        }
         let mut s = self.stream.lock_recover();
         let mut header = Vec::with_capacity(5);
         header.push(stream_id);
@@ -211,6 +235,8 @@ pub struct ProxyServer {
     /// child (port waiters mid-connect, the serial error forwarder) so they stop
     /// promptly instead of lingering.
     cancel: Arc<AtomicBool>,
+    /// How the current gdb-server was launched, for its restart policy.
+    gdb_launch: Option<gdb_server::GdbLaunch>,
 }
 
 impl Drop for ProxyServer {
@@ -226,6 +252,9 @@ impl Drop for ProxyServer {
         // port, once opened, never went away for the life of the proxy.
         self.release_all_serial_ports();
         self.end_process();
+        if let Some(log) = self.writer.session_log() {
+            log.finish();
+        }
     }
 }
 
@@ -257,6 +286,7 @@ impl ProxyServer {
             serial_error_subs: std::collections::HashSet::new(),
             serial_direct_paths: std::collections::HashSet::new(),
             cancel: Arc::new(AtomicBool::new(false)),
+            gdb_launch: None,
         }
     }
 
@@ -296,6 +326,9 @@ impl ProxyServer {
             // server it just stopped has stopped.
             self.intentional_stop.store(true, Ordering::SeqCst);
             let mut child = child.lock_recover();
+            // The whole group, not just the child: a server's helpers would otherwise
+            // outlive it, still holding the probe the next session needs.
+            crate::common::process::kill_process_group(&child);
             let _ = child.kill();
             let _ = child.wait();
         }
@@ -389,7 +422,11 @@ impl ProxyServer {
                             let msg_len = content_length.unwrap() as usize;
                             let msg = all_bytes[..msg_len].to_vec();
                             if stream_id == 0 {
-                                // Control message (JSON)
+                                // Control message (JSON). Recorded before parsing: a
+                                // request the proxy could not read is worth keeping too.
+                                if let Some(log) = self.writer.session_log() {
+                                    log.control(Direction::Recv, &msg);
+                                }
                                 let msg_str = String::from_utf8_lossy(&msg);
                                 match serde_json::from_str::<ControlMessage>(&msg_str) {
                                     Ok(control_msg) => {
@@ -552,6 +589,9 @@ impl ProxyServer {
                             },
                         );
                     }
+                    if let Some(launch) = &mut self.gdb_launch {
+                        launch.note_connected(stream_id, port);
+                    }
                     // Unblock the waiter thread only after stream registration so that
                     // forwarding cannot start before self.streams is updated.
                     ready_tx.send(()).ok();
@@ -568,6 +608,12 @@ impl ProxyServer {
                     }
                 }
                 ProxyEvent::PortReady { stream_id, port } => {
+                    // A preset server's port is reported twice, by its ready line and
+                    // by the port monitor. Only the first counts; re-inserting would
+                    // also drop the connection of a stream already started.
+                    if self.streams.contains_key(&stream_id) {
+                        continue;
+                    }
                     eprintln!("Port {} (stream {}) is ready for connection!", port, stream_id);
                     self.streams.insert(
                         stream_id,
@@ -610,20 +656,23 @@ impl ProxyServer {
                     let event = ProxyServerEvents::StreamClosed { stream_id };
                     send_or_break!(event.send(&self.writer));
                 }
-                ProxyEvent::GdbServerExited { pid, exit_code } => {
-                    // The server died on its own — crashed, was signalled, or returned
-                    // (openocd exits when it finds no probe). Tell the client, then end
-                    // the session: that is what a native launch does, and leaving a
-                    // session alive around a dead server serves nobody.
-                    eprintln!("gdb-server pid {pid} exited (code {exit_code}) — ending session");
-                    // Already reaped by the reaper's `try_wait`; drop the handle so
-                    // `end_process` does not kill a pid the OS may have reused.
-                    self.process = None;
-                    let event = ProxyServerEvents::GdbServerExited { pid, exit_code };
-                    send_or_break!(event.send(&self.writer));
-                    self.end_process();
-                    break;
-                }
+                ProxyEvent::GdbServerExited { pid, exit_code } => match self.gdb_server_exited(pid, exit_code) {
+                    Ok(true) => {}
+                    result => {
+                        send_or_break!(result);
+                        break;
+                    }
+                },
+                ProxyEvent::RestartGdbServer {
+                    previous_pid,
+                    exit_code,
+                } => match self.relaunch_gdb_server(previous_pid, exit_code) {
+                    Ok(true) => {}
+                    result => {
+                        send_or_break!(result);
+                        break;
+                    }
+                },
                 ProxyEvent::SerialPortError(err) => {
                     // Port died — remove from registry (drops the backing and fd),
                     // then notify the client so it can update its UI.
@@ -704,6 +753,10 @@ impl ProxyServer {
                 eprintln!("Received StartGdbServer request");
                 self.handle_start_gdb_server(&msg);
             }
+            ControlRequest::StartGdbServerPreset(preset) => {
+                eprintln!("Received StartGdbServerPreset request for {:?}", preset.kind);
+                self.handle_start_gdb_server_preset(msg.seq, preset);
+            }
             ControlRequest::StartStream { stream_id } => {
                 eprintln!("Received StartStream request for stream_id {}", stream_id);
                 self.handle_start_stream(stream_id, msg.seq);
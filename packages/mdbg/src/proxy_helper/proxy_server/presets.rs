@@ -0,0 +1,363 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! What the proxy knows about each [`GdbServerKind`]: its executable, the ports it
+//! opens, how to put a [`GdbServerPreset`] on its command line, and which lines of
+//! its output mean a port is listening.
+
+use regex::Regex;
+use std::collections::HashMap;
+
+use super::protocol::{DebugInterface, GdbServerKind, GdbServerPreset, ServerPortRole};
+
+use ServerPortRole::{Gdb, Rtt, Swo, Tcl, Telnet};
+
+/// Longest unterminated line kept while waiting for its newline. Servers print
+/// progress bars and binary junk too; none of that should grow without bound.
+const MAX_PENDING_LINE: usize = 4096;
+
+impl GdbServerKind {
+    /// Executable run when the preset names none.
+    pub fn default_server_path(self) -> &'static str {
+        match self {
+            GdbServerKind::OpenOcd => "openocd",
+            GdbServerKind::JLink if cfg!(target_os = "windows") => "JLinkGDBServerCL.exe",
+            GdbServerKind::JLink => "JLinkGDBServerCLExe",
+            GdbServerKind::PyOcd => "pyocd",
+            GdbServerKind::StLink => "ST-LINK_gdbserver",
+            GdbServerKind::ProbeRs => "probe-rs",
+        }
+    }
+
+    /// The server's customary GDB port, used as the start of the port block.
+    pub fn default_start_port(self) -> u16 {
+        match self {
+            GdbServerKind::OpenOcd | GdbServerKind::PyOcd => 3333,
+            GdbServerKind::JLink => 2331,
+            GdbServerKind::StLink => 61234,
+            GdbServerKind::ProbeRs => 1337,
+        }
+    }
+
+    /// Every port the server opens, GDB first. All of them are allocated, even the
+    /// ones a session may not use: a server left on its built-in default would
+    /// collide with the next session's.
+    pub fn port_layout(self) -> &'static [ServerPortRole] {
+        match self {
+            GdbServerKind::OpenOcd => &[Gdb, Telnet, Tcl],
+            GdbServerKind::JLink => &[Gdb, Swo, Telnet, Rtt],
+            GdbServerKind::PyOcd => &[Gdb, Telnet],
+            GdbServerKind::StLink => &[Gdb, Swo],
+            GdbServerKind::ProbeRs => &[Gdb],
+        }
+    }
+
+    /// Output lines that announce listening ports, and which ports each announces.
+    /// OpenOCD and pyOCD name every port; J-Link and the ST-LINK server only say
+    /// they are waiting for GDB, by which time all their ports are open.
+    fn ready_lines(self) -> &'static [(&'static str, &'static [ServerPortRole])] {
+        match self {
+            GdbServerKind::OpenOcd => &[
+                (r"Listening on port \d+ for gdb connections", &[Gdb]),
+                (r"Listening on port \d+ for telnet connections", &[Telnet]),
+                (r"Listening on port \d+ for tcl connections", &[Tcl]),
+            ],
+            GdbServerKind::JLink => &[(r"Waiting for GDB connection", &[Gdb, Swo, Telnet, Rtt])],
+            GdbServerKind::PyOcd => &[
+                (r"GDB server (?:started|listening) on port \d+", &[Gdb]),
+                (r"Telnet: server started on port \d+", &[Telnet]),
+            ],
+            GdbServerKind::StLink => &[(r"Waiting for debugger connection", &[Gdb, Swo])],
+            GdbServerKind::ProbeRs => &[(r"Firing up GDB stub at", &[Gdb])],
+        }
+    }
+}
+
+/// Command-line arguments for `preset`, with each role in the kind's
+/// [`port_layout`](GdbServerKind::port_layout) on the port given in `ports`.
+/// `extra_args` come last so they can override anything generated.
+pub fn build_args(preset: &GdbServerPreset, ports: &HashMap<ServerPortRole, u16>) -> Vec<String> {
+    let port = |role: ServerPortRole| ports.get(&role).copied().unwrap_or_default().to_string();
+    let mut args: Vec<String> = Vec::new();
+    let mut push = |items: &[&str]| args.extend(items.iter().map(|s| s.to_string()));
+    match preset.kind {
+        GdbServerKind::OpenOcd => {
+            push(&["-c", &format!("gdb_port {}", port(Gdb))]);
+            push(&["-c", &format!("telnet_port {}", port(Telnet))]);
+            push(&["-c", &format!("tcl_port {}", port(Tcl))]);
+            if let Some(serial) = &preset.serial_number {
+                push(&["-c", &format!("adapter serial {serial}")]);
+            }
+            for file in &preset.config_files {
+                push(&["-f", file]);
+            }
+            // After the adapter configuration, which selects the driver a transport
+            // belongs to, and before the target, which needs the transport set.
+            if let Some(interface) = preset.interface {
+                let transport = match interface {
+                    DebugInterface::Swd => "swd",
+                    DebugInterface::Jtag => "jtag",
+                };
+                push(&["-c", &format!("transport select {transport}")]);
+            }
+            if let Some(device) = &preset.device {
+                push(&["-f", &format!("target/{device}.cfg")]);
+            }
+            if let Some(speed) = preset.speed {
+                push(&["-c", &format!("adapter speed {speed}")]);
+            }
+        }
+        GdbServerKind::JLink => {
+            push(&["-singlerun", "-nogui", "-noir"]);
+            push(&["-port", &port(Gdb), "-swoport", &port(Swo)]);
+            push(&["-telnetport", &port(Telnet), "-rtttelnetport", &port(Rtt)]);
+            if let Some(device) = &preset.device {
+                push(&["-device", device]);
+            }
+            if let Some(interface) = preset.interface {
+                let name = match interface {
+                    DebugInterface::Swd => "SWD",
+                    DebugInterface::Jtag => "JTAG",
+                };
+                push(&["-if", name]);
+            }
+            if let Some(speed) = preset.speed {
+                push(&["-speed", &speed.to_string()]);
+            }
+            if let Some(serial) = &preset.serial_number {
+                push(&["-select", &format!("USB={serial}")]);
+            }
+        }
+        GdbServerKind::PyOcd => {
+            push(&["gdbserver", "--port", &port(Gdb), "--telnet-port", &port(Telnet)]);
+            if let Some(device) = &preset.device {
+                push(&["--target", device]);
+            }
+            if let Some(interface) = preset.interface {
+                let protocol = match interface {
+                    DebugInterface::Swd => "swd",
+                    DebugInterface::Jtag => "jtag",
+                };
+                push(&["-O", &format!("dap_protocol={protocol}")]);
+            }
+            if let Some(speed) = preset.speed {
+                push(&["--frequency", &format!("{speed}k")]);
+            }
+            if let Some(serial) = &preset.serial_number {
+                push(&["--uid", serial]);
+            }
+        }
+        GdbServerKind::StLink => {
+            push(&["--port-number", &port(Gdb), "--swo-port", &port(Swo)]);
+            // The ST-LINK server speaks JTAG unless told otherwise, and has no
+            // notion of a device: it identifies the chip itself.
+            if preset.interface == Some(DebugInterface::Swd) {
+                push(&["--swd"]);
+            }
+            if let Some(speed) = preset.speed {
+                push(&["--frequency", &speed.to_string()]);
+            }
+            if let Some(serial) = &preset.serial_number {
+                push(&["--serial-number", serial]);
+            }
+        }
+        GdbServerKind::ProbeRs => {
+            push(&["gdb", "--gdb-connection-string", &format!("127.0.0.1:{}", port(Gdb))]);
+            if let Some(device) = &preset.device {
+                push(&["--chip", device]);
+            }
+            if let Some(interface) = preset.interface {
+                let protocol = match interface {
+                    DebugInterface::Swd => "swd",
+                    DebugInterface::Jtag => "jtag",
+                };
+                push(&["--protocol", protocol]);
+            }
+            if let Some(speed) = preset.speed {
+                push(&["--speed", &speed.to_string()]);
+            }
+            if let Some(serial) = &preset.serial_number {
+                push(&["--probe", serial]);
+            }
+        }
+    }
+    args.extend(preset.extra_args.iter().cloned());
+    args
+}
+
+/// Watches one of the server's output streams for its ready lines.
+///
+/// Each line is reported once, as the `(stream_id, port)` pairs it makes ready.
+/// The port monitor keeps watching the same ports, so a server whose wording has
+/// changed is still detected, only later; the message loop ignores the second
+/// report of a port.
+#[derive(Clone)]
+pub struct ReadyScanner {
+    lines: Vec<(Regex, Vec<(u8, u16)>)>,
+    pending: String,
+}
+
+impl ReadyScanner {
+    /// `ports` maps each role to its `(stream_id, port)`; roles not in it are never
+    /// reported.
+    pub fn new(kind: GdbServerKind, ports: &HashMap<ServerPortRole, (u8, u16)>) -> Self {
+        let lines = kind
+            .ready_lines()
+            .iter()
+            .map(|(pattern, roles)| {
+                let ready = roles.iter().filter_map(|role| ports.get(role).copied()).collect();
+                (Regex::new(pattern).expect("ready-line pattern"), ready)
+            })
+            .collect();
+        Self {
+            lines,
+            pending: String::new(),
+        }
+    }
+
+    /// Feed the next chunk of output; returns the ports it announced.
+    ///
+    /// The unterminated tail is matched too, because a server that prints
+    /// "Waiting for GDB connection..." may not end the line until GDB arrives.
+    pub fn feed(&mut self, data: &[u8]) -> Vec<(u8, u16)> {
+        if self.lines.is_empty() {
+            return Vec::new();
+        }
+        self.pending.push_str(&String::from_utf8_lossy(data));
+        let mut ready = Vec::new();
+        for line in self.pending.split('\n') {
+            self.lines.retain(|(pattern, ports)| {
+                if pattern.is_match(line) {
+                    ready.extend_from_slice(ports);
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        let tail_start = self.pending.rfind('\n').map_or(0, |i| i + 1);
+        self.pending.drain(..tail_start);
+        if self.pending.len() > MAX_PENDING_LINE {
+            self.pending.clear();
+        }
+        ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(kind: GdbServerKind) -> GdbServerPreset {
+        serde_json::from_value(serde_json::json!({ "kind": kind })).unwrap()
+    }
+
+    fn layout_ports(kind: GdbServerKind) -> HashMap<ServerPortRole, u16> {
+        kind.port_layout()
+            .iter()
+            .enumerate()
+            .map(|(i, role)| (*role, 50000 + i as u16))
+            .collect()
+    }
+
+    #[test]
+    fn every_port_in_a_layout_is_on_the_command_line() {
+        for kind in [
+            GdbServerKind::OpenOcd,
+            GdbServerKind::JLink,
+            GdbServerKind::PyOcd,
+            GdbServerKind::StLink,
+            GdbServerKind::ProbeRs,
+        ] {
+            assert_eq!(kind.port_layout()[0], ServerPortRole::Gdb);
+            let ports = layout_ports(kind);
+            let args = build_args(&preset(kind), &ports).join(" ");
+            for port in ports.values() {
+                assert!(args.contains(&port.to_string()), "{kind:?} lacks port {port}: {args}");
+            }
+        }
+    }
+
+    #[test]
+    fn builds_openocd_and_jlink_command_lines() {
+        let mut openocd = preset(GdbServerKind::OpenOcd);
+        openocd.config_files = vec!["interface/cmsis-dap.cfg".into()];
+        openocd.interface = Some(DebugInterface::Swd);
+        openocd.device = Some("stm32f4x".into());
+        openocd.speed = Some(4000);
+        openocd.extra_args = vec!["-d2".into()];
+        let args = build_args(&openocd, &layout_ports(GdbServerKind::OpenOcd));
+        assert_eq!(
+            args,
+            [
+                "-c",
+                "gdb_port 50000",
+                "-c",
+                "telnet_port 50001",
+                "-c",
+                "tcl_port 50002",
+                "-f",
+                "interface/cmsis-dap.cfg",
+                "-c",
+                "transport select swd",
+                "-f",
+                "target/stm32f4x.cfg",
+                "-c",
+                "adapter speed 4000",
+                "-d2",
+            ]
+        );
+
+        let jlink: GdbServerPreset = serde_json::from_str(
+            r#"{"kind":"jlink","device":"STM32F407VG","interface":"swd","speed":4000,"serial_number":"123"}"#,
+        )
+        .unwrap();
+        let args = build_args(&jlink, &layout_ports(GdbServerKind::JLink)).join(" ");
+        assert_eq!(
+            args,
+            "-singlerun -nogui -noir -port 50000 -swoport 50001 -telnetport 50002 -rtttelnetport 50003 \
+             -device STM32F407VG -if SWD -speed 4000 -select USB=123"
+        );
+    }
+
+    #[test]
+    fn ready_lines_are_recognised_across_reads() {
+        let ports = HashMap::from([
+            (ServerPortRole::Gdb, (3, 3333)),
+            (ServerPortRole::Telnet, (4, 3334)),
+            (ServerPortRole::Tcl, (5, 3335)),
+        ]);
+        let mut scanner = ReadyScanner::new(GdbServerKind::OpenOcd, &ports);
+        assert!(scanner.feed(b"Info : Listening on port 3335 for tcl conn").is_empty());
+        assert_eq!(
+            scanner.feed(b"ections\nInfo : Listening on port 3334 for telnet connections\n"),
+            [(5, 3335), (4, 3334)]
+        );
+        assert_eq!(
+            scanner.feed(b"Info : Listening on port 3333 for gdb connections\n"),
+            [(3, 3333)]
+        );
+        // Reported once only.
+        assert!(scanner
+            .feed(b"Info : Listening on port 3333 for gdb connections\n")
+            .is_empty());
+
+        let ports = HashMap::from([(ServerPortRole::Gdb, (3, 2331)), (ServerPortRole::Swo, (4, 2332))]);
+        let mut scanner = ReadyScanner::new(GdbServerKind::JLink, &ports);
+        assert_eq!(
+            scanner.feed(b"Listening on TCP/IP port 2331\nWaiting for GDB connection..."),
+            [(3, 2331), (4, 2332)]
+        );
+    }
+}
@@ -46,6 +46,8 @@ pub enum SessionThreadRole {
     SerialErrorForwarder,
     /// Waits for the gdb-server child to exit on its own.
     GdbReaper,
+    /// Sleeps out the backoff before a crashed gdb-server is relaunched.
+    GdbRestartTimer,
 }
 
 impl SessionThreadRole {
@@ -110,6 +112,9 @@ pub enum ProxyEvent {
     /// a signal: `128 + signo`, so a segfault reports 139. `ExitStatus::code()` is
     /// `None` in that case, and the wire field is a plain `i32`.
     GdbServerExited { pid: u32, exit_code: i32 },
+    /// The backoff after the crash of `previous_pid` is over; relaunch the server
+    /// if the session still wants it.
+    RestartGdbServer { previous_pid: u32, exit_code: i32 },
     /// A serial port's reader thread hit a fatal error.
     /// The port should be removed from the registry and the client notified.
     SerialPortError(PortErrorEvent),
@@ -181,6 +186,140 @@ pub struct PortAllocatorSpec {
     pub all_ports: Vec<PortSet>,
 }
 
+// ── gdb-server presets ────────────────────────────────────────────────────────
+
+/// gdb-servers the proxy knows how to launch from a [`GdbServerPreset`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ts_rs::TS)]
+#[ts(export, export_to = "proxy-protocol/")]
+pub enum GdbServerKind {
+    #[serde(rename = "openocd")]
+    OpenOcd,
+    #[serde(rename = "jlink")]
+    JLink,
+    #[serde(rename = "pyocd")]
+    PyOcd,
+    #[serde(rename = "stlink")]
+    StLink,
+    #[serde(rename = "probe-rs")]
+    ProbeRs,
+}
+
+/// Wire protocol between the probe and the target.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ts_rs::TS)]
+#[ts(export, export_to = "proxy-protocol/")]
+#[serde(rename_all = "lowercase")]
+pub enum DebugInterface {
+    Swd,
+    Jtag,
+}
+
+/// What a gdb-server port is for. The lowercase name is the `stream_id_str` of the
+/// port in the `startGdbServerPreset` response.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ts_rs::TS)]
+#[ts(export, export_to = "proxy-protocol/")]
+#[serde(rename_all = "lowercase")]
+pub enum ServerPortRole {
+    /// GDB remote serial protocol.
+    Gdb,
+    /// The server's command console.
+    Telnet,
+    /// Raw SWO trace output.
+    Swo,
+    /// RTT channel 0.
+    Rtt,
+    /// OpenOCD's Tcl RPC server.
+    Tcl,
+}
+
+impl ServerPortRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ServerPortRole::Gdb => "gdb",
+            ServerPortRole::Telnet => "telnet",
+            ServerPortRole::Swo => "swo",
+            ServerPortRole::Rtt => "rtt",
+            ServerPortRole::Tcl => "tcl",
+        }
+    }
+}
+
+/// Parameters of `startGdbServerPreset`. Everything but `kind` is optional; a
+/// setting the chosen server has no option for is ignored.
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
+#[ts(export, export_to = "proxy-protocol/")]
+pub struct GdbServerPreset {
+    pub kind: GdbServerKind,
+    /// Executable to run. Defaults to the server's usual name, looked up on `PATH`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_path: Option<String>,
+    /// Target device or chip name, in the server's own spelling.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interface: Option<DebugInterface>,
+    /// Probe clock in kHz.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed: Option<u32>,
+    /// Serial number (probe-rs: probe selector) of the probe to use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial_number: Option<String>,
+    /// OpenOCD `-f` configuration files, in order.
+    #[serde(default)]
+    pub config_files: Vec<String>,
+    /// Appended verbatim after the generated arguments.
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_env: Option<HashMap<String, String>>,
+    /// First port to try for the server's consecutive port block. Defaults to the
+    /// server's usual GDB port.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_port: Option<u16>,
+    /// Relaunch the server when it exits on its own. Absent means never.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart: Option<RestartPolicy>,
+    /// Working directory for the server, relative to the session directory unless
+    /// absolute. Defaults to the session directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<ResourceLimits>,
+}
+
+/// When a gdb-server that exits on its own is launched again.
+///
+/// The relaunch reuses the command line and ports of the first launch, so clients
+/// reconnect to the same stream ids. Only after the last restart is spent does the
+/// exit end the session, as it does without a policy.
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
+#[ts(export, export_to = "proxy-protocol/")]
+pub struct RestartPolicy {
+    /// Relaunches allowed over the whole session.
+    pub max_restarts: u32,
+    /// Wait before the first relaunch, doubling for each one after. Defaults to 500 ms.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backoff_ms: Option<u32>,
+    /// Ceiling for the doubled wait. Defaults to 8 s.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_backoff_ms: Option<u32>,
+}
+
+/// Resource limits applied to a launched gdb-server and everything it starts.
+///
+/// Enforced with `setrlimit` on Unix, where they are inherited by the server's own
+/// helpers. Other platforms ignore them, with a warning in the proxy log.
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
+#[ts(export, export_to = "proxy-protocol/")]
+pub struct ResourceLimits {
+    /// CPU time, in seconds, before the server is killed (`RLIMIT_CPU`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_secs: Option<u32>,
+    /// Address space, in MiB (`RLIMIT_AS`). This bounds virtual memory, not resident
+    /// memory, so leave headroom for what the server maps but never touches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<u32>,
+}
+
 // ── Control requests ──────────────────────────────────────────────────────────
 
 #[derive(Debug, Serialize, Deserialize, ts_rs::TS)]
@@ -214,8 +353,22 @@ pub enum ControlRequest {
         server_args: Vec<String>,
         /** Environment variables for the gdb-server process */
         server_env: Option<HashMap<String, String>>,
+        /** Relaunch the gdb-server when it exits on its own. Absent means never */
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        restart: Option<RestartPolicy>,
+        /** Working directory, relative to the session directory unless absolute. Defaults to the session directory */
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cwd: Option<String>,
+        /** Resource limits for the gdb-server and the processes it starts */
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limits: Option<ResourceLimits>,
     },
 
+    /// Launch a known gdb-server from typed settings. The proxy builds the command
+    /// line, allocates the server's ports and watches its output for readiness.
+    #[serde(rename = "startGdbServerPreset")]
+    StartGdbServerPreset(GdbServerPreset),
+
     #[serde(rename = "endSession")]
     EndSession,
 
@@ -273,6 +426,7 @@ impl ControlRequest {
             ControlRequest::Initialize { .. } => "initialize",
             ControlRequest::AllocatePorts { .. } => "allocatePorts",
             ControlRequest::StartGdbServer { .. } => "startGdbServer",
+            ControlRequest::StartGdbServerPreset(..) => "startGdbServerPreset",
             ControlRequest::EndSession => "endSession",
             ControlRequest::StreamStatus { .. } => "streamStatus",
             ControlRequest::StartStream { .. } => "startStream",
@@ -349,6 +503,16 @@ pub enum ControlResponseData {
     #[serde(rename = "startGdbServer")]
     StartGdbServer { pid: u32 },
 
+    /// `startGdbServerPreset` response: the command line that was run and the
+    /// ports it was given, one per `ServerPortRole` in the preset's layout.
+    #[serde(rename = "startGdbServerPreset")]
+    StartGdbServerPreset {
+        pid: u32,
+        server_path: String,
+        server_args: Vec<String>,
+        ports: Vec<PortReserved>,
+    },
+
     #[serde(rename = "streamStatus")]
     StreamStatus {
         stream_id: u8,
@@ -442,6 +606,18 @@ pub enum ProxyServerEvents {
     #[serde(rename = "gdbServerExited")]
     GdbServerExited { pid: u32, exit_code: i32 },
 
+    /// The gdb-server `previous_pid` exited on its own and was relaunched as `pid`
+    /// under its restart policy. `attempt` counts from 1. Streams that were
+    /// connected are reconnected and reported with `streamStarted`; GDB itself has
+    /// to reconnect.
+    #[serde(rename = "gdbServerRestarted")]
+    GdbServerRestarted {
+        previous_pid: u32,
+        pid: u32,
+        exit_code: i32,
+        attempt: u32,
+    },
+
     #[serde(rename = "streamReady")]
     StreamReady { stream_id: u8, port: u16 },
 
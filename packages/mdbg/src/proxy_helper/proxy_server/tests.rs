@@ -2,8 +2,8 @@ use super::*;
 use crate::common::sync::MutexExt;
 use crate::serial::port::{FlowControl, Parity, SerialErrorKind, SerialParams, SerialTransport, StopBits};
 use crate::serial::AvailablePort;
-use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
-use std::sync::Mutex;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Mutex, Once};
 use std::thread;
 use std::time::{Duration, Instant};
 use ts_rs::{Config, TS};
@@ -22,6 +22,12 @@ fn ensure_ts_exports() {
     PortReserved::export(&config).unwrap();
     PortSet::export(&config).unwrap();
     SerialPortInfo::export(&config).unwrap();
+    GdbServerKind::export(&config).unwrap();
+    DebugInterface::export(&config).unwrap();
+    ServerPortRole::export(&config).unwrap();
+    GdbServerPreset::export(&config).unwrap();
+    RestartPolicy::export(&config).unwrap();
+    ResourceLimits::export(&config).unwrap();
     // Serial types (exported to serial-helper/)
     SerialParams::export(&config).unwrap();
     StopBits::export(&config).unwrap();
@@ -44,7 +50,15 @@ fn send_to_stream(stream_id: u8, stream: &mut TcpStream, bytes: &[u8]) -> io::Re
     Ok(())
 }
 
-fn read_from_stream(reader: &mut TcpStream, tx: Sender<String>) {
+fn send_control(stream: &mut TcpStream, msg: &ControlMessage) {
+    let msg_bytes = serde_json::to_vec(msg).unwrap();
+    send_to_stream(StreamId::Control.to_u8(), stream, &msg_bytes).unwrap();
+}
+
+/// A message from the proxy: the stream it arrived on and its content
+type Frame = (u8, String);
+
+fn read_from_stream(reader: &mut TcpStream, tx: Sender<Frame>) {
     let mut all_bytes: Vec<u8> = Vec::new();
     let mut buffer = [0; 4096];
     loop {
@@ -76,28 +90,71 @@ fn read_from_stream(reader: &mut TcpStream, tx: Sender<String>) {
                 "Client received message: stream_id={}, content_length={}, content={}",
                 stream_id, content_length, msg_str
             );
-            tx.send(msg_str.to_string()).unwrap();
+            if tx.send((stream_id, msg_str.to_string())).is_err() {
+                return; // The test is done with this connection
+            }
             all_bytes.drain(..5 + content_length); // Remove the processed message
         }
     }
 }
 
-fn wait_for_message(rx: &Receiver<String>, timeout: Duration) -> Option<String> {
-    let deadline = Instant::now() + timeout;
-    loop {
-        match rx.try_recv() {
-            Ok(msg) => return Some(msg),
-            Err(TryRecvError::Empty) => {
-                if Instant::now() >= deadline {
-                    return None; // Timeout
-                }
-                std::thread::sleep(Duration::from_millis(10)); // Avoid busy waiting
-            }
-            Err(TryRecvError::Disconnected) => {
-                return None; // Channel closed
+/// Frames read from one proxy connection. Frames a test is not waiting for yet are kept,
+/// so an event that overtakes the response before it is not lost.
+struct Frames {
+    rx: Receiver<Frame>,
+    pending: Vec<Frame>,
+}
+
+impl Frames {
+    fn new(client: &TcpStream) -> Self {
+        let (tx, rx) = channel();
+        let mut reader = client.try_clone().unwrap();
+        thread::spawn(move || {
+            read_from_stream(&mut reader, tx);
+        });
+        Frames {
+            rx,
+            pending: Vec::new(),
+        }
+    }
+
+    /// The first frame `matches` accepts, waiting up to `timeout` for it
+    fn wait_for(&mut self, timeout: Duration, matches: impl Fn(&Frame) -> bool) -> Option<Frame> {
+        if let Some(i) = self.pending.iter().position(&matches) {
+            return Some(self.pending.remove(i));
+        }
+        let deadline = Instant::now() + timeout;
+        loop {
+            let frame = self
+                .rx
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .ok()?;
+            if matches(&frame) {
+                return Some(frame);
             }
+            self.pending.push(frame);
         }
     }
+
+    /// The response to the control request `seq`
+    fn response(&mut self, seq: u64) -> ControlResponse {
+        let (_, text) = self
+            .wait_for(Duration::from_secs(5), |(stream_id, text)| {
+                *stream_id == StreamId::Control.to_u8()
+                    && serde_json::from_str::<ControlResponse>(text).is_ok_and(|r| r.seq == seq)
+            })
+            .unwrap_or_else(|| panic!("No response to request {} within timeout", seq));
+        serde_json::from_str(&text).unwrap()
+    }
+
+    /// The first event `matches` accepts
+    fn event(&mut self, timeout: Duration, matches: impl Fn(&ProxyServerEvents) -> bool) -> Option<ProxyServerEvents> {
+        let (_, text) = self.wait_for(timeout, |(stream_id, text)| {
+            *stream_id == StreamId::Control.to_u8()
+                && serde_json::from_str::<ProxyServerEvents>(text).is_ok_and(|e| matches(&e))
+        })?;
+        Some(serde_json::from_str(&text).unwrap())
+    }
 }
 
 /// Wait for server to be ready by attempting to connect with exponential backoff
@@ -122,74 +179,78 @@ fn wait_for_server(addr: &str, timeout: Duration) -> io::Result<TcpStream> {
     }
 }
 
-#[test]
-fn test_proxy_server() {
-    let tx: Sender<String>;
-    let rx: Receiver<String>;
-    (tx, rx) = channel();
+const TEST_TOKEN: &str = "adis-ababa-0123456789";
 
-    // Keep the singleton state dir (lock + endpoint.json) out of the real home
-    // directory during tests — point it at a throwaway temp path instead.
-    let state_dir = std::env::temp_dir().join(format!("mdbg-test-proxy-{}", std::process::id()));
-    std::env::set_var("MDBG_PROXY_STATE_DIR", &state_dir);
+fn test_args() -> ProxyArgs {
+    ProxyArgs {
+        host: None,
+        port: 4567,
+        token: Some(TEST_TOKEN.to_string()),
+        debug: false,
+        log_stderr: false,
+        log_dir: None,
+        heartbeat: false,
+        // Distinct instance so the test never touches (or is blocked by) a
+        // real `default` proxy running on the dev machine.
+        instance: "test-proxy-server".to_string(),
+        idle_timeout: 0, // no idle monitor during the test
+        status: false,
+        shutdown: false,
+        all: false,
+        close_serial: None,
+        sessions: false,
+        session_log: None,
+        log_stream: "stdout".to_string(),
+        tail: 0,
+        daemonized: true, // run the proxy in-process; don't re-spawn a daemon
+    }
+}
 
-    thread::spawn(|| {
-        let args = ProxyArgs {
-            host: None,
-            port: 4567,
-            token: Some("adis-ababa-0123456789".to_string()),
-            debug: false,
-            log_stderr: false,
-            log_dir: None,
-            heartbeat: false,
-            // Distinct instance so the test never touches (or is blocked by) a
-            // real `default` proxy running on the dev machine.
-            instance: "test-proxy-server".to_string(),
-            idle_timeout: 0, // no idle monitor during the test
-            status: false,
-            shutdown: false,
-            all: false,
-            close_serial: None,
-            daemonized: true, // run the proxy in-process; don't re-spawn a daemon
-        };
-        let _ = crate::proxy_helper::run::run(args);
+/// Start the proxy the tests share, once, and open a session of its own on it
+fn connect_to_proxy(workspace_uid: &str, session_uid: &str) -> (TcpStream, Frames) {
+    static START: Once = Once::new();
+    START.call_once(|| {
+        // Keep the singleton state dir (lock + endpoint.json) out of the real home
+        // directory during tests — point it at a throwaway temp path instead.
+        let state_dir = std::env::temp_dir().join(format!("mdbg-test-proxy-{}", std::process::id()));
+        std::env::set_var("MDBG_PROXY_STATE_DIR", &state_dir);
+
+        thread::spawn(|| {
+            let _ = crate::proxy_helper::run::run(test_args());
+        });
     });
 
     // Wait for server to be ready by attempting connection with retry
-    let client =
+    let mut client =
         wait_for_server("127.0.0.1:4567", Duration::from_secs(5)).expect("Server failed to start within 5 seconds");
-    let mut seq: u64 = 1;
+    let mut frames = Frames::new(&client);
     let init_msg = ControlMessage {
-        seq,
+        seq: 1,
         request: ControlRequest::Initialize {
-            token: "adis-ababa-0123456789".to_string(),
+            token: TEST_TOKEN.to_string(),
             version: CURRENT_VERSION.to_string(),
-            workspace_uid: "test-uid".to_string(),
-            session_uid: "test-session-uid".to_string(),
+            workspace_uid: workspace_uid.to_string(),
+            session_uid: session_uid.to_string(),
         },
     };
-    seq += 1;
-    let mut reader = client.try_clone().unwrap();
-    let tx_clone = tx.clone();
-    thread::spawn(move || {
-        read_from_stream(&mut reader, tx_clone);
-    });
-    let msg_bytes = serde_json::to_vec(&init_msg).unwrap();
-    send_to_stream(StreamId::Control.to_u8(), &mut client.try_clone().unwrap(), &msg_bytes).unwrap();
-    let msg = wait_for_message(&rx, Duration::from_secs(5)).unwrap_or_else(|| {
-        panic!("Did not receive any message from server within timeout");
-    });
-    let response: ControlResponse = serde_json::from_str(&msg).unwrap();
+    send_control(&mut client, &init_msg);
+    let response = frames.response(1);
     assert!(response.success);
     if let Some(ControlResponseData::Initialize { version, server_cwd }) = response.data {
         assert_eq!(version, CURRENT_VERSION);
-        assert!(server_cwd.contains("test-uid"));
+        assert!(server_cwd.contains(workspace_uid));
     } else {
         panic!("Expected Initialize response data");
     }
+    (client, frames)
+}
+
+#[test]
+fn test_proxy_server() {
+    let (mut client, mut frames) = connect_to_proxy("test-uid", "test-session-uid");
 
     let allc_ports_msg = ControlMessage {
-        seq,
+        seq: 2,
         request: ControlRequest::AllocatePorts {
             ports_spec: PortAllocatorSpec {
                 all_ports: vec![PortSet {
@@ -199,12 +260,8 @@ fn test_proxy_server() {
             },
         },
     };
-    let msg_bytes = serde_json::to_vec(&allc_ports_msg).unwrap();
-    send_to_stream(StreamId::Control.to_u8(), &mut client.try_clone().unwrap(), &msg_bytes).unwrap();
-    let msg = wait_for_message(&rx, Duration::from_secs(5)).unwrap_or_else(|| {
-        panic!("Did not receive any message from server within timeout");
-    });
-    let response: ControlResponse = serde_json::from_str(&msg).unwrap();
+    send_control(&mut client, &allc_ports_msg);
+    let response = frames.response(2);
     assert!(response.success);
     if let Some(ControlResponseData::AllocatePorts { ports }) = response.data {
         assert_eq!(ports.len(), 2);
@@ -219,3 +276,325 @@ fn test_proxy_server() {
         panic!("Expected AllocatePorts response data");
     }
 }
+
+/// Set for the copy of this test binary that `sim_server_end_to_end` has the proxy launch
+const SIM_PORT_ENV: &str = "MDBG_TEST_SIM_PORT";
+
+/// The gdb-server of `sim_server_end_to_end`: that test has the proxy run this binary again with
+/// only this test selected, which then serves a simulated target like `mdbg sim-server`.
+#[test]
+#[ignore = "launched by sim_server_end_to_end as its gdb-server"]
+fn sim_server_child() {
+    let Ok(port) = std::env::var(SIM_PORT_ENV) else {
+        return;
+    };
+    // Never outlive a parent that failed before ending its session
+    thread::spawn(|| {
+        thread::sleep(Duration::from_secs(60));
+        std::process::exit(0);
+    });
+    let mut memory = crate::da_helper::image::MemoryImage::new();
+    // movs r0, #1; b .
+    memory.write(0x0800_0000, &[0x01, 0x20, 0xfe, 0xe7]);
+    memory.write(0x2000_0000, &[0; 0x100]);
+    let sim = crate::da_helper::sim_target::SimTarget::from_image(memory, 0x2000_0100, 0x0800_0001);
+    let listener = TcpListener::bind(("127.0.0.1", port.parse::<u16>().unwrap())).unwrap();
+    crate::da_helper::sim_cmd::serve(listener, sim).unwrap();
+}
+
+#[test]
+fn sim_server_end_to_end() {
+    let (mut client, mut frames) = connect_to_proxy("sim-uid", "sim-session-uid");
+
+    let allocate = ControlMessage {
+        seq: 2,
+        request: ControlRequest::AllocatePorts {
+            ports_spec: PortAllocatorSpec {
+                all_ports: vec![PortSet {
+                    start_port: 5100,
+                    port_ids: vec!["gdbPort".to_string()],
+                }],
+            },
+        },
+    };
+    send_control(&mut client, &allocate);
+    let Some(ControlResponseData::AllocatePorts { ports }) = frames.response(2).data else {
+        panic!("Expected AllocatePorts response data");
+    };
+    let (stream_id, port) = (ports[0].stream_id, ports[0].port);
+
+    let start = ControlMessage {
+        seq: 3,
+        request: ControlRequest::StartGdbServer {
+            server_path: std::env::current_exe().unwrap().to_string_lossy().into_owned(),
+            server_args: [
+                "--exact",
+                "proxy_helper::proxy_server::tests::sim_server_child",
+                "--ignored",
+            ]
+            .map(String::from)
+            .to_vec(),
+            server_env: Some(HashMap::from([(SIM_PORT_ENV.to_string(), port.to_string())])),
+            restart: None,
+            cwd: None,
+            limits: None,
+        },
+    };
+    send_control(&mut client, &start);
+    let response = frames.response(3);
+    assert!(response.success, "startGdbServer failed: {:?}", response.message);
+
+    // The port monitor sees the simulator listen on the port it was given
+    let ready = frames.event(
+        Duration::from_secs(20),
+        |event| matches!(event, ProxyServerEvents::StreamReady { stream_id: id, .. } if *id == stream_id),
+    );
+    assert!(ready.is_some(), "The simulator's port never became ready");
+
+    send_control(
+        &mut client,
+        &ControlMessage {
+            seq: 4,
+            request: ControlRequest::StartStream { stream_id },
+        },
+    );
+    let response = frames.response(4);
+    assert!(matches!(
+        response.data,
+        Some(ControlResponseData::StreamStatus {
+            status: StreamStatus::Connected,
+            ..
+        })
+    ));
+
+    // RSP goes through the proxy to the simulator and its answer comes back on the same stream
+    send_to_stream(stream_id, &mut client, b"$?#3f").unwrap();
+    let mut rsp = String::new();
+    while !rsp.contains("$T05thread:1;#") {
+        let (_, text) = frames
+            .wait_for(Duration::from_secs(5), |(id, _)| *id == stream_id)
+            .unwrap_or_else(|| panic!("No stop reply from the simulator, got '{}'", rsp));
+        rsp.push_str(&text);
+    }
+    assert!(rsp.starts_with('+'));
+
+    send_control(
+        &mut client,
+        &ControlMessage {
+            seq: 5,
+            request: ControlRequest::EndSession,
+        },
+    );
+    assert!(frames.response(5).success);
+}
+
+/// A session on a control connection of its own, working in `server_cwd`. Tests drive it
+/// directly rather than through its message loop.
+fn test_session(server_cwd: &std::path::Path) -> (ProxyServer, Frames) {
+    let control = TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = TcpStream::connect(control.local_addr().unwrap()).unwrap();
+    let frames = Frames::new(&client);
+    let mut server = ProxyServer::new(
+        test_args(),
+        control.accept().unwrap().0,
+        Arc::new(Mutex::new(HashMap::new())),
+        Arc::new(SerialAvailabilityHub::new()),
+    );
+    server.server_cwd = server_cwd.to_string_lossy().into_owned();
+    (server, frames)
+}
+
+/// An executable shell script in `dir` for a session to launch as its gdb-server
+#[cfg(unix)]
+fn write_script(dir: &std::path::Path, body: &str) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let script = dir.join("gdb-server.sh");
+    std::fs::write(&script, format!("#!/bin/sh\n{body}")).unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    script
+}
+
+/// Launch `script` with `startGdbServer`, returning its pid
+fn start_gdb_server(
+    server: &mut ProxyServer,
+    frames: &mut Frames,
+    script: &std::path::Path,
+    restart: Option<RestartPolicy>,
+) -> u32 {
+    let start = ControlMessage {
+        seq: 1,
+        request: ControlRequest::StartGdbServer {
+            server_path: script.to_string_lossy().into_owned(),
+            server_args: Vec::new(),
+            server_env: None,
+            restart,
+            cwd: None,
+            limits: None,
+        },
+    };
+    server.handle_start_gdb_server(&start);
+    let Some(ControlResponseData::StartGdbServer { pid }) = frames.response(1).data else {
+        panic!("Expected StartGdbServer response data");
+    };
+    pid
+}
+
+/// The next event `matches` accepts on the session's own channel, as its message loop would see it
+fn next_event(server: &ProxyServer, matches: impl Fn(&ProxyEvent) -> bool) -> ProxyEvent {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let event = server
+            .event_rx
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .expect("No session event within timeout");
+        if matches(&event) {
+            return event;
+        }
+    }
+}
+
+/// A server that keeps crashing is relaunched on its ports with its streams reconnected,
+/// until the restart policy runs out and the session ends. The session is driven one
+/// event at a time, the way its message loop does, so its state can be checked in between.
+#[cfg(unix)]
+#[test]
+fn a_crashing_gdb_server_is_restarted_until_the_policy_runs_out() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = write_script(dir.path(), "sleep 0.2\nexit 3\n");
+    // Stands in for the port the server listens on, so its stream can connect
+    let gdb_port = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = gdb_port.local_addr().unwrap().port();
+
+    let (mut server, mut frames) = test_session(dir.path());
+    server.reserved_ports.push(PortInfoListner {
+        port,
+        stream_id: 3,
+        listener: None,
+    });
+    let policy = RestartPolicy {
+        max_restarts: 1,
+        backoff_ms: Some(10),
+        max_backoff_ms: None,
+    };
+    let first_pid = start_gdb_server(&mut server, &mut frames, &script, Some(policy));
+    // GDB is connected when the server crashes, as the message loop records it
+    server.streams.insert(
+        3,
+        PortInfo {
+            port,
+            stream_id: 3,
+            stream: Some(TcpStream::connect(("127.0.0.1", port)).unwrap()),
+        },
+    );
+    server.gdb_launch.as_mut().unwrap().note_connected(3, port);
+    gdb_port.accept().unwrap();
+
+    next_event(
+        &server,
+        |e| matches!(e, ProxyEvent::GdbServerExited { pid, .. } if *pid == first_pid),
+    );
+    assert!(
+        server.gdb_server_exited(first_pid, 3).unwrap(),
+        "The first crash must be restarted"
+    );
+    // Only the launch that crashed can be relaunched
+    assert!(server.restart_gdb_server(first_pid + 1).is_none());
+
+    next_event(&server, |e| matches!(e, ProxyEvent::RestartGdbServer { .. }));
+    assert!(server.relaunch_gdb_server(first_pid, 3).unwrap());
+    let Some(ProxyServerEvents::GdbServerRestarted {
+        previous_pid,
+        pid: second_pid,
+        exit_code,
+        attempt,
+    }) = frames.event(Duration::from_secs(5), |e| {
+        matches!(e, ProxyServerEvents::GdbServerRestarted { .. })
+    })
+    else {
+        panic!("Expected gdbServerRestarted");
+    };
+    assert_eq!((previous_pid, exit_code, attempt), (first_pid, 3, 1));
+    assert_ne!(second_pid, first_pid);
+    // The old connection is dropped at once and a new one made to the relaunched server
+    assert!(server.streams[&3].stream.is_none());
+    next_event(&server, |e| matches!(e, ProxyEvent::PortConnected { stream_id: 3, .. }));
+    gdb_port.accept().unwrap();
+    // A restart timer still running for the first launch is stale now
+    assert!(server.restart_gdb_server(first_pid).is_none());
+
+    next_event(
+        &server,
+        |e| matches!(e, ProxyEvent::GdbServerExited { pid, .. } if *pid == second_pid),
+    );
+    assert!(
+        !server.gdb_server_exited(second_pid, 3).unwrap(),
+        "The restart is spent"
+    );
+    let exited = frames.event(
+        Duration::from_secs(5),
+        |e| matches!(e, ProxyServerEvents::GdbServerExited { pid, exit_code: 3 } if *pid == second_pid),
+    );
+    assert!(exited.is_some(), "The client must hear that the session is over");
+}
+
+/// The pid a gdb-server script wrote to `helper.pid` for the helper it forked
+#[cfg(unix)]
+fn helper_pid(dir: &std::path::Path) -> u32 {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let text = std::fs::read_to_string(dir.join("helper.pid")).unwrap_or_default();
+        if let Ok(pid) = text.trim().parse() {
+            return pid;
+        }
+        assert!(Instant::now() < deadline, "The script never reported its helper");
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// Alive and not a zombie; an orphaned helper's zombie holds nothing
+#[cfg(target_os = "linux")]
+fn assert_dies(pid: u32) {
+    let running = || match std::fs::read_to_string(format!("/proc/{pid}/stat")) {
+        Ok(stat) => !stat.rsplit(')').next().unwrap_or("").trim_start().starts_with('Z'),
+        Err(_) => false,
+    };
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while running() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(20));
+    }
+    assert!(!running(), "helper {pid} outlived its gdb-server");
+}
+
+/// Ending the session takes down what the gdb-server forked, not just the server.
+#[cfg(target_os = "linux")]
+#[test]
+fn ending_the_session_kills_the_helpers_a_gdb_server_forked() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = write_script(dir.path(), "sleep 30 &\necho $! > helper.pid\nwait\n");
+    let (mut server, mut frames) = test_session(dir.path());
+    start_gdb_server(&mut server, &mut frames, &script, None);
+    let helper = helper_pid(dir.path());
+
+    server.end_process();
+    assert_dies(helper);
+}
+
+/// A gdb-server that exits on its own leaves no helpers behind either: the reaper kills
+/// its group before it reaps the server, while the group's id is still the server's.
+#[cfg(target_os = "linux")]
+#[test]
+fn a_gdb_server_that_exits_takes_its_helpers_with_it() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = write_script(dir.path(), "sleep 30 &\necho $! > helper.pid\n");
+    let (mut server, mut frames) = test_session(dir.path());
+    let pid = start_gdb_server(&mut server, &mut frames, &script, None);
+    let helper = helper_pid(dir.path());
+
+    next_event(
+        &server,
+        |e| matches!(e, ProxyEvent::GdbServerExited { pid: exited, .. } if *exited == pid),
+    );
+    assert_dies(helper);
+    assert!(!server.gdb_server_exited(pid, 0).unwrap());
+}
@@ -129,6 +129,25 @@ pub struct ProxyArgs {
     #[arg(long = "close-serial", value_name = "PATH|all")]
     pub close_serial: Option<String>,
 
+    /// Client mode: list the sessions the running proxy has recorded (newest first)
+    /// as JSON, then exit. Each keeps its gdb-server output and control traffic under
+    /// `<log-dir>/sessions/`, so a failed session can be examined after the fact.
+    #[arg(long = "sessions", default_value_t = false)]
+    pub sessions: bool,
+
+    /// Client mode: print a recorded session's gdb-server output, then exit. Takes a
+    /// session id from `--sessions`, or the client's session uid (newest match).
+    #[arg(long = "session-log", value_name = "ID")]
+    pub session_log: Option<String>,
+
+    /// With `--session-log`: which capture to print.
+    #[arg(long = "log-stream", value_name = "stdout|stderr|control", default_value = "stdout")]
+    pub log_stream: String,
+
+    /// With `--session-log`: print only the last N lines (0 = everything kept).
+    #[arg(long = "tail", value_name = "N", default_value_t = 0)]
+    pub tail: usize,
+
     /// Internal: marks the re-spawned, detached daemon so it runs the proxy
     /// instead of launching another daemon. Not for direct use.
     #[arg(long = "daemonized", hide = true, default_value_t = false)]
@@ -172,12 +191,17 @@ fn resolve_token(supplied: Option<&str>) -> Result<String> {
     Ok(token.to_string())
 }
 
-fn init_logging(args: &ProxyArgs) -> Option<LoggerHandle> {
-    let log_dir = args
-        .log_dir
+/// Where this proxy writes its log files and per-session captures: `--log-dir`, or a
+/// fixed spot under the temp dir.
+pub(crate) fn log_dir(args: &ProxyArgs) -> PathBuf {
+    args.log_dir
         .clone()
         .map(PathBuf::from)
-        .unwrap_or_else(|| std::env::temp_dir().join("mcu-debug").join("proxy-logs"));
+        .unwrap_or_else(|| std::env::temp_dir().join("mcu-debug").join("proxy-logs"))
+}
+
+fn init_logging(args: &ProxyArgs) -> Option<LoggerHandle> {
+    let log_dir = log_dir(args);
 
     let ts = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -295,6 +319,10 @@ fn run_admin_client(args: &ProxyArgs) -> Result<()> {
         };
     }
 
+    if args.sessions || args.session_log.is_some() {
+        return session_request(args);
+    }
+
     let instance = singleton::Instance::resolve(&args.instance)?;
     let print = |resp: &admin::AdminResponse| -> Result<()> {
         println!("{}", serde_json::to_string_pretty(resp)?);
@@ -307,6 +335,8 @@ fn run_admin_client(args: &ProxyArgs) -> Result<()> {
         message: None,
         closed: Vec::new(),
         hosts: Vec::new(),
+        sessions: Vec::new(),
+        log: None,
     };
 
     let endpoint = match singleton::read_endpoint(&instance.endpoint_path) {
@@ -326,6 +356,9 @@ fn run_admin_client(args: &ProxyArgs) -> Result<()> {
         version: String::new(),
         path: String::new(),
         host: String::new(),
+        session: String::new(),
+        stream: String::new(),
+        tail: 0,
     };
     match admin::query(&endpoint, &req) {
         Ok(resp) => print(&resp),
@@ -333,6 +366,66 @@ fn run_admin_client(args: &ProxyArgs) -> Result<()> {
     }
 }
 
+/// `--sessions` / `--session-log` against the single resolved instance.
+///
+/// A fetched log is printed as plain text, exactly as the server wrote it, so it can
+/// be piped through `grep` or `less`. Everything else — the listing, and any failure —
+/// is JSON, like the other client modes.
+fn session_request(args: &ProxyArgs) -> Result<()> {
+    let instance = singleton::Instance::resolve(&args.instance)?;
+    let resp = match singleton::read_endpoint(&instance.endpoint_path) {
+        Ok(endpoint) => {
+            let req = admin::AdminRequest {
+                v: 1,
+                cmd: if args.session_log.is_some() {
+                    "sessionLog"
+                } else {
+                    "sessions"
+                }
+                .to_string(),
+                token: endpoint.token.clone(),
+                graceful: false,
+                version: String::new(),
+                path: String::new(),
+                host: String::new(),
+                session: args.session_log.clone().unwrap_or_default(),
+                stream: args.log_stream.clone(),
+                tail: args.tail,
+            };
+            admin::query(&endpoint, &req).unwrap_or_else(|e| admin::AdminResponse {
+                ok: false,
+                error: Some(format!("proxy not reachable ({e:#})")),
+                status: None,
+                message: None,
+                closed: Vec::new(),
+                hosts: Vec::new(),
+                sessions: Vec::new(),
+                log: None,
+            })
+        }
+        Err(_) => admin::AdminResponse {
+            ok: false,
+            error: Some(format!("no proxy running for instance '{}'", instance.name)),
+            status: None,
+            message: None,
+            closed: Vec::new(),
+            hosts: Vec::new(),
+            sessions: Vec::new(),
+            log: None,
+        },
+    };
+    match (&resp.log, resp.ok) {
+        (Some(text), true) => {
+            if let Some(note) = &resp.message {
+                eprintln!("({note})");
+            }
+            print!("{text}");
+        }
+        _ => println!("{}", serde_json::to_string_pretty(&resp)?),
+    }
+    Ok(())
+}
+
 /// One instance's answer to `--close-serial`.
 #[derive(serde::Serialize)]
 struct CloseSerialResult {
@@ -364,6 +457,9 @@ fn close_serial_request(endpoint: &singleton::Endpoint, path: &str) -> admin::Ad
         version: String::new(),
         path: path.to_string(),
         host: String::new(),
+        session: String::new(),
+        stream: String::new(),
+        tail: 0,
     }
 }
 
@@ -466,6 +562,9 @@ fn print_status_all() -> Result<()> {
             graceful: true,
             version: String::new(),
             host: String::new(),
+            session: String::new(),
+            stream: String::new(),
+            tail: 0,
         };
         if let Ok(resp) = admin::query(&endpoint, &req) {
             if let Some(status) = resp.status {
@@ -520,6 +619,9 @@ fn shutdown_all() -> Result<()> {
             version: String::new(),
             path: String::new(),
             host: String::new(),
+            session: String::new(),
+            stream: String::new(),
+            tail: 0,
         };
         // Only report instances that actually answered — a dead proxy's stale
         // endpoint refuses the connection and needs no shutdown.
@@ -576,6 +678,9 @@ fn widen_running_proxy(ep: &singleton::Endpoint, args: &ProxyArgs) -> (Vec<Strin
         version: String::new(),
         path: String::new(),
         host: host.to_string(),
+        session: String::new(),
+        stream: String::new(),
+        tail: 0,
     };
     match admin::query(ep, &req) {
         Ok(resp) if resp.ok => (if resp.hosts.is_empty() { known } else { resp.hosts }, Vec::new()),
@@ -785,7 +890,7 @@ pub fn run(mut args: ProxyArgs) -> Result<()> {
 
     // Client modes: query/command a running proxy and exit — do not start one.
     // Kept lightweight: no daemon logging setup.
-    if args.status || args.shutdown || args.close_serial.is_some() {
+    if args.status || args.shutdown || args.close_serial.is_some() || args.sessions || args.session_log.is_some() {
         return run_admin_client(&args);
     }
 
@@ -939,6 +1044,7 @@ pub fn run(mut args: ProxyArgs) -> Result<()> {
         version: singleton::self_version(),
         instance: instance.name.clone(),
         started_at_unix: endpoint.started_at_unix,
+        sessions_root: crate::proxy_helper::session_log::sessions_root(&log_dir(&args)),
     });
 
     // Stdin heartbeat watchdog — only when explicitly requested via --heartbeat.
@@ -1030,6 +1136,8 @@ pub fn run(mut args: ProxyArgs) -> Result<()> {
             status: false,
             shutdown: false,
             all: false,
+            sessions: false,
+            session_log: None,
             daemonized: true,
             ..args.clone()
         },
@@ -1162,6 +1270,10 @@ mod tests {
             shutdown: false,
             all: false,
             close_serial: None,
+            sessions: false,
+            session_log: None,
+            log_stream: "stdout".to_string(),
+            tail: 0,
             daemonized: false,
         };
 
@@ -0,0 +1,485 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-session capture of gdb-server output and funnel control traffic.
+//!
+//! gdb-server stdout/stderr reach the client as `StreamData` on the reserved streams
+//! and are gone once forwarded — so when a session fails, the lines that explain why
+//! (openocd's "no device found", a J-Link license prompt) often went to a client that
+//! had already stopped listening. Every initialized session therefore also writes
+//! them to disk, next to the proxy's own log:
+//!
+//! ```text
+//! <log-dir>/sessions/<id>/
+//! ├── session.json   # who the session was, when it ran, how its server ended
+//! ├── stdout.log     # gdb-server stdout, raw bytes
+//! ├── stderr.log     # gdb-server stderr, raw bytes
+//! └── control.log    # one line per control frame, `<unix-ms> recv|send <json>`
+//! ```
+//!
+//! Each file rotates at [`MAX_FILE_BYTES`] (`stdout.log.1`, `.2`, …) and only the
+//! newest [`KEEP_SESSIONS`] session directories are kept, so a long-lived lab daemon
+//! cannot fill the disk. Past sessions are listed and read back through the admin
+//! channel (`mdbg proxy --sessions` / `--session-log`).
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::common::sync::MutexExt;
+
+/// Size at which a capture file is rotated.
+pub const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Rotated generations kept per capture file, besides the live one.
+pub const KEEP_ROTATED: usize = 3;
+
+/// Session directories kept under `sessions/`; older ones are pruned when a new
+/// session starts.
+pub const KEEP_SESSIONS: usize = 50;
+
+/// Most bytes one fetch returns. A whole capture can be several megabytes, and the
+/// admin channel is one line of JSON; the newest output is what explains a failure.
+pub const MAX_FETCH_BYTES: usize = 256 * 1024;
+
+/// Control frames longer than this are cut in the log. Only `syncFile` gets near it,
+/// and its payload is a file the client already has.
+const MAX_CONTROL_LINE: usize = 4096;
+
+const META_FILE: &str = "session.json";
+
+/// Distinguishes sessions started in the same second by the same proxy.
+static NEXT_SESSION: AtomicU32 = AtomicU32::new(1);
+
+/// Where session directories live for a given proxy log directory.
+pub fn sessions_root(log_dir: &Path) -> PathBuf {
+    log_dir.join("sessions")
+}
+
+/// One of the files captured per session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureStream {
+    Stdout,
+    Stderr,
+    Control,
+}
+
+impl CaptureStream {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "stdout" => Some(CaptureStream::Stdout),
+            "stderr" => Some(CaptureStream::Stderr),
+            "control" => Some(CaptureStream::Control),
+            _ => None,
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            CaptureStream::Stdout => "stdout.log",
+            CaptureStream::Stderr => "stderr.log",
+            CaptureStream::Control => "control.log",
+        }
+    }
+}
+
+/// Which way a control frame went, as written in `control.log`.
+#[derive(Debug, Clone, Copy)]
+pub enum Direction {
+    /// Client → proxy.
+    Recv,
+    /// Proxy → client.
+    Send,
+}
+
+/// Contents of `session.json`, and one entry of the admin `sessions` listing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub id: String,
+    pub workspace_uid: String,
+    pub session_uid: String,
+    pub proxy_pid: u32,
+    pub started_at_unix: u64,
+    /// `None` while the session is live — or if the proxy died before it could
+    /// close the session out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended_at_unix: Option<u64>,
+    /// Exit code of a gdb-server that exited on its own. Absent if it was stopped by
+    /// the session ending, or never started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_exit_code: Option<i32>,
+}
+
+/// A capture file that rotates itself by size.
+struct RotatingFile {
+    path: PathBuf,
+    file: Option<File>,
+    written: u64,
+}
+
+impl RotatingFile {
+    fn new(path: PathBuf) -> Self {
+        RotatingFile {
+            path,
+            file: None,
+            written: 0,
+        }
+    }
+
+    /// Append `bytes`, rotating first if they would push the file past the cap.
+    /// Opened lazily, so a server that never prints leaves no empty file behind.
+    fn append(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if self.written > 0 && self.written + bytes.len() as u64 > MAX_FILE_BYTES {
+            self.file = None;
+            rotate(&self.path)?;
+            self.written = 0;
+        }
+        if self.file.is_none() {
+            self.file = Some(OpenOptions::new().create(true).append(true).open(&self.path)?);
+        }
+        // Unbuffered on purpose: the point is that the bytes are on disk when the
+        // session dies, however it dies.
+        self.file.as_mut().expect("opened above").write_all(bytes)?;
+        self.written += bytes.len() as u64;
+        Ok(())
+    }
+}
+
+/// `x.log.2 → x.log.3`, …, `x.log → x.log.1`; the oldest generation is dropped.
+fn rotate(path: &Path) -> io::Result<()> {
+    let _ = fs::remove_file(rotated(path, KEEP_ROTATED));
+    for n in (1..KEEP_ROTATED).rev() {
+        let from = rotated(path, n);
+        if from.exists() {
+            fs::rename(&from, rotated(path, n + 1))?;
+        }
+    }
+    fs::rename(path, rotated(path, 1))
+}
+
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+/// The capture for one live session. Shared by every thread that writes frames for
+/// it, so each file sits behind its own lock.
+pub struct SessionLog {
+    dir: PathBuf,
+    meta: Mutex<SessionSummary>,
+    stdout: Mutex<RotatingFile>,
+    stderr: Mutex<RotatingFile>,
+    control: Mutex<RotatingFile>,
+}
+
+impl SessionLog {
+    /// Create the directory for a new session under `root` and record who it is.
+    /// Prunes the oldest sessions first so the directory count stays bounded.
+    pub fn create(root: &Path, workspace_uid: &str, session_uid: &str) -> Result<SessionLog> {
+        fs::create_dir_all(root).with_context(|| format!("could not create {}", root.display()))?;
+        prune(root, KEEP_SESSIONS.saturating_sub(1));
+        let started_at_unix = now_millis() / 1000;
+        let id = format!(
+            "{}-{}-{:06}",
+            started_at_unix,
+            std::process::id(),
+            NEXT_SESSION.fetch_add(1, Ordering::Relaxed)
+        );
+        let dir = root.join(&id);
+        fs::create_dir(&dir).with_context(|| format!("could not create {}", dir.display()))?;
+        let meta = SessionSummary {
+            id,
+            workspace_uid: workspace_uid.to_string(),
+            session_uid: session_uid.to_string(),
+            proxy_pid: std::process::id(),
+            started_at_unix,
+            ended_at_unix: None,
+            server_exit_code: None,
+        };
+        write_meta(&dir, &meta)?;
+        Ok(SessionLog {
+            stdout: Mutex::new(RotatingFile::new(dir.join(CaptureStream::Stdout.file_name()))),
+            stderr: Mutex::new(RotatingFile::new(dir.join(CaptureStream::Stderr.file_name()))),
+            control: Mutex::new(RotatingFile::new(dir.join(CaptureStream::Control.file_name()))),
+            meta: Mutex::new(meta),
+            dir,
+        })
+    }
+
+    pub fn id(&self) -> String {
+        self.meta.lock_recover().id.clone()
+    }
+
+    /// Record a frame on the gdb-server's stdout (stream 1) or stderr (stream 2).
+    /// Any other stream is ignored: that is target traffic, not server output.
+    pub fn server_output(&self, stream_id: u8, bytes: &[u8]) {
+        let file = match stream_id {
+            1 => &self.stdout,
+            2 => &self.stderr,
+            _ => return,
+        };
+        if let Err(e) = file.lock_recover().append(bytes) {
+            log::warn!("session log: could not write stream {stream_id}: {e}");
+        }
+    }
+
+    /// Record one control frame. The initialize token is blanked first — these
+    /// files outlive the session and are readable by anyone who can read the logs.
+    pub fn control(&self, direction: Direction, json: &[u8]) {
+        let mut text = redact(json);
+        if text.len() > MAX_CONTROL_LINE {
+            let total = text.len();
+            let mut cut = MAX_CONTROL_LINE;
+            while !text.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            text.truncate(cut);
+            text.push_str(&format!("… ({total} bytes)"));
+        }
+        let dir = match direction {
+            Direction::Recv => "recv",
+            Direction::Send => "send",
+        };
+        let line = format!("{} {dir} {text}\n", now_millis());
+        if let Err(e) = self.control.lock_recover().append(line.as_bytes()) {
+            log::warn!("session log: could not write control frame: {e}");
+        }
+    }
+
+    /// Note that the gdb-server exited on its own, with `exit_code`.
+    pub fn server_exited(&self, exit_code: i32) {
+        let mut meta = self.meta.lock_recover();
+        meta.server_exit_code = Some(exit_code);
+        let _ = write_meta(&self.dir, &meta);
+    }
+
+    /// Close the session out. Called once, as the session ends.
+    pub fn finish(&self) {
+        let mut meta = self.meta.lock_recover();
+        meta.ended_at_unix = Some(now_millis() / 1000);
+        if let Err(e) = write_meta(&self.dir, &meta) {
+            log::warn!("session log: could not record end of session {}: {e:#}", meta.id);
+        }
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Written via a temp file, so a lister never reads half a `session.json`.
+fn write_meta(dir: &Path, meta: &SessionSummary) -> Result<()> {
+    let path = dir.join(META_FILE);
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec_pretty(meta)?).with_context(|| format!("could not write {}", tmp.display()))?;
+    fs::rename(&tmp, &path).with_context(|| format!("could not rename {}", tmp.display()))?;
+    Ok(())
+}
+
+/// The frame as text, with any `token` field blanked. Frames that are not JSON are
+/// logged as they came; they carry nothing to hide because nothing could parse them.
+fn redact(json: &[u8]) -> String {
+    fn blank(v: &mut serde_json::Value) {
+        match v {
+            serde_json::Value::Object(map) => {
+                for (k, v) in map.iter_mut() {
+                    if k == "token" {
+                        *v = serde_json::Value::String("***".into());
+                    } else {
+                        blank(v);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(blank),
+            _ => {}
+        }
+    }
+    match serde_json::from_slice::<serde_json::Value>(json) {
+        Ok(mut v) => {
+            blank(&mut v);
+            v.to_string()
+        }
+        Err(_) => String::from_utf8_lossy(json).into_owned(),
+    }
+}
+
+/// Every session under `root`, newest first. Directories without a readable
+/// `session.json` are skipped rather than failing the listing.
+pub fn list_sessions(root: &Path) -> Vec<SessionSummary> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut sessions: Vec<SessionSummary> = entries
+        .flatten()
+        .filter_map(|e| fs::read(e.path().join(META_FILE)).ok())
+        .filter_map(|bytes| serde_json::from_slice(&bytes).ok())
+        .collect();
+    sessions.sort_by(|a, b| b.started_at_unix.cmp(&a.started_at_unix).then_with(|| b.id.cmp(&a.id)));
+    sessions
+}
+
+/// Remove the oldest session directories so at most `keep` remain.
+fn prune(root: &Path, keep: usize) {
+    for old in list_sessions(root).into_iter().skip(keep) {
+        if let Err(e) = fs::remove_dir_all(root.join(&old.id)) {
+            log::warn!("session log: could not prune {}: {e}", old.id);
+        }
+    }
+}
+
+/// Find a session by its id or, failing that, by the client's `session_uid` (the
+/// newest such session) — the client knows the latter without listing first.
+pub fn find_session(root: &Path, key: &str) -> Option<SessionSummary> {
+    let sessions = list_sessions(root);
+    sessions
+        .iter()
+        .find(|s| s.id == key)
+        .or_else(|| sessions.iter().find(|s| s.session_uid == key))
+        .cloned()
+}
+
+/// What a fetch returned: the text, and whether older output was left out.
+pub struct Fetched {
+    pub session: SessionSummary,
+    pub text: String,
+    pub truncated: bool,
+}
+
+/// Read back one capture of a past (or live) session, rotated generations first so
+/// the text is in order. `tail` keeps only the last that many lines; 0 keeps all.
+/// Either way at most [`MAX_FETCH_BYTES`] come back, the newest ones.
+pub fn read_capture(root: &Path, key: &str, stream: CaptureStream, tail: usize) -> Result<Fetched> {
+    let Some(session) = find_session(root, key) else {
+        bail!("no session '{key}'");
+    };
+    let path = root.join(&session.id).join(stream.file_name());
+    let mut bytes = Vec::new();
+    for n in (1..=KEEP_ROTATED).rev() {
+        if let Ok(older) = fs::read(rotated(&path, n)) {
+            bytes.extend_from_slice(&older);
+        }
+    }
+    match fs::read(&path) {
+        Ok(live) => bytes.extend_from_slice(&live),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e).with_context(|| format!("could not read {}", path.display())),
+    }
+    let text = String::from_utf8_lossy(&bytes);
+    let mut start = 0;
+    if tail > 0 {
+        // Find where the last `tail` lines begin; a trailing newline ends the last
+        // line rather than starting an empty one.
+        let body = text.strip_suffix('\n').unwrap_or(&text);
+        if let Some(pos) = body.rmatch_indices('\n').nth(tail - 1).map(|(i, _)| i + 1) {
+            start = pos;
+        }
+    }
+    let mut truncated = start > 0;
+    if text.len() - start > MAX_FETCH_BYTES {
+        start = text.len() - MAX_FETCH_BYTES;
+        while !text.is_char_boundary(start) {
+            start += 1;
+        }
+        truncated = true;
+    }
+    Ok(Fetched {
+        session,
+        text: text[start..].to_string(),
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_server_output_and_redacted_control_frames() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let log = SessionLog::create(root, "ws", "sess-1").unwrap();
+        log.server_output(1, b"Open On-Chip Debugger\n");
+        log.server_output(2, b"Error: no device found\n");
+        log.server_output(3, b"$qSupported#37");
+        log.control(
+            Direction::Recv,
+            br#"{"seq":1,"method":"initialize","params":{"token":"secret-0123456789","version":"1"}}"#,
+        );
+        log.server_exited(1);
+        log.finish();
+
+        let out = read_capture(root, "sess-1", CaptureStream::Stdout, 0).unwrap();
+        assert_eq!(out.text, "Open On-Chip Debugger\n");
+        let err = read_capture(root, &log.id(), CaptureStream::Stderr, 0).unwrap();
+        assert_eq!(err.text, "Error: no device found\n");
+        let control = read_capture(root, "sess-1", CaptureStream::Control, 0).unwrap();
+        assert!(control.text.contains(" recv "));
+        assert!(control.text.contains("\"initialize\""));
+        assert!(!control.text.contains("secret"));
+
+        let listed = list_sessions(root);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].server_exit_code, Some(1));
+        assert!(listed[0].ended_at_unix.is_some());
+    }
+
+    #[test]
+    fn rotation_keeps_order_and_tail_takes_the_last_lines() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let log = SessionLog::create(root, "ws", "sess-2").unwrap();
+        // Lines of 64 KiB, enough to roll the file over more than once.
+        let lines: Vec<String> = (0..40)
+            .map(|i| format!("{i:04} {}\n", "x".repeat(64 * 1024 - 6)))
+            .collect();
+        for line in &lines {
+            log.server_output(1, line.as_bytes());
+        }
+        assert!(rotated(&root.join(log.id()).join("stdout.log"), 2).exists());
+
+        let last = read_capture(root, "sess-2", CaptureStream::Stdout, 2).unwrap();
+        assert_eq!(last.text, format!("{}{}", lines[38], lines[39]));
+        assert!(last.truncated);
+
+        // Everything that survived rotation comes back in order, newest last, capped.
+        let all = read_capture(root, "sess-2", CaptureStream::Stdout, 0).unwrap();
+        assert!(all.truncated);
+        assert_eq!(all.text.len(), MAX_FETCH_BYTES);
+        assert!(all.text.ends_with(&lines[39]));
+    }
+
+    #[test]
+    fn old_sessions_are_pruned_and_unknown_ones_are_reported() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        for i in 0..KEEP_SESSIONS + 3 {
+            SessionLog::create(root, "ws", &format!("s{i}")).unwrap();
+        }
+        let listed = list_sessions(root);
+        assert_eq!(listed.len(), KEEP_SESSIONS);
+        assert!(find_session(root, "s0").is_none());
+        assert!(find_session(root, &format!("s{}", KEEP_SESSIONS + 2)).is_some());
+        assert!(read_capture(root, "nope", CaptureStream::Stdout, 0).is_err());
+    }
+}
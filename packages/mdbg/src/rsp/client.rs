@@ -0,0 +1,790 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The client side of RSP: talks to a gdb-server (OpenOCD, J-Link, pyOCD, or our own stub) the way
+//! GDB does, so mdbg can read memory and control the target without going through GDB. The client
+//! runs in all-stop mode: after `resume` the server answers with a stop reply once the target halts.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use regex::Regex;
+
+use crate::common::utils::{decode_hex, encode_hex};
+use crate::rsp::packet::{Connection, Incoming};
+
+/// Packet size assumed when the server doesn't say
+const DEFAULT_PACKET_SIZE: usize = 256;
+
+/// Room for the command, address and length in front of memory data in `M` and `X` packets
+const WRITE_HEADER: usize = 32;
+
+/// What we tell the server we support in `qSupported`
+const CLIENT_FEATURES: &str = "qSupported:multiprocess-;swbreak+;hwbreak+;xmlRegisters=arm;vContSupported+";
+
+#[derive(Debug)]
+pub enum RspError {
+    Io(io::Error),
+    /// The server closed the connection
+    Closed,
+    /// An `Exx` reply
+    Target {
+        code: u8,
+        packet: String,
+    },
+    /// The empty reply: the server doesn't implement the packet
+    Unsupported(String),
+    /// A reply that doesn't fit the packet that was sent
+    Unexpected {
+        packet: String,
+        reply: String,
+    },
+}
+
+impl fmt::Display for RspError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RspError::Io(e) => write!(f, "{}", e),
+            RspError::Closed => write!(f, "the gdb-server closed the connection"),
+            RspError::Target { code, packet } => {
+                write!(f, "the gdb-server answered '{}' with error {:02x}", packet, code)
+            }
+            RspError::Unsupported(packet) => write!(f, "the gdb-server doesn't support '{}'", packet),
+            RspError::Unexpected { packet, reply } => {
+                write!(f, "unexpected reply '{}' to '{}'", reply, packet)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RspError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RspError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RspError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            RspError::Closed
+        } else {
+            RspError::Io(e)
+        }
+    }
+}
+
+pub type RspResult<T> = Result<T, RspError>;
+
+/// The server's `qSupported` reply
+#[derive(Debug, Clone, Default)]
+pub struct Features {
+    /// Largest packet the server accepts
+    pub packet_size: usize,
+    /// Feature name to `+`, `-`, `?` or the value after `=`
+    values: HashMap<String, String>,
+}
+
+impl Features {
+    pub fn parse(reply: &str) -> Self {
+        let mut values = HashMap::new();
+        for item in reply.split(';').filter(|item| !item.is_empty()) {
+            let (name, value) = match item.split_once('=') {
+                Some((name, value)) => (name, value),
+                None => item.split_at(item.len() - 1),
+            };
+            values.insert(name.to_string(), value.to_string());
+        }
+        let packet_size = values
+            .get("PacketSize")
+            .and_then(|size| usize::from_str_radix(size, 16).ok())
+            .unwrap_or(DEFAULT_PACKET_SIZE);
+        Features { packet_size, values }
+    }
+
+    /// True when the server lists `name+`
+    pub fn supports(&self, name: &str) -> bool {
+        self.values.get(name).is_some_and(|value| value == "+")
+    }
+
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+}
+
+/// How to resume the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resume {
+    Continue,
+    Step,
+}
+
+/// Why the target stopped, from a stop reply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReply {
+    /// `S` or `T`: stopped with a signal. `info` holds the `T` packet's `name:value` pairs, such as
+    /// `thread`, `swbreak` or `watch`.
+    Signal { signal: u8, info: Vec<(String, String)> },
+    /// `W`: the process exited
+    Exited(u8),
+    /// `X`: the process was terminated by a signal
+    Terminated(u8),
+}
+
+impl StopReply {
+    pub fn parse(reply: &str) -> Option<Self> {
+        let code = u8::from_str_radix(reply.get(1..3)?, 16).ok()?;
+        match reply.as_bytes()[0] {
+            b'S' => Some(StopReply::Signal {
+                signal: code,
+                info: Vec::new(),
+            }),
+            b'T' => {
+                let info = reply[3..]
+                    .split(';')
+                    .filter_map(|pair| pair.split_once(':'))
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect();
+                Some(StopReply::Signal { signal: code, info })
+            }
+            b'W' => Some(StopReply::Exited(code)),
+            b'X' => Some(StopReply::Terminated(code)),
+            _ => None,
+        }
+    }
+
+    /// A `name:value` pair of a `T` reply
+    pub fn info(&self, name: &str) -> Option<&str> {
+        match self {
+            StopReply::Signal { info, .. } => info.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str()),
+            _ => None,
+        }
+    }
+}
+
+/// Breakpoint and watchpoint types, numbered as in `Z` packets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakpointKind {
+    Software = 0,
+    Hardware = 1,
+    WriteWatch = 2,
+    ReadWatch = 3,
+    AccessWatch = 4,
+}
+
+/// A `<memory>` element of the target's memory map
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryMapEntry {
+    /// `ram`, `rom` or `flash`
+    pub kind: String,
+    pub start: u64,
+    pub length: u64,
+    /// Erase block size, for flash
+    pub block_size: Option<u64>,
+}
+
+/// A `<thread>` element of `qXfer:threads`, or a thread from `qfThreadInfo`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadInfo {
+    pub id: String,
+    pub name: Option<String>,
+    pub core: Option<String>,
+    /// Free-form description, such as an RTOS thread's state
+    pub extra: Option<String>,
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"([\w-]+)\s*=\s*"([^"]*)""#).unwrap());
+
+fn attributes(text: &str) -> HashMap<String, String> {
+    ATTRIBUTE
+        .captures_iter(text)
+        .map(|c| (c[1].to_string(), xml_unescape(&c[2])))
+        .collect()
+}
+
+/// A number in a memory map: hex with `0x`, else decimal
+fn parse_number(text: &str) -> Option<u64> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+/// Parse a `memory-map` document (GDB manual, "Memory Map Format")
+pub fn parse_memory_map(xml: &str) -> Vec<MemoryMapEntry> {
+    static MEMORY: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?s)<memory\s([^>]*?)(?:/>|>(.*?)</memory>)").unwrap());
+    static BLOCK_SIZE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<property\s+name\s*=\s*"blocksize"\s*>([^<]*)</property>"#).unwrap());
+    MEMORY
+        .captures_iter(xml)
+        .filter_map(|c| {
+            let attrs = attributes(&c[1]);
+            let block_size = c
+                .get(2)
+                .and_then(|body| BLOCK_SIZE.captures(body.as_str()))
+                .and_then(|b| parse_number(&b[1]));
+            Some(MemoryMapEntry {
+                kind: attrs.get("type")?.clone(),
+                start: parse_number(attrs.get("start")?)?,
+                length: parse_number(attrs.get("length")?)?,
+                block_size,
+            })
+        })
+        .collect()
+}
+
+/// Parse a `threads` document (GDB manual, "Thread List Format")
+pub fn parse_threads(xml: &str) -> Vec<ThreadInfo> {
+    static THREAD: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?s)<thread\s([^>]*?)(?:/>|>(.*?)</thread>)").unwrap());
+    THREAD
+        .captures_iter(xml)
+        .filter_map(|c| {
+            let mut attrs = attributes(&c[1]);
+            let extra = c
+                .get(2)
+                .map(|body| xml_unescape(body.as_str().trim()))
+                .filter(|text| !text.is_empty());
+            Some(ThreadInfo {
+                id: attrs.remove("id")?,
+                name: attrs.remove("name"),
+                core: attrs.remove("core"),
+                extra,
+            })
+        })
+        .collect()
+}
+
+/// An RSP connection to a gdb-server
+pub struct Client<R: Read, W: Write> {
+    conn: Connection<R, W>,
+    features: Features,
+    /// Whether `X` works; unknown until the first write
+    binary_writes: Option<bool>,
+    /// The `vCont?` reply, once asked
+    vcont: Option<Vec<String>>,
+    /// Console output (`O` packets) not yet taken
+    console: Vec<u8>,
+}
+
+impl Client<TcpStream, TcpStream> {
+    /// Connect and negotiate features. `timeout` bounds the connect and each reply.
+    pub fn connect(addr: impl ToSocketAddrs, timeout: Duration) -> RspResult<Self> {
+        let mut last_error = io::Error::new(io::ErrorKind::InvalidInput, "no address to connect to");
+        for addr in addr.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, timeout) {
+                Ok(stream) => {
+                    stream.set_nodelay(true)?;
+                    stream.set_read_timeout(Some(timeout))?;
+                    let mut client = Client::new(stream.try_clone()?, stream);
+                    client.handshake()?;
+                    return Ok(client);
+                }
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error.into())
+    }
+
+    /// How long to wait for a reply; None waits forever, as `wait_stop` may need to
+    pub fn set_timeout(&self, timeout: Option<Duration>) -> RspResult<()> {
+        Ok(self.conn.writer().set_read_timeout(timeout)?)
+    }
+}
+
+impl<R: Read, W: Write> Client<R, W> {
+    /// A client over an established stream. Call `handshake` before anything else.
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            conn: Connection::new(reader, writer),
+            features: Features::parse(""),
+            binary_writes: None,
+            vcont: None,
+            console: Vec::new(),
+        }
+    }
+
+    /// Exchange `qSupported` and switch to no-ack mode when the server offers it
+    pub fn handshake(&mut self) -> RspResult<&Features> {
+        let reply = self.request(CLIENT_FEATURES.as_bytes())?;
+        self.features = Features::parse(&String::from_utf8_lossy(&reply));
+        if self.features.supports("QStartNoAckMode") {
+            self.expect_ok("QStartNoAckMode")?;
+            self.conn.set_no_ack(true);
+        }
+        Ok(&self.features)
+    }
+
+    pub fn features(&self) -> &Features {
+        &self.features
+    }
+
+    /// Send a packet and return the reply as is
+    pub fn request(&mut self, packet: &[u8]) -> RspResult<Vec<u8>> {
+        self.conn.send(packet)?;
+        loop {
+            match self.conn.recv()? {
+                Some(Incoming::Packet(reply)) => return Ok(reply),
+                Some(_) => continue,
+                None => return Err(RspError::Closed),
+            }
+        }
+    }
+
+    /// Send a packet; the empty reply and `Exx` become errors
+    fn command(&mut self, packet: &str) -> RspResult<Vec<u8>> {
+        let reply = self.request(packet.as_bytes())?;
+        check_reply(packet, reply)
+    }
+
+    fn expect_ok(&mut self, packet: &str) -> RspResult<()> {
+        let reply = self.command(packet)?;
+        if reply == b"OK" {
+            Ok(())
+        } else {
+            Err(unexpected(packet, &reply))
+        }
+    }
+
+    /// Bytes of memory per `m` packet, so the hex reply and its `$`, `#` and checksum fit the
+    /// packet size
+    fn read_chunk(&self) -> usize {
+        (self.features.packet_size.saturating_sub(4) / 2).max(1)
+    }
+
+    pub fn read_memory(&mut self, addr: u64, len: usize) -> RspResult<Vec<u8>> {
+        let mut out = Vec::with_capacity(len);
+        while out.len() < len {
+            let at = addr + out.len() as u64;
+            let want = (len - out.len()).min(self.read_chunk());
+            let packet = format!("m{:x},{:x}", at, want);
+            let reply = self.command(&packet)?;
+            let bytes = decode_hex(&String::from_utf8_lossy(&reply)).ok_or_else(|| unexpected(&packet, &reply))?;
+            // A short read means the rest isn't readable
+            let short = bytes.len() < want;
+            out.extend_from_slice(&bytes);
+            if short {
+                break;
+            }
+        }
+        Ok(out)
+    }
+
+    pub fn write_memory(&mut self, addr: u64, data: &[u8]) -> RspResult<()> {
+        let chunk = (self.features.packet_size.saturating_sub(WRITE_HEADER) / 2).max(1);
+        for (ix, part) in data.chunks(chunk).enumerate() {
+            let at = addr + (ix * chunk) as u64;
+            if self.binary_writes != Some(false) {
+                let mut packet = format!("X{:x},{:x}:", at, part.len()).into_bytes();
+                packet.extend_from_slice(part);
+                let reply = self.request(&packet)?;
+                if reply.is_empty() {
+                    self.binary_writes = Some(false);
+                } else {
+                    self.binary_writes = Some(true);
+                    match check_reply(&format!("X{:x},{:x}", at, part.len()), reply)? {
+                        reply if reply == b"OK" => continue,
+                        reply => return Err(unexpected("X", &reply)),
+                    }
+                }
+            }
+            self.expect_ok(&format!("M{:x},{:x}:{}", at, part.len(), encode_hex(part)))?;
+        }
+        Ok(())
+    }
+
+    /// All registers from `g`, in target byte order. Bytes the server reports as unavailable
+    /// (`xx`) read as zero; use `read_register` to tell them apart.
+    pub fn read_registers(&mut self) -> RspResult<Vec<u8>> {
+        let reply = self.command("g")?;
+        let text = String::from_utf8_lossy(&reply).replace("xx", "00");
+        decode_hex(&text).ok_or_else(|| unexpected("g", &reply))
+    }
+
+    pub fn write_registers(&mut self, data: &[u8]) -> RspResult<()> {
+        self.expect_ok(&format!("G{}", encode_hex(data)))
+    }
+
+    /// One register in target byte order, or None if its value is unavailable
+    pub fn read_register(&mut self, regnum: usize) -> RspResult<Option<Vec<u8>>> {
+        let packet = format!("p{:x}", regnum);
+        let reply = self.command(&packet)?;
+        if reply.iter().all(|&b| b == b'x') {
+            return Ok(None);
+        }
+        decode_hex(&String::from_utf8_lossy(&reply))
+            .map(Some)
+            .ok_or_else(|| unexpected(&packet, &reply))
+    }
+
+    pub fn write_register(&mut self, regnum: usize, value: &[u8]) -> RspResult<()> {
+        self.expect_ok(&format!("P{:x}={}", regnum, encode_hex(value)))
+    }
+
+    /// The actions `vCont` supports (`c`, `s`, `t`, ...); empty if the server has no `vCont`
+    pub fn vcont_actions(&mut self) -> RspResult<Vec<String>> {
+        if let Some(actions) = &self.vcont {
+            return Ok(actions.clone());
+        }
+        let actions = match self.command("vCont?") {
+            Ok(reply) => {
+                let text = String::from_utf8_lossy(&reply);
+                let actions = text.strip_prefix("vCont").ok_or_else(|| unexpected("vCont?", &reply))?;
+                actions
+                    .split(';')
+                    .filter(|a| !a.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            }
+            Err(RspError::Unsupported(_)) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        self.vcont = Some(actions.clone());
+        Ok(actions)
+    }
+
+    /// Resume the target. The stop reply comes when it halts: see `wait_stop`.
+    pub fn resume(&mut self, how: Resume) -> RspResult<()> {
+        let action = match how {
+            Resume::Continue => "c",
+            Resume::Step => "s",
+        };
+        let packet = if self.vcont_actions()?.iter().any(|a| a == action) {
+            format!("vCont;{}", action)
+        } else {
+            action.to_string()
+        };
+        Ok(self.conn.send(packet.as_bytes())?)
+    }
+
+    /// Wait for the stop reply after `resume` or `interrupt`, collecting console output meanwhile
+    pub fn wait_stop(&mut self) -> RspResult<StopReply> {
+        loop {
+            let reply = match self.conn.recv()? {
+                Some(Incoming::Packet(reply)) => reply,
+                Some(_) => continue,
+                None => return Err(RspError::Closed),
+            };
+            if let Some(output) = console_output(&reply) {
+                self.console.extend_from_slice(&output);
+                continue;
+            }
+            let text = String::from_utf8_lossy(&reply);
+            return StopReply::parse(&text).ok_or_else(|| unexpected("stop reply", &reply));
+        }
+    }
+
+    /// Single-step and wait for the target to stop again
+    pub fn step(&mut self) -> RspResult<StopReply> {
+        self.resume(Resume::Step)?;
+        self.wait_stop()
+    }
+
+    /// Ask a running target to halt; the stop reply follows
+    pub fn interrupt(&mut self) -> RspResult<()> {
+        Ok(self.conn.send_interrupt()?)
+    }
+
+    /// Why the target is stopped (`?`)
+    pub fn halt_reason(&mut self) -> RspResult<StopReply> {
+        let reply = self.command("?")?;
+        StopReply::parse(&String::from_utf8_lossy(&reply)).ok_or_else(|| unexpected("?", &reply))
+    }
+
+    /// Console output received from `O` packets since the last call
+    pub fn take_console_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.console)
+    }
+
+    pub fn insert_breakpoint(&mut self, kind: BreakpointKind, addr: u64, size: usize) -> RspResult<()> {
+        self.expect_ok(&format!("Z{},{:x},{:x}", kind as u8, addr, size))
+    }
+
+    pub fn remove_breakpoint(&mut self, kind: BreakpointKind, addr: u64, size: usize) -> RspResult<()> {
+        self.expect_ok(&format!("z{},{:x},{:x}", kind as u8, addr, size))
+    }
+
+    /// A whole `qXfer` object, read in chunks that fit the packet size
+    pub fn xfer_read(&mut self, object: &str, annex: &str) -> RspResult<Vec<u8>> {
+        let chunk = self.features.packet_size.saturating_sub(8).max(1);
+        let mut out = Vec::new();
+        loop {
+            let packet = format!("qXfer:{}:read:{}:{:x},{:x}", object, annex, out.len(), chunk);
+            let reply = self.command(&packet)?;
+            match reply.split_first() {
+                Some((b'm', data)) if !data.is_empty() => out.extend_from_slice(data),
+                Some((b'l', data)) => {
+                    out.extend_from_slice(data);
+                    return Ok(out);
+                }
+                _ => return Err(unexpected(&packet, &reply)),
+            }
+        }
+    }
+
+    /// The target description (`target.xml`), if the server provides one
+    pub fn target_xml(&mut self) -> RspResult<Option<String>> {
+        self.optional_xfer("features", "target.xml")
+    }
+
+    /// The memory map, if the server provides one
+    pub fn memory_map(&mut self) -> RspResult<Option<Vec<MemoryMapEntry>>> {
+        Ok(self.optional_xfer("memory-map", "")?.map(|xml| parse_memory_map(&xml)))
+    }
+
+    /// Threads from `qXfer:threads`, else from `qfThreadInfo`/`qsThreadInfo`
+    pub fn threads(&mut self) -> RspResult<Vec<ThreadInfo>> {
+        if let Some(xml) = self.optional_xfer("threads", "")? {
+            return Ok(parse_threads(&xml));
+        }
+        let mut threads = Vec::new();
+        let mut packet = "qfThreadInfo";
+        loop {
+            let reply = self.command(packet)?;
+            match reply.split_first() {
+                Some((b'm', ids)) => threads.extend(String::from_utf8_lossy(ids).split(',').map(|id| ThreadInfo {
+                    id: id.to_string(),
+                    name: None,
+                    core: None,
+                    extra: None,
+                })),
+                Some((b'l', _)) => return Ok(threads),
+                _ => return Err(unexpected(packet, &reply)),
+            }
+            packet = "qsThreadInfo";
+        }
+    }
+
+    fn optional_xfer(&mut self, object: &str, annex: &str) -> RspResult<Option<String>> {
+        if !self.features.supports(&format!("qXfer:{}:read", object)) {
+            return Ok(None);
+        }
+        let data = self.xfer_read(object, annex)?;
+        Ok(Some(String::from_utf8_lossy(&data).into_owned()))
+    }
+
+    /// Run a server command (`monitor` in GDB) and return its output
+    pub fn monitor(&mut self, command: &str) -> RspResult<String> {
+        let packet = format!("qRcmd,{}", encode_hex(command.as_bytes()));
+        self.conn.send(packet.as_bytes())?;
+        let mut output = Vec::new();
+        loop {
+            let reply = match self.conn.recv()? {
+                Some(Incoming::Packet(reply)) => reply,
+                Some(_) => continue,
+                None => return Err(RspError::Closed),
+            };
+            if let Some(text) = console_output(&reply) {
+                output.extend_from_slice(&text);
+                continue;
+            }
+            check_reply(&packet, reply.clone())?;
+            // OK ends the output; anything else is output sent as a single hex reply
+            if reply != b"OK" {
+                output.extend(decode_hex(&String::from_utf8_lossy(&reply)).ok_or_else(|| unexpected(&packet, &reply))?);
+            }
+            return Ok(String::from_utf8_lossy(&output).into_owned());
+        }
+    }
+
+    /// Detach, leaving the target running
+    pub fn detach(&mut self) -> RspResult<()> {
+        self.expect_ok("D")
+    }
+}
+
+/// The empty reply and `Exx` as errors
+fn check_reply(packet: &str, reply: Vec<u8>) -> RspResult<Vec<u8>> {
+    if reply.is_empty() {
+        return Err(RspError::Unsupported(packet.to_string()));
+    }
+    if reply.len() == 3 && reply[0] == b'E' {
+        if let Ok(code) = u8::from_str_radix(&String::from_utf8_lossy(&reply[1..]), 16) {
+            return Err(RspError::Target {
+                code,
+                packet: packet.to_string(),
+            });
+        }
+    }
+    Ok(reply)
+}
+
+fn unexpected(packet: &str, reply: &[u8]) -> RspError {
+    RspError::Unexpected {
+        packet: packet.to_string(),
+        reply: String::from_utf8_lossy(reply).into_owned(),
+    }
+}
+
+/// The text of an `O` console output packet
+fn console_output(reply: &[u8]) -> Option<Vec<u8>> {
+    match reply.split_first() {
+        Some((b'O', hex)) if !hex.is_empty() && reply != b"OK" => decode_hex(&String::from_utf8_lossy(hex)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsp::stub::{serve_connection, StubTarget};
+    use std::net::TcpListener;
+
+    struct Board {
+        memory: Vec<u8>,
+        registers: [u32; 2],
+    }
+
+    impl StubTarget for Board {
+        fn target_xml(&self) -> Option<String> {
+            Some(format!("<target>{}</target>", "r".repeat(600)))
+        }
+        fn register_count(&self) -> usize {
+            2
+        }
+        fn register_size(&self, _regnum: usize) -> usize {
+            4
+        }
+        fn read_register(&mut self, regnum: usize) -> Option<Vec<u8>> {
+            (regnum == 0).then(|| self.registers[0].to_le_bytes().to_vec())
+        }
+        fn write_register(&mut self, regnum: usize, value: &[u8]) -> bool {
+            self.registers[regnum] = u32::from_le_bytes(value.try_into().unwrap());
+            true
+        }
+        fn read_memory(&mut self, addr: u64, len: usize) -> Vec<u8> {
+            let start = (addr as usize).min(self.memory.len());
+            self.memory[start..(start + len).min(self.memory.len())].to_vec()
+        }
+        fn write_memory(&mut self, addr: u64, data: &[u8]) -> bool {
+            let start = addr as usize;
+            self.memory[start..start + data.len()].copy_from_slice(data);
+            true
+        }
+    }
+
+    #[test]
+    fn client_against_the_stub() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut board = Board {
+                memory: (0..10000).map(|ix| ix as u8).collect(),
+                registers: [0x1234_5678, 0],
+            };
+            let mut conn = Connection::new(stream.try_clone().unwrap(), stream);
+            serve_connection(&mut conn, &mut board).unwrap();
+            board
+        });
+
+        let mut client = Client::connect(addr, Duration::from_secs(5)).unwrap();
+        assert_eq!(client.features().packet_size, 0x1000);
+        assert!(client.features().supports("qXfer:features:read"));
+
+        // Several m packets, and a read that runs off the end of memory
+        let bytes = client.read_memory(100, 5000).unwrap();
+        assert_eq!(bytes.len(), 5000);
+        assert_eq!(bytes[4999], ((100 + 4999) % 256) as u8);
+        assert_eq!(client.read_memory(9998, 8).unwrap().len(), 2);
+        assert!(matches!(client.read_memory(20000, 4), Err(RspError::Target { .. })));
+
+        client.write_memory(0, b"$#}*").unwrap();
+        assert_eq!(client.read_memory(0, 4).unwrap(), b"$#}*");
+
+        assert_eq!(client.read_register(0).unwrap(), Some(vec![0x78, 0x56, 0x34, 0x12]));
+        assert_eq!(client.read_register(1).unwrap(), None);
+        assert_eq!(
+            client.read_registers().unwrap(),
+            vec![0x78, 0x56, 0x34, 0x12, 0, 0, 0, 0]
+        );
+        client.write_register(1, &[1, 0, 0, 0]).unwrap();
+
+        let xml = client.target_xml().unwrap().unwrap();
+        assert_eq!(xml.len(), 617);
+        assert_eq!(client.memory_map().unwrap(), None);
+        assert_eq!(client.threads().unwrap()[0].id, "1");
+
+        assert!(matches!(
+            client.insert_breakpoint(BreakpointKind::Hardware, 0x100, 2),
+            Err(RspError::Unsupported(_))
+        ));
+        assert_eq!(client.vcont_actions().unwrap(), vec!["c", "C", "s", "S"]);
+        let stop = client.step().unwrap();
+        assert_eq!(stop.info("thread"), Some("1"));
+        assert_eq!(client.halt_reason().unwrap(), stop);
+        client.detach().unwrap();
+
+        let board = server.join().unwrap();
+        assert_eq!(board.registers[1], 1);
+    }
+
+    #[test]
+    fn xml_documents_and_stop_replies() {
+        let map = r#"<?xml version="1.0"?>
+            <memory-map>
+              <memory type="flash" start="0x08000000" length="0x100000">
+                <property name="blocksize">0x800</property>
+              </memory>
+              <memory type="ram" start="0x20000000" length="131072"/>
+            </memory-map>"#;
+        assert_eq!(
+            parse_memory_map(map),
+            vec![
+                MemoryMapEntry {
+                    kind: "flash".to_string(),
+                    start: 0x0800_0000,
+                    length: 0x10_0000,
+                    block_size: Some(0x800),
+                },
+                MemoryMapEntry {
+                    kind: "ram".to_string(),
+                    start: 0x2000_0000,
+                    length: 0x2_0000,
+                    block_size: None,
+                },
+            ]
+        );
+
+        let threads =
+            r#"<threads><thread id="1" name="idle"/><thread id="2" core="0" name="a&amp;b">Blocked</thread></threads>"#;
+        let threads = parse_threads(threads);
+        assert_eq!(threads.len(), 2);
+        assert_eq!(threads[1].name.as_deref(), Some("a&b"));
+        assert_eq!(threads[1].extra.as_deref(), Some("Blocked"));
+
+        assert_eq!(StopReply::parse("W00"), Some(StopReply::Exited(0)));
+        let stop = StopReply::parse("T05thread:2;hwbreak:;").unwrap();
+        assert_eq!(stop.info("hwbreak"), Some(""));
+        assert_eq!(console_output(b"4869"), None);
+        assert_eq!(console_output(b"O4869"), Some(b"Hi".to_vec()));
+        assert_eq!(console_output(b"OK"), None);
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! RSP framing: `$payload#cs` packets, `+`/`-` acknowledgements, `}` escapes and `*` run-length
+//! encoding. Payloads are handled as bytes; escaping is applied on send and undone on receive, so
+//! binary payloads (`X`, `qXfer` replies) need no special treatment by callers.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+/// Sent by GDB, outside any packet, to interrupt a running target
+pub const INTERRUPT: u8 = 0x03;
+
+/// Retransmissions of a packet the other side keeps rejecting before giving up
+const MAX_RETRIES: usize = 5;
+
+pub fn checksum(payload: &[u8]) -> u8 {
+    payload.iter().fold(0u8, |sum, b| sum.wrapping_add(*b))
+}
+
+/// Escape the bytes that can't appear literally in a packet body
+pub fn escape(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    for &b in data {
+        if matches!(b, b'$' | b'#' | b'}' | b'*') {
+            out.push(b'}');
+            out.push(b ^ 0x20);
+        } else {
+            out.push(b);
+        }
+    }
+    out
+}
+
+/// Undo `}` escapes and expand `*` run-length encoding
+pub fn unescape(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut bytes = data.iter();
+    while let Some(&b) = bytes.next() {
+        match b {
+            b'}' => {
+                if let Some(&next) = bytes.next() {
+                    out.push(next ^ 0x20);
+                }
+            }
+            b'*' => {
+                // The previous byte repeats (count - 29) more times
+                if let (Some(&count), Some(&last)) = (bytes.next(), out.last()) {
+                    let repeat = (count as usize).saturating_sub(29);
+                    out.extend(std::iter::repeat_n(last, repeat));
+                }
+            }
+            _ => out.push(b),
+        }
+    }
+    out
+}
+
+/// A complete packet on the wire, escapes and checksum included
+pub fn frame(payload: &[u8]) -> Vec<u8> {
+    let body = escape(payload);
+    let mut out = Vec::with_capacity(body.len() + 4);
+    out.push(b'$');
+    out.extend_from_slice(&body);
+    out.extend_from_slice(format!("#{:02x}", checksum(&body)).as_bytes());
+    out
+}
+
+/// One unit read from the wire
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Incoming {
+    /// A packet whose checksum matched, with escapes already undone
+    Packet(Vec<u8>),
+    Ack,
+    Nack,
+    Interrupt,
+    /// A packet whose checksum didn't match
+    BadChecksum,
+}
+
+/// Read the next packet, acknowledgement or interrupt, skipping anything else; None at end of stream
+pub fn read_incoming(reader: &mut impl BufRead) -> io::Result<Option<Incoming>> {
+    let mut byte = [0u8; 1];
+    loop {
+        if reader.read(&mut byte)? == 0 {
+            return Ok(None);
+        }
+        match byte[0] {
+            b'+' => return Ok(Some(Incoming::Ack)),
+            b'-' => return Ok(Some(Incoming::Nack)),
+            INTERRUPT => return Ok(Some(Incoming::Interrupt)),
+            b'$' => break,
+            _ => continue,
+        }
+    }
+    let mut body = Vec::new();
+    if reader.read_until(b'#', &mut body)? == 0 || body.pop() != Some(b'#') {
+        return Ok(None);
+    }
+    let mut cs = [0u8; 2];
+    reader.read_exact(&mut cs)?;
+    let expected = std::str::from_utf8(&cs)
+        .ok()
+        .and_then(|s| u8::from_str_radix(s, 16).ok());
+    if expected != Some(checksum(&body)) {
+        return Ok(Some(Incoming::BadChecksum));
+    }
+    Ok(Some(Incoming::Packet(unescape(&body))))
+}
+
+/// Either end of an RSP connection. Acknowledgements are handled here: received packets are acked
+/// (or nacked), and sent packets wait for the other side's ack until no-ack mode is switched on.
+pub struct Connection<R: Read, W: Write> {
+    reader: BufReader<R>,
+    writer: W,
+    no_ack: bool,
+}
+
+impl<R: Read, W: Write> Connection<R, W> {
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            writer,
+            no_ack: false,
+        }
+    }
+
+    /// Stop sending and expecting acknowledgements (after a successful `QStartNoAckMode`)
+    pub fn set_no_ack(&mut self, no_ack: bool) {
+        self.no_ack = no_ack;
+    }
+
+    pub fn no_ack(&self) -> bool {
+        self.no_ack
+    }
+
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn send(&mut self, payload: &[u8]) -> io::Result<()> {
+        let packet = frame(payload);
+        for _ in 0..MAX_RETRIES {
+            self.writer.write_all(&packet)?;
+            self.writer.flush()?;
+            if self.no_ack {
+                return Ok(());
+            }
+            loop {
+                match read_incoming(&mut self.reader)? {
+                    Some(Incoming::Ack) => return Ok(()),
+                    Some(Incoming::Nack) => break,
+                    // Anything else while waiting for the ack is dropped, as gdbserver does
+                    Some(_) => continue,
+                    None => return Err(io::ErrorKind::UnexpectedEof.into()),
+                }
+            }
+        }
+        Err(io::Error::other("Packet rejected too many times"))
+    }
+
+    /// Ask the other side to stop the target (sent outside any packet, never acknowledged)
+    pub fn send_interrupt(&mut self) -> io::Result<()> {
+        self.writer.write_all(&[INTERRUPT])?;
+        self.writer.flush()
+    }
+
+    /// The next packet or interrupt; None when the other side has closed the connection
+    pub fn recv(&mut self) -> io::Result<Option<Incoming>> {
+        loop {
+            match read_incoming(&mut self.reader)? {
+                Some(Incoming::Packet(payload)) => {
+                    if !self.no_ack {
+                        self.writer.write_all(b"+")?;
+                        self.writer.flush()?;
+                    }
+                    return Ok(Some(Incoming::Packet(payload)));
+                }
+                Some(Incoming::BadChecksum) => {
+                    if !self.no_ack {
+                        self.writer.write_all(b"-")?;
+                        self.writer.flush()?;
+                    }
+                }
+                Some(Incoming::Interrupt) => return Ok(Some(Incoming::Interrupt)),
+                Some(Incoming::Ack | Incoming::Nack) => continue,
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn framing_and_escapes() {
+        assert_eq!(frame(b"OK"), b"$OK#9a");
+        assert_eq!(frame(b"a$b"), b"$a}\x04b#44");
+        assert_eq!(unescape(b"a}\x04b"), b"a$b");
+        assert_eq!(
+            unescape(escape(&[0, b'#', b'}', b'*', 0xff]).as_slice()),
+            vec![0, b'#', b'}', b'*', 0xff]
+        );
+        // '0' followed by 3 more
+        assert_eq!(unescape(b"0* 1"), b"00001");
+    }
+
+    #[test]
+    fn reading_packets_and_acks() {
+        let mut input = Cursor::new(b"junk+$g#67-\x03$g#00".to_vec());
+        assert_eq!(read_incoming(&mut input).unwrap(), Some(Incoming::Ack));
+        assert_eq!(
+            read_incoming(&mut input).unwrap(),
+            Some(Incoming::Packet(b"g".to_vec()))
+        );
+        assert_eq!(read_incoming(&mut input).unwrap(), Some(Incoming::Nack));
+        assert_eq!(read_incoming(&mut input).unwrap(), Some(Incoming::Interrupt));
+        assert_eq!(read_incoming(&mut input).unwrap(), Some(Incoming::BadChecksum));
+        assert_eq!(read_incoming(&mut input).unwrap(), None);
+    }
+
+    #[test]
+    fn connection_acks_and_retransmits() {
+        // The peer rejects our first copy, accepts the second, then sends a bad and a good packet
+        let peer = Cursor::new(b"-+$m0,4#00$m0,4#fd".to_vec());
+        let mut conn = Connection::new(peer, Vec::new());
+        conn.send(b"OK").unwrap();
+        assert_eq!(conn.recv().unwrap(), Some(Incoming::Packet(b"m0,4".to_vec())));
+        assert_eq!(conn.recv().unwrap(), None);
+        assert_eq!(conn.writer, b"$OK#9a$OK#9a-+");
+    }
+}
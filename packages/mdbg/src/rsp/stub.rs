@@ -0,0 +1,506 @@
+// Copyright (c) 2026 MCU-Debug Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The server side of RSP: enough of the protocol for plain GDB to attach to a target that lives
+//! inside mdbg (a core dump or a simulated target), read its registers and memory, set breakpoints
+//! and look around. Each connection is served on one thread. After a resume the target either stops
+//! at once or runs until GDB interrupts it.
+
+use std::io::{self, Read, Write};
+use std::net::TcpListener;
+
+use crate::common::utils::{decode_hex, encode_hex};
+use crate::rsp::client::BreakpointKind;
+use crate::rsp::packet::{Connection, Incoming};
+
+/// Signal numbers GDB expects in stop replies
+pub const SIGINT: u8 = 2;
+pub const SIGTRAP: u8 = 5;
+
+/// Largest packet GDB may send us, as advertised in `qSupported`
+const PACKET_SIZE: usize = 0x1000;
+/// Memory a single `m` reply can carry: two hex digits a byte, less the `$`, `#` and checksum
+const MAX_MEMORY_READ: usize = (PACKET_SIZE - 4) / 2;
+
+/// What the stub serves. Registers are numbered as in `target_xml`.
+pub trait StubTarget {
+    /// GDB target description, sent as `target.xml`
+    fn target_xml(&self) -> Option<String> {
+        None
+    }
+
+    /// Registers in the `g` packet
+    fn register_count(&self) -> usize;
+
+    fn register_size(&self, regnum: usize) -> usize;
+
+    /// Target-endian bytes of a register, or None if its value is unknown
+    fn read_register(&mut self, regnum: usize) -> Option<Vec<u8>>;
+
+    fn write_register(&mut self, _regnum: usize, _value: &[u8]) -> bool {
+        false
+    }
+
+    /// The bytes at `addr`, or as many of them as can be read; empty if none can
+    fn read_memory(&mut self, addr: u64, len: usize) -> Vec<u8>;
+
+    fn write_memory(&mut self, _addr: u64, _data: &[u8]) -> bool {
+        false
+    }
+
+    /// Continue (or single-step) and return the signal the target stopped with, or None if it is
+    /// now running until `halt`. Targets that can't run, such as core dumps, stop again at once.
+    fn resume(&mut self, _step: bool) -> Option<u8> {
+        Some(SIGTRAP)
+    }
+
+    /// Stop a running target (GDB's Ctrl-C) and return the stop signal
+    fn halt(&mut self) -> u8 {
+        SIGINT
+    }
+
+    /// None if this kind of breakpoint isn't supported, else whether it was set
+    fn insert_breakpoint(&mut self, _kind: BreakpointKind, _addr: u64, _size: usize) -> Option<bool> {
+        None
+    }
+
+    fn remove_breakpoint(&mut self, _kind: BreakpointKind, _addr: u64, _size: usize) -> Option<bool> {
+        None
+    }
+
+    /// GDB memory map, sent as `qXfer:memory-map`
+    fn memory_map_xml(&self) -> Option<String> {
+        None
+    }
+
+    /// Output of a `monitor` command, or None if the command isn't known
+    fn monitor(&mut self, _command: &str) -> Option<String> {
+        None
+    }
+}
+
+/// What to do after handling a packet
+#[derive(Debug, PartialEq, Eq)]
+pub enum Reply {
+    Send(Vec<u8>),
+    /// Send, then stop using acknowledgements
+    SendThenNoAck(Vec<u8>),
+    /// Send if given, then close the connection
+    Close(Option<Vec<u8>>),
+    /// The target is running: nothing to send until it is interrupted
+    Running,
+}
+
+/// Per-connection protocol state
+#[derive(Debug)]
+pub struct Session {
+    /// Signal in the last stop reply
+    pub last_signal: u8,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self { last_signal: SIGTRAP }
+    }
+}
+
+fn stop_reply(signal: u8) -> Vec<u8> {
+    format!("T{:02x}thread:1;", signal).into_bytes()
+}
+
+fn error(code: u8) -> Vec<u8> {
+    format!("E{:02x}", code).into_bytes()
+}
+
+fn ok() -> Vec<u8> {
+    b"OK".to_vec()
+}
+
+/// `addr,len` as in `m` and `M` packets
+fn parse_addr_len(text: &str) -> Option<(u64, usize)> {
+    let (addr, len) = text.split_once(',')?;
+    Some((
+        u64::from_str_radix(addr, 16).ok()?,
+        usize::from_str_radix(len, 16).ok()?,
+    ))
+}
+
+/// A `qXfer` read of `offset,length` from `doc`: `m` and a chunk if more follows, `l` for the last
+fn xfer_chunk(doc: &[u8], annex: &str) -> Vec<u8> {
+    let Some((offset, length)) = parse_addr_len(annex) else {
+        return error(0);
+    };
+    let start = (offset as usize).min(doc.len());
+    let end = start.saturating_add(length).min(doc.len());
+    let mut out = vec![if end < doc.len() { b'm' } else { b'l' }];
+    out.extend_from_slice(&doc[start..end]);
+    out
+}
+
+/// `type,addr,kind` as in `Z` and `z` packets
+fn parse_breakpoint(text: &str) -> Option<(BreakpointKind, u64, usize)> {
+    let mut fields = text.split(',');
+    let kind = match fields.next()? {
+        "0" => BreakpointKind::Software,
+        "1" => BreakpointKind::Hardware,
+        "2" => BreakpointKind::WriteWatch,
+        "3" => BreakpointKind::ReadWatch,
+        "4" => BreakpointKind::AccessWatch,
+        _ => return None,
+    };
+    let addr = u64::from_str_radix(fields.next()?, 16).ok()?;
+    // Conditions and commands may follow the kind after a ';'
+    let size = fields.next()?.split(';').next()?;
+    Some((kind, addr, usize::from_str_radix(size, 16).ok()?))
+}
+
+/// Handle one packet. Unsupported packets get the empty reply, as the protocol asks.
+pub fn handle_packet(target: &mut dyn StubTarget, session: &mut Session, packet: &[u8]) -> Reply {
+    let Some((&kind, rest)) = packet.split_first() else {
+        return Reply::Send(Vec::new());
+    };
+    let text = String::from_utf8_lossy(rest);
+    let reply = match kind {
+        b'?' => stop_reply(session.last_signal),
+        b'g' => {
+            let mut out = String::new();
+            for regnum in 0..target.register_count() {
+                match target.read_register(regnum) {
+                    Some(value) => out.push_str(&encode_hex(&value)),
+                    None => out.push_str(&"xx".repeat(target.register_size(regnum))),
+                }
+            }
+            out.into_bytes()
+        }
+        b'G' => {
+            let Some(bytes) = decode_hex(&text) else {
+                return Reply::Send(error(1));
+            };
+            let mut offset = 0;
+            let mut all_written = true;
+            for regnum in 0..target.register_count() {
+                let size = target.register_size(regnum);
+                let Some(value) = bytes.get(offset..offset + size) else {
+                    break;
+                };
+                all_written &= target.write_register(regnum, value);
+                offset += size;
+            }
+            if all_written {
+                ok()
+            } else {
+                error(1)
+            }
+        }
+        b'p' => match usize::from_str_radix(&text, 16) {
+            Ok(regnum) if regnum < target.register_count() => match target.read_register(regnum) {
+                Some(value) => encode_hex(&value).into_bytes(),
+                None => "xx".repeat(target.register_size(regnum)).into_bytes(),
+            },
+            _ => error(1),
+        },
+        b'P' => {
+            let parsed = text
+                .split_once('=')
+                .and_then(|(n, v)| Some((usize::from_str_radix(n, 16).ok()?, decode_hex(v)?)));
+            match parsed {
+                Some((regnum, value)) if target.write_register(regnum, &value) => ok(),
+                _ => error(1),
+            }
+        }
+        b'm' => match parse_addr_len(&text) {
+            Some((addr, len)) => {
+                // A shorter reply is allowed; GDB asks again for the rest
+                let len = len.min(MAX_MEMORY_READ);
+                let bytes = target.read_memory(addr, len);
+                if bytes.is_empty() && len > 0 {
+                    error(1)
+                } else {
+                    encode_hex(&bytes).into_bytes()
+                }
+            }
+            None => error(1),
+        },
+        b'M' => {
+            let parsed = text.split_once(':').and_then(|(range, data)| {
+                let (addr, len) = parse_addr_len(range)?;
+                let bytes = decode_hex(data)?;
+                (bytes.len() == len).then_some((addr, bytes))
+            });
+            match parsed {
+                Some((addr, bytes)) if target.write_memory(addr, &bytes) => ok(),
+                _ => error(1),
+            }
+        }
+        b'X' => {
+            // Binary data: split the raw bytes, not the lossy text
+            let colon = rest.iter().position(|&b| b == b':');
+            let parsed = colon.and_then(|ix| {
+                let (addr, len) = parse_addr_len(std::str::from_utf8(&rest[..ix]).ok()?)?;
+                let data = &rest[ix + 1..];
+                (data.len() == len).then_some((addr, data))
+            });
+            match parsed {
+                Some((_, [])) => ok(),
+                Some((addr, data)) if target.write_memory(addr, data) => ok(),
+                _ => error(1),
+            }
+        }
+        b'c' | b'C' | b's' | b'S' => return resume(target, session, matches!(kind, b's' | b'S')),
+        b'Z' | b'z' => {
+            let Some((bp_kind, addr, size)) = parse_breakpoint(&text) else {
+                return Reply::Send(error(1));
+            };
+            let done = if kind == b'Z' {
+                target.insert_breakpoint(bp_kind, addr, size)
+            } else {
+                target.remove_breakpoint(bp_kind, addr, size)
+            };
+            match done {
+                Some(true) => ok(),
+                Some(false) => error(1),
+                None => Vec::new(),
+            }
+        }
+        b'H' | b'T' => ok(),
+        b'k' => return Reply::Close(None),
+        b'D' => return Reply::Close(Some(ok())),
+        b'v' => {
+            if text == "Cont?" {
+                b"vCont;c;C;s;S".to_vec()
+            } else if let Some(actions) = text.strip_prefix("Cont;") {
+                let step = actions.starts_with('s') || actions.starts_with('S');
+                return resume(target, session, step);
+            } else {
+                Vec::new()
+            }
+        }
+        b'q' => query(target, &text),
+        b'Q' if text == "StartNoAckMode" => return Reply::SendThenNoAck(ok()),
+        _ => Vec::new(),
+    };
+    Reply::Send(reply)
+}
+
+fn resume(target: &mut dyn StubTarget, session: &mut Session, step: bool) -> Reply {
+    match target.resume(step) {
+        Some(signal) => {
+            session.last_signal = signal;
+            Reply::Send(stop_reply(signal))
+        }
+        None => Reply::Running,
+    }
+}
+
+fn query(target: &mut dyn StubTarget, text: &str) -> Vec<u8> {
+    if text.starts_with("Supported") {
+        let mut features = format!("PacketSize={:x};QStartNoAckMode+;vContSupported+", PACKET_SIZE);
+        if target.target_xml().is_some() {
+            features.push_str(";qXfer:features:read+");
+        }
+        if target.memory_map_xml().is_some() {
+            features.push_str(";qXfer:memory-map:read+");
+        }
+        return features.into_bytes();
+    }
+    if let Some(annex) = text.strip_prefix("Xfer:memory-map:read::") {
+        return match target.memory_map_xml() {
+            Some(xml) => xfer_chunk(xml.as_bytes(), annex),
+            None => error(0),
+        };
+    }
+    if let Some(hex) = text.strip_prefix("Rcmd,") {
+        let command = decode_hex(hex).map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+        return match command.and_then(|command| target.monitor(&command)) {
+            // Output goes back hex-encoded in place of OK, which would end it
+            Some(output) if output.is_empty() => ok(),
+            Some(output) => encode_hex(output.as_bytes()).into_bytes(),
+            None => Vec::new(),
+        };
+    }
+    if let Some(annex) = text.strip_prefix("Xfer:features:read:target.xml:") {
+        return match target.target_xml() {
+            Some(xml) => xfer_chunk(xml.as_bytes(), annex),
+            None => error(0),
+        };
+    }
+    match text {
+        "Attached" => b"1".to_vec(),
+        "C" => b"QC1".to_vec(),
+        "fThreadInfo" => b"m1".to_vec(),
+        "sThreadInfo" => b"l".to_vec(),
+        "Symbol::" => ok(),
+        _ => Vec::new(),
+    }
+}
+
+/// Talk to one GDB until it detaches, kills the session or disconnects
+pub fn serve_connection<R: Read, W: Write>(conn: &mut Connection<R, W>, target: &mut dyn StubTarget) -> io::Result<()> {
+    let mut session = Session::default();
+    while let Some(incoming) = conn.recv()? {
+        let packet = match incoming {
+            Incoming::Packet(packet) => packet,
+            // Nothing ever runs in the background, so the target is already stopped
+            Incoming::Interrupt => {
+                session.last_signal = SIGINT;
+                conn.send(&stop_reply(SIGINT))?;
+                continue;
+            }
+            _ => continue,
+        };
+        match handle_packet(target, &mut session, &packet) {
+            Reply::Send(reply) => conn.send(&reply)?,
+            Reply::SendThenNoAck(reply) => {
+                conn.send(&reply)?;
+                conn.set_no_ack(true);
+            }
+            Reply::Close(reply) => {
+                if let Some(reply) = reply {
+                    conn.send(&reply)?;
+                }
+                break;
+            }
+            Reply::Running => {
+                // All-stop mode: GDB sends nothing but an interrupt until the target stops
+                loop {
+                    match conn.recv()? {
+                        Some(Incoming::Interrupt) => break,
+                        Some(_) => continue,
+                        None => return Ok(()),
+                    }
+                }
+                session.last_signal = target.halt();
+                conn.send(&stop_reply(session.last_signal))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Accept GDB connections one after another, forever. `on_connect` is told who connected.
+pub fn serve_tcp(
+    listener: &TcpListener,
+    target: &mut dyn StubTarget,
+    on_connect: &dyn Fn(&std::net::SocketAddr),
+) -> io::Result<()> {
+    loop {
+        let (stream, peer) = listener.accept()?;
+        on_connect(&peer);
+        stream.set_nodelay(true)?;
+        let mut conn = Connection::new(stream.try_clone()?, stream);
+        if let Err(e) = serve_connection(&mut conn, target) {
+            // A dropped connection only ends that session
+            if e.kind() != io::ErrorKind::UnexpectedEof && e.kind() != io::ErrorKind::ConnectionReset {
+                return Err(e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    struct TwoRegs {
+        memory: Vec<u8>,
+    }
+
+    impl StubTarget for TwoRegs {
+        fn target_xml(&self) -> Option<String> {
+            Some("<target>0123456789</target>".to_string())
+        }
+        fn register_count(&self) -> usize {
+            2
+        }
+        fn register_size(&self, _regnum: usize) -> usize {
+            4
+        }
+        fn read_register(&mut self, regnum: usize) -> Option<Vec<u8>> {
+            (regnum == 0).then(|| vec![0x78, 0x56, 0x34, 0x12])
+        }
+        fn read_memory(&mut self, addr: u64, len: usize) -> Vec<u8> {
+            let start = (addr as usize).min(self.memory.len());
+            self.memory[start..(start + len).min(self.memory.len())].to_vec()
+        }
+        fn write_memory(&mut self, addr: u64, data: &[u8]) -> bool {
+            let start = addr as usize;
+            match self.memory.get_mut(start..start + data.len()) {
+                Some(dest) => {
+                    dest.copy_from_slice(data);
+                    true
+                }
+                None => false,
+            }
+        }
+    }
+
+    fn reply(target: &mut TwoRegs, packet: &[u8]) -> Vec<u8> {
+        match handle_packet(target, &mut Session::default(), packet) {
+            Reply::Send(r) | Reply::SendThenNoAck(r) => r,
+            Reply::Close(r) => r.unwrap_or_default(),
+            Reply::Running => b"running".to_vec(),
+        }
+    }
+
+    #[test]
+    fn registers_memory_and_queries() {
+        let mut target = TwoRegs {
+            memory: vec![1, 2, 3, 4],
+        };
+        assert_eq!(reply(&mut target, b"g"), b"78563412xxxxxxxx");
+        assert_eq!(reply(&mut target, b"p1"), b"xxxxxxxx");
+        assert_eq!(reply(&mut target, b"m1,8"), b"020304");
+        assert_eq!(reply(&mut target, b"m10,4"), b"E01");
+        assert_eq!(reply(&mut target, b"M0,2:aabb"), b"OK");
+        assert_eq!(reply(&mut target, b"X2,2:\x00#"), b"OK");
+        assert_eq!(target.memory, vec![0xaa, 0xbb, 0x00, b'#']);
+        assert_eq!(reply(&mut target, b"P0=00000000"), b"E01");
+        assert_eq!(reply(&mut target, b"?"), b"T05thread:1;");
+        assert_eq!(reply(&mut target, b"vCont;s:1"), b"T05thread:1;");
+        assert_eq!(reply(&mut target, b"Z0,100,2"), b"");
+        assert!(String::from_utf8(reply(&mut target, b"qSupported:multiprocess+"))
+            .unwrap()
+            .contains("qXfer:features:read+"));
+        assert_eq!(reply(&mut target, b"qXfer:features:read:target.xml:0,8"), b"m<target>");
+        assert_eq!(
+            reply(&mut target, b"qXfer:features:read:target.xml:11,100"),
+            b"l9</target>"
+        );
+    }
+
+    #[test]
+    fn memory_reads_are_clamped_to_the_packet_size() {
+        let mut target = TwoRegs {
+            memory: vec![0; 2 * PACKET_SIZE],
+        };
+        assert_eq!(reply(&mut target, b"m0,ffffffffffff").len(), 2 * MAX_MEMORY_READ);
+        assert!(String::from_utf8(reply(&mut target, b"qSupported"))
+            .unwrap()
+            .starts_with("PacketSize=1000;"));
+    }
+
+    #[test]
+    fn session_over_a_connection() {
+        let mut target = TwoRegs { memory: vec![0; 4] };
+        let mut gdb = Vec::new();
+        for packet in [&b"QStartNoAckMode"[..], b"m0,2", b"D"] {
+            gdb.extend_from_slice(&crate::rsp::packet::frame(packet));
+            gdb.push(b'+');
+        }
+        let mut conn = Connection::new(Cursor::new(gdb), Vec::new());
+        serve_connection(&mut conn, &mut target).unwrap();
+        assert_eq!(conn.writer().as_slice(), b"+$OK#9a$0000#c0$OK#9a");
+    }
+}
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * BatchRequest carries several requests in one message. Each entry is a complete request with its own
+ * `req` and `seq`; batches can't be nested. The helper answers with a single BatchResponse.
+ */
+export type BatchRequest = { req: string; seq: number; requests: Array<unknown> };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Responses in the same order as the batch's requests. Failed requests have an ErrorResponse
+ * in their slot; an entry is null only when that request was cancelled.
+ */
+export type BatchResponse = { req: string; seq: number; responses: Array<unknown> };
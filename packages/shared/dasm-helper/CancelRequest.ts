@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * CancelRequest drops queued or in-flight work for the request (or batch) with this `seq`;
+ * no response is sent for it, or for the cancelled request. Cancelling finished work is a no-op.
+ */
+export type CancelRequest = { req: string; seq: number };
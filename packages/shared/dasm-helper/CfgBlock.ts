@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SerInstruction } from "./SerInstruction";
+
+export type CfgBlock = {
+    id: number;
+    /**
+     * Address of the first instruction in hex
+     */
+    start: string;
+    /**
+     * Address just past the last instruction in hex
+     */
+    end: string;
+    instructions: Array<SerInstruction>;
+};
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CfgEdgeKind } from "./CfgEdgeKind";
+
+export type CfgEdge = {
+    from: number;
+    /**
+     * Destination block id, or null when the edge leaves the function (call, return, tail call, indirect)
+     */
+    to: number | null;
+    kind: CfgEdgeKind;
+    /**
+     * Branch/call target address in hex, when it is known statically
+     */
+    target: string | null;
+    /**
+     * Symbol objdump printed for the target, e.g. "HAL_Init"
+     */
+    symbol: string | null;
+};
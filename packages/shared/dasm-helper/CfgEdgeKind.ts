@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Kind of a control-flow edge leaving a basic block.
+ */
+export type CfgEdgeKind = "fallthrough" | "jump" | "conditional" | "call" | "return" | "indirect";
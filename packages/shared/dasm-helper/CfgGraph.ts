@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CfgBlock } from "./CfgBlock";
+import type { CfgEdge } from "./CfgEdge";
+
+/**
+ * Control-flow graph of one function. Instructions carry the same compact source info as in
+ * a DisasmResponse, and `file_table` resolves their `F` ids.
+ */
+export type CfgGraph = {
+    function: string;
+    /**
+     * Function start address in hex
+     */
+    start: string;
+    file_table: { [key in number]: string };
+    blocks: Array<CfgBlock>;
+    edges: Array<CfgEdge>;
+};
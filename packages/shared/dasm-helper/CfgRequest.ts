@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * CfgRequest asks for the control-flow graph of a single function. The function is selected either by
+ * name (as shown in the disassembly, in any demangle style, or the raw mangled name) or by any address
+ * inside it. Set `dot` to also get a Graphviz rendering of the graph in the response.
+ */
+export type CfgRequest = {
+    req: string;
+    seq: number;
+    function: string | null;
+    address: string | null;
+    dot: boolean | null;
+};
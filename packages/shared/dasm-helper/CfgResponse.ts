@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CfgGraph } from "./CfgGraph";
+
+export type CfgResponse = {
+    req: string;
+    seq: number;
+    graph: CfgGraph;
+    /**
+     * Graphviz DOT rendering of `graph`, only when the request asked for it
+     */
+    dot: string | null;
+};
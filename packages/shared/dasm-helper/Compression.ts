@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Compression applied on top of the encoding. Only bodies of at least 1 KiB are compressed, so a
+ * frame's Content-Encoding header says whether this one was.
+ */
+export type Compression = "none" | "zstd" | "deflate";
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Body encodings for messages the helper sends. JSON is the default; MessagePack and CBOR carry the
+ * same objects (same field names and values), only smaller and cheaper to parse.
+ */
+export type Encoding = "json" | "msgpack" | "cbor";
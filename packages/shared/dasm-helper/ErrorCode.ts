@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Stable error codes for ErrorResponse. Clients may switch on these; new codes may be added.
+ */
+export type ErrorCode =
+    | "parse_error"
+    | "unknown_request"
+    | "not_ready"
+    | "address_not_found"
+    | "reload_in_progress"
+    | "not_implemented"
+    | "target_unavailable"
+    | "internal";
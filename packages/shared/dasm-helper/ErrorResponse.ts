@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RequestError } from "./RequestError";
+
+/**
+ * Sent instead of the normal response when a request fails. Every request that carries a `seq`
+ * gets exactly one of the two (except `cancel`, and requests it cancelled).
+ */
+export type ErrorResponse = { req: string; seq: number; error: RequestError };
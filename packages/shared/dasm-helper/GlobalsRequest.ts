@@ -1,3 +1,10 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type GlobalsRequest = { req: string; seq: number };
+export type GlobalsRequest = {
+    req: string;
+    seq: number;
+    /**
+     * When true, the response carries `variables` instead of the (name, address) tuples
+     */
+    detailed?: boolean | null;
+};
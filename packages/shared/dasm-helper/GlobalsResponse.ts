@@ -1,3 +1,9 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { VariableInfo } from "./VariableInfo";
 
-export type GlobalsResponse = { req: string; seq: number; globals: Array<[string, string]> };
+export type GlobalsResponse = {
+    req: string;
+    seq: number;
+    globals: Array<[string, string]>;
+    variables: Array<VariableInfo> | null;
+};
@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * HelloRequest negotiates how the helper encodes what it sends to this client, e.g. to shrink large
+ * disassembly responses over slow links. List the names you accept in order of preference; the helper
+ * picks the first one it supports from each list, falling back to "json" and "none". The choice applies
+ * to everything sent after the HelloResponse, which itself is still JSON. Every frame also names its
+ * encoding in `Content-Type` (absent for JSON) and `Content-Encoding` headers. Requests stay JSON.
+ */
+export type HelloRequest = {
+    req: string;
+    seq: number;
+    /**
+     * e.g. ["msgpack", "cbor", "json"]; unknown names are skipped
+     */
+    encodings: Array<string>;
+    /**
+     * e.g. ["zstd", "deflate", "none"]; unknown names are skipped
+     */
+    compression: Array<string>;
+};
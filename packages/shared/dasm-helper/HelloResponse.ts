@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Compression } from "./Compression";
+import type { Encoding } from "./Encoding";
+
+export type HelloResponse = { req: string; seq: number; version: string; encoding: Encoding; compression: Compression };
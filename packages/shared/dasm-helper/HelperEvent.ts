@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LiveSample } from "./LiveSample";
 
 /**
  * Events generated by the helper process and sent to the DA.
@@ -12,4 +13,5 @@ export type HelperEvent =
     | { type: "Progress"; session_id: string; operation: string; percentage: number | null; message: string | null }
     | { type: "Output"; session_id: string; category: string; message: string }
     | { type: "Error"; session_id: string; code: string | null; message: string; details: string | null }
+    | { type: "LiveSamples"; session_id: string; samples: Array<LiveSample>; slowdown: number }
     | { type: "Log"; session_id: string; level: string; message: string };
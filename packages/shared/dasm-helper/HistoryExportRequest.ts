@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HistoryFormat } from "./HistoryFormat";
+
+/**
+ * HistoryExportRequest exports the recorded history, to `path` if given, else in the response's
+ * `data`. Recording may still be running.
+ */
+export type HistoryExportRequest = { req: string; seq: number; format: HistoryFormat; path: string | null };
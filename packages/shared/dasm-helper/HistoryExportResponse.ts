@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type HistoryExportResponse = {
+    req: string;
+    seq: number;
+    samples: number;
+    path: string | null;
+    data: string | null;
+};
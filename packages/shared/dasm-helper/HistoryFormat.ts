@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Variable history formats. CSV has one row per sample (`time_s,id,value,bytes`); JSON holds the
+ * signals and samples; VCD opens in waveform viewers such as GTKWave.
+ */
+export type HistoryFormat = "csv" | "json" | "vcd";
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * HistoryStartRequest records the values of running live sampling (see `LiveStartRequest`): the items
+ * named in `ids`, or all of them. The last `capacity` samples (default 100000) are kept in memory, or
+ * with `file` every sample is appended to that JSON-lines file. It replaces any earlier recording.
+ */
+export type HistoryStartRequest = {
+    req: string;
+    seq: number;
+    ids: Array<string> | null;
+    capacity: number | null;
+    file: string | null;
+};
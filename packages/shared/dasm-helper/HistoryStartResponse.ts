@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type HistoryStartResponse = {
+    req: string;
+    seq: number;
+    /**
+     * Ids of the recorded items
+     */
+    signals: Array<string>;
+};
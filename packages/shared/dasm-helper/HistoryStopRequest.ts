@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * HistoryStopRequest stops recording. What was recorded can still be exported. 
+ */
+export type HistoryStopRequest = { req: string; seq: number };
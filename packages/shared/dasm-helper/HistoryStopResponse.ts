@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type HistoryStopResponse = {
+    req: string;
+    seq: number;
+    samples: number;
+    /**
+     * Samples the in-memory ring overwrote because it was full
+     */
+    dropped: number;
+};
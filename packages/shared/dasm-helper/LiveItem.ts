@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One thing to sample. Give either `symbol` (a global or static variable, typed from DWARF) or
+ * `address` with `size`. `type_ref` (from `VariableInfo`) types an address; without a type the value
+ * is shown as hex.
+ */
+export type LiveItem = {
+    id: string;
+    symbol: string | null;
+    address: string | null;
+    size: number | null;
+    type_ref: string | null;
+    interval_ms: number;
+};
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Where each item was resolved to, in request order 
+ */
+export type LiveItemInfo = { id: string; address: string; size: number; type_name: string | null };
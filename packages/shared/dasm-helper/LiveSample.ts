@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A sampled value that changed since it was last reported 
+ */
+export type LiveSample = { id: string; value: string; bytes: string };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LiveItem } from "./LiveItem";
+
+/**
+ * LiveStartRequest starts sampling `items` over a connection of its own to the gdb-server at
+ * `gdb_server` (`host:port`, e.g. a port the proxy duplicated the GDB stream to). It replaces any
+ * sampling this client already started. Changed values arrive as `LiveSamples` events.
+ * `max_busy_percent` (default 25) caps the share of time spent reading; `max_gap` (default 32) is how
+ * many unused bytes may be read to join two items into one read.
+ */
+export type LiveStartRequest = {
+    req: string;
+    seq: number;
+    gdb_server: string;
+    items: Array<LiveItem>;
+    max_busy_percent: number | null;
+    max_gap: number | null;
+};
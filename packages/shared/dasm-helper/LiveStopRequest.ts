@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * LiveStopRequest stops this client's sampling; `stopped` is false if none was running 
+ */
+export type LiveStopRequest = { req: string; seq: number };
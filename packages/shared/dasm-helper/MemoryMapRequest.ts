@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * MemoryMapRequest asks how the image fills the device's memory. Regions come from the MEMORY block of
+ * `linker_script` when given, else from the ELF's PT_LOAD segments; `svd` adds the peripheral blocks of
+ * a CMSIS-SVD file. Both are paths the helper reads itself.
+ */
+export type MemoryMapRequest = { req: string; seq: number; linker_script: string | null; svd: string | null };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MemoryOverflow } from "./MemoryOverflow";
+import type { MemoryOverlap } from "./MemoryOverlap";
+import type { MemoryRegionUsage } from "./MemoryRegionUsage";
+import type { SectionPlacement } from "./SectionPlacement";
+
+export type MemoryMapResponse = {
+    req: string;
+    seq: number;
+    regions: Array<MemoryRegionUsage>;
+    sections: Array<SectionPlacement>;
+    overlaps: Array<MemoryOverlap>;
+    overflows: Array<MemoryOverflow>;
+};
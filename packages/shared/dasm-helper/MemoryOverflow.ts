@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A section that doesn't fit: `bytes` of it lie past the end of `region` (the region it starts in), or
+ * outside every region when `region` is null. `load` is set when it is the section's load image.
+ */
+export type MemoryOverflow = { section: string; region: string | null; bytes: number; load: boolean };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Two regions, or two sections, that share addresses
+ */
+export type MemoryOverlap = {
+    first: string;
+    second: string;
+    start: string;
+    size: number;
+    /**
+     * True for two sections, false for two regions
+     */
+    sections: boolean;
+};
@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RegionSource } from "./RegionSource";
+
+/**
+ * One physical memory region and how much of it the image uses. `used` counts run-time placement
+ * (VMA) and, for linker regions, load images (LMA) such as the initial values of `.data` in flash.
+ */
+export type MemoryRegionUsage = {
+    name: string;
+    origin: string;
+    length: number;
+    /**
+     * Linker attributes such as "rx" or "rwx", or the segment's flags; null for SVD peripherals
+     */
+    attributes: string | null;
+    source: RegionSource;
+    used: number;
+    free: number;
+    /**
+     * Bytes of sections that start in this region but run past its end
+     */
+    overflow: number;
+    /**
+     * Sections placed in this region; a copy-down section is also listed in its load region
+     */
+    sections: Array<string>;
+};
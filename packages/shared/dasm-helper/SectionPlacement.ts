@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An allocated ELF section. `vma` is where it lives at run time, `lma` where its contents are stored in
+ * the image; they differ for sections the startup code copies down (`copy_down`), such as `.data`.
+ */
+export type SectionPlacement = {
+    name: string;
+    vma: string;
+    lma: string;
+    size: number;
+    /**
+     * False for sections without contents in the file, such as `.bss`
+     */
+    has_data: boolean;
+    copy_down: boolean;
+    region: string | null;
+    load_region: string | null;
+};
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SourceLine = {
+    /**
+     * File ID, resolved through the response's file_table
+     */
+    F: number;
+    /**
+     * 1-based line number
+     */
+    l: number;
+    /**
+     * Line text, or null if the file could not be read
+     */
+    text: string | null;
+    /**
+     * True if this line was already shown earlier in the listing (code reordered by the optimizer)
+     */
+    repeat: boolean;
+};
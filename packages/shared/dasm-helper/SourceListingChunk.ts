@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SerInstruction } from "./SerInstruction";
+import type { SourceLine } from "./SourceLine";
+
+/**
+ * One group of a source-interleaved listing: zero or more source lines followed by the
+ * instructions generated for them. A chunk with no source lines holds instructions without line info.
+ */
+export type SourceListingChunk = { source: Array<SourceLine>; instructions: Array<SerInstruction> };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * SourceListingRequest asks for a mixed source/assembly listing of a function (by name) or of the
+ * address range [start, end). The helper reads the source files itself, so the DA does not have to
+ * rebuild the listing from the `F`/`sl`/`el` fields.
+ */
+export type SourceListingRequest = {
+    req: string;
+    seq: number;
+    function: string | null;
+    start: string | null;
+    end: string | null;
+};
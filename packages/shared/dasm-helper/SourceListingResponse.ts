@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SourceListingChunk } from "./SourceListingChunk";
+
+export type SourceListingResponse = {
+    req: string;
+    seq: number;
+    file_table: { [key in number]: string };
+    func_table: { [key in number]: string };
+    chunks: Array<SourceListingChunk>;
+};
@@ -1,3 +1,11 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type StaticsRequest = { req: string; seq: number; file_name: string };
+export type StaticsRequest = {
+    req: string;
+    seq: number;
+    file_name: string;
+    /**
+     * When true, the response carries `variables` instead of the (name, address) tuples
+     */
+    detailed?: boolean | null;
+};
@@ -1,3 +1,9 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { VariableInfo } from "./VariableInfo";
 
-export type StaticsResponse = { req: string; seq: number; statics: Array<[string, string]> };
+export type StaticsResponse = {
+    req: string;
+    seq: number;
+    statics: Array<[string, string]>;
+    variables: Array<VariableInfo> | null;
+};
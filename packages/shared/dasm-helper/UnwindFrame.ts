@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One frame, innermost first. `method` says which table recovered it from the frame below (absent
+ * for the innermost frame); `exception` is set on code that an exception interrupted. `registers`
+ * holds the values known in this frame, for evaluating its locals.
+ */
+export type UnwindFrame = {
+    pc: string;
+    sp: string;
+    function: string | null;
+    file: string | null;
+    line: number | null;
+    method: string | null;
+    exception: string | null;
+    exc_return: string | null;
+    registers: Array<[string, string]>;
+};
@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { VerifyDump } from "./VerifyDump";
+
+/**
+ * UnwindRequest asks for the call stack of a halted Cortex-M. `registers` maps names (r0-r12, sp, lr,
+ * pc, xpsr, msp, psp, control) to hex values; `memory` holds the stack the client read, in the same
+ * form as verify dumps. Alternatively `core` is the path of a core-dump manifest to unwind. Memory that
+ * isn't supplied is read from the ELF's load image.
+ */
+export type UnwindRequest = {
+    req: string;
+    seq: number;
+    registers: { [key in string]: string };
+    memory: Array<VerifyDump>;
+    core: string | null;
+    max_frames: number | null;
+};
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UnwindFrame } from "./UnwindFrame";
+
+export type UnwindResponse = {
+    req: string;
+    seq: number;
+    frames: Array<UnwindFrame>;
+    /**
+     * Why unwinding stopped after the last frame
+     */
+    stop_reason: string;
+};
@@ -0,0 +1,30 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A global or static variable, as returned by detailed globals/statics requests.
+ */
+export type VariableInfo = {
+    name: string;
+    /**
+     * Mangled name when it differs from `name` (C++, Rust), e.g. for breakpoints by linkage name
+     */
+    raw_name: string | null;
+    address: string;
+    /**
+     * Size in bytes, from the ELF symbol
+     */
+    size: number;
+    /**
+     * ELF section, e.g. ".data", ".bss", ".rodata" or a custom one
+     */
+    section: string | null;
+    /**
+     * Declaring file (after source map rules) and 1-based line, from DWARF
+     */
+    decl_file: string | null;
+    decl_line: number | null;
+    /**
+     * Hex offset of the variable's type DIE in .debug_info, usable as a type reference
+     */
+    type_ref: string | null;
+};
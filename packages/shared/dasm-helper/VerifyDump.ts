@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Target memory to compare against the ELF: either a HEX, S-record or binary `file` the helper reads
+ * (binary files are placed at `address`), or inline `data` at `address`. `data` is hex, two digits per
+ * byte, as gdb's `-data-read-memory-bytes` returns it.
+ */
+export type VerifyDump = { file: string | null; address: string | null; data: string | null };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A range of load addresses where target memory differs from the ELF. Differences a few bytes apart
+ * are reported as one range; `differing` counts the bytes that actually differ. `symbols` are the
+ * symbols at the matching run-time addresses (they differ from the load addresses for `.data`).
+ */
+export type VerifyMismatch = {
+    start: string;
+    size: number;
+    differing: number;
+    section: string | null;
+    symbols: Array<string>;
+    /**
+     * The first bytes of the range, in hex, from the ELF and from the target
+     */
+    expected: string;
+    actual: string;
+};
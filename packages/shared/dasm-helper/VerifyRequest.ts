@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { VerifyDump } from "./VerifyDump";
+
+/**
+ * VerifyRequest asks whether target memory holds what the ELF would load there. Only the dumped
+ * addresses are compared, against the contents of the loadable sections at their load addresses.
+ */
+export type VerifyRequest = { req: string; seq: number; dumps: Array<VerifyDump> };
@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { VerifyMismatch } from "./VerifyMismatch";
+
+export type VerifyResponse = {
+    req: string;
+    seq: number;
+    /**
+     * True when every compared byte matched
+     */
+    matched: boolean;
+    /**
+     * Bytes that were in both the dumps and the ELF
+     */
+    compared: number;
+    /**
+     * Dumped bytes at addresses the ELF has no contents for; not compared
+     */
+    ignored: number;
+    /**
+     * ELF contents at addresses that weren't dumped
+     */
+    not_dumped: number;
+    mismatches: Array<VerifyMismatch>;
+};
@@ -1,6 +1,9 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { SerialParams } from "../serial-helper/SerialParams";
+import type { GdbServerPreset } from "./GdbServerPreset";
 import type { PortAllocatorSpec } from "./PortAllocatorSpec";
+import type { ResourceLimits } from "./ResourceLimits";
+import type { RestartPolicy } from "./RestartPolicy";
 
 export type ControlRequest =
     | {
@@ -48,8 +51,21 @@ export type ControlRequest =
                * Environment variables for the gdb-server process
                */
               server_env: { [key in string]: string } | null;
+              /**
+               * Relaunch the gdb-server when it exits on its own. Absent means never
+               */
+              restart?: RestartPolicy | null;
+              /**
+               * Working directory, relative to the session directory unless absolute. Defaults to the session directory
+               */
+              cwd?: string | null;
+              /**
+               * Resource limits for the gdb-server and the processes it starts
+               */
+              limits?: ResourceLimits | null;
           };
       }
+    | { method: "startGdbServerPreset"; params: GdbServerPreset }
     | { method: "endSession" }
     | { method: "streamStatus"; params: { stream_id: number } }
     | { method: "startStream"; params: { stream_id: number } }
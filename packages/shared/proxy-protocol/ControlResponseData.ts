@@ -9,6 +9,14 @@ export type ControlResponseData =
     | { initialize: { version: string; server_cwd: string } }
     | { allocatePorts: { ports: Array<PortReserved> } }
     | { startGdbServer: { pid: number } }
+    | {
+          startGdbServerPreset: {
+              pid: number;
+              server_path: string;
+              server_args: Array<string>;
+              ports: Array<PortReserved>;
+          };
+      }
     | { streamStatus: { stream_id: number; status: StreamStatus; msg_seq: number } }
     | "heartbeat"
     | {
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Wire protocol between the probe and the target.
+ */
+export type DebugInterface = "swd" | "jtag";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * gdb-servers the proxy knows how to launch from a [`GdbServerPreset`].
+ */
+export type GdbServerKind = "openocd" | "jlink" | "pyocd" | "stlink" | "probe-rs";
@@ -0,0 +1,54 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DebugInterface } from "./DebugInterface";
+import type { GdbServerKind } from "./GdbServerKind";
+import type { ResourceLimits } from "./ResourceLimits";
+import type { RestartPolicy } from "./RestartPolicy";
+
+/**
+ * Parameters of `startGdbServerPreset`. Everything but `kind` is optional; a
+ * setting the chosen server has no option for is ignored.
+ */
+export type GdbServerPreset = {
+    kind: GdbServerKind;
+    /**
+     * Executable to run. Defaults to the server's usual name, looked up on `PATH`.
+     */
+    server_path?: string | null;
+    /**
+     * Target device or chip name, in the server's own spelling.
+     */
+    device?: string | null;
+    interface?: DebugInterface | null;
+    /**
+     * Probe clock in kHz.
+     */
+    speed?: number | null;
+    /**
+     * Serial number (probe-rs: probe selector) of the probe to use.
+     */
+    serial_number?: string | null;
+    /**
+     * OpenOCD `-f` configuration files, in order.
+     */
+    config_files: Array<string>;
+    /**
+     * Appended verbatim after the generated arguments.
+     */
+    extra_args: Array<string>;
+    server_env?: { [key in string]: string } | null;
+    /**
+     * First port to try for the server's consecutive port block. Defaults to the
+     * server's usual GDB port.
+     */
+    start_port?: number | null;
+    /**
+     * Relaunch the server when it exits on its own. Absent means never.
+     */
+    restart?: RestartPolicy | null;
+    /**
+     * Working directory for the server, relative to the session directory unless
+     * absolute. Defaults to the session directory.
+     */
+    cwd?: string | null;
+    limits?: ResourceLimits | null;
+};
@@ -9,6 +9,7 @@ import type { SerialErrorKind } from "../serial-helper/SerialErrorKind";
 export type ProxyServerEvents =
     | { event: "gdbServerLaunched"; params: { pid: number; port: number } }
     | { event: "gdbServerExited"; params: { pid: number; exit_code: number } }
+    | { event: "gdbServerRestarted"; params: { previous_pid: number; pid: number; exit_code: number; attempt: number } }
     | { event: "streamReady"; params: { stream_id: number; port: number } }
     | { event: "streamStarted"; params: { stream_id: number; port: number } }
     | { event: "streamClosed"; params: { stream_id: number } }
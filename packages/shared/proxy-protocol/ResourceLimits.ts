@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Resource limits applied to a launched gdb-server and everything it starts.
+ *
+ * Enforced with `setrlimit` on Unix, where they are inherited by the server's own
+ * helpers. Other platforms ignore them, with a warning in the proxy log.
+ */
+export type ResourceLimits = {
+    /**
+     * CPU time, in seconds, before the server is killed (`RLIMIT_CPU`).
+     */
+    cpu_secs?: number | null;
+    /**
+     * Address space, in MiB (`RLIMIT_AS`). This bounds virtual memory, not resident
+     * memory, so leave headroom for what the server maps but never touches.
+     */
+    max_memory_mb?: number | null;
+};
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * When a gdb-server that exits on its own is launched again.
+ *
+ * The relaunch reuses the command line and ports of the first launch, so clients
+ * reconnect to the same stream ids. Only after the last restart is spent does the
+ * exit end the session, as it does without a policy.
+ */
+export type RestartPolicy = {
+    /**
+     * Relaunches allowed over the whole session.
+     */
+    max_restarts: number;
+    /**
+     * Wait before the first relaunch, doubling for each one after. Defaults to 500 ms.
+     */
+    backoff_ms?: number | null;
+    /**
+     * Ceiling for the doubled wait. Defaults to 8 s.
+     */
+    max_backoff_ms?: number | null;
+};
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What a gdb-server port is for. The lowercase name is the `stream_id_str` of the
+ * port in the `startGdbServerPreset` response.
+ */
+export type ServerPortRole = "gdb" | "telnet" | "swo" | "rtt" | "tcl";